@@ -0,0 +1,54 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ironcode_tool::bm25::{tokenize, Bm25Index};
+
+fn generate_document(i: usize) -> Vec<String> {
+    let mut text = String::new();
+    for j in 0..40 {
+        text.push_str(&format!(
+            "handler{}Request{} parse_field_{} ",
+            i,
+            j,
+            (i + j) % 97
+        ));
+    }
+    tokenize(&text)
+}
+
+fn build_index(docs: usize) -> Bm25Index {
+    let mut idx = Bm25Index::new();
+    for i in 0..docs {
+        idx.add_document(i, &generate_document(i));
+    }
+    idx
+}
+
+fn bench_update_document(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bm25_update_document");
+
+    for docs in [1_000, 10_000, 50_000].iter() {
+        let mut idx = build_index(*docs);
+        let doc = generate_document(docs / 2);
+
+        group.bench_with_input(BenchmarkId::from_parameter(docs), docs, |b, &docs| {
+            b.iter(|| {
+                // Re-adding an existing id removes its old postings first
+                idx.add_document(black_box(docs / 2), black_box(&doc));
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_search(c: &mut Criterion) {
+    let idx = build_index(10_000);
+    let query = tokenize("handler request parse field");
+
+    c.bench_function("bm25_search_10k", |b| {
+        b.iter(|| idx.search(black_box(&query), black_box(20)))
+    });
+}
+
+criterion_group!(benches, bench_update_document, bench_search);
+
+criterion_main!(benches);
@@ -0,0 +1,54 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ironcode_tool::grep::{execute_with_options, GrepOptions};
+use std::fs;
+use std::path::PathBuf;
+
+/// A directory holding one large log, so grep searches just that file
+fn create_large_log(mb: usize) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ironcode_grep_bench_{}mb", mb));
+    let path = dir.join("app.log");
+    if !path.exists() {
+        fs::create_dir_all(&dir).unwrap();
+        let mut content = String::with_capacity(mb * 1024 * 1024);
+        let mut i = 0;
+        while content.len() < mb * 1024 * 1024 {
+            if i % 50_000 == 0 {
+                content.push_str("2026-01-01T00:00:00Z ERROR request failed: upstream timeout\n");
+            } else {
+                content.push_str("2026-01-01T00:00:00Z INFO handled request in 12ms status=200\n");
+            }
+            i += 1;
+        }
+        fs::write(&path, content).unwrap();
+    }
+    dir
+}
+
+fn bench_large_file_grep(c: &mut Criterion) {
+    let dir = create_large_log(256);
+    let dir_str = dir.to_str().unwrap().to_string();
+
+    let mut group = c.benchmark_group("grep_large_file_256mb");
+    group.sample_size(10);
+
+    for (name, threshold) in [("streaming", u64::MAX), ("mmap", 0)] {
+        let options = GrepOptions {
+            mmap_threshold: threshold,
+            ..GrepOptions::default()
+        };
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                execute_with_options(black_box("ERROR"), &dir_str, None, &options)
+                    .unwrap()
+                    .metadata
+                    .count
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_large_file_grep);
+
+criterion_main!(benches);
@@ -0,0 +1,52 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ironcode_tool::read::with_file_bytes;
+use std::fs;
+use std::io::{BufReader, Read};
+use std::path::PathBuf;
+
+fn create_large_file(mb: usize) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("ironcode_read_bench_{}mb.txt", mb));
+    if !path.exists() {
+        let line = "    const value = computeSomething(input, options); // filler line\n";
+        let mut content = String::with_capacity(mb * 1024 * 1024);
+        while content.len() < mb * 1024 * 1024 {
+            content.push_str(line);
+        }
+        fs::write(&path, content).unwrap();
+    }
+    path
+}
+
+fn bench_large_file_read(c: &mut Criterion) {
+    let path = create_large_file(64);
+    let path_str = path.to_str().unwrap().to_string();
+
+    let mut group = c.benchmark_group("read_large_file_64mb");
+    group.sample_size(10);
+
+    group.bench_function("bufreader_read_to_string", |b| {
+        b.iter(|| {
+            let file = fs::File::open(black_box(&path_str)).unwrap();
+            let capacity = file.metadata().map(|m| m.len() as usize).unwrap_or(0);
+            let mut reader = BufReader::with_capacity(65536, file);
+            let mut content = String::with_capacity(capacity);
+            reader.read_to_string(&mut content).unwrap();
+            content.len()
+        })
+    });
+
+    group.bench_function("mmap_validate_utf8", |b| {
+        b.iter(|| {
+            with_file_bytes(black_box(&path_str), |bytes| {
+                std::str::from_utf8(bytes).map(|s| s.len()).unwrap_or(0)
+            })
+            .unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_large_file_read);
+
+criterion_main!(benches);
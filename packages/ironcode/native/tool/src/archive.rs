@@ -1,11 +1,15 @@
 use std::fs;
 use std::io;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 
 #[derive(Debug)]
 pub enum ArchiveError {
     IoError(io::Error),
     ZipError(String),
+    EntryNotFound(String),
+    /// Entry name that would resolve outside the destination (zip-slip), or
+    /// whose path inside the destination goes through an existing symlink
+    UnsafePath(String),
 }
 
 impl From<io::Error> for ArchiveError {
@@ -16,7 +20,11 @@ impl From<io::Error> for ArchiveError {
 
 impl From<s_zip::SZipError> for ArchiveError {
     fn from(err: s_zip::SZipError) -> Self {
-        ArchiveError::ZipError(err.to_string())
+        match err {
+            s_zip::SZipError::Io(e) => ArchiveError::IoError(e),
+            s_zip::SZipError::EntryNotFound(name) => ArchiveError::EntryNotFound(name),
+            other => ArchiveError::ZipError(other.to_string()),
+        }
     }
 }
 
@@ -47,3 +55,127 @@ pub fn extract_zip(zip_path: &str, dest_dir: &str) -> Result<(), ArchiveError> {
 
     Ok(())
 }
+
+/// Extract only `entry_name` to `dest_dir/entry_name`, streaming it to disk
+/// without reading the rest of the archive. Entry names that are absolute or
+/// contain `..` are rejected, as are paths through an existing symlink under
+/// `dest_dir`. A directory entry (name ending in `/`) creates the directory.
+/// Returns the path written.
+pub fn extract_entry(
+    zip_path: &str,
+    entry_name: &str,
+    dest_dir: &str,
+) -> Result<PathBuf, ArchiveError> {
+    let mut reader = s_zip::StreamingZipReader::open(zip_path)?;
+    let entry = reader
+        .find_entry(entry_name)
+        .ok_or_else(|| ArchiveError::EntryNotFound(entry_name.to_string()))?
+        .clone();
+
+    let relative = Path::new(entry_name);
+    let safe = relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if !safe {
+        return Err(ArchiveError::UnsafePath(entry_name.to_string()));
+    }
+
+    // A symlink planted in the destination would redirect the write elsewhere
+    let mut entry_path = PathBuf::from(dest_dir);
+    for component in relative.components() {
+        entry_path.push(component);
+        if fs::symlink_metadata(&entry_path).is_ok_and(|meta| meta.file_type().is_symlink()) {
+            return Err(ArchiveError::UnsafePath(entry_name.to_string()));
+        }
+    }
+
+    if entry_name.ends_with('/') {
+        fs::create_dir_all(&entry_path)?;
+        return Ok(entry_path);
+    }
+    if let Some(parent) = entry_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut data = reader.read_entry_streaming(&entry)?;
+    let mut file = fs::File::create(&entry_path)?;
+    io::copy(&mut data, &mut file)?;
+
+    Ok(entry_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    fn write_zip(path: &Path, entries: &[(&str, &str)]) {
+        let mut writer = s_zip::StreamingZipWriter::new(path).unwrap();
+        for (name, content) in entries {
+            writer.start_entry(name).unwrap();
+            writer.write_data(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn test_extract_entry_writes_only_that_entry() {
+        let dir = temp_dir("single");
+        let zip = dir.join("bundle.zip");
+        write_zip(
+            &zip,
+            &[
+                ("README.md", "readme"),
+                ("meta/manifest.json", "{\"name\":\"pkg\"}"),
+                ("lib/code.js", "code"),
+                ("../escape.txt", "evil"),
+            ],
+        );
+        let out = dir.join("out");
+        let (zip, out_str) = (zip.to_str().unwrap(), out.to_str().unwrap());
+
+        let written = extract_entry(zip, "meta/manifest.json", out_str).unwrap();
+        assert_eq!(written, out.join("meta/manifest.json"));
+        assert_eq!(fs::read_to_string(&written).unwrap(), "{\"name\":\"pkg\"}");
+        assert!(!out.join("README.md").exists());
+        assert!(!out.join("lib").exists());
+
+        assert!(matches!(
+            extract_entry(zip, "missing.txt", out_str),
+            Err(ArchiveError::EntryNotFound(_))
+        ));
+        assert!(matches!(
+            extract_entry(zip, "../escape.txt", out_str),
+            Err(ArchiveError::UnsafePath(_))
+        ));
+        assert!(!dir.join("escape.txt").exists());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_extract_entry_directory_and_symlink() {
+        let dir = temp_dir("dirlink");
+        let zip = dir.join("bundle.zip");
+        write_zip(&zip, &[("assets/", ""), ("link/payload.txt", "evil")]);
+        let out = dir.join("out");
+        let (zip, out_str) = (zip.to_str().unwrap(), out.to_str().unwrap());
+
+        let written = extract_entry(zip, "assets/", out_str).unwrap();
+        assert!(written.is_dir());
+        assert_eq!(written, out.join("assets"));
+
+        #[cfg(unix)]
+        {
+            let target = dir.join("elsewhere");
+            fs::create_dir_all(&target).unwrap();
+            std::os::unix::fs::symlink(&target, out.join("link")).unwrap();
+            assert!(matches!(
+                extract_entry(zip, "link/payload.txt", out_str),
+                Err(ArchiveError::UnsafePath(_))
+            ));
+            assert!(!target.join("payload.txt").exists());
+        }
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
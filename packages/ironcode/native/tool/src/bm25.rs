@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 
 const K1: f64 = 1.2;
 const B: f64 = 0.75;
@@ -76,13 +77,88 @@ const STOP_WORDS: &[&str] = &[
     "with",
 ];
 
+/// How much one query term added to a document's score
+#[derive(Debug, Clone, Serialize)]
+pub struct TermContribution {
+    pub term: String,
+    pub idf: f64,
+    pub tf: usize,
+    pub score: f64,
+}
+
+/// A search hit with the per-term breakdown of its score
+#[derive(Debug, Clone, Serialize)]
+pub struct Explanation {
+    pub doc_id: usize,
+    pub score: f64,
+    pub terms: Vec<TermContribution>,
+}
+
+/// A search query: bare words are ranked by BM25, `+word` must appear in a
+/// document and `-word` must not. Required words are ranked too.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Query {
+    /// Tokens to score, including those of required words
+    pub terms: Vec<String>,
+    /// Tokens of each `+word`; a document must contain all tokens of each
+    pub required: Vec<Vec<String>>,
+    /// Tokens of each `-word`; a document containing all tokens of one is dropped
+    pub excluded: Vec<Vec<String>>,
+}
+
+impl Query {
+    /// Parse `auth -test +session`. A sign only counts before a word
+    /// character, so `--verbose` or a lone `-` are ordinary text.
+    pub fn parse(text: &str) -> Self {
+        let signed = |word: &str, sign: char| {
+            word.strip_prefix(sign)
+                .filter(|rest| rest.starts_with(|c: char| c.is_alphanumeric() || c == '_'))
+                .map(tokenize)
+        };
+        let mut query = Query::default();
+        for word in text.split_whitespace() {
+            if let Some(tokens) = signed(word, '+') {
+                query.terms.extend(tokens.iter().cloned());
+                if !tokens.is_empty() {
+                    query.required.push(tokens);
+                }
+            } else if let Some(tokens) = signed(word, '-') {
+                if !tokens.is_empty() {
+                    query.excluded.push(tokens);
+                }
+            } else {
+                query.terms.extend(tokenize(word));
+            }
+        }
+        query
+    }
+}
+
+/// Documents a query's `+`/`-` words allow, resolved from the inverted index.
+/// The default filter allows everything.
+#[derive(Default)]
+struct DocFilter {
+    required: Vec<HashSet<usize>>,
+    excluded: HashSet<usize>,
+}
+
+impl DocFilter {
+    fn allows(&self, doc_id: usize) -> bool {
+        !self.excluded.contains(&doc_id) && self.required.iter().all(|docs| docs.contains(&doc_id))
+    }
+}
+
 pub struct Bm25Index {
     /// term -> Vec<(doc_id, term_frequency)>
     inverted_index: HashMap<String, Vec<(usize, usize)>>,
     /// doc_id -> token count (0 = deleted)
     doc_lengths: Vec<usize>,
+    /// doc_id -> unique terms of the document, so removal only touches its own postings
+    doc_terms: Vec<Vec<String>>,
     /// Number of active documents
     num_docs: usize,
+    /// Sum of all active document lengths
+    total_doc_length: usize,
     /// Average document length
     avg_doc_length: f64,
 }
@@ -98,7 +174,9 @@ impl Bm25Index {
         Self {
             inverted_index: HashMap::new(),
             doc_lengths: Vec::new(),
+            doc_terms: Vec::new(),
             num_docs: 0,
+            total_doc_length: 0,
             avg_doc_length: 0.0,
         }
     }
@@ -107,6 +185,7 @@ impl Bm25Index {
         // Grow if needed
         if doc_id >= self.doc_lengths.len() {
             self.doc_lengths.resize(doc_id + 1, 0);
+            self.doc_terms.resize(doc_id + 1, Vec::new());
         }
         // If already exists, remove first
         if self.doc_lengths[doc_id] > 0 {
@@ -122,71 +201,235 @@ impl Bm25Index {
         let doc_len = tokens.len();
         self.doc_lengths[doc_id] = doc_len;
 
+        let mut terms = Vec::with_capacity(tf.len());
         for (term, count) in &tf {
             self.inverted_index
                 .entry(term.to_string())
                 .or_default()
                 .push((doc_id, *count));
+            terms.push(term.to_string());
         }
+        self.doc_terms[doc_id] = terms;
 
         self.num_docs += 1;
+        self.total_doc_length += doc_len;
         self.recalculate_avg();
     }
 
+    /// Remove a document. Only the postings of the document's own terms are
+    /// touched, so the cost is proportional to its unique terms rather than
+    /// the whole vocabulary.
     pub fn remove_document(&mut self, doc_id: usize) {
         if doc_id >= self.doc_lengths.len() || self.doc_lengths[doc_id] == 0 {
             return;
         }
+        self.total_doc_length = self
+            .total_doc_length
+            .saturating_sub(self.doc_lengths[doc_id]);
         self.doc_lengths[doc_id] = 0;
         self.num_docs = self.num_docs.saturating_sub(1);
-        for postings in self.inverted_index.values_mut() {
-            postings.retain(|(id, _)| *id != doc_id);
+        for term in std::mem::take(&mut self.doc_terms[doc_id]) {
+            if let Some(postings) = self.inverted_index.get_mut(&term) {
+                postings.retain(|(id, _)| *id != doc_id);
+                if postings.is_empty() {
+                    self.inverted_index.remove(&term);
+                }
+            }
         }
         self.recalculate_avg();
     }
 
-    fn recalculate_avg(&mut self) {
-        let (total, count) = self
+    /// Append every live document of `other`, e.g. a per-thread index built
+    /// over part of a project. `other`'s documents get fresh ids after this
+    /// index's highest id; the returned map takes each old id to its new id so
+    /// callers can fix up anything keyed by doc id.
+    pub fn merge(&mut self, other: Bm25Index) -> HashMap<usize, usize> {
+        let base = self.doc_lengths.len();
+        let mut remap = HashMap::with_capacity(other.num_docs);
+        for (old_id, (len, terms)) in other
             .doc_lengths
-            .iter()
-            .filter(|&&l| l > 0)
-            .fold((0usize, 0usize), |(s, c), &l| (s + l, c + 1));
-        self.avg_doc_length = if count > 0 {
-            total as f64 / count as f64
+            .into_iter()
+            .zip(other.doc_terms)
+            .enumerate()
+        {
+            if len == 0 {
+                continue;
+            }
+            remap.insert(old_id, base + remap.len());
+            self.doc_lengths.push(len);
+            self.doc_terms.push(terms);
+        }
+
+        for (term, postings) in other.inverted_index {
+            let target = self.inverted_index.entry(term).or_default();
+            target.extend(
+                postings
+                    .into_iter()
+                    .filter_map(|(old_id, tf)| remap.get(&old_id).map(|&new_id| (new_id, tf))),
+            );
+        }
+
+        self.num_docs += remap.len();
+        self.total_doc_length += other.total_doc_length;
+        self.recalculate_avg();
+        remap
+    }
+
+    fn recalculate_avg(&mut self) {
+        self.avg_doc_length = if self.num_docs > 0 {
+            self.total_doc_length as f64 / self.num_docs as f64
         } else {
             0.0
         };
     }
 
+    fn idf(&self, df: usize) -> f64 {
+        let n = self.num_docs as f64;
+        let df = df as f64;
+        ((n - df + 0.5) / (df + 0.5) + 1.0).ln().max(0.0)
+    }
+
+    fn term_score(idf: f64, tf: usize, dl: usize, avgdl: f64) -> f64 {
+        let tf_f = tf as f64;
+        let dl_f = dl as f64;
+        idf * (tf_f * (K1 + 1.0)) / (tf_f + K1 * (1.0 - B + B * dl_f / avgdl))
+    }
+
+    /// Documents containing every one of `tokens`
+    fn docs_with_all(&self, tokens: &[String]) -> HashSet<usize> {
+        let mut docs: Option<HashSet<usize>> = None;
+        for token in tokens {
+            let with: HashSet<usize> = self
+                .inverted_index
+                .get(token)
+                .map(|postings| postings.iter().map(|&(doc_id, _)| doc_id).collect())
+                .unwrap_or_default();
+            docs = Some(match docs {
+                Some(docs) => docs.intersection(&with).copied().collect(),
+                None => with,
+            });
+        }
+        docs.unwrap_or_default()
+    }
+
+    fn doc_filter(&self, query: &Query) -> DocFilter {
+        DocFilter {
+            required: query
+                .required
+                .iter()
+                .map(|tokens| self.docs_with_all(tokens))
+                .collect(),
+            excluded: query
+                .excluded
+                .iter()
+                .flat_map(|tokens| self.docs_with_all(tokens))
+                .collect(),
+        }
+    }
+
     /// BM25 search. Returns Vec<(doc_id, score)> sorted by score descending.
     pub fn search(&self, query_tokens: &[String], top_k: usize) -> Vec<(usize, f64)> {
+        self.search_filtered(query_tokens, top_k, &DocFilter::default())
+    }
+
+    /// `search` for a parsed query: ranks `query.terms` among the documents
+    /// its required and excluded words allow.
+    pub fn search_query(&self, query: &Query, top_k: usize) -> Vec<(usize, f64)> {
+        self.search_filtered(&query.terms, top_k, &self.doc_filter(query))
+    }
+
+    fn search_filtered(
+        &self,
+        query_tokens: &[String],
+        top_k: usize,
+        filter: &DocFilter,
+    ) -> Vec<(usize, f64)> {
         if self.num_docs == 0 || query_tokens.is_empty() {
             return vec![];
         }
-        let n = self.num_docs as f64;
         let avgdl = self.avg_doc_length.max(1.0);
         let mut scores: HashMap<usize, f64> = HashMap::new();
 
         for token in query_tokens {
             if let Some(postings) = self.inverted_index.get(token) {
-                let df = postings.len() as f64;
-                let idf = ((n - df + 0.5) / (df + 0.5) + 1.0).ln().max(0.0);
+                let idf = self.idf(postings.len());
                 for &(doc_id, tf) in postings {
                     let dl = self.doc_lengths.get(doc_id).copied().unwrap_or(0);
-                    if dl == 0 {
+                    if dl == 0 || !filter.allows(doc_id) {
                         continue;
                     }
-                    let tf_f = tf as f64;
-                    let dl_f = dl as f64;
-                    let score =
-                        idf * (tf_f * (K1 + 1.0)) / (tf_f + K1 * (1.0 - B + B * dl_f / avgdl));
-                    *scores.entry(doc_id).or_insert(0.0) += score;
+                    *scores.entry(doc_id).or_insert(0.0) += Self::term_score(idf, tf, dl, avgdl);
                 }
             }
         }
 
         let mut results: Vec<(usize, f64)> = scores.into_iter().collect();
-        results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        // Ties break on doc_id so equal scores rank deterministically
+        results.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.0.cmp(&b.0))
+        });
+        results.truncate(top_k);
+        results
+    }
+
+    /// Diagnostic variant of `search` that also reports each query term's
+    /// contribution. Ranking is identical; the partial scores sum to `score`.
+    /// Slower than `search`, so keep it out of hot paths.
+    pub fn search_explain(&self, query_tokens: &[String], top_k: usize) -> Vec<Explanation> {
+        self.search_explain_filtered(query_tokens, top_k, &DocFilter::default())
+    }
+
+    /// `search_explain` for a parsed query, ranked like `search_query`
+    pub fn search_explain_query(&self, query: &Query, top_k: usize) -> Vec<Explanation> {
+        self.search_explain_filtered(&query.terms, top_k, &self.doc_filter(query))
+    }
+
+    fn search_explain_filtered(
+        &self,
+        query_tokens: &[String],
+        top_k: usize,
+        filter: &DocFilter,
+    ) -> Vec<Explanation> {
+        if self.num_docs == 0 || query_tokens.is_empty() {
+            return vec![];
+        }
+        let avgdl = self.avg_doc_length.max(1.0);
+        let mut explained: HashMap<usize, Explanation> = HashMap::new();
+
+        for token in query_tokens {
+            if let Some(postings) = self.inverted_index.get(token) {
+                let idf = self.idf(postings.len());
+                for &(doc_id, tf) in postings {
+                    let dl = self.doc_lengths.get(doc_id).copied().unwrap_or(0);
+                    if dl == 0 || !filter.allows(doc_id) {
+                        continue;
+                    }
+                    let score = Self::term_score(idf, tf, dl, avgdl);
+                    let entry = explained.entry(doc_id).or_insert_with(|| Explanation {
+                        doc_id,
+                        score: 0.0,
+                        terms: Vec::new(),
+                    });
+                    entry.score += score;
+                    entry.terms.push(TermContribution {
+                        term: token.clone(),
+                        idf,
+                        tf,
+                        score,
+                    });
+                }
+            }
+        }
+
+        let mut results: Vec<Explanation> = explained.into_values().collect();
+        results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.doc_id.cmp(&b.doc_id))
+        });
         results.truncate(top_k);
         results
     }
@@ -198,6 +441,29 @@ impl Bm25Index {
     pub fn term_count(&self) -> usize {
         self.inverted_index.len()
     }
+
+    /// Rough heap footprint in bytes: term strings, postings, doc lengths and
+    /// per-document term lists. Ignores allocator and hash table overhead.
+    pub fn estimated_bytes(&self) -> usize {
+        let string = std::mem::size_of::<String>();
+        let postings: usize = self
+            .inverted_index
+            .iter()
+            .map(|(term, docs)| {
+                string + term.len() + docs.len() * std::mem::size_of::<(usize, usize)>()
+            })
+            .sum();
+        let doc_terms: usize = self
+            .doc_terms
+            .iter()
+            .flatten()
+            .map(|term| string + term.len())
+            .sum();
+        postings
+            + doc_terms
+            + self.doc_lengths.len() * std::mem::size_of::<usize>()
+            + self.doc_terms.len() * std::mem::size_of::<Vec<String>>()
+    }
 }
 
 /// Tokenize code text into searchable terms.
@@ -348,4 +614,120 @@ mod tests {
         // Document 0 should rank highest for "user authentication"
         assert_eq!(results[0].0, 0);
     }
+
+    #[test]
+    fn test_remove_document_drops_only_its_postings() {
+        let mut idx = Bm25Index::new();
+        idx.add_document(0, &tokenize("parse config file"));
+        idx.add_document(1, &tokenize("parse user input"));
+        let terms_before = idx.term_count();
+
+        idx.remove_document(0);
+        assert_eq!(idx.doc_count(), 1);
+        // "config" and "file" were unique to doc 0 and must disappear
+        assert_eq!(idx.term_count(), terms_before - 2);
+        assert!(idx.search(&tokenize("config"), 5).is_empty());
+        let hits = idx.search(&tokenize("parse"), 5);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].0, 1);
+
+        // Re-adding the same id replaces rather than duplicates postings
+        idx.add_document(1, &tokenize("parse user input"));
+        assert_eq!(idx.doc_count(), 1);
+        assert_eq!(idx.search(&tokenize("input"), 5).len(), 1);
+    }
+
+    #[test]
+    fn test_merge_matches_single_index_of_union() {
+        let docs = [
+            "parse config file",
+            "read user profile",
+            "load config from disk config",
+            "render user avatar",
+            "write config backup",
+        ];
+        let mut single = Bm25Index::new();
+        for (id, doc) in docs.iter().enumerate() {
+            single.add_document(id, &tokenize(doc));
+        }
+
+        let mut left = Bm25Index::new();
+        left.add_document(0, &tokenize(docs[0]));
+        left.add_document(1, &tokenize(docs[1]));
+        let mut right = Bm25Index::new();
+        right.add_document(0, &tokenize("stale entry"));
+        right.add_document(1, &tokenize(docs[2]));
+        right.add_document(2, &tokenize(docs[3]));
+        right.add_document(3, &tokenize(docs[4]));
+        right.remove_document(0);
+
+        let remap = left.merge(right);
+        assert_eq!(remap.len(), 3);
+        assert_eq!(remap[&1], 2);
+        assert_eq!(left.doc_count(), single.doc_count());
+
+        let query = tokenize("config");
+        let merged_hits = left.search(&query, 5);
+        let single_hits = single.search(&query, 5);
+        assert_eq!(merged_hits[0].0, single_hits[0].0);
+        assert_eq!(single_hits[0].0, 2);
+        assert!((merged_hits[0].1 - single_hits[0].1).abs() < 1e-9);
+        assert!(left.search(&tokenize("stale"), 5).is_empty());
+    }
+
+    #[test]
+    fn test_search_explain_sums_to_score() {
+        let mut idx = Bm25Index::new();
+        idx.add_document(0, &tokenize("parse config file parse"));
+        idx.add_document(1, &tokenize("read config from disk"));
+        idx.add_document(2, &tokenize("render user profile"));
+
+        let query = tokenize("parse config");
+        let plain = idx.search(&query, 5);
+        let explained = idx.search_explain(&query, 5);
+        assert_eq!(plain.len(), explained.len());
+
+        for ((doc_id, score), explanation) in plain.iter().zip(&explained) {
+            assert_eq!(*doc_id, explanation.doc_id);
+            assert!((score - explanation.score).abs() < 1e-9);
+            let sum: f64 = explanation.terms.iter().map(|t| t.score).sum();
+            assert!((sum - explanation.score).abs() < 1e-9);
+        }
+
+        let top = &explained[0];
+        assert_eq!(top.doc_id, 0);
+        let parse = top.terms.iter().find(|t| t.term == "parse").unwrap();
+        assert_eq!(parse.tf, 2);
+        assert!(parse.idf > 0.0);
+    }
+
+    #[test]
+    fn test_query_required_and_excluded_terms() {
+        let query = Query::parse("auth -test +session --verbose");
+        assert_eq!(query.terms, vec!["auth", "session", "verbose"]);
+        assert_eq!(query.required, vec![vec!["session".to_string()]]);
+        assert_eq!(query.excluded, vec![vec!["test".to_string()]]);
+
+        let mut idx = Bm25Index::new();
+        idx.add_document(0, &tokenize("auth auth test helper"));
+        idx.add_document(1, &tokenize("auth session token refresh"));
+        idx.add_document(2, &tokenize("render user profile page"));
+
+        let plain = idx.search(&tokenize("auth"), 5);
+        assert_eq!(plain[0].0, 0);
+        let hits = idx.search_query(&Query::parse("auth -test"), 5);
+        assert_eq!(hits.iter().map(|h| h.0).collect::<Vec<_>>(), vec![1]);
+        // Excluding a term no document has changes nothing
+        assert_eq!(idx.search_query(&Query::parse("auth -missing"), 5), plain);
+
+        let hits = idx.search_query(&Query::parse("auth +token"), 5);
+        assert_eq!(hits.iter().map(|h| h.0).collect::<Vec<_>>(), vec![1]);
+        assert!(idx
+            .search_query(&Query::parse("+missing auth"), 5)
+            .is_empty());
+
+        let explained = idx.search_explain_query(&Query::parse("auth -test"), 5);
+        assert_eq!(explained.len(), 1);
+        assert_eq!(explained[0].doc_id, 1);
+    }
 }
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use lazy_static::lazy_static;
+
+static NEXT_OP_ID: AtomicU64 = AtomicU64::new(1);
+
+lazy_static! {
+    /// Operations reserved with `begin` that have not finished yet
+    static ref OPERATIONS: Mutex<HashMap<u64, Arc<AtomicBool>>> = Mutex::new(HashMap::new());
+}
+
+fn operations() -> MutexGuard<'static, HashMap<u64, Arc<AtomicBool>>> {
+    OPERATIONS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Reserve an operation id before starting a long call (grep, index build).
+/// Pass the id to the call, then `cancel` it from another thread to stop the
+/// call early. The id is released when the call returns.
+pub fn begin() -> u64 {
+    let op_id = NEXT_OP_ID.fetch_add(1, Ordering::SeqCst);
+    operations().insert(op_id, Arc::new(AtomicBool::new(false)));
+    op_id
+}
+
+/// Ask the operation to stop. Returns false if the id is unknown or the
+/// operation has already finished.
+pub fn cancel(op_id: u64) -> bool {
+    match operations().get(&op_id) {
+        Some(flag) => {
+            flag.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Polled by long-running loops between files. Dropping the token finishes
+/// the operation and releases its id.
+#[derive(Debug, Default)]
+pub struct CancelToken {
+    op_id: Option<u64>,
+    flag: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// A token that is never cancelled
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Token for an id from `begin`. Unknown ids get a token that is never
+    /// cancelled, so a stale id does not fail the call.
+    pub fn for_op(op_id: u64) -> Self {
+        match operations().get(&op_id) {
+            Some(flag) => Self {
+                op_id: Some(op_id),
+                flag: Arc::clone(flag),
+            },
+            None => Self::none(),
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for CancelToken {
+    fn drop(&mut self) {
+        if let Some(op_id) = self.op_id {
+            operations().remove(&op_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_reaches_token_until_finished() {
+        let op_id = begin();
+        let token = CancelToken::for_op(op_id);
+        assert!(!token.is_cancelled());
+
+        assert!(cancel(op_id));
+        assert!(token.is_cancelled());
+
+        drop(token);
+        assert!(!cancel(op_id));
+        assert!(!CancelToken::for_op(op_id).is_cancelled());
+        assert!(!CancelToken::none().is_cancelled());
+    }
+}
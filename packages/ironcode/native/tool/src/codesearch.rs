@@ -1,17 +1,28 @@
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Mutex;
 
-use ignore::Walk;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::WalkBuilder;
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 
-use crate::bm25::{tokenize, Bm25Index};
-use crate::indexer::{detect_language, extract_symbols, language_name, CodeSymbol};
+use crate::bm25::{tokenize, Bm25Index, Query, TermContribution};
+use crate::cancel::CancelToken;
+use crate::indexer::{
+    extract_symbols, language_name, sniff_language, CodeSymbol, Language, SymbolKind,
+};
+use crate::path::fs_normalize;
 
 /// Max file size to index (512 KB)
 const MAX_FILE_BYTES: u64 = 512 * 1024;
 
+/// BM25 candidates fetched per requested result, so kind boosting can promote
+/// symbols that ranked just below the cut
+const BOOST_CANDIDATE_FACTOR: usize = 4;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SearchResult {
     pub symbol: CodeSymbol,
@@ -25,18 +36,332 @@ pub struct IndexStats {
     pub total_terms: usize,
     pub languages: HashMap<String, usize>,
     pub index_time_ms: u64,
+    /// Approximate memory held by the index, for warning on very large
+    /// projects. Not exact: allocator and hash table overhead are ignored.
+    #[serde(default)]
+    pub bytes_used: usize,
+    /// Set when the last build was cancelled, so the index is partial
+    #[serde(default)]
+    pub cancelled: bool,
+    /// Files skipped as generated or minified (see `IndexConfig`)
+    #[serde(default)]
+    pub skipped_generated: usize,
+    /// Updates skipped because the file's content was unchanged since it was indexed
+    #[serde(default)]
+    pub cache_hits: usize,
+    /// Language with the most symbols; ties go to the alphabetically first
+    #[serde(default)]
+    pub primary_language: Option<String>,
+}
+
+/// Build options for `index_project_with_config`: which files are treated as
+/// generated and skipped (minified bundles otherwise fill the index with huge
+/// single-line chunks), and how symbol text is weighted.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct IndexConfig {
+    /// File name globs that are always skipped, e.g. `*.min.js`
+    pub generated_patterns: Vec<String>,
+    /// Skip files whose average line length in bytes exceeds this; 0 disables the check
+    pub max_avg_line_length: usize,
+    /// How many times a symbol's name tokens are indexed. Raising a term's
+    /// frequency is a cheap BM25 boost, so name matches outrank matches deep in
+    /// a body. Values below 1 count as 1.
+    pub name_boost: usize,
+    /// Extra gitignore-style patterns excluded from this build on top of
+    /// `.gitignore`, e.g. `tests/`. A `!` pattern re-includes paths an
+    /// earlier pattern excluded; as in git, it can't reach inside an
+    /// excluded directory.
+    pub ignore_globs: Vec<String>,
+}
+
+impl Default for IndexConfig {
+    fn default() -> Self {
+        Self {
+            generated_patterns: ["*.min.js", "*.min.css", "*.bundle.js", "*-lock.json"]
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            max_avg_line_length: 300,
+            name_boost: 3,
+            ignore_globs: Vec::new(),
+        }
+    }
+}
+
+impl IndexConfig {
+    fn generated_set(&self) -> Result<GlobSet, String> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &self.generated_patterns {
+            builder.add(Glob::new(pattern).map_err(|e| format!("Invalid glob: {}", e))?);
+        }
+        builder
+            .build()
+            .map_err(|e| format!("Failed to build glob set: {}", e))
+    }
+
+    fn ignore_matcher(&self, root: &str) -> Result<Gitignore, String> {
+        let mut builder = GitignoreBuilder::new(root);
+        for pattern in &self.ignore_globs {
+            builder
+                .add_line(None, pattern)
+                .map_err(|e| format!("Invalid ignore glob '{}': {}", pattern, e))?;
+        }
+        builder
+            .build()
+            .map_err(|e| format!("Failed to build ignore matcher: {}", e))
+    }
+}
+
+/// The file filters of an `IndexConfig`, compiled for one build and kept on
+/// the index so `update_file` and `reindex_changed` apply the same rules
+#[derive(Clone)]
+struct FileFilter {
+    generated: GlobSet,
+    ignored: Gitignore,
+    max_avg_line_length: usize,
+}
+
+impl FileFilter {
+    fn new(config: &IndexConfig, root: &str) -> Result<Self, String> {
+        Ok(Self {
+            generated: config.generated_set()?,
+            ignored: config.ignore_matcher(root)?,
+            max_avg_line_length: config.max_avg_line_length,
+        })
+    }
+
+    /// Filters nothing; used until the first build
+    fn none() -> Self {
+        Self {
+            generated: GlobSet::empty(),
+            ignored: Gitignore::empty(),
+            max_avg_line_length: 0,
+        }
+    }
+
+    /// Whether `ignore_globs` exclude `path` or a parent of it
+    fn is_excluded(&self, path: &Path) -> bool {
+        // The matcher panics on absolute paths outside its root
+        if path.has_root() && !path.starts_with(self.ignored.path()) {
+            return false;
+        }
+        self.ignored
+            .matched_path_or_any_parents(path, false)
+            .is_ignore()
+    }
+
+    fn is_generated_name(&self, path: &Path) -> bool {
+        path.file_name()
+            .is_some_and(|name| self.generated.is_match(name))
+    }
+
+    fn looks_minified(&self, source: &[u8]) -> bool {
+        if self.max_avg_line_length == 0 || source.is_empty() {
+            return false;
+        }
+        let lines = source
+            .split(|&b| b == b'\n')
+            .filter(|l| !l.is_empty())
+            .count();
+        source.len() / lines.max(1) > self.max_avg_line_length
+    }
+}
+
+/// Files found by `walk_candidates`
+struct Candidates {
+    files: Vec<(PathBuf, Language)>,
+    skipped_generated: usize,
+    cancelled: bool,
+}
+
+/// Files under `project_path` an index build reads: outside `.gitignore` and
+/// the ignore globs, at most `MAX_FILE_BYTES`, in a supported language and not
+/// named like a generated file. Minified content is only caught once read.
+fn walk_candidates(project_path: &str, filter: &FileFilter, cancel: &CancelToken) -> Candidates {
+    let mut candidates = Candidates {
+        files: Vec::new(),
+        skipped_generated: 0,
+        cancelled: false,
+    };
+    let ignored = filter.ignored.clone();
+    let walk = WalkBuilder::new(project_path)
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            !ignored.matched(entry.path(), is_dir).is_ignore()
+        })
+        .build();
+    for result in walk {
+        if cancel.is_cancelled() {
+            candidates.cancelled = true;
+            break;
+        }
+        let entry = match result {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        // Skip large files
+        if let Ok(meta) = path.metadata() {
+            if meta.len() > MAX_FILE_BYTES {
+                continue;
+            }
+        }
+        let lang = match sniff_language(path) {
+            Some(l) => l,
+            None => continue,
+        };
+        if filter.is_generated_name(path) {
+            candidates.skipped_generated += 1;
+            continue;
+        }
+        candidates.files.push((path.to_path_buf(), lang));
+    }
+    candidates
+}
+
+/// Diagnostic search hit: the BM25 term breakdown plus the kind weight applied.
+/// `score == bm25_score * kind_weight`, and the term scores sum to `bm25_score`.
+#[derive(Debug, Serialize, Clone)]
+pub struct ExplainedResult {
+    pub symbol: CodeSymbol,
+    pub score: f64,
+    pub bm25_score: f64,
+    pub kind_weight: f64,
+    pub terms: Vec<TermContribution>,
+}
+
+/// Ranking knobs for `search_with_config`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SearchConfig {
+    /// Score multiplier per symbol kind, applied after BM25. Kinds not listed
+    /// keep a weight of 1.0.
+    pub kind_weights: HashMap<SymbolKind, f64>,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        // Line chunks are a fallback; structured symbols should outrank them
+        Self {
+            kind_weights: HashMap::from([(SymbolKind::Chunk, 0.5)]),
+        }
+    }
+}
+
+impl SearchConfig {
+    fn weight(&self, kind: SymbolKind) -> f64 {
+        self.kind_weights.get(&kind).copied().unwrap_or(1.0)
+    }
+}
+
+/// Search hits from one file, from `search_grouped`
+#[derive(Debug, Serialize, Clone)]
+pub struct FileResults {
+    pub file_path: String,
+    /// Sum of the member scores
+    pub file_score: f64,
+    /// Best first
+    pub symbols: Vec<SearchResult>,
+}
+
+/// A ranked result set held for paging, from `search_cursor`
+#[derive(Debug, Serialize, Clone)]
+pub struct SearchCursor {
+    pub cursor_id: u64,
+    pub total: usize,
+}
+
+/// One batch from `search_next`
+#[derive(Debug, Serialize, Clone)]
+pub struct SearchBatch {
+    pub results: Vec<SearchResult>,
+    /// No results remain; the cursor has been freed
+    pub done: bool,
+}
+
+/// Full source of one symbol, from `read_symbol`
+#[derive(Debug, Serialize, Clone)]
+pub struct SymbolSource {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub line_start: usize,
+    pub line_end: usize,
+    /// Lines `line_start..=line_end`, untruncated, without the final newline
+    pub content: String,
+}
+
+/// Outcome of `reindex_changed`
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ReindexStats {
+    /// Files re-indexed because they were modified after `since_ms`
+    pub files_updated: usize,
+    /// Files modified after `since_ms` whose content was unchanged, so their
+    /// symbols were kept as they were
+    pub cache_hits: usize,
+    /// Indexed files dropped because they no longer exist or are now skipped
+    pub files_removed: usize,
+    pub index_time_ms: u64,
+}
+
+/// Progress of the current (or last) `index_project` run.
+/// `files_total` stays 0 while the walk is still collecting candidate files.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct IndexProgress {
+    pub files_indexed: usize,
+    pub files_total: usize,
+    pub running: bool,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    /// Waiting for an earlier build to finish
+    Queued,
+    Running,
+    Done,
+    Failed,
+}
+
+/// Status of a background index job started with `index_project_async`.
+#[derive(Debug, Serialize, Clone)]
+pub struct JobStatus {
+    pub state: JobState,
+    pub progress: IndexProgress,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<IndexStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Documents of one indexed file
+struct FileDocs {
+    doc_ids: Vec<usize>,
+    /// Hash of the source the documents were built from; re-adding identical
+    /// content is skipped
+    content_hash: blake3::Hash,
 }
 
 struct Inner {
     bm25: Bm25Index,
     /// doc_id → symbol (None = deleted slot)
     symbols: Vec<Option<CodeSymbol>>,
-    /// file_path → list of doc_ids
-    file_docs: HashMap<String, Vec<usize>>,
+    /// file_path → its doc_ids and content hash
+    file_docs: HashMap<String, FileDocs>,
+    /// lowercased symbol name → list of doc_ids, for exact name lookups
+    names: HashMap<String, Vec<usize>>,
     /// Freed doc_id slots for reuse
     free_ids: Vec<usize>,
     next_id: usize,
     stats: IndexStats,
+    /// `IndexConfig::name_boost` of the last build, reused by `update_file`
+    name_boost: usize,
+    /// Filters of the last build's `IndexConfig`, so `update_file` and
+    /// `reindex_changed` don't bring excluded files back
+    filter: FileFilter,
 }
 
 impl Inner {
@@ -45,9 +370,12 @@ impl Inner {
             bm25: Bm25Index::new(),
             symbols: Vec::new(),
             file_docs: HashMap::new(),
+            names: HashMap::new(),
             free_ids: Vec::new(),
             next_id: 0,
             stats: IndexStats::default(),
+            name_boost: IndexConfig::default().name_boost,
+            filter: FileFilter::none(),
         }
     }
 
@@ -62,12 +390,23 @@ impl Inner {
         }
     }
 
-    fn add_file(&mut self, file_path: &str, source: &[u8], lang: crate::indexer::Language) {
+    /// Index `source` as `file_path`. Returns false when the indexed content
+    /// already had the same hash and nothing was re-parsed.
+    fn add_file(&mut self, file_path: &str, source: &[u8], lang: crate::indexer::Language) -> bool {
+        let content_hash = blake3::hash(source);
+        if self
+            .file_docs
+            .get(file_path)
+            .is_some_and(|docs| docs.content_hash == content_hash)
+        {
+            self.stats.cache_hits += 1;
+            return false;
+        }
         self.remove_file(file_path);
 
         let syms = extract_symbols(file_path, source, lang);
         if syms.is_empty() {
-            return;
+            return true;
         }
 
         let lang_str = language_name(lang).to_string();
@@ -78,22 +417,41 @@ impl Inner {
         let mut doc_ids = Vec::with_capacity(syms.len());
         for sym in syms {
             let doc_id = self.alloc_id();
-            // Index: name + kind + content
-            let text = format!("{} {} {}", sym.name, sym.kind, sym.content);
-            let tokens = tokenize(&text);
+            // Index: name (boosted) + kind + parameters + content
+            let text = format!(
+                "{} {} {} {}",
+                sym.name, sym.kind, sym.parameters, sym.content
+            );
+            let mut tokens = tokenize(&text);
+            let name_tokens = tokenize(&sym.name);
+            for _ in 1..self.name_boost {
+                tokens.extend(name_tokens.iter().cloned());
+            }
             self.bm25.add_document(doc_id, &tokens);
+            self.names
+                .entry(sym.name.to_lowercase())
+                .or_default()
+                .push(doc_id);
             self.symbols[doc_id] = Some(sym);
             doc_ids.push(doc_id);
         }
-        self.file_docs.insert(file_path.to_string(), doc_ids);
+        self.file_docs.insert(
+            file_path.to_string(),
+            FileDocs {
+                doc_ids,
+                content_hash,
+            },
+        );
+        true
     }
 
     fn remove_file(&mut self, file_path: &str) {
-        if let Some(doc_ids) = self.file_docs.remove(file_path) {
-            for doc_id in &doc_ids {
+        if let Some(docs) = self.file_docs.remove(file_path) {
+            for doc_id in &docs.doc_ids {
                 self.bm25.remove_document(*doc_id);
                 if *doc_id < self.symbols.len() {
                     if let Some(sym) = self.symbols[*doc_id].take() {
+                        self.forget_name(&sym.name, *doc_id);
                         if let Some(cnt) = self.stats.languages.get_mut(&sym.language) {
                             *cnt = cnt.saturating_sub(1);
                         }
@@ -106,18 +464,91 @@ impl Inner {
         }
     }
 
-    fn search(&self, query: &str, top_k: usize) -> Vec<SearchResult> {
-        let tokens = tokenize(query);
-        self.bm25
-            .search(&tokens, top_k)
+    fn forget_name(&mut self, name: &str, doc_id: usize) {
+        let key = name.to_lowercase();
+        if let Some(ids) = self.names.get_mut(&key) {
+            ids.retain(|id| *id != doc_id);
+            if ids.is_empty() {
+                self.names.remove(&key);
+            }
+        }
+    }
+
+    fn find_by_name(&self, name: &str, exact: bool) -> Vec<CodeSymbol> {
+        let mut found: Vec<CodeSymbol> = self
+            .names
+            .get(&name.to_lowercase())
+            .into_iter()
+            .flatten()
+            .filter_map(|doc_id| self.symbols.get(*doc_id)?.as_ref())
+            .filter(|sym| !exact || sym.name == name)
+            .cloned()
+            .collect();
+        found.sort_by(|a, b| {
+            a.file_path
+                .cmp(&b.file_path)
+                .then(a.line_start.cmp(&b.line_start))
+        });
+        found
+    }
+
+    fn search(&self, query: &str, top_k: usize, config: &SearchConfig) -> Vec<SearchResult> {
+        let query = Query::parse(query);
+        let candidates = top_k.saturating_mul(BOOST_CANDIDATE_FACTOR);
+        let mut results: Vec<SearchResult> = self
+            .bm25
+            .search_query(&query, candidates)
             .into_iter()
             .filter_map(|(doc_id, score)| {
                 self.symbols.get(doc_id)?.as_ref().map(|sym| SearchResult {
                     symbol: sym.clone(),
-                    score,
+                    score: score * config.weight(sym.kind),
+                })
+            })
+            .collect();
+        // Stable sort keeps BM25 order among equal boosted scores
+        results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let mut results = dedup_overlapping_chunks(results, |r| &r.symbol);
+        results.truncate(top_k);
+        results
+    }
+
+    fn search_explain(
+        &self,
+        query: &str,
+        top_k: usize,
+        config: &SearchConfig,
+    ) -> Vec<ExplainedResult> {
+        let query = Query::parse(query);
+        let candidates = top_k.saturating_mul(BOOST_CANDIDATE_FACTOR);
+        let mut results: Vec<ExplainedResult> = self
+            .bm25
+            .search_explain_query(&query, candidates)
+            .into_iter()
+            .filter_map(|explanation| {
+                let sym = self.symbols.get(explanation.doc_id)?.as_ref()?;
+                let kind_weight = config.weight(sym.kind);
+                Some(ExplainedResult {
+                    symbol: sym.clone(),
+                    score: explanation.score * kind_weight,
+                    bm25_score: explanation.score,
+                    kind_weight,
+                    terms: explanation.terms,
                 })
             })
-            .collect()
+            .collect();
+        results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let mut results = dedup_overlapping_chunks(results, |r| &r.symbol);
+        results.truncate(top_k);
+        results
     }
 
     fn stats(&self) -> IndexStats {
@@ -127,83 +558,531 @@ impl Inner {
             total_terms: self.bm25.term_count(),
             languages: self.stats.languages.clone(),
             index_time_ms: self.stats.index_time_ms,
+            bytes_used: self.estimated_bytes(),
+            cancelled: self.stats.cancelled,
+            skipped_generated: self.stats.skipped_generated,
+            cache_hits: self.stats.cache_hits,
+            primary_language: self
+                .stats
+                .languages
+                .iter()
+                .filter(|(_, &count)| count > 0)
+                .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+                .map(|(lang, _)| lang.clone()),
+        }
+    }
+
+    fn estimated_bytes(&self) -> usize {
+        let symbols: usize = self
+            .symbols
+            .iter()
+            .flatten()
+            .map(|sym| {
+                sym.file_path.len()
+                    + sym.name.len()
+                    + sym.content.len()
+                    + sym.language.len()
+                    + sym.parameters.len()
+            })
+            .sum();
+        let id_lists: usize = self
+            .file_docs
+            .iter()
+            .map(|(key, docs)| (key, &docs.doc_ids))
+            .chain(self.names.iter())
+            .map(|(key, ids)| key.len() + ids.len() * std::mem::size_of::<usize>())
+            .sum::<usize>()
+            + self.file_docs.len() * std::mem::size_of::<blake3::Hash>();
+        self.bm25.estimated_bytes()
+            + symbols
+            + self.symbols.len() * std::mem::size_of::<Option<CodeSymbol>>()
+            + id_lists
+            + self.free_ids.len() * std::mem::size_of::<usize>()
+    }
+}
+
+/// Drop line chunks that overlap a better-scoring chunk from the same file.
+/// Line chunks overlap by design, so one match region can otherwise show up
+/// twice. `results` must already be sorted by descending score.
+fn dedup_overlapping_chunks<T>(results: Vec<T>, symbol: impl Fn(&T) -> &CodeSymbol) -> Vec<T> {
+    let mut kept: Vec<T> = Vec::with_capacity(results.len());
+    for result in results {
+        let sym = symbol(&result);
+        let duplicate = sym.kind == SymbolKind::Chunk
+            && kept.iter().any(|k| {
+                let other = symbol(k);
+                other.kind == SymbolKind::Chunk
+                    && other.file_path == sym.file_path
+                    && other.line_start <= sym.line_end
+                    && sym.line_start <= other.line_end
+            });
+        if !duplicate {
+            kept.push(result);
         }
     }
+    kept
 }
 
 lazy_static! {
     static ref INDEX: Mutex<Inner> = Mutex::new(Inner::new());
+    static ref JOBS: Mutex<HashMap<u64, JobStatus>> = Mutex::new(HashMap::new());
+    static ref CURSORS: Mutex<HashMap<u64, VecDeque<SearchResult>>> = Mutex::new(HashMap::new());
 }
 
+/// Serializes index builds; INDEX itself is only locked per file so searches
+/// can run while a build is in progress.
+static INDEX_RUN: Mutex<()> = Mutex::new(());
+static NEXT_JOB_ID: AtomicU64 = AtomicU64::new(1);
+static NEXT_CURSOR_ID: AtomicU64 = AtomicU64::new(1);
+
+// Progress counters live outside INDEX so they can be polled without
+// contending with the build.
+static FILES_INDEXED: AtomicUsize = AtomicUsize::new(0);
+static FILES_TOTAL: AtomicUsize = AtomicUsize::new(0);
+static INDEXING: AtomicBool = AtomicBool::new(false);
+
 // ── Public API ────────────────────────────────────────────────────────────────
 
 /// Walk a project directory and build the BM25 index.
 /// Respects .gitignore via the `ignore` crate.
+///
+/// The previous index is cleared up front and files are added one at a time,
+/// so searches issued while this runs see whatever has been indexed so far.
+/// Concurrent calls are serialized.
+#[cfg_attr(feature = "logging", tracing::instrument(skip_all, fields(project_path = %project_path), err))]
 pub fn index_project(project_path: &str) -> Result<IndexStats, String> {
+    index_project_with_cancel(project_path, &CancelToken::none())
+}
+
+/// `index_project` that stops early once `cancel` fires. The files indexed so
+/// far stay searchable and the returned stats have `cancelled` set.
+pub fn index_project_with_cancel(
+    project_path: &str,
+    cancel: &CancelToken,
+) -> Result<IndexStats, String> {
+    index_project_with_config(project_path, &IndexConfig::default(), cancel)
+}
+
+/// `index_project_with_cancel` with explicit generated-file heuristics.
+/// Skipped files are counted in `IndexStats::skipped_generated`.
+pub fn index_project_with_config(
+    project_path: &str,
+    config: &IndexConfig,
+    cancel: &CancelToken,
+) -> Result<IndexStats, String> {
+    let filter = FileFilter::new(config, project_path)?;
+    let _run = lock_run();
+    build_index(project_path, config, filter, cancel)
+}
+
+/// Body of `index_project_with_config`; the caller holds `INDEX_RUN`
+fn build_index(
+    project_path: &str,
+    config: &IndexConfig,
+    filter: FileFilter,
+    cancel: &CancelToken,
+) -> Result<IndexStats, String> {
     let start = std::time::Instant::now();
 
-    let mut inner = INDEX.lock().map_err(|e| format!("lock: {}", e))?;
-    *inner = Inner::new();
+    *INDEX.lock().map_err(|e| format!("lock: {}", e))? = Inner {
+        name_boost: config.name_boost,
+        filter: filter.clone(),
+        ..Inner::new()
+    };
 
-    for result in Walk::new(project_path) {
-        let entry = match result {
-            Ok(e) => e,
-            Err(_) => continue,
-        };
-        let path = entry.path();
-        if !path.is_file() {
-            continue;
+    FILES_INDEXED.store(0, Ordering::SeqCst);
+    FILES_TOTAL.store(0, Ordering::SeqCst);
+    INDEXING.store(true, Ordering::SeqCst);
+
+    // Collect candidates first so progress has a known total
+    let Candidates {
+        files,
+        mut skipped_generated,
+        mut cancelled,
+    } = walk_candidates(project_path, &filter, cancel);
+    FILES_TOTAL.store(files.len(), Ordering::SeqCst);
+
+    for (path, lang) in files {
+        if cancel.is_cancelled() {
+            cancelled = true;
+            break;
         }
-        // Skip large files
-        if let Ok(meta) = path.metadata() {
-            if meta.len() > MAX_FILE_BYTES {
+        if let Ok(source) = std::fs::read(&path) {
+            if filter.looks_minified(&source) {
+                skipped_generated += 1;
+                FILES_INDEXED.fetch_add(1, Ordering::SeqCst);
                 continue;
             }
+            let path_str = fs_normalize(&path.to_string_lossy());
+            match INDEX.lock() {
+                Ok(mut inner) => {
+                    inner.add_file(&path_str, &source, lang);
+                }
+                Err(e) => {
+                    INDEXING.store(false, Ordering::SeqCst);
+                    return Err(format!("lock: {}", e));
+                }
+            }
         }
-        let lang = match detect_language(path) {
-            Some(l) => l,
-            None => continue,
-        };
-        let source = match std::fs::read(path) {
-            Ok(s) => s,
-            Err(_) => continue,
-        };
-        let path_str = path.to_string_lossy().to_string();
-        inner.add_file(&path_str, &source, lang);
+        FILES_INDEXED.fetch_add(1, Ordering::SeqCst);
     }
 
+    INDEXING.store(false, Ordering::SeqCst);
+    let mut inner = INDEX.lock().map_err(|e| format!("lock: {}", e))?;
     inner.stats.index_time_ms = start.elapsed().as_millis() as u64;
+    inner.stats.cancelled = cancelled;
+    inner.stats.skipped_generated = skipped_generated;
     Ok(inner.stats())
 }
 
-/// Search the index for the given query string.
+/// Run `index_project` on a background thread and return a job id to poll
+/// with `job_status`. Searches during the build behave as described on
+/// `index_project`: they see the files indexed so far.
+pub fn index_project_async(project_path: &str) -> u64 {
+    let job_id = NEXT_JOB_ID.fetch_add(1, Ordering::SeqCst);
+    set_job(
+        job_id,
+        JobStatus {
+            state: JobState::Queued,
+            progress: IndexProgress::default(),
+            stats: None,
+            error: None,
+        },
+    );
+
+    let project_path = project_path.to_string();
+    std::thread::spawn(move || {
+        run_job(job_id, || {
+            let config = IndexConfig::default();
+            let filter = FileFilter::new(&config, &project_path)?;
+            build_index(&project_path, &config, filter, &CancelToken::none())
+        })
+    });
+    job_id
+}
+
+/// Wait for `INDEX_RUN`. It guards no data, so a panicked build's poison is
+/// ignored.
+fn lock_run() -> std::sync::MutexGuard<'static, ()> {
+    INDEX_RUN.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Run `build` as job `job_id` once earlier builds are done, holding
+/// `INDEX_RUN`, and record its outcome. A panic marks the job failed rather
+/// than leaving it running forever, and resets the half-built index so later
+/// builds and searches still work.
+fn run_job(job_id: u64, build: impl FnOnce() -> Result<IndexStats, String>) {
+    let _run = lock_run();
+    set_job(
+        job_id,
+        JobStatus {
+            state: JobState::Running,
+            progress: IndexProgress::default(),
+            stats: None,
+            error: None,
+        },
+    );
+    let result =
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(build)).unwrap_or_else(|panic| {
+            INDEXING.store(false, Ordering::SeqCst);
+            *INDEX.lock().unwrap_or_else(|e| e.into_inner()) = Inner::new();
+            INDEX.clear_poison();
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            Err(format!("index job panicked: {}", message))
+        });
+    let progress = index_progress();
+    let status = match result {
+        Ok(stats) => JobStatus {
+            state: JobState::Done,
+            progress,
+            stats: Some(stats),
+            error: None,
+        },
+        Err(e) => JobStatus {
+            state: JobState::Failed,
+            progress,
+            stats: None,
+            error: Some(e),
+        },
+    };
+    set_job(job_id, status);
+}
+
+/// Status of a job started with `index_project_async`, or None for an unknown id.
+/// While the job is running, `progress` is the live `index_progress()`; a
+/// queued job reports no progress, since the counters belong to another build. A
+/// finished job is reported once and then forgotten, so later calls return None.
+pub fn job_status(job_id: u64) -> Option<JobStatus> {
+    let mut jobs = JOBS.lock().ok()?;
+    let status = jobs.get_mut(&job_id)?;
+    if status.state == JobState::Queued {
+        return Some(status.clone());
+    }
+    if status.state == JobState::Running {
+        let mut status = status.clone();
+        status.progress = index_progress();
+        return Some(status);
+    }
+    jobs.remove(&job_id)
+}
+
+fn set_job(job_id: u64, status: JobStatus) {
+    if let Ok(mut jobs) = JOBS.lock() {
+        jobs.insert(job_id, status);
+    }
+}
+
+/// Poll indexing progress. Safe to call from another thread while
+/// `index_project` is running.
+pub fn index_progress() -> IndexProgress {
+    IndexProgress {
+        files_indexed: FILES_INDEXED.load(Ordering::SeqCst),
+        files_total: FILES_TOTAL.load(Ordering::SeqCst),
+        running: INDEXING.load(Ordering::SeqCst),
+    }
+}
+
+/// Search the index for the given query string. Words prefixed with `+`
+/// must appear in a result and words prefixed with `-` must not.
 pub fn search(query: &str, top_k: usize) -> Result<Vec<SearchResult>, String> {
+    search_with_config(query, top_k, &SearchConfig::default())
+}
+
+/// `search` with the top `top_k` hits grouped by file. Files are ordered by
+/// their best hit, so the first group holds the overall best match.
+pub fn search_grouped(query: &str, top_k: usize) -> Result<Vec<FileResults>, String> {
+    let mut groups: Vec<FileResults> = Vec::new();
+    let mut group_of: HashMap<String, usize> = HashMap::new();
+    // Results arrive best first, so a file's first hit fixes its position
+    for result in search(query, top_k)? {
+        let index = *group_of
+            .entry(result.symbol.file_path.clone())
+            .or_insert_with(|| {
+                groups.push(FileResults {
+                    file_path: result.symbol.file_path.clone(),
+                    file_score: 0.0,
+                    symbols: Vec::new(),
+                });
+                groups.len() - 1
+            });
+        groups[index].file_score += result.score;
+        groups[index].symbols.push(result);
+    }
+    Ok(groups)
+}
+
+/// Run `search` and keep the ranked results for `search_next` to hand out in
+/// batches, so a huge `top_k` doesn't have to cross the FFI in one string.
+/// Later index changes don't affect a cursor's results.
+pub fn search_cursor(query: &str, top_k: usize) -> Result<SearchCursor, String> {
+    let results: VecDeque<SearchResult> = search(query, top_k)?.into();
+    let cursor = SearchCursor {
+        cursor_id: NEXT_CURSOR_ID.fetch_add(1, Ordering::SeqCst),
+        total: results.len(),
+    };
+    CURSORS
+        .lock()
+        .map_err(|e| format!("lock: {}", e))?
+        .insert(cursor.cursor_id, results);
+    Ok(cursor)
+}
+
+/// The next `batch` results of a cursor, best first. The batch that empties
+/// the cursor has `done` set and frees it; later calls fail as unknown.
+pub fn search_next(cursor_id: u64, batch: usize) -> Result<SearchBatch, String> {
+    let mut cursors = CURSORS.lock().map_err(|e| format!("lock: {}", e))?;
+    let remaining = cursors
+        .get_mut(&cursor_id)
+        .ok_or_else(|| format!("Unknown search cursor: {}", cursor_id))?;
+    let take = batch.max(1).min(remaining.len());
+    let results: Vec<SearchResult> = remaining.drain(..take).collect();
+    let done = remaining.is_empty();
+    if done {
+        cursors.remove(&cursor_id);
+    }
+    Ok(SearchBatch { results, done })
+}
+
+/// Free a cursor that won't be read to the end. Returns false for an unknown
+/// or already finished cursor.
+pub fn search_cursor_close(cursor_id: u64) -> bool {
+    CURSORS
+        .lock()
+        .map(|mut cursors| cursors.remove(&cursor_id).is_some())
+        .unwrap_or(false)
+}
+
+/// Search with explicit ranking config (e.g. custom per-kind weights).
+pub fn search_with_config(
+    query: &str,
+    top_k: usize,
+    config: &SearchConfig,
+) -> Result<Vec<SearchResult>, String> {
     let inner = INDEX.lock().map_err(|e| format!("lock: {}", e))?;
-    Ok(inner.search(query, top_k))
+    Ok(inner.search(query, top_k, config))
 }
 
-/// Re-index a single file (add/update).
+/// Like `search`, but with per-term score contributions. For diagnosing
+/// relevance (stop words, K1/B tuning) only; it is slower than `search`.
+pub fn search_explain(query: &str, top_k: usize) -> Result<Vec<ExplainedResult>, String> {
+    let inner = INDEX.lock().map_err(|e| format!("lock: {}", e))?;
+    Ok(inner.search_explain(query, top_k, &SearchConfig::default()))
+}
+
+/// All symbols named `name`, ordered by file and line. With `exact` the name
+/// must match case-sensitively; otherwise case is ignored. No ranking is applied.
+pub fn find_by_name(name: &str, exact: bool) -> Result<Vec<CodeSymbol>, String> {
+    let inner = INDEX.lock().map_err(|e| format!("lock: {}", e))?;
+    Ok(inner.find_by_name(name, exact))
+}
+
+/// Source of every symbol in `file_path` named `symbol_name`, in file order.
+/// Matches the full name (`Parser::parse`, `Ns.Foo`) or its last segment
+/// (`parse`, `Foo`). Unlike indexed symbols the content is not truncated.
+/// The file is parsed directly; it need not be indexed.
+pub fn read_symbol(file_path: &str, symbol_name: &str) -> Result<Vec<SymbolSource>, String> {
+    let path = Path::new(file_path);
+    let lang =
+        sniff_language(path).ok_or_else(|| format!("unsupported language: {}", file_path))?;
+    let source = std::fs::read(path).map_err(|e| format!("read: {}", e))?;
+    let text = String::from_utf8_lossy(&source);
+    let lines: Vec<&str> = text.lines().collect();
+
+    let found = extract_symbols(file_path, &source, lang)
+        .into_iter()
+        .filter(|sym| {
+            let short = sym.name.rsplit(['.', ':']).next().unwrap_or(&sym.name);
+            sym.kind != SymbolKind::Chunk && (sym.name == symbol_name || short == symbol_name)
+        })
+        .map(|sym| {
+            let end = sym.line_end.min(lines.len());
+            let start = sym.line_start.min(end + 1);
+            SymbolSource {
+                content: lines[start - 1..end].join("\n"),
+                name: sym.name,
+                kind: sym.kind,
+                line_start: sym.line_start,
+                line_end: sym.line_end,
+            }
+        })
+        .collect();
+    Ok(found)
+}
+
+/// Re-index a single file (add/update). The path is stored lexically normalized
+/// (see `path::fs_normalize`), so `./src/../a.ts` and `a.ts` share one entry.
+/// Files the last build's `IndexConfig` would skip (ignored, generated or
+/// minified) and files over `MAX_FILE_BYTES` are dropped instead.
 pub fn update_file(file_path: &str) -> Result<(), String> {
     let path = Path::new(file_path);
-    let lang = match detect_language(path) {
+    let lang = match sniff_language(path) {
         Some(l) => l,
-        None => return Ok(()), // unsupported extension — silently skip
+        None => return Ok(()), // unsupported language — silently skip
+    };
+    let normalized = fs_normalize(file_path);
+    let filter = {
+        let mut inner = INDEX.lock().map_err(|e| format!("lock: {}", e))?;
+        if inner.filter.is_excluded(path) || inner.filter.is_generated_name(path) {
+            inner.remove_file(&normalized);
+            return Ok(());
+        }
+        inner.filter.clone()
     };
-    // Skip large files
     let meta = path.metadata().map_err(|e| format!("stat: {}", e))?;
-    if meta.len() > MAX_FILE_BYTES {
-        return Ok(());
-    }
-    let source = std::fs::read(path).map_err(|e| format!("read: {}", e))?;
+    let source = if meta.len() > MAX_FILE_BYTES {
+        None
+    } else {
+        let source = std::fs::read(path).map_err(|e| format!("read: {}", e))?;
+        (!filter.looks_minified(&source)).then_some(source)
+    };
     let mut inner = INDEX.lock().map_err(|e| format!("lock: {}", e))?;
-    inner.add_file(file_path, &source, lang);
+    match source {
+        Some(source) => {
+            inner.add_file(&normalized, &source, lang);
+        }
+        None => inner.remove_file(&normalized),
+    }
     Ok(())
 }
 
+/// Cheap refresh after an earlier `index_project`: re-index files under
+/// `project_path` modified after `since_ms` (Unix milliseconds) and drop
+/// indexed files under it that no longer exist or that the last build's
+/// filters now skip (e.g. grown past the size limit). Unchanged files keep
+/// their existing symbols.
+pub fn reindex_changed(project_path: &str, since_ms: u64) -> Result<ReindexStats, String> {
+    let _run = lock_run();
+    let start = std::time::Instant::now();
+    let mut stats = ReindexStats::default();
+
+    let filter = INDEX
+        .lock()
+        .map_err(|e| format!("lock: {}", e))?
+        .filter
+        .clone();
+    let candidates = walk_candidates(project_path, &filter, &CancelToken::none());
+    // Candidates that stay indexed; every other indexed file under the root is dropped
+    let mut kept: HashSet<String> = HashSet::new();
+    for (path, lang) in candidates.files {
+        let path_str = fs_normalize(&path.to_string_lossy());
+        let modified_ms = path
+            .metadata()
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|age| age.as_millis() as u64)
+            .unwrap_or(0);
+        if modified_ms > since_ms {
+            // Deleted since the walk, or now minified: dropped below
+            let source = match std::fs::read(&path) {
+                Ok(source) if !filter.looks_minified(&source) => source,
+                _ => continue,
+            };
+            let reindexed = INDEX
+                .lock()
+                .map_err(|e| format!("lock: {}", e))?
+                .add_file(&path_str, &source, lang);
+            if reindexed {
+                stats.files_updated += 1;
+            } else {
+                stats.cache_hits += 1;
+            }
+        }
+        kept.insert(path_str);
+    }
+
+    let root = fs_normalize(project_path);
+    let mut inner = INDEX.lock().map_err(|e| format!("lock: {}", e))?;
+    let missing: Vec<String> = inner
+        .file_docs
+        .keys()
+        .filter(|key| {
+            let file = Path::new(key.as_str());
+            let under_root = if root == "." {
+                file.is_relative()
+            } else {
+                file.starts_with(&root)
+            };
+            under_root && !kept.contains(key.as_str())
+        })
+        .cloned()
+        .collect();
+    for file in &missing {
+        inner.remove_file(file);
+    }
+    stats.files_removed = missing.len();
+    stats.index_time_ms = start.elapsed().as_millis() as u64;
+    Ok(stats)
+}
+
 /// Remove a file's symbols from the index.
 pub fn remove_file(file_path: &str) -> Result<(), String> {
     let mut inner = INDEX.lock().map_err(|e| format!("lock: {}", e))?;
-    inner.remove_file(file_path);
+    inner.remove_file(&fs_normalize(file_path));
     Ok(())
 }
 
@@ -212,3 +1091,598 @@ pub fn get_stats() -> Result<IndexStats, String> {
     let inner = INDEX.lock().map_err(|e| format!("lock: {}", e))?;
     Ok(inner.stats())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::fs;
+
+    // The index is global, so tests that touch it must not run concurrently
+    static TEST_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_index_progress_reaches_total() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = temp_dir("progress");
+        for i in 0..3 {
+            fs::write(
+                dir.join(format!("mod{}.rs", i)),
+                format!("fn handler_{}() {{}}\n", i),
+            )
+            .unwrap();
+        }
+        fs::write(dir.join("notes.unknown"), "not indexed").unwrap();
+
+        index_project(dir.to_str().unwrap()).unwrap();
+        let progress = index_progress();
+        assert!(!progress.running);
+        assert_eq!(progress.files_total, 3);
+        assert_eq!(progress.files_indexed, progress.files_total);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_index_detects_language_by_shebang() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = temp_dir("shebang");
+        fs::write(
+            dir.join("deploy"),
+            "#!/usr/bin/env python3\n\ndef publish_release():\n    pass\n",
+        )
+        .unwrap();
+        fs::write(dir.join("Makefile"), "build:\n\tcargo build\n").unwrap();
+        fs::write(dir.join("README"), "no shebang here\n").unwrap();
+
+        let stats = index_project(dir.to_str().unwrap()).unwrap();
+        assert_eq!(stats.total_files, 2);
+        assert_eq!(stats.languages.get("python"), Some(&1));
+        assert!(stats.languages.contains_key("makefile"));
+
+        let results = search("publish_release", 5).unwrap();
+        assert_eq!(results[0].symbol.language, "python");
+        assert_eq!(results[0].symbol.name, "publish_release");
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_stats_report_primary_language() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = temp_dir("primary");
+        for i in 0..3 {
+            fs::write(
+                dir.join(format!("mod{}.rs", i)),
+                format!("fn load_{0}() {{}}\nfn save_{0}() {{}}\n", i),
+            )
+            .unwrap();
+        }
+        fs::write(dir.join("tool.py"), "def helper():\n    pass\n").unwrap();
+
+        let stats = index_project(dir.to_str().unwrap()).unwrap();
+        assert_eq!(stats.primary_language.as_deref(), Some("rust"));
+        assert_eq!(
+            get_stats().unwrap().primary_language,
+            stats.primary_language
+        );
+
+        // Equal counts resolve alphabetically
+        fs::remove_dir_all(&dir).ok();
+        let dir = temp_dir("primary_tie");
+        fs::write(dir.join("a.rs"), "fn one() {}\n").unwrap();
+        fs::write(dir.join("b.py"), "def two():\n    pass\n").unwrap();
+        let stats = index_project(dir.to_str().unwrap()).unwrap();
+        assert_eq!(stats.languages.get("python"), stats.languages.get("rust"));
+        assert_eq!(stats.primary_language.as_deref(), Some("python"));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_kind_boost_ranks_function_over_chunk() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = temp_dir("boost");
+        fs::write(
+            dir.join("config.rs"),
+            "fn load_settings() {\n    read_settings_file();\n}\n",
+        )
+        .unwrap();
+        // No grammar for Makefiles, so this is indexed as a line chunk
+        fs::write(
+            dir.join("Makefile"),
+            "settings:\n\tload settings settings\n",
+        )
+        .unwrap();
+        // Without the name boost, so only the kind weight separates them
+        let config = IndexConfig {
+            name_boost: 1,
+            ..IndexConfig::default()
+        };
+        index_project_with_config(dir.to_str().unwrap(), &config, &CancelToken::none()).unwrap();
+
+        let unboosted = SearchConfig {
+            kind_weights: HashMap::new(),
+        };
+        let raw = search_with_config("load settings", 5, &unboosted).unwrap();
+        assert_eq!(raw[0].symbol.kind, SymbolKind::Chunk);
+
+        let boosted = search("load settings", 5).unwrap();
+        assert_eq!(boosted[0].symbol.kind, SymbolKind::Function);
+        assert_eq!(boosted[0].symbol.name, "load_settings");
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_find_by_name() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = temp_dir("by_name");
+        fs::write(dir.join("a.rs"), "fn parse_manifest() {}\nfn render() {}\n").unwrap();
+        fs::write(
+            dir.join("b.py"),
+            "def render():\n    pass\n\ndef Render():\n    pass\n",
+        )
+        .unwrap();
+        index_project(dir.to_str().unwrap()).unwrap();
+
+        let unique = find_by_name("parse_manifest", true).unwrap();
+        assert_eq!(unique.len(), 1);
+        assert!(unique[0].file_path.ends_with("a.rs"));
+
+        let exact = find_by_name("render", true).unwrap();
+        assert_eq!(exact.len(), 2);
+        assert!(exact[0].file_path.ends_with("a.rs"));
+        assert!(exact[1].file_path.ends_with("b.py"));
+
+        let any_case = find_by_name("RENDER", false).unwrap();
+        assert_eq!(any_case.len(), 3);
+        assert!(find_by_name("RENDER", true).unwrap().is_empty());
+
+        remove_file(dir.join("b.py").to_str().unwrap()).unwrap();
+        assert_eq!(find_by_name("render", false).unwrap().len(), 1);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_overlapping_chunks_collapse() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = temp_dir("dedup");
+        // 60 lines chunk into 1-50 and 41-60; the match sits in the overlap
+        let script: String = (1..=60)
+            .map(|i| {
+                if i == 1 {
+                    "#!/bin/sh\n".to_string()
+                } else if i == 45 {
+                    "rotate_backups\n".to_string()
+                } else {
+                    format!("echo step {}\n", i)
+                }
+            })
+            .collect();
+        fs::write(dir.join("backup"), script).unwrap();
+        index_project(dir.to_str().unwrap()).unwrap();
+
+        let results = search("rotate_backups", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].symbol.kind, SymbolKind::Chunk);
+        assert!(results[0].symbol.line_start <= 45 && 45 <= results[0].symbol.line_end);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_bytes_used_grows_with_index() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = temp_dir("bytes_used");
+        fs::write(dir.join("small.rs"), "fn tiny() {}\n").unwrap();
+        let before = index_project(dir.to_str().unwrap()).unwrap().bytes_used;
+        assert!(before > 0);
+
+        let body: String = (0..50)
+            .map(|i| format!("fn generated_{}() {{ compute({}); }}\n", i, i))
+            .collect();
+        let big = dir.join("big.rs");
+        fs::write(&big, body).unwrap();
+        update_file(big.to_str().unwrap()).unwrap();
+        let after = get_stats().unwrap().bytes_used;
+        assert!(after > before, "{} should exceed {}", after, before);
+
+        remove_file(big.to_str().unwrap()).unwrap();
+        assert!(get_stats().unwrap().bytes_used < after);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_cancelled_index_returns_early() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = temp_dir("cancel");
+        for i in 0..5 {
+            fs::write(dir.join(format!("m{}.rs", i)), "fn skipped() {}\n").unwrap();
+        }
+
+        let op_id = crate::cancel::begin();
+        crate::cancel::cancel(op_id);
+        let stats =
+            index_project_with_cancel(dir.to_str().unwrap(), &CancelToken::for_op(op_id)).unwrap();
+        assert!(stats.cancelled);
+        assert_eq!(stats.total_files, 0);
+        assert!(!index_progress().running);
+
+        let stats = index_project(dir.to_str().unwrap()).unwrap();
+        assert!(!stats.cancelled);
+        assert_eq!(stats.total_files, 5);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_index_skips_generated_files() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = temp_dir("generated");
+        fs::write(
+            dir.join("app.js"),
+            "function renderApp() {\n  return 1;\n}\n",
+        )
+        .unwrap();
+        fs::write(dir.join("vendor.min.js"), "function minifiedByName(){}\n").unwrap();
+        let minified: String = (0..200)
+            .map(|i| format!("var packed{}=function(){{return {}}};", i, i))
+            .collect();
+        fs::write(dir.join("dist.js"), &minified).unwrap();
+
+        let stats = index_project(dir.to_str().unwrap()).unwrap();
+        assert_eq!(stats.total_files, 1);
+        assert_eq!(stats.skipped_generated, 2);
+        assert!(!search("renderApp", 5).unwrap().is_empty());
+        assert!(search("packed10", 5).unwrap().is_empty());
+
+        let config = IndexConfig {
+            generated_patterns: vec![],
+            max_avg_line_length: 0,
+            ..IndexConfig::default()
+        };
+        let stats = index_project_with_config(dir.to_str().unwrap(), &config, &CancelToken::none())
+            .unwrap();
+        assert_eq!(stats.total_files, 3);
+        assert_eq!(stats.skipped_generated, 0);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_index_skips_ignore_globs() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = temp_dir("ignore_globs");
+        fs::create_dir_all(dir.join("tests")).unwrap();
+        fs::write(dir.join("lib.rs"), "fn productionHandler() {}\n").unwrap();
+        fs::write(dir.join("tests").join("it.rs"), "fn integrationOnly() {}\n").unwrap();
+        fs::write(dir.join("fixture_a.rs"), "fn fixtureAlpha() {}\n").unwrap();
+        fs::write(dir.join("fixture_b.rs"), "fn fixtureBeta() {}\n").unwrap();
+
+        let config = IndexConfig {
+            ignore_globs: vec![
+                "tests/".into(),
+                "fixture_*.rs".into(),
+                "!fixture_b.rs".into(),
+            ],
+            ..IndexConfig::default()
+        };
+        let stats = index_project_with_config(dir.to_str().unwrap(), &config, &CancelToken::none())
+            .unwrap();
+        assert_eq!(stats.total_files, 2);
+        assert_eq!(find_by_name("productionHandler", true).unwrap().len(), 1);
+        assert!(find_by_name("integrationOnly", true).unwrap().is_empty());
+        assert!(find_by_name("fixtureAlpha", true).unwrap().is_empty());
+        assert_eq!(find_by_name("fixtureBeta", true).unwrap().len(), 1);
+
+        // Later updates of excluded paths stay out of the index
+        let added = dir.join("tests").join("more.rs");
+        fs::write(&added, "fn integrationMore() {}\n").unwrap();
+        update_file(added.to_str().unwrap()).unwrap();
+        update_file(dir.join("fixture_a.rs").to_str().unwrap()).unwrap();
+        reindex_changed(dir.to_str().unwrap(), 0).unwrap();
+        assert!(find_by_name("integrationMore", true).unwrap().is_empty());
+        assert!(find_by_name("integrationOnly", true).unwrap().is_empty());
+        assert!(find_by_name("fixtureAlpha", true).unwrap().is_empty());
+        assert_eq!(find_by_name("productionHandler", true).unwrap().len(), 1);
+        assert_eq!(find_by_name("fixtureBeta", true).unwrap().len(), 1);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_search_finds_function_by_parameter_name() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = temp_dir("parameters");
+        fs::write(
+            dir.join("net.rs"),
+            "pub fn connect(host: &str, timeout_ms: u64) -> bool {\n    !host.is_empty()\n}\n\npub fn disconnect() {}\n",
+        )
+        .unwrap();
+
+        index_project(dir.to_str().unwrap()).unwrap();
+        let results = search("timeout_ms", 5).unwrap();
+        assert_eq!(results[0].symbol.name, "connect");
+        assert!(results[0].symbol.parameters.contains("timeout_ms"));
+        assert!(!results.iter().any(|r| r.symbol.name == "disconnect"));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_search_grouped_by_file() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = temp_dir("grouped");
+        fs::write(
+            dir.join("cache.rs"),
+            "fn evict_oldest() {}\n\nfn evict_expired() {}\n\nfn unrelated() {}\n",
+        )
+        .unwrap();
+        fs::write(dir.join("store.rs"), "fn evict() {}\n").unwrap();
+
+        index_project(dir.to_str().unwrap()).unwrap();
+        let flat = search("evict", 10).unwrap();
+        let groups = search_grouped("evict", 10).unwrap();
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].file_path, flat[0].symbol.file_path);
+        assert!(groups[0].symbols[0].score >= groups[1].symbols[0].score);
+        let cache = groups
+            .iter()
+            .find(|g| g.file_path.ends_with("cache.rs"))
+            .unwrap();
+        assert_eq!(cache.symbols.len(), 2);
+        let sum: f64 = cache.symbols.iter().map(|r| r.score).sum();
+        assert!((cache.file_score - sum).abs() < 1e-9);
+        let store = groups
+            .iter()
+            .find(|g| g.file_path.ends_with("store.rs"))
+            .unwrap();
+        assert_eq!(store.symbols.len(), 1);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_search_cursor_pages_all_results() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = temp_dir("cursor");
+        for i in 0..10 {
+            let body: String = (0..10)
+                .map(|j| format!("fn route_handler_{}_{}() {{}}\n\n", i, j))
+                .collect();
+            fs::write(dir.join(format!("routes{}.rs", i)), body).unwrap();
+        }
+
+        index_project(dir.to_str().unwrap()).unwrap();
+        let expected: Vec<String> = search("route handler", 1000)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.symbol.name)
+            .collect();
+        assert_eq!(expected.len(), 100);
+
+        let cursor = search_cursor("route handler", 1000).unwrap();
+        assert_eq!(cursor.total, 100);
+        let mut paged = Vec::new();
+        loop {
+            let batch = search_next(cursor.cursor_id, 7).unwrap();
+            assert!(batch.results.len() <= 7);
+            paged.extend(batch.results.into_iter().map(|r| r.symbol.name));
+            if batch.done {
+                break;
+            }
+        }
+        assert_eq!(paged, expected);
+        assert!(search_next(cursor.cursor_id, 7).is_err());
+        assert!(!search_cursor_close(cursor.cursor_id));
+
+        // A cursor abandoned part way is freed by closing it
+        let cursor = search_cursor("route handler", 1000).unwrap();
+        assert!(!search_next(cursor.cursor_id, 7).unwrap().done);
+        assert!(search_cursor_close(cursor.cursor_id));
+        assert!(search_next(cursor.cursor_id, 7).is_err());
+        assert!(!search_cursor_close(cursor.cursor_id));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_name_match_outranks_body_match() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = temp_dir("name_boost");
+        fs::write(
+            dir.join("ui.rs"),
+            "fn draw_frame(frame: &Frame) {\n    let widget = frame.widget();\n    layout(widget);\n    paint(widget, widget.bounds());\n}\n\nfn build_widget() -> Node {\n    Node::new()\n}\n",
+        )
+        .unwrap();
+
+        index_project(dir.to_str().unwrap()).unwrap();
+        let results = search("widget", 5).unwrap();
+        assert_eq!(results[0].symbol.name, "build_widget");
+        assert!(results.iter().any(|r| r.symbol.name == "draw_frame"));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_update_file_with_same_content_is_cache_hit() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = temp_dir("content_cache");
+        let file = dir.join("lib.rs");
+        fs::write(&file, "fn cached_fn() {}\n").unwrap();
+        index_project(dir.to_str().unwrap()).unwrap();
+        let file = file.to_str().unwrap();
+
+        update_file(file).unwrap();
+        let stats = get_stats().unwrap();
+        assert_eq!(stats.cache_hits, 1);
+        assert_eq!(stats.total_symbols, 1);
+        assert_eq!(find_by_name("cached_fn", true).unwrap().len(), 1);
+
+        fs::write(file, "fn cached_fn() {}\nfn added_fn() {}\n").unwrap();
+        update_file(file).unwrap();
+        let stats = get_stats().unwrap();
+        assert_eq!(stats.cache_hits, 1);
+        assert_eq!(stats.total_symbols, 2);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_reindex_changed_updates_only_modified_files() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = temp_dir("reindex");
+        fs::write(dir.join("a.rs"), "fn alpha_original() {}\n").unwrap();
+        fs::write(dir.join("b.rs"), "fn beta_original() {}\n").unwrap();
+        let root = dir.to_str().unwrap();
+        index_project(root).unwrap();
+
+        let now_ms = || {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64
+        };
+        let since = now_ms();
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        fs::write(dir.join("a.rs"), "fn alpha_renamed() {}\n").unwrap();
+        // Touched but identical: served from the content-hash cache
+        fs::write(dir.join("b.rs"), "fn beta_original() {}\n").unwrap();
+
+        let stats = reindex_changed(root, since).unwrap();
+        assert_eq!(stats.files_updated, 1);
+        assert_eq!(stats.cache_hits, 1);
+        assert_eq!(stats.files_removed, 0);
+        assert!(find_by_name("alpha_original", true).unwrap().is_empty());
+        assert_eq!(find_by_name("alpha_renamed", true).unwrap().len(), 1);
+        assert_eq!(find_by_name("beta_original", true).unwrap().len(), 1);
+
+        let since = now_ms();
+        fs::remove_file(dir.join("b.rs")).unwrap();
+        let stats = reindex_changed(root, since).unwrap();
+        assert_eq!(stats.files_updated, 0);
+        assert_eq!(stats.cache_hits, 0);
+        assert_eq!(stats.files_removed, 1);
+        assert!(find_by_name("beta_original", true).unwrap().is_empty());
+        assert_eq!(get_stats().unwrap().total_files, 1);
+
+        // The build's filters apply: a generated name stays out and a file
+        // grown past the size limit loses its stale symbols
+        let since = now_ms();
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        fs::write(dir.join("vendor.min.js"), "function minifiedHelper() {}\n").unwrap();
+        let big = format!("fn alpha_big() {{}}\n{}", "// pad\n".repeat(80_000));
+        fs::write(dir.join("a.rs"), big).unwrap();
+        let stats = reindex_changed(root, since).unwrap();
+        assert_eq!(stats.files_updated, 0);
+        assert_eq!(stats.files_removed, 1);
+        assert!(find_by_name("minifiedHelper", true).unwrap().is_empty());
+        assert!(find_by_name("alpha_renamed", true).unwrap().is_empty());
+        assert_eq!(get_stats().unwrap().total_files, 0);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_read_symbol_returns_full_body() {
+        let dir = temp_dir("read_symbol");
+        let file = dir.join("lib.rs");
+        let long_body: String = (0..400)
+            .map(|i| format!("    let value_{} = {};\n", i, i))
+            .collect();
+        let source = format!(
+            "struct Parser;\n\nimpl Parser {{\n    fn parse(&self) {{}}\n}}\n\nfn parse() {{\n{}}}\n",
+            long_body
+        );
+        fs::write(&file, &source).unwrap();
+        let path = file.to_str().unwrap();
+
+        let found = read_symbol(path, "parse").unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].name, "Parser::parse");
+        assert_eq!(found[0].content, "    fn parse(&self) {}");
+        assert_eq!((found[0].line_start, found[0].line_end), (4, 4));
+
+        let free = &found[1];
+        assert_eq!(free.line_start, 7);
+        assert_eq!(free.content, format!("fn parse() {{\n{}}}", long_body));
+        // Longer than the indexed content limit, but returned whole
+        assert!(free.content.len() > 8192);
+
+        let qualified = read_symbol(path, "Parser::parse").unwrap();
+        assert_eq!(qualified.len(), 1);
+        assert!(read_symbol(path, "missing").unwrap().is_empty());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_index_project_async_completes() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = temp_dir("async");
+        for i in 0..20 {
+            fs::write(
+                dir.join(format!("job{}.rs", i)),
+                format!("fn background_task_{}() {{}}\n", i),
+            )
+            .unwrap();
+        }
+
+        // Queued behind a build that is still running
+        let run = lock_run();
+        let job_id = index_project_async(dir.to_str().unwrap());
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        let queued = job_status(job_id).expect("job should be registered");
+        assert_eq!(queued.state, JobState::Queued);
+        assert_eq!(queued.progress.files_total, 0);
+        drop(run);
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(30);
+        let status = loop {
+            let status = job_status(job_id).expect("job should be registered");
+            if !matches!(status.state, JobState::Queued | JobState::Running) {
+                break status;
+            }
+            assert!(std::time::Instant::now() < deadline, "index job timed out");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+
+        assert_eq!(status.state, JobState::Done);
+        assert_eq!(status.stats.unwrap().total_files, 20);
+        assert_eq!(status.progress.files_indexed, 20);
+        assert!(!status.progress.running);
+        // Finished jobs are dropped once reported
+        assert!(job_status(job_id).is_none());
+        assert!(job_status(u64::MAX).is_none());
+        assert_eq!(search("background_task_7", 1).unwrap().len(), 1);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_index_job_panic_marks_failed() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let job_id = NEXT_JOB_ID.fetch_add(1, Ordering::SeqCst);
+        run_job(job_id, || {
+            let _inner = INDEX.lock().unwrap();
+            panic!("parser exploded")
+        });
+
+        let status = job_status(job_id).expect("failed job should be reported");
+        assert_eq!(status.state, JobState::Failed);
+        assert!(status.error.unwrap().contains("parser exploded"));
+        assert!(!status.progress.running);
+        assert!(job_status(job_id).is_none());
+
+        // The panic held INDEX; later builds and searches still work
+        let dir = temp_dir("after_panic");
+        fs::write(dir.join("lib.rs"), "fn recovered_after_panic() {}\n").unwrap();
+        assert_eq!(index_project(dir.to_str().unwrap()).unwrap().total_files, 1);
+        assert_eq!(search("recovered_after_panic", 1).unwrap().len(), 1);
+        fs::remove_dir_all(&dir).ok();
+    }
+}
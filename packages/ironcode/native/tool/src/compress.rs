@@ -0,0 +1,53 @@
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+
+/// Gzip `bytes` at the default compression level
+pub fn gzip(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::with_capacity(bytes.len() / 2), Compression::default());
+    // Writing into a Vec cannot fail
+    encoder.write_all(bytes).expect("gzip into memory");
+    encoder.finish().expect("gzip into memory")
+}
+
+/// Decompress gzip data. Concatenated gzip members are not supported; only
+/// the first member is returned.
+pub fn gunzip(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut decoder = GzDecoder::new(bytes);
+    let mut out = Vec::with_capacity(bytes.len() * 2);
+    decoder
+        .read_to_end(&mut out)
+        .map_err(|e| format!("Invalid gzip data: {}", e))?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for input in [
+            &b""[..],
+            b"a",
+            b"\x00\xff binary \x1b[0m",
+            "héllo ✓".as_bytes(),
+        ] {
+            assert_eq!(gunzip(&gzip(input)).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn test_repetitive_input_shrinks() {
+        let transcript = "$ cargo build\n   Compiling ironcode-tool v0.1.0\n".repeat(1000);
+        let compressed = gzip(transcript.as_bytes());
+        assert!(compressed.len() * 20 < transcript.len());
+        assert_eq!(gunzip(&compressed).unwrap(), transcript.as_bytes());
+    }
+
+    #[test]
+    fn test_gunzip_rejects_garbage() {
+        assert!(gunzip(b"not gzip at all").is_err());
+    }
+}
@@ -11,8 +11,44 @@ pub enum ReplaceError {
     NotFound,
     MultipleMatches,
     SameStrings,
+    /// old and new only differ in trailing whitespace or line endings
+    WhitespaceOnly,
 }
 
+#[derive(Debug)]
+pub enum MarkerError {
+    StartNotFound,
+    EndNotFound,
+    /// The end marker only appears before the start marker
+    OutOfOrder,
+    /// A marker is the empty string, which would match at any offset
+    EmptyMarker,
+}
+
+#[derive(Debug)]
+pub enum LineRangeError {
+    /// start_line is 0 or greater than end_line
+    InvalidRange,
+    /// end_line is past the last line of the content
+    OutOfBounds { line_count: usize },
+}
+
+#[derive(Debug)]
+pub enum PatchError {
+    /// The diff could not be parsed; `line` is 1-based within the diff
+    Malformed { line: usize, message: String },
+    /// Hunk `hunk` (1-based) did not match the content within the offset tolerance.
+    /// `actual` holds the lines found where the hunk expected its context.
+    HunkRejected {
+        hunk: usize,
+        expected: Vec<String>,
+        actual: Vec<String>,
+    },
+}
+
+/// How far (in lines) a hunk may drift from its stated position, like `patch`'s offset search
+const PATCH_OFFSET_TOLERANCE: usize = 20;
+
 const SINGLE_CANDIDATE_SIMILARITY_THRESHOLD: f64 = 0.0;
 const MULTIPLE_CANDIDATES_SIMILARITY_THRESHOLD: f64 = 0.3;
 
@@ -506,7 +542,39 @@ fn multi_occurrence_replacer(content: &str, find: &str, _content_lines: &[&str])
 
 type ReplacerFn = fn(&str, &str, &[&str]) -> Vec<String>;
 
+/// Normalize line endings to LF and strip trailing whitespace from every line
+fn normalize_trailing_whitespace(s: &str) -> String {
+    s.replace("\r\n", "\n")
+        .split('\n')
+        .map(|line| line.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_string()
+}
+
+/// True when the strings differ, but only in trailing whitespace or line endings
+pub fn is_whitespace_only_change(old_string: &str, new_string: &str) -> bool {
+    old_string != new_string
+        && normalize_trailing_whitespace(old_string) == normalize_trailing_whitespace(new_string)
+}
+
+/// Same as `replace`, but returns `WhitespaceOnly` instead of applying an edit
+/// whose strings differ only in trailing whitespace or line endings
+pub fn replace_rejecting_whitespace_only(
+    content: &str,
+    old_string: &str,
+    new_string: &str,
+    replace_all: bool,
+) -> Result<String, ReplaceError> {
+    if is_whitespace_only_change(old_string, new_string) {
+        return Err(ReplaceError::WhitespaceOnly);
+    }
+    replace(content, old_string, new_string, replace_all)
+}
+
 /// Main replace function that tries all strategies
+#[cfg_attr(feature = "logging", tracing::instrument(skip_all, fields(content_len = content.len(), old_len = old_string.len(), new_len = new_string.len(), replace_all = replace_all), err(Debug)))]
 pub fn replace(
     content: &str,
     old_string: &str,
@@ -568,10 +636,557 @@ pub fn replace(
     Err(ReplaceError::MultipleMatches)
 }
 
+/// Replace everything between the first `start_marker` and the next `end_marker`
+/// with `new_inner`. When `inclusive` is set the markers themselves are replaced too.
+pub fn replace_between(
+    content: &str,
+    start_marker: &str,
+    end_marker: &str,
+    new_inner: &str,
+    inclusive: bool,
+) -> Result<String, MarkerError> {
+    if start_marker.is_empty() || end_marker.is_empty() {
+        return Err(MarkerError::EmptyMarker);
+    }
+    let start = content
+        .find(start_marker)
+        .ok_or(MarkerError::StartNotFound)?;
+    let inner_start = start + start_marker.len();
+    let inner_end = match content[inner_start..].find(end_marker) {
+        Some(offset) => inner_start + offset,
+        None if content[..start].contains(end_marker) => return Err(MarkerError::OutOfOrder),
+        None => return Err(MarkerError::EndNotFound),
+    };
+
+    let (head, tail) = if inclusive {
+        (&content[..start], &content[inner_end + end_marker.len()..])
+    } else {
+        (&content[..inner_start], &content[inner_end..])
+    };
+
+    let mut result = String::with_capacity(head.len() + new_inner.len() + tail.len());
+    result.push_str(head);
+    result.push_str(new_inner);
+    result.push_str(tail);
+    Ok(result)
+}
+
+/// Replace lines `start_line..=end_line` (1-based) with `new_text`.
+/// The replaced range keeps its line ending: a terminator is added to
+/// `new_text` if the last replaced line had one, and dropped if it did not
+/// (so a file without a trailing newline stays that way). An empty `new_text`
+/// deletes the lines.
+pub fn replace_lines(
+    content: &str,
+    start_line: usize,
+    end_line: usize,
+    new_text: &str,
+) -> Result<String, LineRangeError> {
+    if start_line == 0 || start_line > end_line {
+        return Err(LineRangeError::InvalidRange);
+    }
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    if end_line > lines.len() {
+        return Err(LineRangeError::OutOfBounds {
+            line_count: lines.len(),
+        });
+    }
+
+    let last = lines[end_line - 1];
+    let ending = if last.ends_with("\r\n") {
+        "\r\n"
+    } else if last.ends_with('\n') {
+        "\n"
+    } else {
+        ""
+    };
+
+    let head: usize = lines[..start_line - 1].iter().map(|l| l.len()).sum();
+    let replaced: usize = lines[start_line - 1..end_line]
+        .iter()
+        .map(|l| l.len())
+        .sum();
+    let tail = head + replaced;
+
+    let mut result = String::with_capacity(content.len() + new_text.len());
+    result.push_str(&content[..head]);
+    if !new_text.is_empty() {
+        let body = new_text
+            .strip_suffix("\r\n")
+            .or_else(|| new_text.strip_suffix('\n'))
+            .unwrap_or(new_text);
+        result.push_str(body);
+        result.push_str(ending);
+    }
+    result.push_str(&content[tail..]);
+    Ok(result)
+}
+
+/// The terminator used by the content's first line, `\n` if it has none
+pub(crate) fn line_ending(content: &str) -> &'static str {
+    match content.find('\n') {
+        Some(i) if content[..i].ends_with('\r') => "\r\n",
+        _ => "\n",
+    }
+}
+
+/// Rewrite every line terminator (`\r\n` or lone `\n`) as `ending`
+pub fn normalize_line_endings(content: &str, ending: &str) -> String {
+    let lf = content.replace("\r\n", "\n");
+    if ending == "\n" {
+        lf
+    } else {
+        lf.replace('\n', ending)
+    }
+}
+
+/// `text` without one trailing line terminator
+fn strip_line_ending(text: &str) -> &str {
+    text.strip_suffix("\r\n")
+        .or_else(|| text.strip_suffix('\n'))
+        .unwrap_or(text)
+}
+
+/// Insert `new_text` as whole lines before `line` (1-based). A line past the
+/// end of the content appends instead. The inserted lines use the content's
+/// line ending.
+pub fn insert_at_line(
+    content: &str,
+    line: usize,
+    new_text: &str,
+) -> Result<String, LineRangeError> {
+    if line == 0 {
+        return Err(LineRangeError::InvalidRange);
+    }
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    if line > lines.len() || new_text.is_empty() {
+        return Ok(append(content, new_text));
+    }
+
+    let head: usize = lines[..line - 1].iter().map(|l| l.len()).sum();
+    let mut result = String::with_capacity(content.len() + new_text.len() + 2);
+    result.push_str(&content[..head]);
+    result.push_str(strip_line_ending(new_text));
+    result.push_str(line_ending(content));
+    result.push_str(&content[head..]);
+    Ok(result)
+}
+
+/// Append `new_text` as whole lines after the last line. The content keeps its
+/// trailing-newline convention: if it ended without a newline, so does the result.
+pub fn append(content: &str, new_text: &str) -> String {
+    if new_text.is_empty() {
+        return content.to_string();
+    }
+    if content.is_empty() {
+        return new_text.to_string();
+    }
+
+    let ending = line_ending(content);
+    let mut result = String::with_capacity(content.len() + new_text.len() + 2);
+    result.push_str(content);
+    if content.ends_with('\n') {
+        result.push_str(strip_line_ending(new_text));
+        result.push_str(ending);
+    } else {
+        result.push_str(ending);
+        result.push_str(strip_line_ending(new_text));
+    }
+    result
+}
+
+struct Hunk {
+    old_start: usize,
+    /// Context and removed lines, in order
+    old_lines: Vec<String>,
+    /// Context and added lines, in order
+    new_lines: Vec<String>,
+    /// "\\ No newline at end of file" followed the old side's last line
+    old_no_eol: bool,
+    /// "\\ No newline at end of file" followed the new side's last line
+    new_no_eol: bool,
+}
+
+/// Parse `@@ -a,b +c,d @@`, returning (a, b, d). Omitted counts default to 1.
+fn parse_hunk_header(line: &str) -> Option<(usize, usize, usize)> {
+    let ranges = line.strip_prefix("@@ ")?;
+    let ranges = &ranges[..ranges.find(" @@")?];
+    let (old, new) = ranges.split_once(' ')?;
+    let parse = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_count) = parse(old.strip_prefix('-')?)?;
+    let (_, new_count) = parse(new.strip_prefix('+')?)?;
+    Some((old_start, old_count, new_count))
+}
+
+fn parse_hunks(diff: &str) -> Result<Vec<Hunk>, PatchError> {
+    let mut hunks = Vec::new();
+    let mut lines = diff.lines().enumerate().peekable();
+
+    while let Some((index, line)) = lines.next() {
+        if line.starts_with("--- ") && !hunks.is_empty() {
+            return Err(PatchError::Malformed {
+                line: index + 1,
+                message: "patch touches more than one file".to_string(),
+            });
+        }
+        if !line.starts_with("@@") {
+            continue; // diff/index/---/+++ headers and trailing noise
+        }
+
+        let (old_start, mut old_left, mut new_left) =
+            parse_hunk_header(line).ok_or_else(|| PatchError::Malformed {
+                line: index + 1,
+                message: format!("invalid hunk header: {}", line),
+            })?;
+        let mut hunk = Hunk {
+            old_start,
+            old_lines: Vec::new(),
+            new_lines: Vec::new(),
+            old_no_eol: false,
+            new_no_eol: false,
+        };
+        let mut last_kind = ' ';
+
+        while old_left > 0
+            || new_left > 0
+            || matches!(lines.peek(), Some((_, l)) if l.starts_with('\\'))
+        {
+            let (index, body) = match lines.next() {
+                Some(next) => next,
+                None => {
+                    return Err(PatchError::Malformed {
+                        line: diff.lines().count(),
+                        message: "unexpected end of patch inside a hunk".to_string(),
+                    })
+                }
+            };
+            // Some tools strip the single space from empty context lines
+            let (kind, text) = match body.chars().next() {
+                Some(kind) => (kind, &body[kind.len_utf8()..]),
+                None => (' ', ""),
+            };
+            match kind {
+                ' ' if old_left > 0 && new_left > 0 => {
+                    hunk.old_lines.push(text.to_string());
+                    hunk.new_lines.push(text.to_string());
+                    old_left -= 1;
+                    new_left -= 1;
+                }
+                '-' if old_left > 0 => {
+                    hunk.old_lines.push(text.to_string());
+                    old_left -= 1;
+                }
+                '+' if new_left > 0 => {
+                    hunk.new_lines.push(text.to_string());
+                    new_left -= 1;
+                }
+                '\\' => {
+                    match last_kind {
+                        '-' => hunk.old_no_eol = true,
+                        '+' => hunk.new_no_eol = true,
+                        _ => {
+                            hunk.old_no_eol = true;
+                            hunk.new_no_eol = true;
+                        }
+                    }
+                    continue;
+                }
+                ' ' | '-' | '+' => {
+                    return Err(PatchError::Malformed {
+                        line: index + 1,
+                        message: "hunk body does not match its header line counts".to_string(),
+                    })
+                }
+                _ => {
+                    return Err(PatchError::Malformed {
+                        line: index + 1,
+                        message: format!("unknown hunk line prefix: {:?}", kind),
+                    })
+                }
+            }
+            last_kind = kind;
+        }
+        hunks.push(hunk);
+    }
+
+    if hunks.is_empty() {
+        return Err(PatchError::Malformed {
+            line: 0,
+            message: "no hunks found".to_string(),
+        });
+    }
+    Ok(hunks)
+}
+
+/// Apply a unified diff for a single file to `content`.
+///
+/// Hunks are applied in order. Like `patch`, a hunk whose context is not at
+/// its stated line is searched for up to `PATCH_OFFSET_TOLERANCE` lines away
+/// (nearest first), and later hunks are shifted by the offset found. Context
+/// must otherwise match exactly. Line endings follow the original content.
+pub fn apply_patch(content: &str, diff: &str) -> Result<String, PatchError> {
+    let hunks = parse_hunks(diff)?;
+
+    let line_ending = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    // A file created from nothing ends in a newline unless the patch says otherwise
+    let mut has_trailing_newline = content.is_empty() || content.ends_with('\n');
+    let mut lines: Vec<String> = content
+        .lines()
+        .map(|l| l.strip_suffix('\r').unwrap_or(l).to_string())
+        .collect();
+
+    // `shift` tracks how far applied hunks (and offsets) moved later lines
+    let mut shift: isize = 0;
+    let mut min_pos = 0;
+    for (index, hunk) in hunks.iter().enumerate() {
+        let stated = if hunk.old_lines.is_empty() {
+            hunk.old_start
+        } else {
+            hunk.old_start.saturating_sub(1)
+        };
+        let expected = (stated as isize + shift).max(0) as usize;
+
+        let fits = |pos: usize| {
+            pos >= min_pos
+                && pos + hunk.old_lines.len() <= lines.len()
+                && lines[pos..pos + hunk.old_lines.len()] == hunk.old_lines[..]
+        };
+        let found = (0..=PATCH_OFFSET_TOLERANCE).find_map(|delta| {
+            if fits(expected + delta) {
+                Some(expected + delta)
+            } else if delta > 0 && delta <= expected && fits(expected - delta) {
+                Some(expected - delta)
+            } else {
+                None
+            }
+        });
+
+        let pos = match found {
+            Some(pos) => pos,
+            None => {
+                let start = expected.min(lines.len());
+                let end = (start + hunk.old_lines.len()).min(lines.len());
+                return Err(PatchError::HunkRejected {
+                    hunk: index + 1,
+                    expected: hunk.old_lines.clone(),
+                    actual: lines[start..end].to_vec(),
+                });
+            }
+        };
+
+        let touches_end = pos + hunk.old_lines.len() == lines.len();
+        lines.splice(
+            pos..pos + hunk.old_lines.len(),
+            hunk.new_lines.iter().cloned(),
+        );
+        shift += pos as isize - expected as isize;
+        shift += hunk.new_lines.len() as isize - hunk.old_lines.len() as isize;
+        min_pos = pos + hunk.new_lines.len();
+
+        if touches_end {
+            if hunk.new_no_eol {
+                has_trailing_newline = false;
+            } else if hunk.old_no_eol {
+                has_trailing_newline = true;
+            }
+        }
+    }
+
+    let mut result = lines.join(line_ending);
+    if has_trailing_newline && !lines.is_empty() {
+        result.push_str(line_ending);
+    }
+    Ok(result)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_apply_patch_clean() {
+        let content = "fn main() {\n    let x = 1;\n    println!(\"{}\", x);\n}\n";
+        let diff = "\
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,4 +1,5 @@
+ fn main() {
+-    let x = 1;
++    let x = 2;
++    let y = 3;
+     println!(\"{}\", x);
+ }
+";
+        let patched = apply_patch(content, diff).unwrap();
+        assert_eq!(
+            patched,
+            "fn main() {\n    let x = 2;\n    let y = 3;\n    println!(\"{}\", x);\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_patch_with_offset() {
+        // Three lines were added above the hunk since the diff was made
+        let content = "// a\n// b\n// c\none\ntwo\nthree\nfour\nfive\n";
+        let diff = "\
+@@ -1,3 +1,3 @@
+ one
+-two
++TWO
+ three
+@@ -4,2 +4,2 @@
+ four
+-five
++FIVE
+";
+        let patched = apply_patch(content, diff).unwrap();
+        assert_eq!(patched, "// a\n// b\n// c\none\nTWO\nthree\nfour\nFIVE\n");
+    }
+
+    #[test]
+    fn test_apply_patch_rejected_hunk() {
+        let content = "one\ntwo\nthree\n";
+        let diff = "\
+@@ -1,3 +1,3 @@
+ one
+-deux
++DEUX
+ three
+";
+        match apply_patch(content, diff) {
+            Err(PatchError::HunkRejected {
+                hunk,
+                expected,
+                actual,
+            }) => {
+                assert_eq!(hunk, 1);
+                assert_eq!(expected, vec!["one", "deux", "three"]);
+                assert_eq!(actual, vec!["one", "two", "three"]);
+            }
+            other => panic!("expected rejected hunk, got {:?}", other),
+        }
+
+        assert!(matches!(
+            apply_patch(content, "not a diff"),
+            Err(PatchError::Malformed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_apply_patch_rejects_non_ascii_line_prefix() {
+        // A context line that lost its leading space starts with a multibyte char
+        let diff = "@@ -1,2 +1,2 @@\nédition\n-two\n+TWO\n";
+        match apply_patch("édition\ntwo\n", diff) {
+            Err(PatchError::Malformed { line, message }) => {
+                assert_eq!(line, 2);
+                assert!(message.contains("prefix"));
+            }
+            other => panic!("expected malformed patch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_apply_patch_creates_file_from_empty() {
+        let result = apply_patch("", "@@ -0,0 +1,2 @@\n+a\n+b\n").unwrap();
+        assert_eq!(result, "a\nb\n");
+
+        let diff = "@@ -0,0 +1,2 @@\n+a\n+b\n\\ No newline at end of file\n";
+        assert_eq!(apply_patch("", diff).unwrap(), "a\nb");
+    }
+
+    #[test]
+    fn test_replace_lines_middle() {
+        let content = "one\ntwo\nthree\nfour\n";
+        let result = replace_lines(content, 2, 3, "TWO\nTHREE\nextra").unwrap();
+        assert_eq!(result, "one\nTWO\nTHREE\nextra\nfour\n");
+
+        let deleted = replace_lines(content, 2, 3, "").unwrap();
+        assert_eq!(deleted, "one\nfour\n");
+    }
+
+    #[test]
+    fn test_replace_lines_first_and_last() {
+        let content = "one\r\ntwo\r\nthree";
+        assert_eq!(
+            replace_lines(content, 1, 1, "ONE").unwrap(),
+            "ONE\r\ntwo\r\nthree"
+        );
+        // No trailing newline on the last line, so none is added
+        assert_eq!(
+            replace_lines(content, 3, 3, "THREE\n").unwrap(),
+            "one\r\ntwo\r\nTHREE"
+        );
+    }
+
+    #[test]
+    fn test_replace_lines_invalid_range() {
+        let content = "one\ntwo\n";
+        assert!(matches!(
+            replace_lines(content, 0, 1, "x"),
+            Err(LineRangeError::InvalidRange)
+        ));
+        assert!(matches!(
+            replace_lines(content, 2, 1, "x"),
+            Err(LineRangeError::InvalidRange)
+        ));
+        assert!(matches!(
+            replace_lines(content, 2, 3, "x"),
+            Err(LineRangeError::OutOfBounds { line_count: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_insert_at_line_start_and_middle() {
+        let content = "one\ntwo\nthree\n";
+        assert_eq!(
+            insert_at_line(content, 1, "zero").unwrap(),
+            "zero\none\ntwo\nthree\n"
+        );
+        assert_eq!(
+            insert_at_line(content, 3, "a\nb\n").unwrap(),
+            "one\ntwo\na\nb\nthree\n"
+        );
+        // Inserted lines take the file's line ending
+        assert_eq!(
+            insert_at_line("one\r\ntwo", 2, "x").unwrap(),
+            "one\r\nx\r\ntwo"
+        );
+        assert!(matches!(
+            insert_at_line(content, 0, "x"),
+            Err(LineRangeError::InvalidRange)
+        ));
+    }
+
+    #[test]
+    fn test_insert_past_last_line_appends() {
+        assert_eq!(
+            insert_at_line("one\ntwo\n", 3, "three").unwrap(),
+            "one\ntwo\nthree\n"
+        );
+        assert_eq!(
+            insert_at_line("one\ntwo", 10, "three\n").unwrap(),
+            "one\ntwo\nthree"
+        );
+        assert_eq!(insert_at_line("", 1, "first\n").unwrap(), "first\n");
+    }
+
+    #[test]
+    fn test_append_keeps_trailing_newline_convention() {
+        assert_eq!(append("a\n", "b"), "a\nb\n");
+        assert_eq!(append("a", "b\n"), "a\nb");
+        assert_eq!(append("a\r\n", "b"), "a\r\nb\r\n");
+        assert_eq!(append("a\n", ""), "a\n");
+    }
+
     #[test]
     fn test_levenshtein() {
         assert_eq!(levenshtein("", ""), 0);
@@ -629,4 +1244,79 @@ mod tests {
         let result = replace(content, "world", "world", false);
         assert!(matches!(result, Err(ReplaceError::SameStrings)));
     }
+
+    #[test]
+    fn test_whitespace_only_trailing_spaces() {
+        let content = "let a = 1;   \nlet b = 2;";
+        let result =
+            replace_rejecting_whitespace_only(content, "let a = 1;   ", "let a = 1;", false);
+        assert!(matches!(result, Err(ReplaceError::WhitespaceOnly)));
+
+        // Plain `replace` still applies it
+        let applied = replace(content, "let a = 1;   ", "let a = 1;", false).unwrap();
+        assert_eq!(applied, "let a = 1;\nlet b = 2;");
+    }
+
+    #[test]
+    fn test_whitespace_only_line_endings() {
+        let content = "one\r\ntwo\r\n";
+        let result = replace_rejecting_whitespace_only(content, "one\r\ntwo", "one\ntwo", false);
+        assert!(matches!(result, Err(ReplaceError::WhitespaceOnly)));
+        assert_eq!(
+            replace(content, "one\r\ntwo", "one\ntwo", false).unwrap(),
+            "one\ntwo\r\n"
+        );
+
+        // Leading whitespace is a real change
+        assert!(!is_whitespace_only_change("one", "  one"));
+    }
+
+    #[test]
+    fn test_replace_between_exclusive() {
+        let content = "a\n// BEGIN GENERATED\nold();\n// END GENERATED\nb\n";
+        let result = replace_between(
+            content,
+            "// BEGIN GENERATED\n",
+            "// END GENERATED",
+            "new();\n",
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            "a\n// BEGIN GENERATED\nnew();\n// END GENERATED\nb\n"
+        );
+    }
+
+    #[test]
+    fn test_replace_between_inclusive() {
+        let content = "keep <<x y z>> keep";
+        let result = replace_between(content, "<<", ">>", "[]", true).unwrap();
+        assert_eq!(result, "keep [] keep");
+    }
+
+    #[test]
+    fn test_replace_between_marker_errors() {
+        let content = "END ... BEGIN";
+        assert!(matches!(
+            replace_between(content, "MISSING", "END", "", false),
+            Err(MarkerError::StartNotFound)
+        ));
+        assert!(matches!(
+            replace_between(content, "BEGIN", "MISSING", "", false),
+            Err(MarkerError::EndNotFound)
+        ));
+        assert!(matches!(
+            replace_between(content, "BEGIN", "END", "", false),
+            Err(MarkerError::OutOfOrder)
+        ));
+        assert!(matches!(
+            replace_between("abc END", "", "END", "X", false),
+            Err(MarkerError::EmptyMarker)
+        ));
+        assert!(matches!(
+            replace_between("BEGIN abc", "BEGIN", "", "X", false),
+            Err(MarkerError::EmptyMarker)
+        ));
+    }
 }
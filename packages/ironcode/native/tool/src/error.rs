@@ -0,0 +1,171 @@
+use serde::Serialize;
+use std::io;
+
+use crate::archive::ArchiveError;
+use crate::edit::{LineRangeError, MarkerError, PatchError, ReplaceError};
+use crate::vcs::VcsError;
+
+/// Stable error category included as `"code"` in FFI error envelopes, so the
+/// host can branch on it instead of matching message text.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    NotFound,
+    PermissionDenied,
+    NotGitRepo,
+    /// More than one candidate matched where exactly one was required
+    Ambiguous,
+    /// The target already exists or its content no longer matches
+    Conflict,
+    InvalidArgument,
+    Io,
+    Unsupported,
+    /// Any other libgit2 failure
+    Git,
+    Other,
+}
+
+/// A message with the code chosen where the error was raised, for functions
+/// that would otherwise return a plain `String` error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl ToolError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        ToolError {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Wrap an I/O failure, keeping the code of its `io::ErrorKind`
+    pub fn io(err: &io::Error, message: impl Into<String>) -> Self {
+        ToolError::new(err.into(), message)
+    }
+}
+
+impl std::fmt::Display for ToolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ToolError {}
+
+impl From<&ToolError> for ErrorCode {
+    fn from(err: &ToolError) -> Self {
+        err.code
+    }
+}
+
+impl From<&io::Error> for ErrorCode {
+    fn from(err: &io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::NotFound => ErrorCode::NotFound,
+            io::ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
+            io::ErrorKind::AlreadyExists => ErrorCode::Conflict,
+            io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData => ErrorCode::InvalidArgument,
+            io::ErrorKind::Unsupported => ErrorCode::Unsupported,
+            _ => ErrorCode::Io,
+        }
+    }
+}
+
+impl From<&VcsError> for ErrorCode {
+    fn from(err: &VcsError) -> Self {
+        match err {
+            VcsError::NotGitRepo(_) => ErrorCode::NotGitRepo,
+            VcsError::NotFound(_) => ErrorCode::NotFound,
+            VcsError::AlreadyExists(_) => ErrorCode::Conflict,
+            VcsError::GitError(_) => ErrorCode::Git,
+        }
+    }
+}
+
+impl From<&ReplaceError> for ErrorCode {
+    fn from(err: &ReplaceError) -> Self {
+        match err {
+            ReplaceError::NotFound => ErrorCode::NotFound,
+            ReplaceError::MultipleMatches => ErrorCode::Ambiguous,
+            ReplaceError::SameStrings | ReplaceError::WhitespaceOnly => ErrorCode::InvalidArgument,
+        }
+    }
+}
+
+impl From<&MarkerError> for ErrorCode {
+    fn from(err: &MarkerError) -> Self {
+        match err {
+            MarkerError::StartNotFound | MarkerError::EndNotFound => ErrorCode::NotFound,
+            MarkerError::OutOfOrder | MarkerError::EmptyMarker => ErrorCode::InvalidArgument,
+        }
+    }
+}
+
+impl From<&LineRangeError> for ErrorCode {
+    fn from(_: &LineRangeError) -> Self {
+        ErrorCode::InvalidArgument
+    }
+}
+
+impl From<&PatchError> for ErrorCode {
+    fn from(err: &PatchError) -> Self {
+        match err {
+            PatchError::Malformed { .. } => ErrorCode::InvalidArgument,
+            PatchError::HunkRejected { .. } => ErrorCode::Conflict,
+        }
+    }
+}
+
+impl From<&ArchiveError> for ErrorCode {
+    fn from(err: &ArchiveError) -> Self {
+        match err {
+            ArchiveError::IoError(e) => e.into(),
+            ArchiveError::EntryNotFound(_) => ErrorCode::NotFound,
+            ArchiveError::ZipError(_) | ArchiveError::UnsafePath(_) => ErrorCode::InvalidArgument,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_missing_file_is_not_found() {
+        let dir = temp_dir("missing");
+        let missing = dir.join("missing.txt");
+
+        let err = std::fs::read(&missing).unwrap_err();
+        assert_eq!(ErrorCode::from(&err), ErrorCode::NotFound);
+
+        let err = crate::file_list::list_files(
+            missing.to_str().unwrap(),
+            vec![],
+            false,
+            false,
+            None,
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(ErrorCode::from(&err), ErrorCode::NotFound);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_non_repo_is_not_git_repo() {
+        let dir = temp_dir("not_repo");
+        let err = crate::vcs::get_info(dir.to_str().unwrap()).err().unwrap();
+        assert_eq!(ErrorCode::from(&err), ErrorCode::NotGitRepo);
+        assert_eq!(
+            serde_json::to_value(ErrorCode::NotGitRepo).unwrap(),
+            "not_git_repo"
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,22 +1,70 @@
+use crate::error::{ErrorCode, ToolError};
 use ignore::WalkBuilder;
+use serde::Serialize;
+use std::fs;
 use std::path::Path;
 
+/// A bounded file listing
+#[derive(Debug, Serialize)]
+pub struct FileList {
+    pub files: Vec<String>,
+    /// All matching files, including those past the limit
+    pub total: usize,
+    pub truncated: bool,
+}
+
 /// List files in a directory using the ignore crate (respects .gitignore)
-/// Returns a vector of relative file paths
+/// Returns file paths relative to `cwd`, or with `absolute` full paths under
+/// the canonicalized `cwd` (symlinks in `cwd` itself resolved)
 pub fn list_files(
     cwd: &str,
     globs: Vec<String>,
     hidden: bool,
     follow: bool,
     max_depth: Option<usize>,
-) -> Result<Vec<String>, String> {
+    absolute: bool,
+) -> Result<Vec<String>, ToolError> {
+    walk(cwd, globs, hidden, follow, max_depth, None, absolute).map(|list| list.files)
+}
+
+/// Like `list_files`, but returns at most `limit` files. The rest of the tree
+/// is still walked to count `total`, but only the first `limit` paths are kept.
+pub fn list_files_limited(
+    cwd: &str,
+    globs: Vec<String>,
+    hidden: bool,
+    follow: bool,
+    max_depth: Option<usize>,
+    limit: usize,
+) -> Result<FileList, ToolError> {
+    walk(cwd, globs, hidden, follow, max_depth, Some(limit), false)
+}
+
+fn walk(
+    cwd: &str,
+    globs: Vec<String>,
+    hidden: bool,
+    follow: bool,
+    max_depth: Option<usize>,
+    limit: Option<usize>,
+    absolute: bool,
+) -> Result<FileList, ToolError> {
     // Validate directory exists
     let cwd_path = Path::new(cwd);
     if !cwd_path.exists() || !cwd_path.is_dir() {
-        return Err(format!("No such file or directory: '{}'", cwd));
+        return Err(ToolError::new(
+            ErrorCode::NotFound,
+            format!("No such file or directory: '{}'", cwd),
+        ));
     }
+    let root = if absolute {
+        fs::canonicalize(cwd_path)
+            .map_err(|e| ToolError::io(&e, format!("Failed to resolve '{}': {}", cwd, e)))?
+    } else {
+        cwd_path.to_path_buf()
+    };
 
-    let mut builder = WalkBuilder::new(cwd);
+    let mut builder = WalkBuilder::new(&root);
     builder
         .git_ignore(true)
         .git_exclude(true)
@@ -45,14 +93,14 @@ pub fn list_files(
             let glob = GlobBuilder::new(pattern)
                 .literal_separator(false)
                 .build()
-                .map_err(|e| format!("Invalid glob pattern '{}': {}", pattern, e))?;
+                .map_err(|e| invalid(format!("Invalid glob pattern '{}': {}", pattern, e)))?;
             glob_set_builder.add(glob);
         }
 
         Some(
             glob_set_builder
                 .build()
-                .map_err(|e| format!("Failed to build glob set: {}", e))?,
+                .map_err(|e| invalid(format!("Failed to build glob set: {}", e)))?,
         )
     } else {
         None
@@ -66,20 +114,21 @@ pub fn list_files(
             let glob = GlobBuilder::new(&pattern)
                 .literal_separator(false)
                 .build()
-                .map_err(|e| format!("Invalid glob pattern '{}': {}", pattern, e))?;
+                .map_err(|e| invalid(format!("Invalid glob pattern '{}': {}", pattern, e)))?;
             glob_set_builder.add(glob);
         }
 
         Some(
             glob_set_builder
                 .build()
-                .map_err(|e| format!("Failed to build glob set: {}", e))?,
+                .map_err(|e| invalid(format!("Failed to build glob set: {}", e)))?,
         )
     } else {
         None
     };
 
     let mut files = Vec::new();
+    let mut total = 0;
 
     for result in builder.build() {
         let entry = match result {
@@ -96,7 +145,7 @@ pub fn list_files(
 
         // Get relative path from cwd
         let rel_path = path
-            .strip_prefix(cwd)
+            .strip_prefix(&root)
             .unwrap_or(path)
             .to_string_lossy()
             .to_string();
@@ -116,10 +165,26 @@ pub fn list_files(
             }
         }
 
-        files.push(rel_path);
+        total += 1;
+        if limit.is_some_and(|l| files.len() >= l) {
+            continue;
+        }
+        files.push(if absolute {
+            path.to_string_lossy().to_string()
+        } else {
+            rel_path
+        });
     }
 
-    Ok(files)
+    Ok(FileList {
+        truncated: total > files.len(),
+        files,
+        total,
+    })
+}
+
+fn invalid(message: String) -> ToolError {
+    ToolError::new(ErrorCode::InvalidArgument, message)
 }
 
 #[cfg(test)]
@@ -157,7 +222,7 @@ mod tests {
         let test_dir = setup_test_dir();
         let cwd = test_dir.to_str().unwrap();
 
-        let files = list_files(cwd, vec![], false, false, None).unwrap();
+        let files = list_files(cwd, vec![], false, false, None, false).unwrap();
 
         assert!(files.len() >= 2); // At least file1.txt and file2.rs
         assert!(files.iter().any(|f| f.contains("file1.txt")));
@@ -171,7 +236,7 @@ mod tests {
         let test_dir = setup_test_dir();
         let cwd = test_dir.to_str().unwrap();
 
-        let files = list_files(cwd, vec!["*.txt".to_string()], false, false, None).unwrap();
+        let files = list_files(cwd, vec!["*.txt".to_string()], false, false, None, false).unwrap();
 
         assert!(files.iter().any(|f| f.contains("file1.txt")));
         assert!(!files.iter().any(|f| f.contains("file2.rs")));
@@ -184,8 +249,8 @@ mod tests {
         let test_dir = setup_test_dir();
         let cwd = test_dir.to_str().unwrap();
 
-        let files_no_hidden = list_files(cwd, vec![], false, false, None).unwrap();
-        let files_with_hidden = list_files(cwd, vec![], true, false, None).unwrap();
+        let files_no_hidden = list_files(cwd, vec![], false, false, None, false).unwrap();
+        let files_with_hidden = list_files(cwd, vec![], true, false, None, false).unwrap();
 
         assert!(files_with_hidden.len() >= files_no_hidden.len());
 
@@ -194,9 +259,19 @@ mod tests {
 
     #[test]
     fn test_list_files_invalid_dir() {
-        let result = list_files("/nonexistent_directory_12345", vec![], false, false, None);
+        let result = list_files(
+            "/nonexistent_directory_12345",
+            vec![],
+            false,
+            false,
+            None,
+            false,
+        );
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("No such file or directory"));
+        assert!(result
+            .unwrap_err()
+            .message
+            .contains("No such file or directory"));
     }
 
     #[test]
@@ -204,7 +279,7 @@ mod tests {
         let test_dir = setup_test_dir();
         let cwd = test_dir.to_str().unwrap();
 
-        let files_depth_1 = list_files(cwd, vec![], false, false, Some(1)).unwrap();
+        let files_depth_1 = list_files(cwd, vec![], false, false, Some(1), false).unwrap();
 
         // With depth 1, should not include subdir/file3.txt
         assert!(!files_depth_1
@@ -213,4 +288,65 @@ mod tests {
 
         cleanup_test_dir(&test_dir);
     }
+
+    #[test]
+    fn test_list_files_absolute() {
+        let test_dir = setup_test_dir();
+        let cwd = test_dir.to_str().unwrap();
+        let root = fs::canonicalize(&test_dir).unwrap();
+
+        let relative =
+            list_files(cwd, vec!["*.txt".to_string()], false, false, None, false).unwrap();
+        let absolute =
+            list_files(cwd, vec!["*.txt".to_string()], false, false, None, true).unwrap();
+        assert_eq!(absolute.len(), relative.len());
+        for path in &absolute {
+            assert!(
+                Path::new(path).starts_with(&root),
+                "{} not under {:?}",
+                path,
+                root
+            );
+            assert!(Path::new(path).exists());
+        }
+        assert!(absolute.iter().any(|p| p.ends_with("file3.txt")));
+
+        cleanup_test_dir(&test_dir);
+    }
+
+    #[test]
+    fn test_list_files_limited() {
+        let test_dir = setup_test_dir();
+        let cwd = test_dir.to_str().unwrap();
+        for i in 0..10 {
+            fs::write(test_dir.join(format!("extra{}.txt", i)), "test").unwrap();
+        }
+
+        let all = list_files_limited(cwd, vec![], false, false, None, 1000).unwrap();
+        assert!(!all.truncated);
+        assert_eq!(all.total, all.files.len());
+
+        let list = list_files_limited(cwd, vec![], false, false, None, 5).unwrap();
+        assert_eq!(list.files.len(), 5);
+        assert!(list.truncated);
+        assert_eq!(list.total, all.total);
+
+        let exact = list_files_limited(cwd, vec![], false, false, None, all.total).unwrap();
+        assert!(!exact.truncated);
+        assert_eq!(exact.files.len(), all.total);
+
+        let none = list_files_limited(cwd, vec![], false, false, None, 0).unwrap();
+        assert!(none.files.is_empty() && none.truncated);
+        assert_eq!(none.total, all.total);
+
+        let empty = test_dir.join("empty");
+        fs::create_dir_all(&empty).unwrap();
+        let list =
+            list_files_limited(empty.to_str().unwrap(), vec![], false, false, None, 0).unwrap();
+        assert!(list.files.is_empty());
+        assert!(!list.truncated);
+        assert_eq!(list.total, 0);
+
+        cleanup_test_dir(&test_dir);
+    }
 }
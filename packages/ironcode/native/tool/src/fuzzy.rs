@@ -1,16 +1,75 @@
 use nucleo_matcher::{Config, Matcher, Utf32Str};
+use serde::{Deserialize, Serialize};
+
+/// How letter case is compared between query and items
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseMode {
+    Insensitive,
+    Sensitive,
+    /// Case-sensitive only when the query contains an uppercase character
+    #[default]
+    Smart,
+}
+
+impl CaseMode {
+    fn ignores_case(self, query: &str) -> bool {
+        match self {
+            CaseMode::Insensitive => true,
+            CaseMode::Sensitive => false,
+            CaseMode::Smart => !query.chars().any(char::is_uppercase),
+        }
+    }
+}
+
+/// Options accepted by `fuzzy_search_with_options_ffi`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct FuzzyOptions {
+    pub limit: Option<usize>,
+    pub case: CaseMode,
+}
+
+/// An item matched on two fields, e.g. a command name and its description
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct FieldItem {
+    pub primary: String,
+    pub secondary: String,
+}
+
+/// A `search_fields` hit: position of the item in the input and its weighted score
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldMatch {
+    pub index: usize,
+    pub score: f64,
+}
 
 /// Nucleo implementation (Helix editor's algorithm - closest to fuzzysort)
 /// Kept for future optimization attempts
-pub fn search_nucleo(query: &str, items: &[String], limit: Option<usize>) -> Vec<String> {
+pub fn search_nucleo(
+    query: &str,
+    items: &[String],
+    limit: Option<usize>,
+    case: CaseMode,
+) -> Vec<String> {
     if query.is_empty() {
         let limit = limit.unwrap_or(items.len());
         return items.iter().take(limit).cloned().collect();
     }
 
-    let mut matcher = Matcher::new(Config::DEFAULT);
+    let mut config = Config::DEFAULT;
+    config.ignore_case = case.ignores_case(query);
+    // nucleo folds only the haystack, so the needle must already be lowercase
+    let needle = if config.ignore_case {
+        query.to_lowercase()
+    } else {
+        query.to_string()
+    };
+
+    let mut matcher = Matcher::new(config);
     let mut query_buf = Vec::new();
-    let query_utf32 = Utf32Str::new(query, &mut query_buf);
+    let query_utf32 = Utf32Str::new(&needle, &mut query_buf);
 
     let mut scored: Vec<(u16, &String)> = items
         .iter()
@@ -34,16 +93,75 @@ pub fn search_nucleo(query: &str, items: &[String], limit: Option<usize>) -> Vec
         .collect()
 }
 
+/// Rank items by `primary_weight * primary score + secondary_weight * secondary score`,
+/// where a field that does not match contributes 0. Items matching neither field
+/// are dropped; ties keep input order. An empty query returns every item with score 0.
+pub fn search_fields(
+    query: &str,
+    items: &[FieldItem],
+    primary_weight: f64,
+    secondary_weight: f64,
+    limit: Option<usize>,
+) -> Vec<FieldMatch> {
+    let limit = limit.unwrap_or(items.len());
+    if query.is_empty() {
+        return (0..items.len().min(limit))
+            .map(|index| FieldMatch { index, score: 0.0 })
+            .collect();
+    }
+
+    let mut config = Config::DEFAULT;
+    config.ignore_case = CaseMode::Smart.ignores_case(query);
+    let needle = if config.ignore_case {
+        query.to_lowercase()
+    } else {
+        query.to_string()
+    };
+
+    let mut matcher = Matcher::new(config);
+    let mut query_buf = Vec::new();
+    let query_utf32 = Utf32Str::new(&needle, &mut query_buf);
+    let mut item_buf = Vec::new();
+    let mut field_score = |field: &str| {
+        matcher
+            .fuzzy_match(Utf32Str::new(field, &mut item_buf), query_utf32)
+            .map(f64::from)
+    };
+
+    let mut scored: Vec<FieldMatch> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(index, item)| {
+            let primary = field_score(&item.primary);
+            let secondary = field_score(&item.secondary);
+            if primary.is_none() && secondary.is_none() {
+                return None;
+            }
+            let score = primary_weight * primary.unwrap_or(0.0)
+                + secondary_weight * secondary.unwrap_or(0.0);
+            Some(FieldMatch { index, score })
+        })
+        .collect();
+
+    scored.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    scored.truncate(limit);
+    scored
+}
+
 /// Default search function (uses nucleo)
 /// NOTE: Currently NOT used in production - fuzzysort (JavaScript) is faster
 /// See RUST_MIGRATION_PLAN.md section 2.1 for benchmark results
-pub fn search(query: &str, items: &[String], limit: Option<usize>) -> Vec<String> {
-    search_nucleo(query, items, limit)
+pub fn search(query: &str, items: &[String], limit: Option<usize>, case: CaseMode) -> Vec<String> {
+    search_nucleo(query, items, limit, case)
 }
 
 /// Optimized version that returns newline-separated string
-pub fn search_raw(query: &str, items: &[String], limit: Option<usize>) -> String {
-    let results = search(query, items, limit);
+pub fn search_raw(query: &str, items: &[String], limit: Option<usize>, case: CaseMode) -> String {
+    let results = search(query, items, limit, case);
     results.join("\n")
 }
 
@@ -54,7 +172,7 @@ mod tests {
     #[test]
     fn test_fuzzy_search_empty_query() {
         let items = vec!["foo".to_string(), "bar".to_string(), "baz".to_string()];
-        let results = search("", &items, Some(2));
+        let results = search("", &items, Some(2), CaseMode::Smart);
         assert_eq!(results.len(), 2);
     }
 
@@ -65,7 +183,7 @@ mod tests {
             "src/lib.rs".to_string(),
             "tests/test.rs".to_string(),
         ];
-        let results = search("main", &items, None);
+        let results = search("main", &items, None, CaseMode::Smart);
         assert!(!results.is_empty());
         assert_eq!(results[0], "src/main.rs");
     }
@@ -78,14 +196,14 @@ mod tests {
             "file3.txt".to_string(),
             "file4.txt".to_string(),
         ];
-        let results = search("file", &items, Some(2));
+        let results = search("file", &items, Some(2), CaseMode::Smart);
         assert_eq!(results.len(), 2);
     }
 
     #[test]
     fn test_fuzzy_search_no_match() {
         let items = vec!["foo".to_string(), "bar".to_string()];
-        let results = search("xyz", &items, None);
+        let results = search("xyz", &items, None, CaseMode::Smart);
         assert_eq!(results.len(), 0);
     }
 
@@ -96,7 +214,7 @@ mod tests {
             "src/lib.rs".to_string(),
             "tests/test.rs".to_string(),
         ];
-        let result = search_raw("main", &items, None);
+        let result = search_raw("main", &items, None, CaseMode::Smart);
         assert!(result.contains("src/main.rs"));
     }
 
@@ -108,7 +226,43 @@ mod tests {
             "src/utils/base.ts".to_string(),
         ];
 
-        let nucleo_results = search_nucleo("bt", &items, None);
+        let nucleo_results = search_nucleo("bt", &items, None, CaseMode::Smart);
         assert!(nucleo_results.len() >= 2);
     }
+
+    #[test]
+    fn test_case_modes() {
+        let items = vec!["Foo".to_string(), "foo".to_string()];
+
+        assert_eq!(search("Foo", &items, None, CaseMode::Smart), vec!["Foo"]);
+        assert_eq!(search("foo", &items, None, CaseMode::Smart).len(), 2);
+
+        assert_eq!(search("Foo", &items, None, CaseMode::Insensitive).len(), 2);
+        assert_eq!(
+            search("foo", &items, None, CaseMode::Sensitive),
+            vec!["foo"]
+        );
+        assert_eq!(CaseMode::default(), CaseMode::Smart);
+    }
+
+    #[test]
+    fn test_search_fields_ranks_name_over_description() {
+        let item = |primary: &str, secondary: &str| FieldItem {
+            primary: primary.to_string(),
+            secondary: secondary.to_string(),
+        };
+        let items = vec![
+            item("git commit", "Record changes, then open the editor"),
+            item("close tab", "Close the current tab"),
+            item("open file", "Pick a file from disk"),
+        ];
+
+        let results = search_fields("open", &items, 1.0, 0.5, None);
+        let order: Vec<usize> = results.iter().map(|m| m.index).collect();
+        assert_eq!(order, vec![2, 0]);
+        assert!(results[0].score > results[1].score);
+
+        assert_eq!(search_fields("open", &items, 1.0, 0.5, Some(1)).len(), 1);
+        assert_eq!(search_fields("", &items, 1.0, 0.5, None).len(), 3);
+    }
 }
@@ -1,9 +1,53 @@
 use crate::types::{Metadata, Output};
-use globset::{GlobBuilder, GlobSetBuilder};
+use globset::{Glob, GlobBuilder, GlobSetBuilder};
 use ignore::WalkBuilder;
+use serde::Deserialize;
 use std::time::UNIX_EPOCH;
 
+/// Which kinds of entries `execute_typed` returns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryType {
+    #[default]
+    Files,
+    Dirs,
+    Both,
+}
+
+impl EntryType {
+    /// Parse a filter name; empty selects the default (files)
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "" | "files" | "file" => Ok(EntryType::Files),
+            "dirs" | "dir" | "directories" => Ok(EntryType::Dirs),
+            "both" | "all" => Ok(EntryType::Both),
+            other => Err(format!("Unknown entry type: {}", other)),
+        }
+    }
+
+    fn accepts(self, file_type: std::fs::FileType) -> bool {
+        match self {
+            EntryType::Files => file_type.is_file(),
+            EntryType::Dirs => file_type.is_dir(),
+            EntryType::Both => file_type.is_file() || file_type.is_dir(),
+        }
+    }
+}
+
 pub fn execute(pattern: &str, search: &str) -> Result<Output, String> {
+    execute_typed(pattern, search, EntryType::Files)
+}
+
+/// `execute` that can also match directories, e.g. `**/node_modules`.
+/// Directories are matched in a walk that includes hidden and gitignored
+/// entries, so `**/.git` or an ignored `**/node_modules` can match; files are
+/// always matched as `execute` does, skipping hidden and ignored ones. A
+/// search root that is a directory is never returned.
+pub fn execute_typed(
+    pattern: &str,
+    search: &str,
+    type_filter: EntryType,
+) -> Result<Output, String> {
     let mut set_builder = GlobSetBuilder::new();
     let g = GlobBuilder::new(pattern)
         .literal_separator(false)
@@ -16,38 +60,11 @@ pub fn execute(pattern: &str, search: &str) -> Result<Output, String> {
         .map_err(|e| format!("Failed to build glob set: {}", e))?;
 
     let mut files: Vec<(String, u128)> = Vec::new();
-
-    let mut builder = WalkBuilder::new(search);
-    builder
-        .git_ignore(true)
-        .git_exclude(true)
-        .hidden(true)
-        .ignore(true);
-
-    for result in builder.build() {
-        let entry = match result {
-            Ok(e) => e,
-            Err(_) => continue,
-        };
-        if !entry.file_type().map(|ft| ft.is_file()).unwrap_or(false) {
-            continue;
-        }
-        // Check match on borrowed path first — avoid allocating PathBuf for non-matching files
-        let path = entry.path();
-        let rel = path.strip_prefix(search).unwrap_or(path);
-        if !(matcher.is_match(path) || matcher.is_match(rel)) {
-            continue;
-        }
-
-        // Use cached DirEntry metadata instead of an extra fs::metadata syscall
-        let mtime = entry
-            .metadata()
-            .ok()
-            .and_then(|m| m.modified().ok())
-            .map(|t| t.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis())
-            .unwrap_or(0);
-
-        files.push((path.to_string_lossy().to_string(), mtime));
+    if type_filter != EntryType::Dirs {
+        collect_matches(search, &matcher, EntryType::Files, &mut files);
+    }
+    if type_filter != EntryType::Files {
+        collect_matches(search, &matcher, EntryType::Dirs, &mut files);
     }
 
     let limit = 100usize;
@@ -78,7 +95,186 @@ pub fn execute(pattern: &str, search: &str) -> Result<Output, String> {
         metadata: Metadata {
             count: files.len(),
             truncated,
+            cancelled: false,
+            scanned: None,
         },
         output,
     })
 }
+
+/// Push entries of `kind` (files or dirs) under `search` that match, with
+/// their mtime. Files come from a walk honouring hidden/gitignore filters;
+/// directories from one that doesn't.
+fn collect_matches(
+    search: &str,
+    matcher: &globset::GlobSet,
+    kind: EntryType,
+    files: &mut Vec<(String, u128)>,
+) {
+    let filtered = kind == EntryType::Files;
+    let mut builder = WalkBuilder::new(search);
+    builder
+        .git_ignore(filtered)
+        .git_exclude(filtered)
+        .hidden(filtered)
+        .ignore(filtered);
+
+    for result in builder.build() {
+        let entry = match result {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+        let file_type = match entry.file_type() {
+            Some(ft) if kind.accepts(ft) => ft,
+            _ => continue,
+        };
+        if entry.depth() == 0 && file_type.is_dir() {
+            continue;
+        }
+        // Check match on borrowed path first — avoid allocating PathBuf for non-matching files
+        let path = entry.path();
+        let rel = path.strip_prefix(search).unwrap_or(path);
+        if !(matcher.is_match(path) || matcher.is_match(rel)) {
+            continue;
+        }
+
+        // Use cached DirEntry metadata instead of an extra fs::metadata syscall
+        let mtime = entry
+            .metadata()
+            .ok()
+            .and_then(|m| m.modified().ok())
+            .map(|t| t.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis())
+            .unwrap_or(0);
+
+        files.push((path.to_string_lossy().to_string(), mtime));
+    }
+}
+
+fn build_glob(pattern: &str, case_insensitive: bool) -> Result<Glob, String> {
+    GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .case_insensitive(case_insensitive)
+        .build()
+        .map_err(|e| format!("Invalid glob: {}", e))
+}
+
+/// Test a single path against a glob pattern without touching the filesystem.
+/// `*` and `?` never cross a `/`; use `**` to match across directories.
+pub fn matches(pattern: &str, path: &str, case_insensitive: bool) -> Result<bool, String> {
+    let glob = build_glob(pattern, case_insensitive)?;
+    Ok(glob.compile_matcher().is_match(path))
+}
+
+/// Returns true if `path` matches any of `patterns`.
+pub fn matches_any(
+    patterns: &[String],
+    path: &str,
+    case_insensitive: bool,
+) -> Result<bool, String> {
+    let mut set_builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        set_builder.add(build_glob(pattern, case_insensitive)?);
+    }
+    let set = set_builder
+        .build()
+        .map_err(|e| format!("Failed to build glob set: {}", e))?;
+    Ok(set.is_match(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_matches_double_star() {
+        assert!(matches("src/**/*.ts", "src/a/b.ts", false).unwrap());
+        assert!(matches("src/**/*.ts", "src/b.ts", false).unwrap());
+        assert!(matches("**/*.ts", "a/b/c.ts", false).unwrap());
+        assert!(!matches("src/**/*.ts", "lib/a/b.ts", false).unwrap());
+    }
+
+    #[test]
+    fn test_matches_star_does_not_cross_separator() {
+        assert!(matches("src/*.ts", "src/b.ts", false).unwrap());
+        assert!(!matches("src/*.ts", "src/a/b.ts", false).unwrap());
+        assert!(!matches("*.ts", "a/b.ts", false).unwrap());
+        assert!(!matches("src?b.ts", "src/b.ts", false).unwrap());
+    }
+
+    #[test]
+    fn test_matches_case() {
+        assert!(!matches("*.TS", "b.ts", false).unwrap());
+        assert!(matches("*.TS", "b.ts", true).unwrap());
+    }
+
+    #[test]
+    fn test_matches_invalid_pattern() {
+        assert!(matches("src/[a", "src/a", false).is_err());
+    }
+
+    #[test]
+    fn test_execute_typed_returns_directories() {
+        let dir = temp_dir("glob_typed");
+        std::fs::create_dir_all(dir.join("app/node_modules/left-pad")).unwrap();
+        std::fs::create_dir_all(dir.join("lib/node_modules")).unwrap();
+        std::fs::write(dir.join("app/node_modules/left-pad/index.js"), "").unwrap();
+        // A plain file with the same name only matches when files are included
+        std::fs::create_dir_all(dir.join("docs")).unwrap();
+        std::fs::write(dir.join("docs/node_modules"), "").unwrap();
+        let search = dir.to_str().unwrap();
+
+        let dirs = execute_typed("**/node_modules", search, EntryType::Dirs).unwrap();
+        assert_eq!(dirs.metadata.count, 2);
+        assert!(dirs
+            .output
+            .lines()
+            .all(|line| std::path::Path::new(line).is_dir()));
+
+        let files = execute("**/node_modules", search).unwrap();
+        assert_eq!(files.metadata.count, 1);
+        assert!(std::path::Path::new(&files.output).is_file());
+
+        let both = execute_typed("**/node_modules", search, EntryType::Both).unwrap();
+        assert_eq!(both.metadata.count, 3);
+
+        // Hidden and gitignored directories still match when asked for
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+        std::fs::write(dir.join(".gitignore"), "node_modules/\n").unwrap();
+        let dirs = execute_typed("**/node_modules", search, EntryType::Dirs).unwrap();
+        assert_eq!(dirs.metadata.count, 2);
+        let git = execute_typed("**/.git", search, EntryType::Dirs).unwrap();
+        assert_eq!(git.metadata.count, 1);
+
+        // Both is exactly files ∪ dirs: no files from ignored or hidden trees
+        std::fs::write(dir.join(".git/config"), "").unwrap();
+        let lines = |out: Output| -> std::collections::BTreeSet<String> {
+            out.output.lines().map(str::to_string).collect()
+        };
+        for pattern in ["**/node_modules/**", "**/.git/**", "**/*"] {
+            let files = lines(execute(pattern, search).unwrap());
+            let dirs = lines(execute_typed(pattern, search, EntryType::Dirs).unwrap());
+            let both = lines(execute_typed(pattern, search, EntryType::Both).unwrap());
+            assert!(both.is_subset(&files.union(&dirs).cloned().collect()));
+            assert!(!both
+                .iter()
+                .any(|p| p.ends_with("index.js") || p.ends_with("config")));
+        }
+
+        // A search path that is itself a matching file is returned, as before
+        let file = dir.join("docs/node_modules");
+        let single = execute("**/node_modules", file.to_str().unwrap()).unwrap();
+        assert_eq!(single.metadata.count, 1);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_matches_any() {
+        let patterns = vec!["*.md".to_string(), "src/**/*.rs".to_string()];
+        assert!(matches_any(&patterns, "README.md", false).unwrap());
+        assert!(matches_any(&patterns, "src/a/lib.rs", false).unwrap());
+        assert!(!matches_any(&patterns, "docs/README.md", false).unwrap());
+        assert!(!matches_any(&[], "README.md", false).unwrap());
+    }
+}
@@ -1,11 +1,69 @@
-use crate::types::{Metadata, Output};
-use ignore::WalkBuilder;
+use crate::cancel::CancelToken;
+use crate::error::{ErrorCode, ToolError};
+use crate::types::{Metadata, Output, ScanSummary};
+use crate::vcs::{self, VcsError};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::{Match, WalkBuilder};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::SystemTime;
 
+/// Bytes inspected at the start of a file to decide whether it is binary
+const BINARY_SNIFF_BYTES: usize = 8192;
+
+/// Default `GrepOptions::mmap_threshold` (32 MB)
+const MMAP_THRESHOLD: u64 = 32 * 1024 * 1024;
+
+/// How grep treats files that look binary (contain a NUL byte near the start)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BinaryMode {
+    /// Skip binary files entirely
+    #[default]
+    Skip,
+    /// Search binary files as bytes, reporting only the byte offset of each match
+    SearchAsText,
+    /// Search every file line by line, escaping control bytes in binary matches
+    TreatAllAsText,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GrepOptions {
+    pub binary: BinaryMode,
+    /// Order matches by path, then line number, so output is stable across
+    /// runs and platforms. When false, matches are ordered newest file first.
+    pub sort: bool,
+    /// Id from `cancel::begin`; cancelling it stops the walk and returns the
+    /// matches found so far with `metadata.cancelled` set
+    pub op_id: Option<u64>,
+    /// Skip `.git` and anything ignored by `.gitignore` files, `.git/info/exclude`
+    /// or the global excludes, without descending into ignored directories.
+    /// Off by default, so every file under the search path is searched.
+    pub gitignore: bool,
+    /// Text files of at least this many bytes are memory-mapped and scanned
+    /// in place instead of read line by line. Results are identical either way.
+    pub mmap_threshold: u64,
+}
+
+impl Default for GrepOptions {
+    fn default() -> Self {
+        Self {
+            binary: BinaryMode::default(),
+            sort: true,
+            op_id: None,
+            gitignore: false,
+            mmap_threshold: MMAP_THRESHOLD,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GrepMatch {
     path: String,
@@ -15,6 +73,74 @@ pub struct GrepMatch {
     line_num: usize,
     #[serde(rename = "lineText")]
     line_text: String,
+    /// Set when the match came from a binary file
+    binary: bool,
+    /// Byte offset of the match, only reported for binary files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<usize>,
+}
+
+/// Position a paged grep resumes after: everything up to and including
+/// `line` of `path` has been returned (or deliberately skipped)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GrepCursor {
+    pub path: String,
+    pub line: usize,
+}
+
+/// A matching line from `execute_paged`
+#[derive(Debug, Serialize)]
+pub struct LineMatch {
+    #[serde(rename = "lineNum")]
+    pub line_num: usize,
+    #[serde(rename = "lineText")]
+    pub line_text: String,
+    /// Byte ranges `[start, end)` of each match within `line_text`, for highlighting
+    pub ranges: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileMatches {
+    pub path: String,
+    pub matches: Vec<LineMatch>,
+    /// `max_per_file` cut this file's matches; the rest of the file is skipped
+    pub truncated_in_file: bool,
+}
+
+/// One page of `execute_paged` results
+#[derive(Debug, Serialize)]
+pub struct GrepPage {
+    pub files: Vec<FileMatches>,
+    pub count: usize,
+    /// Pass back as the cursor to get the next page; None when the search is complete
+    pub next_offset: Option<GrepCursor>,
+}
+
+/// A single line changed by `replace_in_files`
+#[derive(Debug, Serialize)]
+pub struct ReplaceHunk {
+    #[serde(rename = "lineNum")]
+    pub line_num: usize,
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileReplacement {
+    pub path: String,
+    pub replacements: usize,
+    /// Changed lines, only reported for dry runs
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hunks: Vec<ReplaceHunk>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplaceResult {
+    pub files: Vec<FileReplacement>,
+    #[serde(rename = "totalReplacements")]
+    pub total_replacements: usize,
+    #[serde(rename = "dryRun")]
+    pub dry_run: bool,
 }
 
 pub fn execute(
@@ -22,64 +148,84 @@ pub fn execute(
     search_path: &str,
     include_glob: Option<&str>,
 ) -> Result<Output, String> {
-    grep(pattern, search_path, include_glob).map_err(|e| e.to_string())
+    execute_with_options(pattern, search_path, include_glob, &GrepOptions::default())
 }
 
-fn grep(
+pub fn execute_with_options(
     pattern: &str,
     search_path: &str,
     include_glob: Option<&str>,
-) -> Result<Output, Box<dyn std::error::Error>> {
-    let regex = Regex::new(pattern)?;
-    let mut matches = Vec::with_capacity(128);
+    options: &GrepOptions,
+) -> Result<Output, String> {
+    grep(pattern, search_path, include_glob, options).map_err(|e| e.to_string())
+}
 
-    let mut builder = WalkBuilder::new(search_path);
-    builder
-        .hidden(false)
-        .ignore(false)
-        .git_ignore(false)
-        .git_global(false)
-        .git_exclude(false);
-
-    for entry in builder.build() {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => continue,
-        };
+/// Grep only the files that differ from `base_ref` in the repository
+/// containing `cwd`: staged, unstaged and untracked changes. Outside a
+/// repository this falls back to grepping all of `cwd`.
+pub fn execute_changed(
+    pattern: &str,
+    cwd: &str,
+    base_ref: &str,
+    include_glob: Option<&str>,
+) -> Result<Output, String> {
+    let (root, changed) = match vcs::repo_root(cwd).and_then(|root| {
+        let changed = vcs::changed_files(cwd, base_ref)?;
+        Ok((root, changed))
+    }) {
+        Ok(found) => found,
+        Err(VcsError::NotGitRepo(_)) => return execute(pattern, cwd, include_glob),
+        Err(e) => return Err(e.to_string()),
+    };
 
-        if !entry.file_type().map(|ft| ft.is_file()).unwrap_or(false) {
-            continue;
-        }
+    let root = Path::new(&root);
+    let files = changed
+        .iter()
+        .map(|rel| root.join(rel))
+        .filter(|path| match include_glob {
+            Some(glob_pattern) => matches_include(path, glob_pattern),
+            None => true,
+        });
+    grep_files(pattern, files, &GrepOptions::default()).map_err(|e| e.to_string())
+}
 
-        let path = entry.path();
+fn grep(
+    pattern: &str,
+    search_path: &str,
+    include_glob: Option<&str>,
+    options: &GrepOptions,
+) -> Result<Output, Box<dyn std::error::Error>> {
+    let (entries, ignored) = walk_entries(search_path, options.gitignore);
+    let files = entries
+        .filter(move |entry| is_included_file(entry, include_glob))
+        .map(ignore::DirEntry::into_path);
+    let mut output = grep_files(pattern, files, options)?;
+    if let Some(scanned) = output.metadata.scanned.as_mut() {
+        scanned.files_skipped_ignored = ignored.files.load(Ordering::Relaxed);
+        scanned.dirs_skipped_ignored = ignored.dirs.load(Ordering::Relaxed);
+    }
+    Ok(output)
+}
 
-        // Apply include glob filter if specified
-        if let Some(glob_pattern) = include_glob {
-            let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-
-            // Simple glob matching for common patterns
-            let matches_glob = if let Some(ext) = glob_pattern.strip_prefix("*.") {
-                file_name.ends_with(ext)
-            } else if glob_pattern.contains("*.{") && glob_pattern.ends_with("}") {
-                // Handle *.{ts,tsx} pattern
-                if let Some(start) = glob_pattern.find("{") {
-                    if let Some(end) = glob_pattern.find("}") {
-                        let exts = &glob_pattern[start + 1..end];
-                        exts.split(',').any(|ext| file_name.ends_with(ext))
-                    } else {
-                        true
-                    }
-                } else {
-                    true
-                }
-            } else {
-                true
-            };
+/// Search `files`
+fn grep_files(
+    pattern: &str,
+    files: impl Iterator<Item = PathBuf>,
+    options: &GrepOptions,
+) -> Result<Output, Box<dyn std::error::Error>> {
+    let regex = Regex::new(pattern)?;
+    let mut byte_regex: Option<regex::bytes::Regex> = None;
+    let mut matches = Vec::with_capacity(128);
+    let cancel = options.op_id.map(CancelToken::for_op).unwrap_or_default();
+    let mut cancelled = false;
+    let mut scanned = ScanSummary::default();
 
-            if !matches_glob {
-                continue;
-            }
+    for path in files {
+        if cancel.is_cancelled() {
+            cancelled = true;
+            break;
         }
+        let path = path.as_path();
 
         // Read file and search for pattern using streaming
         let file = match fs::File::open(path) {
@@ -87,9 +233,9 @@ fn grep(
             Err(_) => continue,
         };
 
-        let mod_time = entry
-            .metadata()
-            .ok()
+        let metadata = file.metadata().ok();
+        let size = metadata.as_ref().map_or(0, |m| m.len());
+        let mod_time = metadata
             .and_then(|m| m.modified().ok())
             .unwrap_or(SystemTime::UNIX_EPOCH)
             .duration_since(SystemTime::UNIX_EPOCH)
@@ -97,7 +243,50 @@ fn grep(
             .as_millis() as u64;
 
         // Use streaming read with 64KB buffer for memory efficiency
-        let reader = BufReader::with_capacity(65536, file);
+        let mut reader = BufReader::with_capacity(65536, file);
+
+        // Sniff the first buffer for NUL bytes to classify the file
+        let is_binary = match reader.fill_buf() {
+            Ok(buf) => buf[..buf.len().min(BINARY_SNIFF_BYTES)].contains(&0),
+            Err(_) => continue,
+        };
+
+        if is_binary && options.binary == BinaryMode::Skip {
+            scanned.files_skipped_binary += 1;
+            continue;
+        }
+        scanned.files_searched += 1;
+
+        if is_binary {
+            let byte_regex = match &byte_regex {
+                Some(r) => r,
+                None => byte_regex.insert(regex::bytes::Regex::new(pattern)?),
+            };
+            search_binary(
+                reader,
+                byte_regex,
+                options.binary,
+                &path.to_string_lossy(),
+                mod_time,
+                &mut matches,
+            );
+            continue;
+        }
+
+        if size > 0 && size >= options.mmap_threshold {
+            // SAFETY: read-only map dropped at the end of this iteration; a file
+            // truncated while mapped can still fault, as with `read::with_file_bytes`
+            if let Ok(map) = unsafe { memmap2::Mmap::map(reader.get_ref()) } {
+                search_mapped(
+                    &map,
+                    &regex,
+                    &path.to_string_lossy(),
+                    mod_time,
+                    &mut matches,
+                );
+                continue;
+            }
+        }
 
         for (line_num, line_result) in reader.lines().enumerate() {
             let line = match line_result {
@@ -111,6 +300,8 @@ fn grep(
                     mod_time,
                     line_num: line_num + 1,
                     line_text: line,
+                    binary: false,
+                    offset: None,
                 });
 
                 // Early exit if we have enough matches (limit is 100)
@@ -122,8 +313,12 @@ fn grep(
         }
     }
 
-    // Sort by modification time (newest first)
-    matches.sort_by(|a, b| b.mod_time.cmp(&a.mod_time));
+    if options.sort {
+        matches.sort_by(|a, b| a.path.cmp(&b.path).then(a.line_num.cmp(&b.line_num)));
+    } else {
+        // Sort by modification time (newest first)
+        matches.sort_by(|a, b| b.mod_time.cmp(&a.mod_time));
+    }
 
     let limit = 100;
     let truncated = matches.len() > limit;
@@ -139,8 +334,14 @@ fn grep(
             metadata: Metadata {
                 count: 0,
                 truncated: false,
+                cancelled,
+                scanned: Some(scanned),
+            },
+            output: if cancelled {
+                "Search cancelled".to_string()
+            } else {
+                "No files found".to_string()
             },
-            output: "No files found".to_string(),
         });
     }
 
@@ -155,10 +356,19 @@ fn grep(
                 output_lines.push(String::new());
             }
             current_file = m.path.clone();
-            output_lines.push(format!("{}:", m.path));
+            if m.binary {
+                output_lines.push(format!("{} (binary):", m.path));
+            } else {
+                output_lines.push(format!("{}:", m.path));
+            }
         }
 
-        if m.line_text.len() > MAX_LINE_LENGTH {
+        if let Some(offset) = m.offset {
+            output_lines.push(format!(
+                "  Line {}: binary match at byte {}",
+                m.line_num, offset
+            ));
+        } else if m.line_text.len() > MAX_LINE_LENGTH {
             output_lines.push(format!(
                 "  Line {}: {}...",
                 m.line_num,
@@ -175,13 +385,824 @@ fn grep(
             "(Results are truncated. Consider using a more specific path or pattern.)".to_string(),
         );
     }
+    if cancelled {
+        output_lines.push(String::new());
+        output_lines.push("(Search cancelled; results are partial.)".to_string());
+    }
 
     Ok(Output {
         title: pattern.to_string(),
         metadata: Metadata {
             count: final_matches.len(),
             truncated,
+            cancelled,
+            scanned: Some(scanned),
         },
         output: output_lines.join("\n"),
     })
 }
+
+/// Grep in pages of at most `page_size` matches. Files are visited in path
+/// order and the search resumes right after `cursor`, so following
+/// `next_offset` pages through every match exactly once. With `max_per_file`,
+/// a file's matches beyond that count are skipped and the file is marked
+/// `truncated_in_file`. Binary files are skipped.
+pub fn execute_paged(
+    pattern: &str,
+    search_path: &str,
+    include_glob: Option<&str>,
+    cursor: Option<&GrepCursor>,
+    page_size: usize,
+    max_per_file: Option<usize>,
+) -> Result<GrepPage, String> {
+    let regex = Regex::new(pattern).map_err(|e| e.to_string())?;
+    // An empty page could never advance the cursor
+    let page_size = page_size.max(1);
+    let mut paths: Vec<String> = walk_files(search_path, include_glob, false)
+        .map(|entry| entry.path().to_string_lossy().to_string())
+        .filter(|path| cursor.is_none_or(|c| *path >= c.path))
+        .collect();
+    paths.sort();
+
+    let mut files: Vec<FileMatches> = Vec::new();
+    let mut count = 0;
+    // Position after the last returned (or skipped) line
+    let mut resume: Option<GrepCursor> = None;
+
+    for path in paths {
+        let skip_until = match cursor {
+            Some(c) if c.path == path => c.line,
+            _ => 0,
+        };
+        let file = match fs::File::open(&path) {
+            Ok(f) => f,
+            Err(_) => continue,
+        };
+        let mut reader = BufReader::with_capacity(65536, file);
+        match reader.fill_buf() {
+            Ok(buf) if !buf[..buf.len().min(BINARY_SNIFF_BYTES)].contains(&0) => {}
+            _ => continue,
+        }
+
+        let mut file_matches = FileMatches {
+            path: path.clone(),
+            matches: Vec::new(),
+            truncated_in_file: false,
+        };
+        // Matches of this file returned on earlier pages count towards `max_per_file`
+        let mut returned_before = 0;
+        let mut lines = reader.lines().enumerate();
+        while let Some((index, line_result)) = lines.next() {
+            let line_num = index + 1;
+            let line = match line_result {
+                Ok(l) => l,
+                Err(_) => break,
+            };
+            if line_num <= skip_until {
+                if max_per_file.is_some() && regex.is_match(&line) {
+                    returned_before += 1;
+                }
+                continue;
+            }
+            if !regex.is_match(&line) {
+                continue;
+            }
+            if max_per_file.is_some_and(|max| returned_before + file_matches.matches.len() >= max) {
+                // Skip the rest of the file; the cursor moves past its last line
+                file_matches.truncated_in_file = true;
+                let last_line = line_num + lines.by_ref().count();
+                resume = Some(GrepCursor {
+                    path: path.clone(),
+                    line: last_line,
+                });
+                break;
+            }
+            if count >= page_size {
+                if !file_matches.matches.is_empty() {
+                    files.push(file_matches);
+                }
+                return Ok(GrepPage {
+                    files,
+                    count,
+                    next_offset: resume,
+                });
+            }
+            let ranges = regex
+                .find_iter(&line)
+                .map(|m| (m.start(), m.end()))
+                .collect();
+            file_matches.matches.push(LineMatch {
+                line_num,
+                line_text: line,
+                ranges,
+            });
+            count += 1;
+            resume = Some(GrepCursor {
+                path: path.clone(),
+                line: line_num,
+            });
+        }
+        if !file_matches.matches.is_empty() {
+            files.push(file_matches);
+        }
+    }
+
+    Ok(GrepPage {
+        files,
+        count,
+        next_offset: None,
+    })
+}
+
+/// Regex replacement across the files grep would search.
+///
+/// Like sed, the pattern is applied line by line and `replacement` may use
+/// `$1`/`${name}` capture references. Binary and non-UTF-8 files are skipped,
+/// as are `.git` and anything ignored by git. With `dry_run` no file is
+/// written and each file reports its changed lines. Each file is replaced
+/// atomically; if a write fails, the error names the files already rewritten.
+#[cfg_attr(feature = "logging", tracing::instrument(skip_all, fields(pattern = %pattern, search_path = %search_path, dry_run = dry_run), err))]
+pub fn replace_in_files(
+    pattern: &str,
+    replacement: &str,
+    search_path: &str,
+    include_glob: Option<&str>,
+    dry_run: bool,
+) -> Result<ReplaceResult, ToolError> {
+    let regex = Regex::new(pattern)
+        .map_err(|e| ToolError::new(ErrorCode::InvalidArgument, e.to_string()))?;
+    let mut files: Vec<FileReplacement> = Vec::new();
+
+    for entry in walk_files(search_path, include_glob, true) {
+        let path = entry.path();
+        let content = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(_) => continue, // Unreadable or not UTF-8
+        };
+        if content.as_bytes()[..content.len().min(BINARY_SNIFF_BYTES)].contains(&0) {
+            continue;
+        }
+
+        let mut replacements = 0;
+        let mut hunks = Vec::new();
+        let mut updated = String::with_capacity(content.len());
+
+        for (index, raw_line) in content.split_inclusive('\n').enumerate() {
+            let line = raw_line.trim_end_matches(['\r', '\n']);
+            let ending = &raw_line[line.len()..];
+
+            let count = regex.find_iter(line).count();
+            if count == 0 {
+                updated.push_str(raw_line);
+                continue;
+            }
+
+            let after = regex.replace_all(line, replacement);
+            replacements += count;
+            updated.push_str(&after);
+            updated.push_str(ending);
+            if dry_run {
+                hunks.push(ReplaceHunk {
+                    line_num: index + 1,
+                    before: line.to_string(),
+                    after: after.into_owned(),
+                });
+            }
+        }
+
+        if replacements == 0 {
+            continue;
+        }
+        if !dry_run {
+            if let Err(e) = crate::write::write_atomic(path, updated.as_bytes()) {
+                let mut written: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+                written.sort();
+                return Err(ToolError::io(
+                    &e,
+                    format!(
+                        "Failed to write {}: {} (already rewritten: [{}])",
+                        path.display(),
+                        e,
+                        written.join(", ")
+                    ),
+                ));
+            }
+        }
+        files.push(FileReplacement {
+            path: path.to_string_lossy().to_string(),
+            replacements,
+            hunks,
+        });
+    }
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    let total_replacements = files.iter().map(|f| f.replacements).sum();
+
+    Ok(ReplaceResult {
+        files,
+        total_replacements,
+        dry_run,
+    })
+}
+
+/// Files under `search_path` that grep considers, filtered by the include glob.
+/// With `gitignore`, `.git` is skipped and `.gitignore` files, `.git/info/exclude`
+/// and the global excludes apply; ignored directories are not descended into.
+fn walk_files<'a>(
+    search_path: &str,
+    include_glob: Option<&'a str>,
+    gitignore: bool,
+) -> impl Iterator<Item = ignore::DirEntry> + 'a {
+    walk_entries(search_path, gitignore)
+        .0
+        .filter(move |entry| is_included_file(entry, include_glob))
+}
+
+/// Paths a gitignore-aware walk pruned
+#[derive(Default)]
+struct IgnoredCounts {
+    files: AtomicUsize,
+    dirs: AtomicUsize,
+}
+
+/// Every entry `walk_files` visits, directories included. Ignored entries are
+/// tallied in the returned counts as they are pruned.
+fn walk_entries(
+    search_path: &str,
+    gitignore: bool,
+) -> (impl Iterator<Item = ignore::DirEntry>, Arc<IgnoredCounts>) {
+    let counts = Arc::new(IgnoredCounts::default());
+    let mut builder = WalkBuilder::new(search_path);
+    builder.standard_filters(false);
+    if gitignore {
+        let rules = GitignoreRules::new(search_path);
+        let tally = Arc::clone(&counts);
+        builder.filter_entry(move |entry| {
+            if entry.file_name() == ".git" {
+                return false;
+            }
+            let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+            if entry.depth() == 0 || !rules.is_ignored(entry.path(), is_dir) {
+                return true;
+            }
+            let counter = if is_dir { &tally.dirs } else { &tally.files };
+            counter.fetch_add(1, Ordering::Relaxed);
+            false
+        });
+    }
+    (builder.build().filter_map(Result::ok), counts)
+}
+
+/// Gitignore rules checked per entry during a walk, so pruned paths can be
+/// counted. As in git, the `.gitignore` files from an entry's directory up to
+/// the repository root decide first, deepest first; then `.git/info/exclude`,
+/// then the global excludes file.
+struct GitignoreRules {
+    search_root: PathBuf,
+    /// Canonical form of `search_root`, so parents above it can be visited
+    abs_root: PathBuf,
+    /// Nearest ancestor of `abs_root` containing `.git`, else `abs_root`
+    repo_root: PathBuf,
+    /// `.git/info/exclude` and the global excludes, in that order
+    excludes: Vec<Gitignore>,
+    /// Directory → its parsed `.gitignore` (None if it has none)
+    per_dir: Mutex<HashMap<PathBuf, Option<Gitignore>>>,
+}
+
+impl GitignoreRules {
+    fn new(search_path: &str) -> Self {
+        let search_root = PathBuf::from(search_path);
+        let abs_root = fs::canonicalize(&search_root).unwrap_or_else(|_| search_root.clone());
+        let repo = abs_root.ancestors().find(|dir| dir.join(".git").exists());
+        let repo_root = repo.unwrap_or(&abs_root).to_path_buf();
+
+        let mut excludes = Vec::new();
+        if repo.is_some() {
+            let mut builder = GitignoreBuilder::new(&repo_root);
+            builder.add(repo_root.join(".git/info/exclude"));
+            excludes.push(builder.build().unwrap_or_else(|_| Gitignore::empty()));
+        }
+        excludes.push(Gitignore::global().0);
+
+        Self {
+            search_root,
+            abs_root,
+            repo_root,
+            excludes,
+            per_dir: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let relative = match path.strip_prefix(&self.search_root) {
+            Ok(relative) => relative,
+            Err(_) => return false,
+        };
+        let absolute = self.abs_root.join(relative);
+
+        let mut per_dir = self.per_dir.lock().unwrap_or_else(|e| e.into_inner());
+        for dir in absolute.ancestors().skip(1) {
+            if !dir.starts_with(&self.repo_root) {
+                break;
+            }
+            let rules = per_dir.entry(dir.to_path_buf()).or_insert_with(|| {
+                let file = dir.join(".gitignore");
+                file.is_file().then(|| Gitignore::new(file).0)
+            });
+            if let Some(rules) = rules {
+                match rules.matched(&absolute, is_dir) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+        }
+        self.excludes
+            .iter()
+            .find_map(|rules| match rules.matched(&absolute, is_dir) {
+                Match::None => None,
+                matched => Some(matched.is_ignore()),
+            })
+            .unwrap_or(false)
+    }
+}
+
+fn is_included_file(entry: &ignore::DirEntry, include_glob: Option<&str>) -> bool {
+    entry.file_type().is_some_and(|ft| ft.is_file())
+        && include_glob.is_none_or(|glob_pattern| matches_include(entry.path(), glob_pattern))
+}
+
+/// Simple glob matching for common include patterns
+fn matches_include(path: &Path, glob_pattern: &str) -> bool {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+
+    if let Some(ext) = glob_pattern.strip_prefix("*.") {
+        file_name.ends_with(ext)
+    } else if glob_pattern.contains("*.{") && glob_pattern.ends_with("}") {
+        // Handle *.{ts,tsx} pattern
+        if let Some(start) = glob_pattern.find("{") {
+            if let Some(end) = glob_pattern.find("}") {
+                let exts = &glob_pattern[start + 1..end];
+                exts.split(',').any(|ext| file_name.ends_with(ext))
+            } else {
+                true
+            }
+        } else {
+            true
+        }
+    } else {
+        true
+    }
+}
+
+/// Line-by-line search of a mapped text file, matching the streaming path in
+/// `grep_files` exactly: lines split on `\n` with one trailing `\r` dropped,
+/// and the file abandoned at the first line that isn't valid UTF-8. Only
+/// matching lines are copied out.
+fn search_mapped(
+    bytes: &[u8],
+    regex: &Regex,
+    path: &str,
+    mod_time: u64,
+    matches: &mut Vec<GrepMatch>,
+) {
+    let mut rest = bytes;
+    let mut line_num = 0;
+    while !rest.is_empty() {
+        let (line, next) = match rest.iter().position(|&b| b == b'\n') {
+            Some(i) => (&rest[..i], &rest[i + 1..]),
+            None => (rest, &rest[rest.len()..]),
+        };
+        rest = next;
+        line_num += 1;
+
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let text = match std::str::from_utf8(line) {
+            Ok(t) => t,
+            Err(_) => break,
+        };
+        if regex.is_match(text) {
+            matches.push(GrepMatch {
+                path: path.to_string(),
+                mod_time,
+                line_num,
+                line_text: text.to_string(),
+                binary: false,
+                offset: None,
+            });
+            if matches.len() >= 1000 {
+                break;
+            }
+        }
+    }
+}
+
+/// Scan a binary file as raw byte lines. Raw bytes never reach the output:
+/// `SearchAsText` reports offsets only, `TreatAllAsText` escapes control bytes.
+fn search_binary<R: BufRead>(
+    mut reader: R,
+    regex: &regex::bytes::Regex,
+    mode: BinaryMode,
+    path: &str,
+    mod_time: u64,
+    matches: &mut Vec<GrepMatch>,
+) {
+    let mut line = Vec::new();
+    let mut line_num = 0;
+    let mut line_start = 0;
+
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                line_num += 1;
+                if let Some(m) = regex.find(&line) {
+                    let (line_text, offset) = match mode {
+                        BinaryMode::TreatAllAsText => (escape_control_bytes(&line), None),
+                        _ => (String::new(), Some(line_start + m.start())),
+                    };
+                    matches.push(GrepMatch {
+                        path: path.to_string(),
+                        mod_time,
+                        line_num,
+                        line_text,
+                        binary: true,
+                        offset,
+                    });
+                    if matches.len() >= 1000 {
+                        break;
+                    }
+                }
+                line_start += n;
+            }
+        }
+    }
+}
+
+fn escape_control_bytes(line: &[u8]) -> String {
+    let text = String::from_utf8_lossy(line);
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.trim_end_matches(['\r', '\n']).chars() {
+        if ch.is_control() && ch != '\t' {
+            escaped.push_str(&format!("\\x{:02x}", ch as u32));
+        } else {
+            escaped.push(ch);
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_binary_modes() {
+        let dir = temp_dir("binary");
+        fs::write(
+            dir.join("blob.bin"),
+            b"\x7fELF\x00\x01\x02 MAGIC_TOKEN\x1b[0m\n",
+        )
+        .unwrap();
+        let search = dir.to_str().unwrap();
+
+        // Default skips the binary file
+        let out = execute("MAGIC_TOKEN", search, None).unwrap();
+        assert_eq!(out.metadata.count, 0);
+
+        // Searching as text reports the offset only
+        let opts = GrepOptions {
+            binary: BinaryMode::SearchAsText,
+            ..Default::default()
+        };
+        let out = execute_with_options("MAGIC_TOKEN", search, None, &opts).unwrap();
+        assert_eq!(out.metadata.count, 1);
+        assert!(out.output.contains("(binary):"));
+        assert!(out.output.contains("binary match at byte 8"));
+        assert!(!out.output.contains('\0'));
+
+        // Treating everything as text shows the line with control bytes escaped
+        let opts = GrepOptions {
+            binary: BinaryMode::TreatAllAsText,
+            ..Default::default()
+        };
+        let out = execute_with_options("MAGIC_TOKEN", search, None, &opts).unwrap();
+        assert_eq!(out.metadata.count, 1);
+        assert!(out.output.contains("\\x00\\x01\\x02 MAGIC_TOKEN\\x1b[0m"));
+        assert!(!out.output.contains('\0'));
+        assert!(!out.output.contains('\x1b'));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_sorted_output_is_deterministic() {
+        let dir = temp_dir("sort");
+        for name in ["zeta.txt", "alpha.txt", "mid/beta.txt", "mid/alpha.txt"] {
+            let path = dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, "needle one\nhay\nneedle two\n").unwrap();
+        }
+        let search = dir.to_str().unwrap();
+
+        let first = execute("needle", search, None).unwrap();
+        let second = execute("needle", search, None).unwrap();
+        assert_eq!(first.output, second.output);
+        assert_eq!(first.metadata.count, 8);
+
+        let headers: Vec<&str> = first
+            .output
+            .lines()
+            .filter(|l| l.ends_with(".txt:"))
+            .collect();
+        let mut sorted = headers.clone();
+        sorted.sort();
+        assert_eq!(headers, sorted);
+        assert!(first
+            .output
+            .contains("  Line 1: needle one\n  Line 3: needle two"));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_mmap_path_matches_streaming() {
+        let dir = temp_dir("mmap");
+        fs::write(
+            dir.join("crlf.log"),
+            "ERROR first\r\nok\r\n\r\nERROR\r\nlast ERROR without newline",
+        )
+        .unwrap();
+        fs::write(dir.join("plain.log"), "\nERROR at 2\n\nERROR at 4\n\n").unwrap();
+        let mut invalid = b"ERROR before\n".to_vec();
+        invalid.extend_from_slice(b"bad \xff byte ERROR\nERROR after\n");
+        fs::write(dir.join("invalid.log"), invalid).unwrap();
+        fs::write(dir.join("empty.log"), "").unwrap();
+        let search = dir.to_str().unwrap();
+
+        for pattern in ["ERROR", "^ERROR$", "^$", "without newline$"] {
+            let streaming = GrepOptions {
+                mmap_threshold: u64::MAX,
+                ..GrepOptions::default()
+            };
+            let mapped = GrepOptions {
+                mmap_threshold: 0,
+                ..GrepOptions::default()
+            };
+            let a = execute_with_options(pattern, search, None, &streaming).unwrap();
+            let b = execute_with_options(pattern, search, None, &mapped).unwrap();
+            assert_eq!(a.output, b.output, "pattern {:?}", pattern);
+            assert_eq!(a.metadata.count, b.metadata.count);
+        }
+        let all = execute("ERROR", search, None).unwrap();
+        assert_eq!(all.metadata.count, 6);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_replace_dry_run_writes_nothing() {
+        let dir = temp_dir("replace_dry");
+        let original = "let old_name = 1;\nprint(old_name);\n";
+        fs::write(dir.join("a.rs"), original).unwrap();
+
+        let result =
+            replace_in_files("old_(name)", "new_$1", dir.to_str().unwrap(), None, true).unwrap();
+        assert!(result.dry_run);
+        assert_eq!(result.total_replacements, 2);
+        assert_eq!(result.files.len(), 1);
+        let hunks = &result.files[0].hunks;
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[1].line_num, 2);
+        assert_eq!(hunks[1].before, "print(old_name);");
+        assert_eq!(hunks[1].after, "print(new_name);");
+        assert_eq!(fs::read_to_string(dir.join("a.rs")).unwrap(), original);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_replace_applies_changes() {
+        let dir = temp_dir("replace_apply");
+        fs::write(dir.join("a.rs"), "foo foo\r\nbar\n").unwrap();
+        fs::write(dir.join("b.rs"), "foo\n").unwrap();
+        fs::write(dir.join("c.txt"), "foo\n").unwrap();
+        fs::write(dir.join("d.rs"), "nothing here\n").unwrap();
+
+        let result =
+            replace_in_files("foo", "baz", dir.to_str().unwrap(), Some("*.rs"), false).unwrap();
+        assert_eq!(result.total_replacements, 3);
+        let counts: Vec<usize> = result.files.iter().map(|f| f.replacements).collect();
+        assert_eq!(counts, vec![2, 1]);
+        assert!(result.files.iter().all(|f| f.hunks.is_empty()));
+
+        assert_eq!(
+            fs::read_to_string(dir.join("a.rs")).unwrap(),
+            "baz baz\r\nbar\n"
+        );
+        assert_eq!(fs::read_to_string(dir.join("b.rs")).unwrap(), "baz\n");
+        // Excluded by the include glob
+        assert_eq!(fs::read_to_string(dir.join("c.txt")).unwrap(), "foo\n");
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_replace_skips_git_dir_and_ignored_files() {
+        let dir = temp_dir("replace_ignored");
+        fs::create_dir_all(dir.join(".git/info")).unwrap();
+        fs::write(dir.join(".git/config"), "foo\n").unwrap();
+        fs::write(dir.join(".git/info/exclude"), "local.txt\n").unwrap();
+        fs::write(dir.join(".gitignore"), "target/\n").unwrap();
+        fs::create_dir_all(dir.join("target")).unwrap();
+        fs::write(dir.join("target/out.txt"), "foo\n").unwrap();
+        fs::write(dir.join("local.txt"), "foo\n").unwrap();
+        fs::write(dir.join("src.txt"), "foo\n").unwrap();
+
+        let result = replace_in_files("foo", "baz", dir.to_str().unwrap(), None, false).unwrap();
+        assert_eq!(result.total_replacements, 1);
+        assert_eq!(fs::read_to_string(dir.join("src.txt")).unwrap(), "baz\n");
+        for untouched in [".git/config", "target/out.txt", "local.txt"] {
+            assert_eq!(fs::read_to_string(dir.join(untouched)).unwrap(), "foo\n");
+        }
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_cancelled_grep_returns_early() {
+        let dir = temp_dir("cancel");
+        for i in 0..10 {
+            fs::write(dir.join(format!("f{}.txt", i)), "needle\n").unwrap();
+        }
+        let search = dir.to_str().unwrap();
+
+        let op_id = crate::cancel::begin();
+        assert!(crate::cancel::cancel(op_id));
+        let opts = GrepOptions {
+            op_id: Some(op_id),
+            ..Default::default()
+        };
+        let out = execute_with_options("needle", search, None, &opts).unwrap();
+        assert!(out.metadata.cancelled);
+        assert_eq!(out.metadata.count, 0);
+        // The id is released once grep returns
+        assert!(!crate::cancel::cancel(op_id));
+
+        let out = execute("needle", search, None).unwrap();
+        assert!(!out.metadata.cancelled);
+        assert_eq!(out.metadata.count, 10);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_scan_summary_counts_ignored_and_binary() {
+        let dir = temp_dir("scanned");
+        fs::create_dir_all(dir.join(".git/info")).unwrap();
+        fs::write(dir.join(".git/info/exclude"), "local.txt\n").unwrap();
+        fs::write(dir.join(".gitignore"), "secret.txt\nbuild/\n").unwrap();
+        fs::write(dir.join("visible.txt"), "needle\n").unwrap();
+        fs::write(dir.join("local.txt"), "needle\n").unwrap();
+        fs::write(dir.join("secret.txt"), "needle\n").unwrap();
+        fs::create_dir_all(dir.join("build/nested")).unwrap();
+        fs::write(dir.join("build/out.txt"), "needle\n").unwrap();
+        fs::write(dir.join("build/more.txt"), "needle\n").unwrap();
+        fs::write(dir.join("build/nested/deep.txt"), "needle\n").unwrap();
+        fs::write(dir.join("blob.bin"), b"\x00needle").unwrap();
+        let search = dir.to_str().unwrap();
+
+        let opts = GrepOptions {
+            gitignore: true,
+            ..Default::default()
+        };
+        let out = execute_with_options("needle", search, None, &opts).unwrap();
+        assert_eq!(out.metadata.count, 1);
+        assert!(!out.output.contains("secret.txt"));
+        assert!(!out.output.contains("local.txt"));
+        assert_eq!(
+            out.metadata.scanned,
+            Some(ScanSummary {
+                // visible.txt and .gitignore
+                files_searched: 2,
+                files_skipped_binary: 1,
+                // local.txt and secret.txt; build/ is pruned without being read
+                files_skipped_ignored: 2,
+                dirs_skipped_ignored: 1,
+            })
+        );
+
+        // Without the option, ignored files are searched as before
+        let out = execute("needle", search, None).unwrap();
+        assert_eq!(out.metadata.count, 6);
+        let scanned = out.metadata.scanned.unwrap();
+        assert_eq!(scanned.files_skipped_ignored, 0);
+        assert_eq!(scanned.dirs_skipped_ignored, 0);
+        assert_eq!(scanned.files_searched, 8);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_paged_grep_has_no_duplicates_or_gaps() {
+        let dir = temp_dir("paged");
+        fs::write(dir.join("a.txt"), "hit 1\nmiss\nhit 2\nhit 3\n").unwrap();
+        fs::write(dir.join("b.txt"), "hit 4\nhit 5 hit\n").unwrap();
+        fs::write(dir.join("c.txt"), "miss\nhit 6\n").unwrap();
+        let search = dir.to_str().unwrap();
+        let flatten = |page: &GrepPage| -> Vec<(String, usize)> {
+            page.files
+                .iter()
+                .flat_map(|f| f.matches.iter().map(|m| (f.path.clone(), m.line_num)))
+                .collect()
+        };
+
+        let all = execute_paged("hit", search, None, None, 100, None).unwrap();
+        assert_eq!(all.count, 6);
+        assert!(all.next_offset.is_none());
+
+        let first = execute_paged("hit", search, None, None, 4, None).unwrap();
+        assert_eq!(first.count, 4);
+        let cursor = first.next_offset.clone().unwrap();
+        assert!(cursor.path.ends_with("b.txt"));
+        assert_eq!(cursor.line, 1);
+        let second = execute_paged("hit", search, None, Some(&cursor), 4, None).unwrap();
+        assert_eq!(second.count, 2);
+        assert!(second.next_offset.is_none());
+
+        let mut paged = flatten(&first);
+        paged.extend(flatten(&second));
+        assert_eq!(paged, flatten(&all));
+        let b_line_2 = &second.files[0].matches[0];
+        assert_eq!(b_line_2.ranges, vec![(0, 3), (6, 9)]);
+
+        // Capped files skip their remaining matches, also on later pages
+        let capped = execute_paged("hit", search, None, None, 2, Some(2)).unwrap();
+        assert!(capped.files[0].truncated_in_file);
+        let cursor = capped.next_offset.clone().unwrap();
+        assert!(cursor.path.ends_with("a.txt"));
+        let rest = execute_paged("hit", search, None, Some(&cursor), 10, Some(2)).unwrap();
+        assert_eq!(rest.count, 3);
+        assert!(rest.files[0].path.ends_with("b.txt"));
+        assert!(!rest.files.iter().any(|f| f.truncated_in_file));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_paged_grep_cap_spans_pages() {
+        let dir = temp_dir("paged_cap");
+        fs::write(dir.join("a.txt"), "hit 1\nhit 2\nhit 3\nhit 4\n").unwrap();
+        fs::write(dir.join("b.txt"), "hit 5\n").unwrap();
+        let search = dir.to_str().unwrap();
+
+        let mut cursor: Option<GrepCursor> = None;
+        let mut seen = Vec::new();
+        loop {
+            let page = execute_paged("hit", search, None, cursor.as_ref(), 1, Some(2)).unwrap();
+            for file in &page.files {
+                let name = Path::new(&file.path).file_name().unwrap().to_string_lossy();
+                seen.extend(file.matches.iter().map(|m| (name.to_string(), m.line_num)));
+            }
+            cursor = page.next_offset;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        let expected = [("a.txt", 1), ("a.txt", 2), ("b.txt", 1)];
+        let expected: Vec<(String, usize)> =
+            expected.iter().map(|(f, l)| (f.to_string(), *l)).collect();
+        assert_eq!(seen, expected);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_grep_changed_searches_only_changed_files() {
+        let dir = temp_dir("changed");
+        let cwd = dir.to_str().unwrap();
+        fs::write(dir.join("edited.rs"), "fn target() {}\n").unwrap();
+        fs::write(dir.join("untouched.rs"), "fn target() {}\n").unwrap();
+        vcs::init(cwd, false, true).unwrap();
+        vcs::stage_files(cwd, vec![]).unwrap();
+        vcs::commit(cwd, "base").unwrap();
+
+        fs::write(
+            dir.join("edited.rs"),
+            "fn target() {}\nfn target_two() {}\n",
+        )
+        .unwrap();
+
+        let out = execute_changed("target", cwd, "HEAD", None).unwrap();
+        assert_eq!(out.metadata.count, 2);
+        assert_eq!(out.metadata.scanned.unwrap().files_searched, 1);
+        assert!(out.output.contains("edited.rs"));
+        assert!(!out.output.contains("untouched.rs"));
+
+        assert!(execute_changed("target", cwd, "no-such-ref", None).is_err());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,254 @@
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Read chunk size for streaming hashes (64KB)
+const HASH_CHUNK: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Algo {
+    #[default]
+    Blake3,
+    Sha256,
+}
+
+impl Algo {
+    /// Parse an algorithm name; empty selects the default (blake3)
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "" | "blake3" => Ok(Algo::Blake3),
+            "sha256" | "sha-256" => Ok(Algo::Sha256),
+            other => Err(format!("Unknown hash algorithm: {}", other)),
+        }
+    }
+}
+
+enum Hasher {
+    Blake3(Box<blake3::Hasher>),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    fn new(algo: Algo) -> Self {
+        match algo {
+            Algo::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+            Algo::Sha256 => Hasher::Sha256(Sha256::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Blake3(h) => {
+                h.update(data);
+            }
+            Hasher::Sha256(h) => h.update(data),
+        }
+    }
+
+    fn finish_hex(self) -> String {
+        match self {
+            Hasher::Blake3(h) => h.finalize().to_hex().to_string(),
+            Hasher::Sha256(h) => h.finalize().iter().map(|b| format!("{:02x}", b)).collect(),
+        }
+    }
+}
+
+/// Hash a file's contents, streaming it in chunks. Returns a lowercase hex digest.
+pub fn fs_hash(path: &str, algo: Algo) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut hasher = Hasher::new(algo);
+    let mut buffer = vec![0u8; HASH_CHUNK];
+
+    loop {
+        let n = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+
+    Ok(hasher.finish_hex())
+}
+
+/// Hash an in-memory buffer. Returns a lowercase hex digest.
+pub fn hash_bytes(data: &[u8], algo: Algo) -> String {
+    let mut hasher = Hasher::new(algo);
+    hasher.update(data);
+    hasher.finish_hex()
+}
+
+/// Files that differ between two directory trees, as sorted `/`-separated
+/// paths relative to each root
+#[derive(Debug, Default, Serialize)]
+pub struct DirDiff {
+    pub only_in_a: Vec<String>,
+    pub only_in_b: Vec<String>,
+    /// Present in both with different content
+    pub differing: Vec<String>,
+}
+
+/// Compare the files under `a` and `b` by content. `ignore_patterns` are
+/// globs matched against both the relative path and each entry's name, so
+/// `node_modules` skips that directory at any depth and `*.log` any log file.
+pub fn fs_diff_dirs(a: &str, b: &str, ignore_patterns: &[String]) -> Result<DirDiff, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in ignore_patterns {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| format!("Invalid glob pattern '{}': {}", pattern, e))?;
+        builder.add(glob);
+    }
+    let ignore = builder
+        .build()
+        .map_err(|e| format!("Failed to build glob set: {}", e))?;
+
+    let files_a = tree_files(Path::new(a), &ignore)?;
+    let mut files_b = tree_files(Path::new(b), &ignore)?;
+
+    let mut diff = DirDiff::default();
+    for (rel, size_a) in files_a {
+        match files_b.remove(&rel) {
+            None => diff.only_in_a.push(rel),
+            Some(size_b) => {
+                let differs = size_a != size_b
+                    || fs_hash(&join(a, &rel), Algo::Blake3)?
+                        != fs_hash(&join(b, &rel), Algo::Blake3)?;
+                if differs {
+                    diff.differing.push(rel);
+                }
+            }
+        }
+    }
+    diff.only_in_b = files_b.into_keys().collect();
+    Ok(diff)
+}
+
+/// Regular files under `root` that survive `ignore`, keyed by relative path, with their sizes
+fn tree_files(root: &Path, ignore: &GlobSet) -> Result<BTreeMap<String, u64>, String> {
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", root.display()));
+    }
+    let mut files = BTreeMap::new();
+    let walker = WalkDir::new(root).into_iter().filter_entry(|entry| {
+        let rel = relative(root, entry.path());
+        entry.depth() == 0 || !(ignore.is_match(&rel) || ignore.is_match(entry.file_name()))
+    });
+    for entry in walker {
+        let entry = entry.map_err(|e| format!("Failed to walk directory: {}", e))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let size = entry
+            .metadata()
+            .map_err(|e| format!("Failed to read metadata: {}", e))?
+            .len();
+        files.insert(relative(root, entry.path()), size);
+    }
+    Ok(files)
+}
+
+fn relative(root: &Path, path: &Path) -> String {
+    let rel = path.strip_prefix(root).unwrap_or(path);
+    rel.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn join(root: &str, rel: &str) -> String {
+    Path::new(root).join(rel).to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_path;
+    use std::fs;
+
+    #[test]
+    fn test_known_vectors() {
+        assert_eq!(
+            hash_bytes(b"", Algo::Blake3),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+        assert_eq!(
+            hash_bytes(b"abc", Algo::Blake3),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+        assert_eq!(
+            hash_bytes(b"abc", Algo::Sha256),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_parse_algo() {
+        assert_eq!(Algo::parse("").unwrap(), Algo::Blake3);
+        assert_eq!(Algo::parse("SHA256").unwrap(), Algo::Sha256);
+        assert!(Algo::parse("md5").is_err());
+    }
+
+    #[test]
+    fn test_large_file_streaming() {
+        let path = temp_path("hash");
+        // Several chunks plus a partial one
+        let content: Vec<u8> = (0..(HASH_CHUNK * 5 + 123))
+            .map(|i| (i % 251) as u8)
+            .collect();
+        fs::write(&path, &content).unwrap();
+
+        let path_str = path.to_str().unwrap();
+        for algo in [Algo::Blake3, Algo::Sha256] {
+            assert_eq!(fs_hash(path_str, algo).unwrap(), hash_bytes(&content, algo));
+        }
+
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_diff_dirs_added_removed_modified() {
+        let base = temp_path("hash_diff");
+        let (a, b) = (base.join("template"), base.join("project"));
+        for dir in [&a, &b] {
+            fs::create_dir_all(dir.join("src")).unwrap();
+            fs::create_dir_all(dir.join("node_modules")).unwrap();
+            fs::write(dir.join("src").join("same.rs"), "fn same() {}").unwrap();
+        }
+        fs::write(a.join("removed.txt"), "only in template").unwrap();
+        fs::write(b.join("src").join("added.rs"), "fn added() {}").unwrap();
+        fs::write(a.join("config.toml"), "name = \"a\"").unwrap();
+        fs::write(b.join("config.toml"), "name = \"b\"").unwrap();
+        fs::write(b.join("node_modules").join("dep.js"), "ignored").unwrap();
+        fs::write(b.join("debug.log"), "ignored").unwrap();
+
+        let (a_str, b_str) = (a.to_str().unwrap(), b.to_str().unwrap());
+        let ignore = vec!["node_modules".to_string(), "*.log".to_string()];
+        let diff = fs_diff_dirs(a_str, b_str, &ignore).unwrap();
+        assert_eq!(diff.only_in_a, vec!["removed.txt"]);
+        assert_eq!(diff.only_in_b, vec!["src/added.rs"]);
+        assert_eq!(diff.differing, vec!["config.toml"]);
+
+        let unfiltered = fs_diff_dirs(a_str, b_str, &[]).unwrap();
+        assert_eq!(
+            unfiltered.only_in_b,
+            vec!["debug.log", "node_modules/dep.js", "src/added.rs"]
+        );
+        assert!(fs_diff_dirs(a_str, base.join("missing").to_str().unwrap(), &[]).is_err());
+
+        fs::remove_dir_all(&base).ok();
+    }
+
+    #[test]
+    fn test_missing_file() {
+        assert!(fs_hash("/nonexistent/ironcode/file", Algo::Blake3).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use portable_pty::{native_pty_system, PtySize};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::indexer::{extract_symbols, Language};
+
+/// A probe returns a short detail on success, or the error
+type Check = fn() -> Result<String, String>;
+
+#[derive(Debug, Serialize)]
+pub struct SubsystemHealth {
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl SubsystemHealth {
+    fn from_result(result: Result<String, String>) -> Self {
+        match result {
+            Ok(detail) => Self { ok: true, detail },
+            Err(detail) => Self { ok: false, detail },
+        }
+    }
+}
+
+/// Run a tiny operation in each native subsystem so missing grammars or
+/// platform problems show up at startup rather than on first use.
+/// Keys: `tree_sitter`, `git2`, `pty`, `notify`, `rule_engine`.
+pub fn healthcheck() -> BTreeMap<&'static str, SubsystemHealth> {
+    let checks: [(&'static str, Check); 5] = [
+        ("tree_sitter", check_tree_sitter),
+        ("git2", check_git),
+        ("pty", check_pty),
+        ("notify", check_watcher),
+        ("rule_engine", check_rule_engine),
+    ];
+    checks
+        .into_iter()
+        .map(|(name, check)| (name, SubsystemHealth::from_result(check())))
+        .collect()
+}
+
+fn check_tree_sitter() -> Result<String, String> {
+    let symbols = extract_symbols("healthcheck.rs", b"fn probe() {}\n", Language::Rust);
+    if symbols.iter().any(|sym| sym.name == "probe") {
+        Ok("rust grammar parsed a function".to_string())
+    } else {
+        Err("rust grammar did not find the probe function".to_string())
+    }
+}
+
+/// Opens the repository around the current directory; not being in one is fine
+fn check_git() -> Result<String, String> {
+    let (major, minor, patch) = git2::Version::get().libgit2_version();
+    let cwd = std::env::current_dir().map_err(|e| format!("current dir: {}", e))?;
+    let repo = match git2::Repository::discover(&cwd) {
+        Ok(repo) => repo
+            .workdir()
+            .unwrap_or_else(|| repo.path())
+            .display()
+            .to_string(),
+        Err(e) if e.code() == git2::ErrorCode::NotFound => "none".to_string(),
+        Err(e) => return Err(e.message().to_string()),
+    };
+    Ok(format!(
+        "libgit2 {}.{}.{}, repository: {}",
+        major, minor, patch, repo
+    ))
+}
+
+fn check_pty() -> Result<String, String> {
+    native_pty_system()
+        .openpty(PtySize {
+            rows: 24,
+            cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map(|_| "opened and closed a pty".to_string())
+        .map_err(|e| format!("Failed to open PTY: {}", e))
+}
+
+fn check_watcher() -> Result<String, String> {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let id = format!("ironcode_healthcheck_{}_{}", std::process::id(), nanos);
+    let dir = std::env::temp_dir().join(&id);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("temp dir: {}", e))?;
+
+    let result = crate::watcher::create(
+        id.clone(),
+        dir.to_string_lossy().to_string(),
+        vec![],
+        16,
+        false,
+    )
+    .and_then(|_| crate::watcher::remove(id));
+    std::fs::remove_dir_all(&dir).ok();
+    result.map(|_| "created and removed a watcher".to_string())
+}
+
+/// The arity rules only yield a two-word prefix for `git status` when the GRL loads
+fn check_rule_engine() -> Result<String, String> {
+    let prefix = crate::shell::extract_command_prefix(&["git".to_string(), "status".to_string()]);
+    if prefix == "git status" {
+        Ok("arity rules loaded".to_string())
+    } else {
+        Err(format!("arity rules not loaded (got '{}')", prefix))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_healthcheck_core_subsystems_ok() {
+        let report = healthcheck();
+        assert_eq!(report.len(), 5);
+        for name in ["tree_sitter", "git2", "notify", "rule_engine", "pty"] {
+            let health = &report[name];
+            assert!(health.ok, "{}: {}", name, health.detail);
+        }
+    }
+}
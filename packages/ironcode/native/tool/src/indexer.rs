@@ -1,11 +1,14 @@
+use crate::error::{ErrorCode, ToolError};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Read;
 use std::path::Path;
 use tree_sitter::Parser;
 
 /// Max content bytes per symbol to keep memory bounded
 const MAX_CONTENT_BYTES: usize = 8192;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum SymbolKind {
     Function,
@@ -18,6 +21,7 @@ pub enum SymbolKind {
     Trait,
     Module,
     Variable,
+    Component, // function or const whose body renders JSX
     Chunk, // fallback line-chunked content
 }
 
@@ -34,6 +38,7 @@ impl std::fmt::Display for SymbolKind {
             SymbolKind::Trait => "trait",
             SymbolKind::Module => "module",
             SymbolKind::Variable => "variable",
+            SymbolKind::Component => "component",
             SymbolKind::Chunk => "chunk",
         };
         write!(f, "{}", s)
@@ -50,6 +55,10 @@ pub struct CodeSymbol {
     /// Truncated source content of the symbol
     pub content: String,
     pub language: String,
+    /// Parameter list of a function or method as written, e.g. `(host: &str, timeout_ms: u64)`.
+    /// Indexed separately so it is searchable even when `content` is truncated.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub parameters: String,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -68,8 +77,60 @@ pub enum Language {
     Cpp,
     Php,
     Scala,
+    Zig,
+    Elixir,
+    Haskell,
+    // Languages below have no tree-sitter grammar and are indexed as line chunks
+    Shell,
+    Dockerfile,
+    Makefile,
+    CMake,
+}
+
+impl Language {
+    pub const ALL: [Language; 21] = [
+        Language::TypeScript,
+        Language::TypeScriptX,
+        Language::JavaScript,
+        Language::JavaScriptX,
+        Language::Python,
+        Language::Rust,
+        Language::Go,
+        Language::Java,
+        Language::CSharp,
+        Language::Ruby,
+        Language::C,
+        Language::Cpp,
+        Language::Php,
+        Language::Scala,
+        Language::Zig,
+        Language::Elixir,
+        Language::Haskell,
+        Language::Shell,
+        Language::Dockerfile,
+        Language::Makefile,
+        Language::CMake,
+    ];
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyntaxError {
+    /// 1-based line
+    pub line: usize,
+    /// 1-based column, in bytes
+    pub column: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyntaxCheck {
+    pub ok: bool,
+    pub errors: Vec<SyntaxError>,
 }
 
+/// Bytes read from the start of a file when sniffing for a shebang
+const SHEBANG_SNIFF_BYTES: usize = 256;
+
 pub fn detect_language(path: &Path) -> Option<Language> {
     match path.extension()?.to_str()? {
         "ts" => Some(Language::TypeScript),
@@ -86,10 +147,95 @@ pub fn detect_language(path: &Path) -> Option<Language> {
         "cpp" | "cc" | "cxx" | "hpp" | "hxx" => Some(Language::Cpp),
         "php" | "php8" | "php7" => Some(Language::Php),
         "scala" | "sc" => Some(Language::Scala),
+        "zig" => Some(Language::Zig),
+        "ex" | "exs" => Some(Language::Elixir),
+        "hs" => Some(Language::Haskell),
+        _ => None,
+    }
+}
+
+/// Parse a language name or common alias (`rust`, `rs`, `tsx`, `c++`, ...)
+pub fn language_from_name(name: &str) -> Option<Language> {
+    match name.to_ascii_lowercase().as_str() {
+        "typescript" | "ts" => Some(Language::TypeScript),
+        "tsx" => Some(Language::TypeScriptX),
+        "javascript" | "js" => Some(Language::JavaScript),
+        "jsx" => Some(Language::JavaScriptX),
+        "python" | "py" => Some(Language::Python),
+        "rust" | "rs" => Some(Language::Rust),
+        "go" => Some(Language::Go),
+        "java" => Some(Language::Java),
+        "csharp" | "c#" | "cs" => Some(Language::CSharp),
+        "ruby" | "rb" => Some(Language::Ruby),
+        "c" => Some(Language::C),
+        "cpp" | "c++" => Some(Language::Cpp),
+        "php" => Some(Language::Php),
+        "scala" => Some(Language::Scala),
+        "zig" => Some(Language::Zig),
+        "elixir" | "ex" => Some(Language::Elixir),
+        "haskell" | "hs" => Some(Language::Haskell),
+        "shell" | "sh" | "bash" => Some(Language::Shell),
+        "dockerfile" => Some(Language::Dockerfile),
+        "makefile" => Some(Language::Makefile),
+        "cmake" => Some(Language::CMake),
+        _ => None,
+    }
+}
+
+/// Fallback for files whose extension is missing or unknown: well-known
+/// filenames first, then a `#!` shebang on the first line of `head`.
+pub fn detect_language_from_content(path: &Path, head: &[u8]) -> Option<Language> {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    match file_name {
+        "Dockerfile" | "Containerfile" => return Some(Language::Dockerfile),
+        "Makefile" | "makefile" | "GNUmakefile" => return Some(Language::Makefile),
+        "CMakeLists.txt" => return Some(Language::CMake),
+        _ if file_name.starts_with("Dockerfile.") => return Some(Language::Dockerfile),
+        _ => {}
+    }
+
+    let first_line = head.split(|&b| b == b'\n').next()?;
+    let shebang = std::str::from_utf8(first_line).ok()?.strip_prefix("#!")?;
+    let mut parts = shebang.split_whitespace();
+    let mut interpreter = parts.next()?.rsplit('/').next()?;
+    // `#!/usr/bin/env [-S] python3` names the interpreter as an argument
+    if interpreter == "env" {
+        interpreter = parts.find(|p| !p.starts_with('-'))?;
+    }
+
+    match interpreter {
+        i if i.starts_with("python") => Some(Language::Python),
+        "node" | "nodejs" | "bun" => Some(Language::JavaScript),
+        "deno" | "ts-node" | "tsx" => Some(Language::TypeScript),
+        i if i.starts_with("ruby") => Some(Language::Ruby),
+        i if i.starts_with("php") => Some(Language::Php),
+        "scala" => Some(Language::Scala),
+        "elixir" => Some(Language::Elixir),
+        "runghc" | "runhaskell" => Some(Language::Haskell),
+        "sh" | "bash" | "zsh" | "dash" | "ksh" => Some(Language::Shell),
         _ => None,
     }
 }
 
+/// Detect by extension, falling back to well-known filenames and, for
+/// extensionless files only, the shebang line. Files with an unknown extension
+/// (images, lockfiles, ...) are never opened.
+pub fn sniff_language(path: &Path) -> Option<Language> {
+    if let Some(lang) = detect_language(path).or_else(|| detect_language_from_content(path, &[])) {
+        return Some(lang);
+    }
+    if path.extension().is_some() {
+        return None;
+    }
+    let mut head = Vec::with_capacity(SHEBANG_SNIFF_BYTES);
+    std::fs::File::open(path)
+        .ok()?
+        .take(SHEBANG_SNIFF_BYTES as u64)
+        .read_to_end(&mut head)
+        .ok()?;
+    detect_language_from_content(path, &head)
+}
+
 pub fn language_name(lang: Language) -> &'static str {
     match lang {
         Language::TypeScript | Language::TypeScriptX => "typescript",
@@ -104,11 +250,18 @@ pub fn language_name(lang: Language) -> &'static str {
         Language::Cpp => "cpp",
         Language::Php => "php",
         Language::Scala => "scala",
+        Language::Zig => "zig",
+        Language::Elixir => "elixir",
+        Language::Haskell => "haskell",
+        Language::Shell => "shell",
+        Language::Dockerfile => "dockerfile",
+        Language::Makefile => "makefile",
+        Language::CMake => "cmake",
     }
 }
 
-fn ts_language(lang: Language) -> tree_sitter::Language {
-    match lang {
+fn ts_language(lang: Language) -> Option<tree_sitter::Language> {
+    let ts_lang = match lang {
         Language::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
         Language::TypeScriptX => tree_sitter_typescript::LANGUAGE_TSX.into(),
         Language::JavaScript | Language::JavaScriptX => tree_sitter_javascript::LANGUAGE.into(),
@@ -122,13 +275,46 @@ fn ts_language(lang: Language) -> tree_sitter::Language {
         Language::Cpp => tree_sitter_cpp::LANGUAGE.into(),
         Language::Php => tree_sitter_php::LANGUAGE_PHP.into(),
         Language::Scala => tree_sitter_scala::LANGUAGE.into(),
+        Language::Zig => tree_sitter_zig::LANGUAGE.into(),
+        Language::Elixir => tree_sitter_elixir::LANGUAGE.into(),
+        Language::Haskell => tree_sitter_haskell::LANGUAGE.into(),
+        Language::Shell | Language::Dockerfile | Language::Makefile | Language::CMake => {
+            return None
+        }
+    };
+    Some(ts_lang)
+}
+
+/// Language version of every compiled-in tree-sitter grammar, keyed by
+/// language name (`tsx` separately from `typescript`). The node kinds the
+/// extractors match on can change between versions, so this is the first
+/// thing to check when extraction breaks after a dependency bump. Languages
+/// indexed as line chunks have no grammar and are omitted; `bash` is the
+/// grammar used by `shell::parse_bash_command`.
+pub fn grammar_info() -> BTreeMap<&'static str, usize> {
+    let mut info: BTreeMap<&'static str, usize> = Language::ALL
+        .iter()
+        .filter_map(|&lang| Some((grammar_name(lang), ts_language(lang)?.version())))
+        .collect();
+    let bash: tree_sitter::Language = tree_sitter_bash::LANGUAGE.into();
+    info.insert("bash", bash.version());
+    info
+}
+
+fn grammar_name(lang: Language) -> &'static str {
+    match lang {
+        Language::TypeScriptX => "tsx",
+        _ => language_name(lang),
     }
 }
 
 /// Extract code symbols from a file.
 pub fn extract_symbols(file_path: &str, source: &[u8], lang: Language) -> Vec<CodeSymbol> {
-    let ts_lang = ts_language(lang);
     let lang_name = language_name(lang);
+    let ts_lang = match ts_language(lang) {
+        Some(l) => l,
+        None => return chunk_by_lines(file_path, source, lang_name),
+    };
     let mut parser = Parser::new();
 
     if parser.set_language(&ts_lang).is_err() {
@@ -180,6 +366,16 @@ pub fn extract_symbols(file_path: &str, source: &[u8], lang: Language) -> Vec<Co
         Language::Scala => {
             extract_scala(source, root, file_path, &mut symbols);
         }
+        Language::Zig => {
+            extract_zig(source, root, file_path, &mut symbols);
+        }
+        Language::Elixir => {
+            extract_elixir(source, root, file_path, &mut symbols);
+        }
+        Language::Haskell => {
+            extract_haskell(source, root, file_path, &mut symbols);
+        }
+        Language::Shell | Language::Dockerfile | Language::Makefile | Language::CMake => {}
     }
 
     if symbols.is_empty() {
@@ -189,6 +385,67 @@ pub fn extract_symbols(file_path: &str, source: &[u8], lang: Language) -> Vec<Co
     }
 }
 
+/// Parse `source` and report `ERROR`/`MISSING` nodes. This is a parse sanity
+/// check only, not a type check.
+pub fn check_syntax(source: &str, language: &str) -> Result<SyntaxCheck, ToolError> {
+    let lang = language_from_name(language).ok_or_else(|| {
+        ToolError::new(
+            ErrorCode::InvalidArgument,
+            format!("Unknown language: {}", language),
+        )
+    })?;
+    let ts_lang = ts_language(lang).ok_or_else(|| {
+        ToolError::new(
+            ErrorCode::Unsupported,
+            format!("No parser available for {}", language_name(lang)),
+        )
+    })?;
+
+    let mut parser = Parser::new();
+    parser
+        .set_language(&ts_lang)
+        .map_err(|e| ToolError::new(ErrorCode::Other, format!("Failed to set language: {}", e)))?;
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| ToolError::new(ErrorCode::Other, "Failed to parse source"))?;
+
+    let mut errors = Vec::new();
+    collect_syntax_errors(tree.root_node(), source.as_bytes(), &mut errors);
+    Ok(SyntaxCheck {
+        ok: errors.is_empty(),
+        errors,
+    })
+}
+
+fn collect_syntax_errors(node: tree_sitter::Node, source: &[u8], errors: &mut Vec<SyntaxError>) {
+    let pos = node.start_position();
+    if node.is_missing() {
+        errors.push(SyntaxError {
+            line: pos.row + 1,
+            column: pos.column + 1,
+            message: format!("missing `{}`", node.kind()),
+        });
+        return;
+    }
+    if node.is_error() {
+        // Report the outermost ERROR only; its children are the unparsed tokens
+        let snippet: String = node_text(&node, source).chars().take(40).collect();
+        errors.push(SyntaxError {
+            line: pos.row + 1,
+            column: pos.column + 1,
+            message: format!("unexpected `{}`", snippet.lines().next().unwrap_or("")),
+        });
+        return;
+    }
+    if !node.has_error() {
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_syntax_errors(child, source, errors);
+    }
+}
+
 // ── helpers ──────────────────────────────────────────────────────────────────
 
 fn node_text<'a>(node: &tree_sitter::Node, source: &'a [u8]) -> &'a str {
@@ -208,6 +465,12 @@ fn make_symbol(
     let content = std::str::from_utf8(&source[start..end])
         .unwrap_or("")
         .to_string();
+    let parameters = match kind {
+        SymbolKind::Function | SymbolKind::Method | SymbolKind::Component => {
+            parameters_text(node, source)
+        }
+        _ => String::new(),
+    };
     CodeSymbol {
         file_path: file_path.to_string(),
         line_start: node.start_position().row + 1,
@@ -216,9 +479,38 @@ fn make_symbol(
         kind,
         content,
         language: language.to_string(),
+        parameters,
     }
 }
 
+/// Source of the `parameters` field of a function-like node. Looks through the
+/// wrappers symbols are recorded on: JS/TS variable declarators (`value`),
+/// Python decorated definitions (`definition`) and C/C++ declarators.
+fn parameters_text(node: &tree_sitter::Node, source: &[u8]) -> String {
+    let mut current = *node;
+    for _ in 0..4 {
+        // Arrow functions with a single unparenthesized parameter use `parameter`
+        if let Some(params) = current
+            .child_by_field_name("parameters")
+            .or_else(|| current.child_by_field_name("parameter"))
+        {
+            let start = params.start_byte();
+            let end = params.end_byte().min(start + MAX_CONTENT_BYTES);
+            return std::str::from_utf8(&source[start..end])
+                .unwrap_or("")
+                .to_string();
+        }
+        current = match ["value", "definition", "declarator"]
+            .iter()
+            .find_map(|field| current.child_by_field_name(field))
+        {
+            Some(inner) => inner,
+            None => break,
+        };
+    }
+    String::new()
+}
+
 // ── TypeScript / JavaScript ───────────────────────────────────────────────────
 
 fn extract_js_ts(
@@ -254,6 +546,40 @@ fn qualify(ns: Option<&str>, name: &str) -> String {
     }
 }
 
+/// True if `node` or anything below it is a JSX element.
+fn contains_jsx(node: tree_sitter::Node) -> bool {
+    if matches!(
+        node.kind(),
+        "jsx_element" | "jsx_self_closing_element" | "jsx_fragment"
+    ) {
+        return true;
+    }
+    let mut cursor = node.walk();
+    let found = node.children(&mut cursor).any(contains_jsx);
+    found
+}
+
+/// React components are capitalized functions that render JSX.
+fn is_component(name: &str, body: tree_sitter::Node) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase()) && contains_jsx(body)
+}
+
+/// Matches wrapped components such as `memo(() => <div/>)` or `React.forwardRef(function (props, ref) { ... })`.
+fn is_wrapped_component(name: &str, call: tree_sitter::Node) -> bool {
+    let args = match call.child_by_field_name("arguments") {
+        Some(a) => a,
+        None => return false,
+    };
+    let mut cursor = args.walk();
+    let found = args.children(&mut cursor).any(|arg| {
+        matches!(
+            arg.kind(),
+            "arrow_function" | "function" | "function_expression"
+        ) && is_component(name, arg)
+    });
+    found
+}
+
 fn extract_js_ts_node(
     source: &[u8],
     child: tree_sitter::Node,
@@ -265,8 +591,9 @@ fn extract_js_ts_node(
     match child.kind() {
         "function_declaration" | "generator_function_declaration" => {
             if let Some(n) = child.child_by_field_name("name") {
+                let kind = if is_component(node_text(&n, source), child) { SymbolKind::Component } else { SymbolKind::Function };
                 let name = qualify(ns_prefix, node_text(&n, source));
-                symbols.push(make_symbol(&child, source, &name, SymbolKind::Function, file_path, lang_name));
+                symbols.push(make_symbol(&child, source, &name, kind, file_path, lang_name));
             }
         }
         "class_declaration" => {
@@ -343,7 +670,7 @@ fn extract_js_ts_node_exported(
 
 /// Extract variable declarators.
 /// `exported`: when true, ANY non-primitive value is indexed (catches `const X = Tool.define(...)`).
-/// When false, only arrow/function expressions are indexed.
+/// When false, only arrow/function expressions and wrapped components are indexed.
 fn extract_js_ts_var_decl(
     source: &[u8],
     node: tree_sitter::Node,
@@ -367,9 +694,13 @@ fn extract_js_ts_var_decl(
             None => continue,
         };
         let name = qualify(ns_prefix, node_text(&name_node, source));
+        let bare_name = node_text(&name_node, source);
         let vk = value_node.kind();
         if matches!(vk, "arrow_function" | "function" | "function_expression") {
-            symbols.push(make_symbol(&declarator, source, &name, SymbolKind::Function, file_path, lang_name));
+            let kind = if is_component(bare_name, value_node) { SymbolKind::Component } else { SymbolKind::Function };
+            symbols.push(make_symbol(&declarator, source, &name, kind, file_path, lang_name));
+        } else if vk == "call_expression" && is_wrapped_component(bare_name, value_node) {
+            symbols.push(make_symbol(&declarator, source, &name, SymbolKind::Component, file_path, lang_name));
         } else if exported {
             // e.g. `export const TaskTool = Tool.define(...)` or `export const Schema = z.object(...)`
             // Skip trivial primitives (string/number/boolean/null/undefined literals)
@@ -1040,6 +1371,173 @@ fn extract_scala_scope(
     }
 }
 
+// ── Zig ───────────────────────────────────────────────────────────────────────
+
+fn extract_zig(
+    source: &[u8],
+    node: tree_sitter::Node,
+    file_path: &str,
+    symbols: &mut Vec<CodeSymbol>,
+) {
+    extract_zig_scope(source, node, file_path, None, symbols);
+}
+
+/// Zig types are anonymous container expressions bound by `const`, so
+/// `const Point = struct { ... }` names the struct and scopes its methods.
+fn extract_zig_scope(
+    source: &[u8],
+    node: tree_sitter::Node,
+    file_path: &str,
+    ns_prefix: Option<&str>,
+    symbols: &mut Vec<CodeSymbol>,
+) {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "function_declaration" => {
+                if let Some(n) = child.child_by_field_name("name") {
+                    let name = qualify(ns_prefix, node_text(&n, source));
+                    let kind = if ns_prefix.is_some() { SymbolKind::Method } else { SymbolKind::Function };
+                    symbols.push(make_symbol(&child, source, &name, kind, file_path, "zig"));
+                }
+            }
+            "variable_declaration" => {
+                let mut inner = child.walk();
+                let named: Vec<_> = child.named_children(&mut inner).collect();
+                let ident = named.iter().find(|c| c.kind() == "identifier");
+                let container = named.iter().find(|c| {
+                    matches!(c.kind(), "struct_declaration" | "enum_declaration" | "union_declaration" | "opaque_declaration")
+                });
+                if let (Some(n), Some(body)) = (ident, container) {
+                    let name = qualify(ns_prefix, node_text(n, source));
+                    let kind = match body.kind() {
+                        "enum_declaration" => SymbolKind::Enum,
+                        "opaque_declaration" => SymbolKind::Type,
+                        _ => SymbolKind::Struct,
+                    };
+                    symbols.push(make_symbol(&child, source, &name, kind, file_path, "zig"));
+                    extract_zig_scope(source, *body, file_path, Some(&name), symbols);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// ── Elixir ────────────────────────────────────────────────────────────────────
+
+fn extract_elixir(
+    source: &[u8],
+    node: tree_sitter::Node,
+    file_path: &str,
+    symbols: &mut Vec<CodeSymbol>,
+) {
+    extract_elixir_scope(source, node, file_path, None, symbols);
+}
+
+/// Definitions in Elixir are ordinary macro calls (`defmodule Foo do ... end`),
+/// so this matches on the call target rather than on node kinds.
+fn extract_elixir_scope(
+    source: &[u8],
+    node: tree_sitter::Node,
+    file_path: &str,
+    module: Option<&str>,
+    symbols: &mut Vec<CodeSymbol>,
+) {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.kind() != "call" {
+            continue;
+        }
+        let target = match child.child_by_field_name("target") {
+            Some(t) => node_text(&t, source),
+            None => continue,
+        };
+        let mut inner = child.walk();
+        let parts: Vec<_> = child.children(&mut inner).collect();
+        let first_arg = parts
+            .iter()
+            .find(|c| c.kind() == "arguments")
+            .and_then(|a| a.named_child(0));
+        let body = parts.iter().find(|c| c.kind() == "do_block");
+        match target {
+            "defmodule" | "defprotocol" => {
+                if let Some(n) = first_arg {
+                    let name = qualify(module, node_text(&n, source));
+                    let kind = if target == "defprotocol" { SymbolKind::Interface } else { SymbolKind::Module };
+                    symbols.push(make_symbol(&child, source, &name, kind, file_path, "elixir"));
+                    if let Some(body) = body {
+                        extract_elixir_scope(source, *body, file_path, Some(&name), symbols);
+                    }
+                }
+            }
+            "def" | "defp" | "defmacro" | "defmacrop" => {
+                if let Some(name) = first_arg.and_then(|n| elixir_def_name(n, source)) {
+                    let name = qualify(module, name);
+                    push_clause(symbols, make_symbol(&child, source, &name, SymbolKind::Function, file_path, "elixir"));
+                }
+            }
+            "defstruct" => {
+                if let Some(m) = module {
+                    symbols.push(make_symbol(&child, source, m, SymbolKind::Struct, file_path, "elixir"));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Name of the function in a `def` head: `add(a, b)`, bare `empty?`, or
+/// either of those on the left of a `when` guard.
+fn elixir_def_name<'a>(head: tree_sitter::Node<'a>, source: &'a [u8]) -> Option<&'a str> {
+    match head.kind() {
+        "identifier" => Some(node_text(&head, source)),
+        "call" => head.child_by_field_name("target").map(|t| node_text(&t, source)),
+        "binary_operator" => head.child_by_field_name("left").and_then(|l| elixir_def_name(l, source)),
+        _ => None,
+    }
+}
+
+/// Elixir and Haskell define a function as consecutive clauses; fold a clause
+/// into the previous symbol when it continues the same definition.
+fn push_clause(symbols: &mut Vec<CodeSymbol>, symbol: CodeSymbol) {
+    if let Some(prev) = symbols.last_mut() {
+        if prev.name == symbol.name && prev.kind == symbol.kind {
+            prev.line_end = symbol.line_end;
+            return;
+        }
+    }
+    symbols.push(symbol);
+}
+
+// ── Haskell ───────────────────────────────────────────────────────────────────
+
+fn extract_haskell(
+    source: &[u8],
+    node: tree_sitter::Node,
+    file_path: &str,
+    symbols: &mut Vec<CodeSymbol>,
+) {
+    let declarations = match node.child_by_field_name("declarations") {
+        Some(d) => d,
+        None => return,
+    };
+    let mut cursor = declarations.walk();
+    for child in declarations.children(&mut cursor) {
+        let kind = match child.kind() {
+            "function" | "bind" => SymbolKind::Function,
+            "data_type" | "newtype" => SymbolKind::Struct,
+            "type_synomym" => SymbolKind::Type,
+            "class" => SymbolKind::Trait,
+            _ => continue,
+        };
+        if let Some(n) = child.child_by_field_name("name") {
+            let name = node_text(&n, source);
+            push_clause(symbols, make_symbol(&child, source, name, kind, file_path, "haskell"));
+        }
+    }
+}
+
 // ── Fallback: line chunks ─────────────────────────────────────────────────────
 
 /// Split file into overlapping 50-line chunks when tree-sitter parse fails
@@ -1072,6 +1570,7 @@ pub fn chunk_by_lines(file_path: &str, source: &[u8], lang_name: &str) -> Vec<Co
             kind: SymbolKind::Chunk,
             content: content[..content.len().min(MAX_CONTENT_BYTES)].to_string(),
             language: lang_name.to_string(),
+            parameters: String::new(),
         });
         if end >= total {
             break;
@@ -1080,3 +1579,221 @@ pub fn chunk_by_lines(file_path: &str, source: &[u8], lang_name: &str) -> Vec<Co
     }
     symbols
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_sniff_language_reads_only_extensionless_files() {
+        let dir = temp_dir("indexer_sniff");
+        let shebang = "#!/usr/bin/env python3\nprint('hi')\n";
+        std::fs::write(dir.join("deploy"), shebang).unwrap();
+        std::fs::write(dir.join("deploy.lock"), shebang).unwrap();
+        std::fs::write(dir.join("CMakeLists.txt"), "project(x)\n").unwrap();
+
+        assert_eq!(sniff_language(&dir.join("deploy")), Some(Language::Python));
+        // An unknown extension is not opened, shebang or not
+        assert_eq!(sniff_language(&dir.join("deploy.lock")), None);
+        // Well-known filenames match without reading the file
+        assert_eq!(
+            sniff_language(&dir.join("CMakeLists.txt")),
+            Some(Language::CMake)
+        );
+        assert_eq!(
+            sniff_language(&dir.join("missing/Dockerfile")),
+            Some(Language::Dockerfile)
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_grammar_info_reports_every_grammar() {
+        let info = grammar_info();
+        for lang in Language::ALL {
+            let name = grammar_name(lang);
+            match ts_language(lang) {
+                Some(_) => assert!(info[name] > 0, "{} has no version", name),
+                None => assert!(!info.contains_key(name)),
+            }
+        }
+        assert!(info["bash"] > 0);
+    }
+
+    #[test]
+    fn test_check_syntax_valid() {
+        let rust = check_syntax("fn main() {\n    let x = 1;\n}\n", "rust").unwrap();
+        assert!(rust.ok);
+        assert!(rust.errors.is_empty());
+
+        let source = "const add = (a: number, b: number): number => a + b;\n";
+        let ts = check_syntax(source, "ts").unwrap();
+        assert!(ts.ok);
+    }
+
+    #[test]
+    fn test_check_syntax_broken() {
+        let rust = check_syntax("fn main() {\n    let x = ;\n}\n", "rust").unwrap();
+        assert!(!rust.ok);
+        assert_eq!(rust.errors[0].line, 2);
+
+        let ts = check_syntax("function f( {\n  return 1;\n", "typescript").unwrap();
+        assert!(!ts.ok);
+        assert!(!ts.errors.is_empty());
+
+        assert!(check_syntax("x", "cobol").is_err());
+        assert!(check_syntax("all:", "makefile").is_err());
+    }
+
+    #[test]
+    fn test_extract_parameters() {
+        let rust = "struct Client;\n\nimpl Client {\n    fn send(&self, retries: u8) {}\n}\n\nfn connect(host: &str, timeout_ms: u64) {}\n";
+        let symbols = extract_symbols("lib.rs", rust.as_bytes(), Language::Rust);
+        let params = |name: &str| {
+            symbols
+                .iter()
+                .find(|s| s.name == name)
+                .map(|s| s.parameters.clone())
+                .unwrap()
+        };
+        assert_eq!(params("connect"), "(host: &str, timeout_ms: u64)");
+        assert_eq!(params("Client::send"), "(&self, retries: u8)");
+        assert_eq!(params("Client"), "");
+
+        let ts = "const load = async (path: string, opts: Options) => read(path);\nconst one = x => x;\n";
+        let symbols = extract_symbols("a.ts", ts.as_bytes(), Language::TypeScript);
+        assert_eq!(symbols[0].parameters, "(path: string, opts: Options)");
+        assert_eq!(symbols[1].parameters, "x");
+
+        let py = "@cached\ndef fetch(url, retry_count=3):\n    pass\n";
+        let symbols = extract_symbols("a.py", py.as_bytes(), Language::Python);
+        assert_eq!(symbols[0].parameters, "(url, retry_count=3)");
+
+        let c = "static char *join(const char *left, size_t max_len) { return 0; }\n";
+        let symbols = extract_symbols("a.c", c.as_bytes(), Language::C);
+        assert_eq!(symbols[0].parameters, "(const char *left, size_t max_len)");
+    }
+
+    #[test]
+    fn test_extract_tsx_components() {
+        let source = r#"import React, { memo } from "react";
+
+function Header() {
+  return <h1>Title</h1>;
+}
+
+const Footer = () => <footer />;
+
+const Row = memo(({ label }: { label: string }) => <li>{label}</li>);
+
+function formatTitle(title: string) {
+  return title.trim();
+}
+
+const renderItem = (item: string) => <span>{item}</span>;
+
+export const Page = () => (
+  <>
+    <Header />
+    <Footer />
+  </>
+);
+"#;
+        let symbols = extract_symbols("App.tsx", source.as_bytes(), Language::TypeScriptX);
+        let kind_of = |name: &str| symbols.iter().find(|s| s.name == name).map(|s| s.kind);
+
+        assert_eq!(kind_of("Header"), Some(SymbolKind::Component));
+        assert_eq!(kind_of("Footer"), Some(SymbolKind::Component));
+        assert_eq!(kind_of("Row"), Some(SymbolKind::Component));
+        assert_eq!(kind_of("Page"), Some(SymbolKind::Component));
+        assert_eq!(kind_of("formatTitle"), Some(SymbolKind::Function));
+        assert_eq!(kind_of("renderItem"), Some(SymbolKind::Function));
+    }
+
+    fn kinds(file: &str, source: &str) -> Vec<(String, SymbolKind)> {
+        let lang = detect_language(Path::new(file)).unwrap();
+        extract_symbols(file, source.as_bytes(), lang)
+            .into_iter()
+            .map(|s| (s.name, s.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_zig() {
+        let source = r#"const std = @import("std");
+
+const Point = struct {
+    x: i32,
+
+    pub fn norm(self: Point) i32 {
+        return self.x;
+    }
+};
+
+const Color = enum { red, green };
+
+pub fn main() void {}
+"#;
+        let symbols = kinds("main.zig", source);
+        assert!(symbols.contains(&("Point".into(), SymbolKind::Struct)));
+        assert!(symbols.contains(&("Point.norm".into(), SymbolKind::Method)));
+        assert!(symbols.contains(&("Color".into(), SymbolKind::Enum)));
+        assert!(symbols.contains(&("main".into(), SymbolKind::Function)));
+        assert!(!symbols.iter().any(|(name, _)| name == "std"));
+    }
+
+    #[test]
+    fn test_extract_elixir() {
+        let source = r#"defmodule Shop.Cart do
+  defstruct items: []
+
+  def add(cart, item) do
+    %{cart | items: [item | cart.items]}
+  end
+
+  def size(%{items: []}), do: 0
+  def size(cart) when is_map(cart), do: length(cart.items)
+
+  defp empty?, do: true
+end
+"#;
+        let symbols = kinds("cart.ex", source);
+        assert!(symbols.contains(&("Shop.Cart".into(), SymbolKind::Module)));
+        assert!(symbols.contains(&("Shop.Cart".into(), SymbolKind::Struct)));
+        assert!(symbols.contains(&("Shop.Cart.add".into(), SymbolKind::Function)));
+        assert!(symbols.contains(&("Shop.Cart.empty?".into(), SymbolKind::Function)));
+        let sizes = symbols.iter().filter(|(name, _)| name == "Shop.Cart.size").count();
+        assert_eq!(sizes, 1, "clauses should fold into one symbol");
+    }
+
+    #[test]
+    fn test_extract_haskell() {
+        let source = r#"module Main where
+
+data Shape = Circle Double | Square Double
+
+newtype Name = Name String
+
+class Describe a where
+  describe :: a -> String
+
+area :: Shape -> Double
+area (Circle r) = 3.14 * r * r
+area (Square s) = s * s
+
+main = print (area (Square 2))
+"#;
+        let symbols = extract_symbols("Main.hs", source.as_bytes(), Language::Haskell);
+        let area: Vec<_> = symbols.iter().filter(|s| s.name == "area").collect();
+        assert_eq!(area.len(), 1);
+        assert_eq!((area[0].line_start, area[0].line_end), (11, 12));
+
+        let symbols = kinds("Main.hs", source);
+        assert!(symbols.contains(&("Shape".into(), SymbolKind::Struct)));
+        assert!(symbols.contains(&("Name".into(), SymbolKind::Struct)));
+        assert!(symbols.contains(&("Describe".into(), SymbolKind::Trait)));
+        assert!(symbols.contains(&("main".into(), SymbolKind::Function)));
+    }
+}
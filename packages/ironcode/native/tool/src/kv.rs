@@ -0,0 +1,80 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{LazyLock, Mutex};
+
+/// Process-lifetime key/value store shared across FFI calls, for lightweight
+/// coordination such as "current index id for project X".
+///
+/// Keys and values are plain strings. Nothing is persisted to disk: the store
+/// starts empty every time the library is loaded.
+static STORE: LazyLock<Mutex<HashMap<String, String>>> = LazyLock::new(Default::default);
+
+fn with_store<T>(f: impl FnOnce(&mut HashMap<String, String>) -> T) -> T {
+    f(&mut STORE.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// Set `key` to `value`, returning the previous value if any
+pub fn set(key: &str, value: &str) -> Option<String> {
+    with_store(|store| store.insert(key.to_string(), value.to_string()))
+}
+
+pub fn get(key: &str) -> Option<String> {
+    with_store(|store| store.get(key).cloned())
+}
+
+/// Remove `key`, returning whether it was present
+pub fn delete(key: &str) -> bool {
+    with_store(|store| store.remove(key).is_some())
+}
+
+/// All entries whose key starts with `prefix`, ordered by key
+pub fn list_prefix(prefix: &str) -> BTreeMap<String, String> {
+    with_store(|store| {
+        store
+            .iter()
+            .filter(|(key, _)| key.starts_with(prefix))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_get_delete() {
+        let key = "test-kv-basic";
+        assert_eq!(get(key), None);
+        assert_eq!(set(key, "one"), None);
+        assert_eq!(get(key).as_deref(), Some("one"));
+        assert_eq!(set(key, "two").as_deref(), Some("one"));
+        assert_eq!(get(key).as_deref(), Some("two"));
+
+        assert!(delete(key));
+        assert!(!delete(key));
+        assert_eq!(get(key), None);
+    }
+
+    #[test]
+    fn test_list_prefix() {
+        set("test-kv-prefix/b", "2");
+        set("test-kv-prefix/a", "1");
+        set("test-kv-prefixed", "x");
+        set("test-kv-other/a", "3");
+
+        let entries = list_prefix("test-kv-prefix/");
+        let keys: Vec<&str> = entries.keys().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["test-kv-prefix/a", "test-kv-prefix/b"]);
+        assert_eq!(entries["test-kv-prefix/a"], "1");
+
+        for key in [
+            "test-kv-prefix/a",
+            "test-kv-prefix/b",
+            "test-kv-prefixed",
+            "test-kv-other/a",
+        ] {
+            delete(key);
+        }
+        assert!(list_prefix("test-kv-prefix/").is_empty());
+    }
+}
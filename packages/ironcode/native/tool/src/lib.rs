@@ -3,25 +3,38 @@ use std::os::raw::c_char;
 
 pub mod archive;
 pub mod bm25;
+pub mod cancel;
 pub mod codesearch;
+pub mod compress;
 pub mod edit;
+pub mod error;
 pub mod file_ignore;
 pub mod file_list;
 pub mod fuzzy;
 pub mod glob;
 pub mod grep;
+pub mod hash;
+pub mod health;
 pub mod indexer;
+pub mod kv;
+#[cfg(feature = "logging")]
+pub mod logging;
 pub mod lock;
 pub mod ls;
+pub mod path;
 pub mod read;
 pub mod permission;
 pub mod shell;
+pub mod stat;
 pub mod stats;
 pub mod terminal;
+#[cfg(test)]
+mod test_util;
 pub mod types;
 pub mod vcs;
 pub mod watcher;
 pub mod wildcard;
+pub mod write;
 #[cfg(feature = "webfetch")]
 pub mod webfetch;
 
@@ -54,6 +67,108 @@ pub unsafe extern "C" fn glob_ffi(pattern: *const c_char, search: *const c_char)
     }
 }
 
+/// Like `glob_ffi`, filtered by entry type: "files" (default when null or
+/// empty), "dirs" or "both". Returns null on an unknown type or invalid pattern.
+///
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `pattern` and `search` are valid, non-null,
+/// null-terminated C strings; `type_filter` may be null.
+#[no_mangle]
+pub unsafe extern "C" fn glob_typed_ffi(
+    pattern: *const c_char,
+    search: *const c_char,
+    type_filter: *const c_char,
+) -> *mut c_char {
+    let pattern_str = unsafe {
+        if pattern.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(pattern).to_str().unwrap_or("")
+    };
+
+    let search_str = unsafe {
+        if search.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(search).to_str().unwrap_or(".")
+    };
+
+    let type_str = unsafe {
+        if type_filter.is_null() {
+            ""
+        } else {
+            CStr::from_ptr(type_filter).to_str().unwrap_or("")
+        }
+    };
+
+    let entry_type = match glob::EntryType::parse(type_str) {
+        Ok(t) => t,
+        Err(_) => return std::ptr::null_mut(),
+    };
+
+    match glob::execute_typed(pattern_str, search_str, entry_type) {
+        Ok(output) => match serde_json::to_string(&output) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Test a single path against a glob pattern, without walking the filesystem.
+/// Returns 1 if it matches, 0 if not, -1 on null input or an invalid pattern.
+///
+/// # Safety
+/// The caller must ensure `pattern` and `path` are valid, non-null, null-terminated C strings.
+#[no_mangle]
+pub unsafe extern "C" fn glob_match_ffi(
+    pattern: *const c_char,
+    path: *const c_char,
+    case_insensitive: bool,
+) -> i32 {
+    if pattern.is_null() || path.is_null() {
+        return -1;
+    }
+    let pattern_str = CStr::from_ptr(pattern).to_str().unwrap_or("");
+    let path_str = CStr::from_ptr(path).to_str().unwrap_or("");
+
+    match glob::matches(pattern_str, path_str, case_insensitive) {
+        Ok(true) => 1,
+        Ok(false) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Test a single path against a JSON array of glob patterns (OR semantics).
+/// Returns 1 if any pattern matches, 0 if none do, -1 on null input or an invalid pattern.
+///
+/// # Safety
+/// The caller must ensure `patterns_json` and `path` are valid, non-null, null-terminated C strings.
+#[no_mangle]
+pub unsafe extern "C" fn glob_match_any_ffi(
+    patterns_json: *const c_char,
+    path: *const c_char,
+) -> i32 {
+    if patterns_json.is_null() || path.is_null() {
+        return -1;
+    }
+    let patterns: Vec<String> = match CStr::from_ptr(patterns_json).to_str() {
+        Ok(s) => match serde_json::from_str(s) {
+            Ok(p) => p,
+            Err(_) => return -1,
+        },
+        Err(_) => return -1,
+    };
+    let path_str = CStr::from_ptr(path).to_str().unwrap_or("");
+
+    match glob::matches_any(&patterns, path_str, false) {
+        Ok(true) => 1,
+        Ok(false) => 0,
+        Err(_) => -1,
+    }
+}
+
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
 /// The caller must ensure that `path` and `ignore_patterns_json` are valid, non-null,
@@ -90,6 +205,54 @@ pub unsafe extern "C" fn ls_ffi(
     }
 }
 
+/// Like `ls_ffi`, with an `entries` array of {"path", "status"} where status is
+/// "modified", "untracked", "staged" or "clean" in the repository containing `cwd`.
+/// Outside a repository `status` is omitted. Returns null on error.
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `path` and `cwd` are valid, non-null, null-terminated
+/// C strings, and `ignore_patterns_json` is null or a valid C string, all remaining
+/// valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn ls_with_status_ffi(
+    path: *const c_char,
+    cwd: *const c_char,
+    ignore_patterns_json: *const c_char,
+) -> *mut c_char {
+    let path_str = unsafe {
+        if path.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(path).to_str().unwrap_or(".")
+    };
+
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
+    };
+
+    let ignore_patterns = unsafe {
+        if ignore_patterns_json.is_null() {
+            vec![]
+        } else {
+            let json_str = CStr::from_ptr(ignore_patterns_json)
+                .to_str()
+                .unwrap_or("[]");
+            serde_json::from_str(json_str).unwrap_or_else(|_| vec![])
+        }
+    };
+
+    match ls::execute_with_status(path_str, cwd_str, ignore_patterns) {
+        Ok(output) => match serde_json::to_string(&output) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
 /// The caller must ensure that `filepath` is a valid, non-null, null-terminated
@@ -123,6 +286,52 @@ pub unsafe extern "C" fn read_ffi(filepath: *const c_char, offset: i32, limit: i
     }
 }
 
+/// Like `read_ffi`, but a file larger than `max_bytes` is not read; returns
+/// `{"too_large": true, "size": N}` instead. A negative `max_bytes` disables the guard.
+///
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `filepath` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn read_guarded_ffi(
+    filepath: *const c_char,
+    offset: i32,
+    limit: i32,
+    max_bytes: i64,
+) -> *mut c_char {
+    let filepath_str = unsafe {
+        if filepath.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(filepath).to_str().unwrap_or("")
+    };
+
+    let offset_opt = if offset >= 0 {
+        Some(offset as usize)
+    } else {
+        None
+    };
+    let limit_opt = if limit >= 0 {
+        Some(limit as usize)
+    } else {
+        None
+    };
+    let max_bytes_opt = if max_bytes >= 0 {
+        Some(max_bytes as usize)
+    } else {
+        None
+    };
+
+    match read::execute_guarded(filepath_str, offset_opt, limit_opt, max_bytes_opt) {
+        Ok(output) => match serde_json::to_string(&output) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
 /// The caller must ensure that `filepath` is a valid, non-null, null-terminated
@@ -138,6 +347,22 @@ pub unsafe extern "C" fn read_raw_ffi(filepath: *const c_char) -> *mut c_char {
 
     use std::io::{BufReader, Read};
 
+    // Large files are memory-mapped and validated in place, so the only copy is
+    // the returned C string. Non-UTF-8 content returns null; use read_bytes_ffi.
+    let is_large = std::fs::metadata(filepath_str)
+        .map(|m| m.len() >= read::MMAP_THRESHOLD)
+        .unwrap_or(false);
+    if is_large {
+        return match read::with_file_bytes(filepath_str, |bytes| {
+            std::str::from_utf8(bytes)
+                .ok()
+                .and_then(|text| CString::new(text).ok())
+        }) {
+            Ok(Some(cstring)) => cstring.into_raw(),
+            _ => std::ptr::null_mut(),
+        };
+    }
+
     // Use BufReader with larger buffer for better performance
     match std::fs::File::open(filepath_str) {
         Ok(file) => {
@@ -201,45 +426,56 @@ pub unsafe extern "C" fn grep_ffi(
     }
 }
 
+/// Grep with options passed as JSON: {"binary": "skip" | "search_as_text" |
+/// "treat_all_as_text", "sort": true, "op_id": 7, "gitignore": false}. Missing fields
+/// use their defaults; `sort` (default true) orders matches by path then line number,
+/// `op_id` (from `operation_begin_ffi`) makes the search cancellable, and `gitignore`
+/// skips ignored files. `metadata.scanned` reports files searched and skipped.
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure that `filepath` and `content` are valid, non-null,
-/// null-terminated C strings that remain valid for the duration of the call.
+/// The caller must ensure that `pattern` and `search` are valid, non-null,
+/// null-terminated C strings, and that `include_glob` and `options_json` are
+/// either null or valid C strings.
 #[no_mangle]
-pub unsafe extern "C" fn write_raw_ffi(filepath: *const c_char, content: *const c_char) -> i32 {
-    let filepath_str = unsafe {
-        if filepath.is_null() {
-            return -1;
+pub unsafe extern "C" fn grep_opts_ffi(
+    pattern: *const c_char,
+    search: *const c_char,
+    include_glob: *const c_char,
+    options_json: *const c_char,
+) -> *mut c_char {
+    let pattern_str = unsafe {
+        if pattern.is_null() {
+            return std::ptr::null_mut();
         }
-        CStr::from_ptr(filepath).to_str().unwrap_or("")
+        CStr::from_ptr(pattern).to_str().unwrap_or("")
     };
 
-    let content_str = unsafe {
-        if content.is_null() {
-            return -1;
+    let search_str = unsafe {
+        if search.is_null() {
+            return std::ptr::null_mut();
         }
-        CStr::from_ptr(content).to_str().unwrap_or("")
+        CStr::from_ptr(search).to_str().unwrap_or(".")
     };
 
-    // Create parent directories if they don't exist
-    if let Some(parent) = std::path::Path::new(filepath_str).parent() {
-        if std::fs::create_dir_all(parent).is_err() {
-            return -1;
+    let include_glob_opt = unsafe {
+        if include_glob.is_null() {
+            None
+        } else {
+            Some(CStr::from_ptr(include_glob).to_str().unwrap_or(""))
         }
-    }
+    };
 
-    match std::fs::write(filepath_str, content_str) {
-        Ok(_) => 0,   // Success
-        Err(_) => -1, // Error
-    }
-}
+    let options: grep::GrepOptions = if options_json.is_null() {
+        grep::GrepOptions::default()
+    } else {
+        match serde_json::from_str(CStr::from_ptr(options_json).to_str().unwrap_or("{}")) {
+            Ok(options) => options,
+            Err(_) => return std::ptr::null_mut(),
+        }
+    };
 
-/// # Safety
-/// This function is safe to call from C as it doesn't take any pointer arguments.
-#[no_mangle]
-pub unsafe extern "C" fn stats_ffi() -> *mut c_char {
-    match stats::get_stats() {
-        Ok(stats) => match serde_json::to_string(&stats) {
+    match grep::execute_with_options(pattern_str, search_str, include_glob_opt, &options) {
+        Ok(output) => match serde_json::to_string(&output) {
             Ok(json) => CString::new(json).unwrap().into_raw(),
             Err(_) => std::ptr::null_mut(),
         },
@@ -247,93 +483,148 @@ pub unsafe extern "C" fn stats_ffi() -> *mut c_char {
     }
 }
 
+/// Reserve an operation id for a long call (grep via `"op_id"` in the options,
+/// `codesearch_index_cancellable_ffi`), so it can be stopped with `cancel_operation_ffi`.
 /// # Safety
-/// This function is unsafe because it takes ownership of and frees a raw pointer.
-/// The caller must ensure that `s` is a valid pointer that was previously returned
-/// by one of the other FFI functions in this module, and that it's only freed once.
+/// This function is safe to call from C as it takes no pointer arguments.
 #[no_mangle]
-pub unsafe extern "C" fn free_string(s: *mut c_char) {
-    if !s.is_null() {
-        unsafe {
-            let _ = CString::from_raw(s);
-        }
-    }
+pub unsafe extern "C" fn operation_begin_ffi() -> u64 {
+    cancel::begin()
 }
 
-// Terminal FFI functions
+/// Cancel a running operation. The call returns early with `cancelled: true`.
+/// Returns 1 if the operation was signalled, 0 if the id is unknown or finished.
+/// # Safety
+/// This function is safe to call from C as it takes no pointer arguments.
+#[no_mangle]
+pub unsafe extern "C" fn cancel_operation_ffi(op_id: u64) -> i32 {
+    if cancel::cancel(op_id) {
+        1
+    } else {
+        0
+    }
+}
 
+/// Grep one page of at most `page_size` matches. `cursor_json` is null for the
+/// first page, then the previous page's `next_offset`. `max_per_file` of 0
+/// means no per-file cap.
+/// Returns JSON: {"files": [{"path", "matches": [{"lineNum", "lineText", "ranges"}],
+/// "truncated_in_file"}], "count", "next_offset": {"path", "line"} | null}, or null on error.
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure that `id` and `cwd` are valid, non-null, null-terminated
-/// C strings that remain valid for the duration of the call.
+/// The caller must ensure that `pattern` and `search` are valid, non-null,
+/// null-terminated C strings, and that `include_glob` and `cursor_json` are
+/// either null or valid C strings.
 #[no_mangle]
-pub unsafe extern "C" fn terminal_create(
-    id: *const c_char,
-    cwd: *const c_char,
-    rows: u16,
-    cols: u16,
+pub unsafe extern "C" fn grep_paged_ffi(
+    pattern: *const c_char,
+    search: *const c_char,
+    include_glob: *const c_char,
+    cursor_json: *const c_char,
+    page_size: u32,
+    max_per_file: u32,
 ) -> *mut c_char {
-    let id_str = unsafe {
-        if id.is_null() {
+    let pattern_str = unsafe {
+        if pattern.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(id).to_str().unwrap_or("")
+        CStr::from_ptr(pattern).to_str().unwrap_or("")
     };
 
-    let cwd_str = unsafe {
-        if cwd.is_null() {
+    let search_str = unsafe {
+        if search.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(search).to_str().unwrap_or(".")
+    };
+
+    let include_glob_opt = unsafe {
+        if include_glob.is_null() {
             None
         } else {
-            Some(CStr::from_ptr(cwd).to_str().unwrap_or("."))
+            Some(CStr::from_ptr(include_glob).to_str().unwrap_or(""))
         }
     };
 
-    match terminal::create(id_str, None, vec![], cwd_str, None, rows, cols) {
-        Ok(info) => match serde_json::to_string(&info) {
-            Ok(json) => CString::new(json).unwrap().into_raw(),
-            Err(_) => std::ptr::null_mut(),
-        },
-        Err(_) => std::ptr::null_mut(),
-    }
-}
-
-/// # Safety
-/// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure that `id` and `data` are valid, non-null, null-terminated
-/// C strings that remain valid for the duration of the call.
-#[no_mangle]
-pub unsafe extern "C" fn terminal_write(id: *const c_char, data: *const c_char) -> bool {
-    let id_str = unsafe {
-        if id.is_null() {
-            return false;
+    let cursor: Option<grep::GrepCursor> = unsafe {
+        if cursor_json.is_null() {
+            None
+        } else {
+            match serde_json::from_str(CStr::from_ptr(cursor_json).to_str().unwrap_or("")) {
+                Ok(cursor) => Some(cursor),
+                Err(_) => return std::ptr::null_mut(),
+            }
         }
-        CStr::from_ptr(id).to_str().unwrap_or("")
     };
 
-    let data_str = unsafe {
-        if data.is_null() {
-            return false;
-        }
-        CStr::from_ptr(data).to_str().unwrap_or("")
+    let max_per_file_opt = if max_per_file > 0 {
+        Some(max_per_file as usize)
+    } else {
+        None
     };
 
-    terminal::write(id_str, data_str).is_ok()
+    match grep::execute_paged(
+        pattern_str,
+        search_str,
+        include_glob_opt,
+        cursor.as_ref(),
+        page_size as usize,
+        max_per_file_opt,
+    ) {
+        Ok(page) => match serde_json::to_string(&page) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
 }
 
+/// Grep only the files that differ from `base_ref` (staged, unstaged or
+/// untracked) in the repository containing `cwd`. Outside a repository the
+/// whole of `cwd` is searched. Returns the same JSON as `grep_ffi`, null on
+/// error (e.g. an unknown `base_ref`).
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure that `id` is a valid, non-null, null-terminated
-/// C string that remains valid for the duration of the call.
+/// The caller must ensure that `pattern`, `cwd` and `base_ref` are valid,
+/// non-null, null-terminated C strings, and that `include_glob` is either null
+/// or a valid C string.
 #[no_mangle]
-pub unsafe extern "C" fn terminal_read(id: *const c_char) -> *mut c_char {
-    let id_str = unsafe {
-        if id.is_null() {
+pub unsafe extern "C" fn grep_changed_ffi(
+    pattern: *const c_char,
+    cwd: *const c_char,
+    base_ref: *const c_char,
+    include_glob: *const c_char,
+) -> *mut c_char {
+    let pattern_str = unsafe {
+        if pattern.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(id).to_str().unwrap_or("")
+        CStr::from_ptr(pattern).to_str().unwrap_or("")
     };
 
-    match terminal::read(id_str) {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
+    };
+
+    let base_ref_str = unsafe {
+        if base_ref.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(base_ref).to_str().unwrap_or("HEAD")
+    };
+
+    let include_glob_opt = unsafe {
+        if include_glob.is_null() {
+            None
+        } else {
+            Some(CStr::from_ptr(include_glob).to_str().unwrap_or(""))
+        }
+    };
+
+    match grep::execute_changed(pattern_str, cwd_str, base_ref_str, include_glob_opt) {
         Ok(output) => match serde_json::to_string(&output) {
             Ok(json) => CString::new(json).unwrap().into_raw(),
             Err(_) => std::ptr::null_mut(),
@@ -342,53 +633,92 @@ pub unsafe extern "C" fn terminal_read(id: *const c_char) -> *mut c_char {
     }
 }
 
+/// Regex replacement across files, using the same walk rules as grep.
+/// With `dry_run` nothing is written and per-file hunks are reported.
+/// Returns JSON: {"files": [{"path", "replacements", "hunks"}], "totalReplacements", "dryRun"}
+/// or {"code", "error": "..."}
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure that `id` is a valid, non-null, null-terminated
-/// C string that remains valid for the duration of the call.
+/// The caller must ensure that `pattern`, `replacement` and `search` are valid,
+/// non-null, null-terminated C strings, and that `include_glob` is either null
+/// or a valid C string.
 #[no_mangle]
-pub unsafe extern "C" fn terminal_resize(id: *const c_char, rows: u16, cols: u16) -> bool {
-    let id_str = unsafe {
-        if id.is_null() {
-            return false;
+pub unsafe extern "C" fn grep_replace_ffi(
+    pattern: *const c_char,
+    replacement: *const c_char,
+    search: *const c_char,
+    include_glob: *const c_char,
+    dry_run: bool,
+) -> *mut c_char {
+    let pattern_str = unsafe {
+        if pattern.is_null() {
+            return std::ptr::null_mut();
         }
-        CStr::from_ptr(id).to_str().unwrap_or("")
+        CStr::from_ptr(pattern).to_str().unwrap_or("")
     };
 
-    terminal::resize(id_str, rows, cols).is_ok()
-}
+    let replacement_str = unsafe {
+        if replacement.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(replacement).to_str().unwrap_or("")
+    };
 
-/// # Safety
-/// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure that `id` is a valid, non-null, null-terminated
-/// C string that remains valid for the duration of the call.
-#[no_mangle]
-pub unsafe extern "C" fn terminal_close(id: *const c_char) -> bool {
-    let id_str = unsafe {
-        if id.is_null() {
-            return false;
+    let search_str = unsafe {
+        if search.is_null() {
+            return std::ptr::null_mut();
         }
-        CStr::from_ptr(id).to_str().unwrap_or("")
+        CStr::from_ptr(search).to_str().unwrap_or(".")
     };
 
-    terminal::close(id_str).is_ok()
+    let include_glob_opt = unsafe {
+        if include_glob.is_null() {
+            None
+        } else {
+            Some(CStr::from_ptr(include_glob).to_str().unwrap_or(""))
+        }
+    };
+
+    let json = match grep::replace_in_files(
+        pattern_str,
+        replacement_str,
+        search_str,
+        include_glob_opt,
+        dry_run,
+    ) {
+        Ok(result) => serde_json::to_string(&result),
+        Err(e) => serde_json::to_string(&serde_json::json!({
+            "code": error::ErrorCode::from(&e),
+            "error": e.message,
+        })),
+    };
+
+    match json {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
 }
 
+/// Read the last `n` lines of a file, scanning backwards from the end.
+/// Returns JSON: {"lines": [...], "size": 1234, "bytes_read": 56}
+/// To follow the file, watch it and read from `size` onwards on changes.
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure that `id` is a valid, non-null, null-terminated
+/// The caller must ensure that `filepath` is a valid, non-null, null-terminated
 /// C string that remains valid for the duration of the call.
 #[no_mangle]
-pub unsafe extern "C" fn terminal_get_info(id: *const c_char) -> *mut c_char {
-    let id_str = unsafe {
-        if id.is_null() {
+pub unsafe extern "C" fn tail_file_ffi(filepath: *const c_char, n: i32) -> *mut c_char {
+    let filepath_str = unsafe {
+        if filepath.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(id).to_str().unwrap_or("")
+        CStr::from_ptr(filepath).to_str().unwrap_or("")
     };
 
-    match terminal::get_info(id_str) {
-        Ok(info) => match serde_json::to_string(&info) {
+    let n_lines = if n < 0 { 0 } else { n as usize };
+
+    match read::tail(filepath_str, n_lines) {
+        Ok(result) => match serde_json::to_string(&result) {
             Ok(json) => CString::new(json).unwrap().into_raw(),
             Err(_) => std::ptr::null_mut(),
         },
@@ -396,44 +726,49 @@ pub unsafe extern "C" fn terminal_get_info(id: *const c_char) -> *mut c_char {
     }
 }
 
+/// Read the first `n` lines of a file, stopping as soon as they are complete.
+/// Returns JSON: {"lines": [...], "has_more": true, "bytes_read": 65536}
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure that `id` and `title` are valid, non-null, null-terminated
-/// C strings that remain valid for the duration of the call.
+/// The caller must ensure that `filepath` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
 #[no_mangle]
-pub unsafe extern "C" fn terminal_update_title(id: *const c_char, title: *const c_char) -> bool {
-    let id_str = unsafe {
-        if id.is_null() {
-            return false;
+pub unsafe extern "C" fn head_file_ffi(filepath: *const c_char, n: i32) -> *mut c_char {
+    let filepath_str = unsafe {
+        if filepath.is_null() {
+            return std::ptr::null_mut();
         }
-        CStr::from_ptr(id).to_str().unwrap_or("")
+        CStr::from_ptr(filepath).to_str().unwrap_or("")
     };
 
-    let title_str = unsafe {
-        if title.is_null() {
-            return false;
-        }
-        CStr::from_ptr(title).to_str().unwrap_or("")
-    };
+    let n_lines = if n < 0 { 0 } else { n as usize };
 
-    terminal::update_title(id_str, title_str).is_ok()
+    match read::head(filepath_str, n_lines) {
+        Ok(result) => match serde_json::to_string(&result) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
 }
 
+/// Count lines, words, bytes and chars of a file, like `wc`.
+/// Returns JSON: {"lines": 10, "words": 42, "bytes": 256, "chars": 250}
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure that `id` is a valid, non-null, null-terminated
+/// The caller must ensure that `filepath` is a valid, non-null, null-terminated
 /// C string that remains valid for the duration of the call.
 #[no_mangle]
-pub unsafe extern "C" fn terminal_check_status(id: *const c_char) -> *mut c_char {
-    let id_str = unsafe {
-        if id.is_null() {
+pub unsafe extern "C" fn wc_ffi(filepath: *const c_char) -> *mut c_char {
+    let filepath_str = unsafe {
+        if filepath.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(id).to_str().unwrap_or("")
+        CStr::from_ptr(filepath).to_str().unwrap_or("")
     };
 
-    match terminal::check_status(id_str) {
-        Ok(status) => match serde_json::to_string(&status) {
+    match read::counts(filepath_str) {
+        Ok(counts) => match serde_json::to_string(&counts) {
             Ok(json) => CString::new(json).unwrap().into_raw(),
             Err(_) => std::ptr::null_mut(),
         },
@@ -441,105 +776,85 @@ pub unsafe extern "C" fn terminal_check_status(id: *const c_char) -> *mut c_char
     }
 }
 
+/// Same as `wc_ffi`, for `len` bytes at `data`
 /// # Safety
-/// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure that `id` is a valid, non-null, null-terminated
-/// C string that remains valid for the duration of the call.
+/// The caller must ensure that `data` points to at least `len` readable bytes
+/// that remain valid for the duration of the call.
 #[no_mangle]
-pub unsafe extern "C" fn terminal_mark_exited(id: *const c_char) -> bool {
-    let id_str = unsafe {
-        if id.is_null() {
-            return false;
-        }
-        CStr::from_ptr(id).to_str().unwrap_or("")
-    };
+pub unsafe extern "C" fn wc_bytes_ffi(data: *const u8, len: usize) -> *mut c_char {
+    if data.is_null() {
+        return std::ptr::null_mut();
+    }
+    let content_bytes = unsafe { std::slice::from_raw_parts(data, len) };
 
-    terminal::mark_exited(id_str).is_ok()
+    match serde_json::to_string(&read::counts_bytes(content_bytes)) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
 }
 
+/// Read a file's raw bytes as base64, for binary or non-UTF-8 content.
+/// Large files are encoded straight from a memory map.
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure that `id` is a valid, non-null, null-terminated
+/// The caller must ensure that `filepath` is a valid, non-null, null-terminated
 /// C string that remains valid for the duration of the call.
 #[no_mangle]
-pub unsafe extern "C" fn terminal_get_buffer(id: *const c_char) -> *mut c_char {
-    let id_str = unsafe {
-        if id.is_null() {
+pub unsafe extern "C" fn read_bytes_ffi(filepath: *const c_char) -> *mut c_char {
+    let filepath_str = unsafe {
+        if filepath.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(id).to_str().unwrap_or("")
+        CStr::from_ptr(filepath).to_str().unwrap_or("")
     };
 
-    match terminal::get_buffer(id_str) {
-        Ok(buffer) => {
-            // Return buffer as base64 encoded string for binary safety
-            let base64 = base64_encode(&buffer);
-            match CString::new(base64) {
-                Ok(cstring) => cstring.into_raw(),
-                Err(_) => std::ptr::null_mut(),
-            }
-        }
+    match read::with_file_bytes(filepath_str, base64_encode) {
+        Ok(encoded) => match CString::new(encoded) {
+            Ok(cstring) => cstring.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
         Err(_) => std::ptr::null_mut(),
     }
 }
 
+/// Guess a file's text encoding from a BOM and its leading bytes.
+/// Returns the encoding name (e.g. "UTF-8", "UTF-16LE", "windows-1252"), null on error.
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure that `id` is a valid, non-null, null-terminated
+/// The caller must ensure that `filepath` is a valid, non-null, null-terminated
 /// C string that remains valid for the duration of the call.
 #[no_mangle]
-pub unsafe extern "C" fn terminal_drain_buffer(id: *const c_char) -> *mut c_char {
-    let id_str = unsafe {
-        if id.is_null() {
+pub unsafe extern "C" fn detect_encoding_ffi(filepath: *const c_char) -> *mut c_char {
+    let filepath_str = unsafe {
+        if filepath.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(id).to_str().unwrap_or("")
+        CStr::from_ptr(filepath).to_str().unwrap_or("")
     };
 
-    match terminal::drain_buffer(id_str) {
-        Ok(buffer) => {
-            // Return buffer as base64 encoded string for binary safety
-            let base64 = base64_encode(&buffer);
-            match CString::new(base64) {
-                Ok(cstring) => cstring.into_raw(),
-                Err(_) => std::ptr::null_mut(),
-            }
-        }
+    match read::detect_encoding(filepath_str) {
+        Ok(encoding) => CString::new(encoding.name()).unwrap().into_raw(),
         Err(_) => std::ptr::null_mut(),
     }
 }
 
+/// Read a file decoded to UTF-8 from its detected encoding.
+/// Returns JSON: {"encoding": "windows-1252", "content": "...", "had_errors": false}
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure that `id` is a valid, non-null, null-terminated
-/// C string that remains valid for the duration of the call.
-#[no_mangle]
-pub unsafe extern "C" fn terminal_clear_buffer(id: *const c_char) -> bool {
-    let id_str = unsafe {
-        if id.is_null() {
-            return false;
-        }
-        CStr::from_ptr(id).to_str().unwrap_or("")
-    };
-
-    terminal::clear_buffer(id_str).is_ok()
-}
-
-/// # Safety
-/// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure that `id` is a valid, non-null, null-terminated
+/// The caller must ensure that `filepath` is a valid, non-null, null-terminated
 /// C string that remains valid for the duration of the call.
 #[no_mangle]
-pub unsafe extern "C" fn terminal_get_buffer_info(id: *const c_char) -> *mut c_char {
-    let id_str = unsafe {
-        if id.is_null() {
+pub unsafe extern "C" fn read_transcoded_ffi(filepath: *const c_char) -> *mut c_char {
+    let filepath_str = unsafe {
+        if filepath.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(id).to_str().unwrap_or("")
+        CStr::from_ptr(filepath).to_str().unwrap_or("")
     };
 
-    match terminal::get_buffer_info(id_str) {
-        Ok(info) => match serde_json::to_string(&info) {
+    match read::read_transcoded(filepath_str) {
+        Ok(result) => match serde_json::to_string(&result) {
             Ok(json) => CString::new(json).unwrap().into_raw(),
             Err(_) => std::ptr::null_mut(),
         },
@@ -547,14 +862,23 @@ pub unsafe extern "C" fn terminal_get_buffer_info(id: *const c_char) -> *mut c_c
     }
 }
 
+/// Display width of each line of a file (wide chars 2, combining marks 0)
+/// Returns JSON array of widths, or null on error
 /// # Safety
-/// This function is safe to call from C as it doesn't take any pointer arguments.
+/// This function is unsafe because it dereferences a raw C string pointer.
+/// The caller must ensure that `filepath` is a valid, non-null, null-terminated C string.
 #[no_mangle]
-pub unsafe extern "C" fn terminal_list() -> *mut c_char {
-    let sessions = terminal::list();
-    match serde_json::to_string(&sessions) {
-        Ok(json) => match CString::new(json) {
-            Ok(cstring) => cstring.into_raw(),
+pub unsafe extern "C" fn line_widths_ffi(filepath: *const c_char) -> *mut c_char {
+    let filepath_str = unsafe {
+        if filepath.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(filepath).to_str().unwrap_or("")
+    };
+
+    match read::line_widths(filepath_str) {
+        Ok(widths) => match serde_json::to_string(&widths) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
             Err(_) => std::ptr::null_mut(),
         },
         Err(_) => std::ptr::null_mut(),
@@ -562,7 +886,611 @@ pub unsafe extern "C" fn terminal_list() -> *mut c_char {
 }
 
 /// # Safety
-/// This function is safe to call from C as it only takes primitive arguments.
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `filepath` and `content` are valid, non-null,
+/// null-terminated C strings that remain valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn write_raw_ffi(filepath: *const c_char, content: *const c_char) -> i32 {
+    let filepath_str = unsafe {
+        if filepath.is_null() {
+            return -1;
+        }
+        CStr::from_ptr(filepath).to_str().unwrap_or("")
+    };
+
+    let content_str = unsafe {
+        if content.is_null() {
+            return -1;
+        }
+        CStr::from_ptr(content).to_str().unwrap_or("")
+    };
+
+    // Create parent directories if they don't exist
+    if let Some(parent) = std::path::Path::new(filepath_str).parent() {
+        if std::fs::create_dir_all(parent).is_err() {
+            return -1;
+        }
+    }
+
+    match std::fs::write(filepath_str, content_str) {
+        Ok(_) => 0,   // Success
+        Err(_) => -1, // Error
+    }
+}
+
+/// Write `content` with its line endings normalized to `eol`: "lf", "crlf",
+/// or "keep" (match the existing file; a new file is written as given).
+/// Parent directories are created. Returns 0 on success, -1 on error.
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `filepath`, `content` and `eol` are valid, non-null,
+/// null-terminated C strings that remain valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn write_normalized_ffi(
+    filepath: *const c_char,
+    content: *const c_char,
+    eol: *const c_char,
+) -> i32 {
+    let filepath_str = unsafe {
+        if filepath.is_null() {
+            return -1;
+        }
+        CStr::from_ptr(filepath).to_str().unwrap_or("")
+    };
+
+    let content_str = unsafe {
+        if content.is_null() {
+            return -1;
+        }
+        CStr::from_ptr(content).to_str().unwrap_or("")
+    };
+
+    let eol_str = unsafe {
+        if eol.is_null() {
+            return -1;
+        }
+        CStr::from_ptr(eol).to_str().unwrap_or("")
+    };
+
+    match write::write_normalized(filepath_str, content_str, eol_str) {
+        Ok(_) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// # Safety
+/// This function is safe to call from C as it doesn't take any pointer arguments.
+#[no_mangle]
+pub unsafe extern "C" fn stats_ffi() -> *mut c_char {
+    match stats::get_stats() {
+        Ok(stats) => match serde_json::to_string(&stats) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Probe each native subsystem (tree-sitter, git2, PTY, notify, rule engine).
+/// Returns JSON: {"git2": {"ok": true, "detail": "..."}, "pty": {...}, ...}
+/// # Safety
+/// This function is safe to call from C as it doesn't take any pointer arguments.
+#[no_mangle]
+pub unsafe extern "C" fn healthcheck_ffi() -> *mut c_char {
+    match serde_json::to_string(&health::healthcheck()) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// # Safety
+/// This function is unsafe because it takes ownership of and frees a raw pointer.
+/// The caller must ensure that `s` is a valid pointer that was previously returned
+/// by one of the other FFI functions in this module, and that it's only freed once.
+#[no_mangle]
+pub unsafe extern "C" fn free_string(s: *mut c_char) {
+    if !s.is_null() {
+        unsafe {
+            let _ = CString::from_raw(s);
+        }
+    }
+}
+
+// Terminal FFI functions
+
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` and `cwd` are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_create(
+    id: *const c_char,
+    cwd: *const c_char,
+    rows: u16,
+    cols: u16,
+) -> *mut c_char {
+    let id_str = unsafe {
+        if id.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            None
+        } else {
+            Some(CStr::from_ptr(cwd).to_str().unwrap_or("."))
+        }
+    };
+
+    match terminal::create(id_str, None, vec![], cwd_str, None, rows, cols) {
+        Ok(info) => match serde_json::to_string(&info) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` and `data` are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_write(id: *const c_char, data: *const c_char) -> bool {
+    let id_str = unsafe {
+        if id.is_null() {
+            return false;
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    let data_str = unsafe {
+        if data.is_null() {
+            return false;
+        }
+        CStr::from_ptr(data).to_str().unwrap_or("")
+    };
+
+    terminal::write(id_str, data_str).is_ok()
+}
+
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_read(id: *const c_char) -> *mut c_char {
+    let id_str = unsafe {
+        if id.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    match terminal::read(id_str) {
+        Ok(output) => match serde_json::to_string(&output) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Write `command` and a newline, then collect output until the terminal has
+/// been idle for `idle_ms` (or `timeout_ms` overall)
+/// Returns JSON: {"output": "...", "timed_out": false}, null on error
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` and `command` are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_run_ffi(
+    id: *const c_char,
+    command: *const c_char,
+    idle_ms: u64,
+    timeout_ms: u64,
+    strip_ansi: bool,
+) -> *mut c_char {
+    let id_str = unsafe {
+        if id.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    let command_str = unsafe {
+        if command.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(command).to_str().unwrap_or("")
+    };
+
+    match terminal::run(id_str, command_str, idle_ms, timeout_ms, strip_ansi) {
+        Ok(output) => match serde_json::to_string(&output) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_resize(id: *const c_char, rows: u16, cols: u16) -> bool {
+    let id_str = unsafe {
+        if id.is_null() {
+            return false;
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    terminal::resize(id_str, rows, cols).is_ok()
+}
+
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_close(id: *const c_char) -> bool {
+    let id_str = unsafe {
+        if id.is_null() {
+            return false;
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    terminal::close(id_str).is_ok()
+}
+
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_get_info(id: *const c_char) -> *mut c_char {
+    let id_str = unsafe {
+        if id.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    match terminal::get_info(id_str) {
+        Ok(info) => match serde_json::to_string(&info) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` and `title` are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_update_title(id: *const c_char, title: *const c_char) -> bool {
+    let id_str = unsafe {
+        if id.is_null() {
+            return false;
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    let title_str = unsafe {
+        if title.is_null() {
+            return false;
+        }
+        CStr::from_ptr(title).to_str().unwrap_or("")
+    };
+
+    terminal::update_title(id_str, title_str).is_ok()
+}
+
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_check_status(id: *const c_char) -> *mut c_char {
+    let id_str = unsafe {
+        if id.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    match terminal::check_status(id_str) {
+        Ok(status) => match serde_json::to_string(&status) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Returns the foreground job's pid, the shell pid if none, or -1 on error.
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_foreground_pid_ffi(id: *const c_char) -> i32 {
+    let id_str = unsafe {
+        if id.is_null() {
+            return -1;
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    match terminal::foreground_pid(id_str) {
+        Ok(pid) => pid as i32,
+        Err(_) => -1,
+    }
+}
+
+/// Switch the terminal between raw (`raw = true`) and cooked line discipline.
+/// Returns null on success, or an error message (e.g. unsupported on Windows).
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_set_mode_ffi(id: *const c_char, raw: bool) -> *mut c_char {
+    let id_str = unsafe {
+        if id.is_null() {
+            return CString::new("Terminal id is null").unwrap().into_raw();
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    match terminal::set_mode(id_str, raw) {
+        Ok(()) => std::ptr::null_mut(),
+        Err(e) => CString::new(e).unwrap().into_raw(),
+    }
+}
+
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_mark_exited(id: *const c_char) -> bool {
+    let id_str = unsafe {
+        if id.is_null() {
+            return false;
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    terminal::mark_exited(id_str).is_ok()
+}
+
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_get_buffer(id: *const c_char) -> *mut c_char {
+    let id_str = unsafe {
+        if id.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    match terminal::get_buffer(id_str) {
+        Ok(buffer) => {
+            // Return buffer as base64 encoded string for binary safety
+            let base64 = base64_encode(&buffer);
+            match CString::new(base64) {
+                Ok(cstring) => cstring.into_raw(),
+                Err(_) => std::ptr::null_mut(),
+            }
+        }
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_drain_buffer(id: *const c_char) -> *mut c_char {
+    let id_str = unsafe {
+        if id.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    match terminal::drain_buffer(id_str) {
+        Ok(buffer) => {
+            // Return buffer as base64 encoded string for binary safety
+            let base64 = base64_encode(&buffer);
+            match CString::new(base64) {
+                Ok(cstring) => cstring.into_raw(),
+                Err(_) => std::ptr::null_mut(),
+            }
+        }
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_clear_buffer(id: *const c_char) -> bool {
+    let id_str = unsafe {
+        if id.is_null() {
+            return false;
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    terminal::clear_buffer(id_str).is_ok()
+}
+
+/// Reset the terminal emulator and clear the scrollback buffer
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_clear_screen_ffi(id: *const c_char) -> bool {
+    let id_str = unsafe {
+        if id.is_null() {
+            return false;
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    terminal::clear_screen(id_str).is_ok()
+}
+
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_get_buffer_info(id: *const c_char) -> *mut c_char {
+    let id_str = unsafe {
+        if id.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    match terminal::get_buffer_info(id_str) {
+        Ok(info) => match serde_json::to_string(&info) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Search a terminal's scrollback with ANSI escapes stripped
+/// Returns JSON: [{"line": 1, "column": 1, "offset": 0, "length": 5}]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` and `query` are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_search_buffer_ffi(
+    id: *const c_char,
+    query: *const c_char,
+    case_insensitive: bool,
+) -> *mut c_char {
+    let id_str = unsafe {
+        if id.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    let query_str = unsafe {
+        if query.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(query).to_str().unwrap_or("")
+    };
+
+    match terminal::search_buffer(id_str, query_str, case_insensitive) {
+        Ok(matches) => match serde_json::to_string(&matches) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` and `file_path` are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_start_recording(
+    id: *const c_char,
+    file_path: *const c_char,
+) -> bool {
+    let id_str = unsafe {
+        if id.is_null() {
+            return false;
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    let path_str = unsafe {
+        if file_path.is_null() {
+            return false;
+        }
+        CStr::from_ptr(file_path).to_str().unwrap_or("")
+    };
+
+    terminal::start_recording(id_str, path_str).is_ok()
+}
+
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_stop_recording(id: *const c_char) -> bool {
+    let id_str = unsafe {
+        if id.is_null() {
+            return false;
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    terminal::stop_recording(id_str).is_ok()
+}
+
+/// # Safety
+/// This function is safe to call from C as it doesn't take any pointer arguments.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_list() -> *mut c_char {
+    let sessions = terminal::list();
+    match serde_json::to_string(&sessions) {
+        Ok(json) => match CString::new(json) {
+            Ok(cstring) => cstring.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Resize every terminal session, e.g. when the host window changes size
+/// Returns JSON array of ids that failed to resize, null if `rows` or `cols` is 0
+/// # Safety
+/// This function is safe to call from C as it only takes primitive arguments.
+#[no_mangle]
+pub unsafe extern "C" fn terminal_resize_all_ffi(rows: u16, cols: u16) -> *mut c_char {
+    let failed = match terminal::resize_all(rows, cols) {
+        Ok(failed) => failed,
+        Err(_) => return std::ptr::null_mut(),
+    };
+    match serde_json::to_string(&failed) {
+        Ok(json) => match CString::new(json) {
+            Ok(cstring) => cstring.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// # Safety
+/// This function is safe to call from C as it only takes primitive arguments.
 #[no_mangle]
 pub unsafe extern "C" fn terminal_cleanup_idle(timeout_secs: u64) -> *mut c_char {
     let removed = terminal::cleanup_idle(timeout_secs);
@@ -575,42 +1503,1943 @@ pub unsafe extern "C" fn terminal_cleanup_idle(timeout_secs: u64) -> *mut c_char
     }
 }
 
-// Helper function for base64 encoding (simple implementation)
-fn base64_encode(data: &[u8]) -> String {
-    const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
-    let mut result = String::new();
+// Helper function for base64 encoding (simple implementation)
+fn base64_encode(data: &[u8]) -> String {
+    const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut result = String::new();
+
+    for chunk in data.chunks(3) {
+        let b1 = chunk[0];
+        let b2 = chunk.get(1).copied().unwrap_or(0);
+        let b3 = chunk.get(2).copied().unwrap_or(0);
+
+        result.push(CHARS[((b1 >> 2) & 0x3F) as usize] as char);
+        result.push(CHARS[(((b1 << 4) | (b2 >> 4)) & 0x3F) as usize] as char);
+
+        if chunk.len() > 1 {
+            result.push(CHARS[(((b2 << 2) | (b3 >> 6)) & 0x3F) as usize] as char);
+        } else {
+            result.push('=');
+        }
+
+        if chunk.len() > 2 {
+            result.push(CHARS[(b3 & 0x3F) as usize] as char);
+        } else {
+            result.push('=');
+        }
+    }
+
+    result
+}
+
+/// Gzip `len` bytes at `data`.
+/// Returns the compressed bytes base64-encoded, null if `data` is null.
+/// # Safety
+/// The caller must ensure that `data` points to at least `len` readable bytes
+/// that remain valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn gzip_ffi(data: *const u8, len: usize) -> *mut c_char {
+    if data.is_null() {
+        return std::ptr::null_mut();
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(data, len) };
+    CString::new(base64_encode(&compress::gzip(bytes)))
+        .unwrap()
+        .into_raw()
+}
+
+/// Decompress `len` bytes of gzip data at `data`.
+/// Returns the decompressed bytes base64-encoded, null on invalid gzip data.
+/// # Safety
+/// The caller must ensure that `data` points to at least `len` readable bytes
+/// that remain valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn gunzip_ffi(data: *const u8, len: usize) -> *mut c_char {
+    if data.is_null() {
+        return std::ptr::null_mut();
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(data, len) };
+    match compress::gunzip(bytes) {
+        Ok(decompressed) => CString::new(base64_encode(&decompressed))
+            .unwrap()
+            .into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// VCS FFI function
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `cwd` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn vcs_info_ffi(cwd: *const c_char) -> *mut c_char {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
+    };
+
+    match vcs::get_info(cwd_str) {
+        Ok(info) => match serde_json::to_string(&info) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// Edit FFI function. With `reject_whitespace_only`, an edit whose strings
+// differ only in trailing whitespace or line endings is not applied: the
+// response has `whitespace_only: true` and `content` holds the would-be result.
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `content`, `old_string`, and `new_string` are valid,
+/// non-null, null-terminated C strings that remain valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn edit_replace_ffi(
+    content: *const c_char,
+    old_string: *const c_char,
+    new_string: *const c_char,
+    replace_all: bool,
+    reject_whitespace_only: bool,
+) -> *mut c_char {
+    let content_str = unsafe {
+        if content.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(content).to_str().unwrap_or("")
+    };
+
+    let old_str = unsafe {
+        if old_string.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(old_string).to_str().unwrap_or("")
+    };
+
+    let new_str = unsafe {
+        if new_string.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(new_string).to_str().unwrap_or("")
+    };
+
+    #[derive(serde::Serialize)]
+    struct Response {
+        success: bool,
+        content: Option<String>,
+        error: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        code: Option<error::ErrorCode>,
+        #[serde(skip_serializing_if = "std::ops::Not::not")]
+        whitespace_only: bool,
+    }
+
+    let result = if reject_whitespace_only {
+        edit::replace_rejecting_whitespace_only(content_str, old_str, new_str, replace_all)
+    } else {
+        edit::replace(content_str, old_str, new_str, replace_all)
+    };
+    let code = result.as_ref().err().map(error::ErrorCode::from);
+    let response = match result {
+        Ok(result) => Response {
+            success: true,
+            content: Some(result),
+            error: None,
+            code,
+            whitespace_only: false,
+        },
+        Err(edit::ReplaceError::NotFound) => Response {
+            success: false,
+            content: None,
+            error: Some("oldString not found in content".to_string()),
+            code,
+            whitespace_only: false,
+        },
+        Err(edit::ReplaceError::MultipleMatches) => Response {
+            success: false,
+            content: None,
+            error: Some(
+                "Found multiple matches for oldString. Provide more surrounding lines in oldString to identify the correct match.".to_string(),
+            ),
+            code,
+            whitespace_only: false,
+        },
+        Err(edit::ReplaceError::SameStrings) => Response {
+            success: false,
+            content: None,
+            error: Some("oldString and newString must be different".to_string()),
+            code,
+            whitespace_only: false,
+        },
+        // Not applied, but `content` carries the result so the host can decide
+        Err(edit::ReplaceError::WhitespaceOnly) => Response {
+            success: false,
+            content: edit::replace(content_str, old_str, new_str, replace_all).ok(),
+            error: Some(
+                "oldString and newString differ only in trailing whitespace or line endings"
+                    .to_string(),
+            ),
+            code,
+            whitespace_only: true,
+        },
+    };
+
+    match serde_json::to_string(&response) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// Replace the region between two marker strings
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure all string pointers are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn edit_replace_between_ffi(
+    content: *const c_char,
+    start_marker: *const c_char,
+    end_marker: *const c_char,
+    new_inner: *const c_char,
+    inclusive: bool,
+) -> *mut c_char {
+    let content_str = unsafe {
+        if content.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(content).to_str().unwrap_or("")
+    };
+
+    let start_str = unsafe {
+        if start_marker.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(start_marker).to_str().unwrap_or("")
+    };
+
+    let end_str = unsafe {
+        if end_marker.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(end_marker).to_str().unwrap_or("")
+    };
+
+    let inner_str = unsafe {
+        if new_inner.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(new_inner).to_str().unwrap_or("")
+    };
+
+    #[derive(serde::Serialize)]
+    struct Response {
+        success: bool,
+        content: Option<String>,
+        error: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        code: Option<error::ErrorCode>,
+    }
+
+    let result = edit::replace_between(content_str, start_str, end_str, inner_str, inclusive);
+    let response = match result {
+        Ok(result) => Response {
+            success: true,
+            content: Some(result),
+            error: None,
+            code: None,
+        },
+        Err(e) => Response {
+            success: false,
+            content: None,
+            code: Some(error::ErrorCode::from(&e)),
+            error: Some(
+                match e {
+                    edit::MarkerError::StartNotFound => "start marker not found in content",
+                    edit::MarkerError::EndNotFound => "end marker not found after start marker",
+                    edit::MarkerError::OutOfOrder => "end marker appears before start marker",
+                    edit::MarkerError::EmptyMarker => "markers must not be empty",
+                }
+                .to_string(),
+            ),
+        },
+    };
+
+    match serde_json::to_string(&response) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// Replace a 1-based inclusive line range
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `content` and `new_text` are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn edit_replace_lines_ffi(
+    content: *const c_char,
+    start_line: i32,
+    end_line: i32,
+    new_text: *const c_char,
+) -> *mut c_char {
+    let content_str = unsafe {
+        if content.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(content).to_str().unwrap_or("")
+    };
+
+    let new_text_str = unsafe {
+        if new_text.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(new_text).to_str().unwrap_or("")
+    };
+
+    #[derive(serde::Serialize)]
+    struct Response {
+        success: bool,
+        content: Option<String>,
+        error: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        code: Option<error::ErrorCode>,
+    }
+
+    let start = start_line.max(0) as usize;
+    let end = end_line.max(0) as usize;
+    let response = match edit::replace_lines(content_str, start, end, new_text_str) {
+        Ok(result) => Response {
+            success: true,
+            content: Some(result),
+            error: None,
+            code: None,
+        },
+        Err(e) => Response {
+            success: false,
+            content: None,
+            code: Some(error::ErrorCode::from(&e)),
+            error: Some(match e {
+                edit::LineRangeError::InvalidRange => format!(
+                    "invalid line range {}-{}: lines are 1-based and start must not exceed end",
+                    start_line, end_line
+                ),
+                edit::LineRangeError::OutOfBounds { line_count } => format!(
+                    "line {} is out of range: content has {} lines",
+                    end_line, line_count
+                ),
+            }),
+        },
+    };
+
+    match serde_json::to_string(&response) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// Insert `new_text` as whole lines before a 1-based line; past the end appends
+// Returns JSON: {"success", "content"} or {"success": false, "code", "error"}
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `content` and `new_text` are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn edit_insert_ffi(
+    content: *const c_char,
+    line: i32,
+    new_text: *const c_char,
+) -> *mut c_char {
+    let content_str = unsafe {
+        if content.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(content).to_str().unwrap_or("")
+    };
+
+    let new_text_str = unsafe {
+        if new_text.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(new_text).to_str().unwrap_or("")
+    };
+
+    #[derive(serde::Serialize)]
+    struct Response {
+        success: bool,
+        content: Option<String>,
+        error: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        code: Option<error::ErrorCode>,
+    }
+
+    let response = match edit::insert_at_line(content_str, line.max(0) as usize, new_text_str) {
+        Ok(result) => Response {
+            success: true,
+            content: Some(result),
+            error: None,
+            code: None,
+        },
+        Err(e) => Response {
+            success: false,
+            content: None,
+            error: Some(format!("invalid line {}: lines are 1-based", line)),
+            code: Some(error::ErrorCode::from(&e)),
+        },
+    };
+
+    match serde_json::to_string(&response) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// Append `new_text` as whole lines, keeping the content's trailing-newline convention
+// Returns JSON: {"success", "content"}
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `content` and `new_text` are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn edit_append_ffi(
+    content: *const c_char,
+    new_text: *const c_char,
+) -> *mut c_char {
+    let content_str = unsafe {
+        if content.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(content).to_str().unwrap_or("")
+    };
+
+    let new_text_str = unsafe {
+        if new_text.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(new_text).to_str().unwrap_or("")
+    };
+
+    let response = serde_json::json!({
+        "success": true,
+        "content": edit::append(content_str, new_text_str),
+    });
+    CString::new(response.to_string()).unwrap().into_raw()
+}
+
+// Apply a single-file unified diff to `content`
+// Returns JSON: {"success", "content"} or {"success": false, "code", "error", "hunk"?, "expected"?, "actual"?}
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `content` and `diff` are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn apply_patch_ffi(
+    content: *const c_char,
+    diff: *const c_char,
+) -> *mut c_char {
+    let content_str = unsafe {
+        if content.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(content).to_str().unwrap_or("")
+    };
+
+    let diff_str = unsafe {
+        if diff.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(diff).to_str().unwrap_or("")
+    };
+
+    #[derive(serde::Serialize, Default)]
+    struct Response {
+        success: bool,
+        content: Option<String>,
+        error: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        code: Option<error::ErrorCode>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        hunk: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        expected: Option<Vec<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        actual: Option<Vec<String>>,
+    }
+
+    let result = edit::apply_patch(content_str, diff_str);
+    let code = result.as_ref().err().map(error::ErrorCode::from);
+    let response = match result {
+        Ok(patched) => Response {
+            success: true,
+            content: Some(patched),
+            ..Default::default()
+        },
+        Err(edit::PatchError::Malformed { line, message }) => Response {
+            error: Some(format!("malformed patch at line {}: {}", line, message)),
+            code,
+            ..Default::default()
+        },
+        Err(edit::PatchError::HunkRejected {
+            hunk,
+            expected,
+            actual,
+        }) => Response {
+            error: Some(format!("hunk {} does not match the content", hunk)),
+            code,
+            hunk: Some(hunk),
+            expected: Some(expected),
+            actual: Some(actual),
+            ..Default::default()
+        },
+    };
+
+    match serde_json::to_string(&response) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// Create a directory; `recursive` behaves like `mkdir -p`
+// Returns 0 on success, -1 on error
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `path` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn mkdir_ffi(path: *const c_char, recursive: bool) -> i32 {
+    let path_str = unsafe {
+        if path.is_null() {
+            return -1;
+        }
+        CStr::from_ptr(path).to_str().unwrap_or("")
+    };
+
+    match write::fs_mkdir(path_str, recursive) {
+        Ok(_) => 0,
+        Err(_) => -1,
+    }
+}
+
+// Create an empty file, or update the mtime of an existing one
+// Returns 0 on success, -1 on error
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `path` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn touch_ffi(path: *const c_char) -> i32 {
+    let path_str = unsafe {
+        if path.is_null() {
+            return -1;
+        }
+        CStr::from_ptr(path).to_str().unwrap_or("")
+    };
+
+    match write::fs_touch(path_str) {
+        Ok(_) => 0,
+        Err(_) => -1,
+    }
+}
+
+// Set a file to `len` bytes, cutting or zero-extending it; `len = 0` empties
+// the file and creates it if missing
+// Returns 0 on success, -1 on error
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `path` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn truncate_file_ffi(path: *const c_char, len: u64) -> i32 {
+    let path_str = unsafe {
+        if path.is_null() {
+            return -1;
+        }
+        CStr::from_ptr(path).to_str().unwrap_or("")
+    };
+
+    match write::fs_truncate(path_str, len) {
+        Ok(_) => 0,
+        Err(_) => -1,
+    }
+}
+
+// Move a file or directory to the OS trash instead of deleting it
+// Returns 0 on success, -1 on error, -2 if this platform has no trash
+// (the host should fall back to a hard delete)
+#[cfg(feature = "trash")]
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `path` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn trash_ffi(path: *const c_char) -> i32 {
+    let path_str = unsafe {
+        if path.is_null() {
+            return -1;
+        }
+        CStr::from_ptr(path).to_str().unwrap_or("")
+    };
+
+    match write::fs_trash(path_str) {
+        Ok(_) => 0,
+        Err(e) if e == write::TRASH_UNSUPPORTED => -2,
+        Err(_) => -1,
+    }
+}
+
+// File existence check
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `filepath` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn file_exists_ffi(filepath: *const c_char) -> i32 {
+    let path_str = unsafe {
+        if filepath.is_null() {
+            return 0;
+        }
+        CStr::from_ptr(filepath).to_str().unwrap_or("")
+    };
+
+    if std::path::Path::new(path_str).exists() {
+        1
+    } else {
+        0
+    }
+}
+
+// Content hashing (hex digest). `algo` is "blake3" (default when null/empty) or "sha256".
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `filepath` is a valid, non-null, null-terminated
+/// C string and `algo` is null or a valid C string, both valid for the duration of the call.
+pub unsafe extern "C" fn hash_file_ffi(
+    filepath: *const c_char,
+    algo: *const c_char,
+) -> *mut c_char {
+    let path_str = unsafe {
+        if filepath.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(filepath).to_str().unwrap_or("")
+    };
+
+    let algo_str = unsafe {
+        if algo.is_null() {
+            ""
+        } else {
+            CStr::from_ptr(algo).to_str().unwrap_or("")
+        }
+    };
+
+    let algo = match hash::Algo::parse(algo_str) {
+        Ok(a) => a,
+        Err(_) => return std::ptr::null_mut(),
+    };
+
+    match hash::fs_hash(path_str, algo) {
+        Ok(digest) => CString::new(digest).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Hash `len` bytes at `data`. Embedded NUL bytes are hashed like any other.
+/// # Safety
+/// The caller must ensure that `data` points to at least `len` readable bytes
+/// and `algo` is null or a valid C string, both valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn hash_bytes_ffi(
+    data: *const u8,
+    len: usize,
+    algo: *const c_char,
+) -> *mut c_char {
+    if data.is_null() {
+        return std::ptr::null_mut();
+    }
+    let content_bytes = unsafe { std::slice::from_raw_parts(data, len) };
+
+    let algo_str = unsafe {
+        if algo.is_null() {
+            ""
+        } else {
+            CStr::from_ptr(algo).to_str().unwrap_or("")
+        }
+    };
+
+    match hash::Algo::parse(algo_str) {
+        Ok(algo) => CString::new(hash::hash_bytes(content_bytes, algo))
+            .unwrap()
+            .into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Compare two directory trees by file content. `ignore_json` is null or a JSON
+/// array of globs matched against relative paths and entry names.
+/// Returns JSON {"only_in_a": [...], "only_in_b": [...], "differing": [...]}, null on error.
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `a` and `b` are valid, non-null, null-terminated
+/// C strings and `ignore_json` is null or a valid C string, all valid for the duration of the call.
+pub unsafe extern "C" fn diff_dirs_ffi(
+    a: *const c_char,
+    b: *const c_char,
+    ignore_json: *const c_char,
+) -> *mut c_char {
+    let a_str = unsafe {
+        if a.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(a).to_str().unwrap_or("")
+    };
+
+    let b_str = unsafe {
+        if b.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(b).to_str().unwrap_or("")
+    };
+
+    let ignore: Vec<String> = if ignore_json.is_null() {
+        Vec::new()
+    } else {
+        let json = unsafe { CStr::from_ptr(ignore_json).to_str().unwrap_or("[]") };
+        match serde_json::from_str(json) {
+            Ok(patterns) => patterns,
+            Err(_) => return std::ptr::null_mut(),
+        }
+    };
+
+    match hash::fs_diff_dirs(a_str, b_str, &ignore) {
+        Ok(diff) => match serde_json::to_string(&diff) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// Get file metadata (size, modified time, etc)
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `filepath` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn file_stat_ffi(filepath: *const c_char) -> *mut c_char {
+    let path_str = unsafe {
+        if filepath.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(filepath).to_str().unwrap_or("")
+    };
+
+    let stat = stat::fs_stat(path_str);
+
+    match serde_json::to_string(&stat) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// Stat a batch of paths in one call
+// paths_json: ["a.txt", "b/"]; returns a JSON array of stats in input order
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `paths_json` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn stat_many_ffi(paths_json: *const c_char) -> *mut c_char {
+    let paths_str = unsafe {
+        if paths_json.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(paths_json).to_str().unwrap_or("[]")
+    };
+
+    let paths: Vec<String> = match serde_json::from_str(paths_str) {
+        Ok(paths) => paths,
+        Err(_) => return std::ptr::null_mut(),
+    };
+
+    match serde_json::to_string(&stat::fs_stat_many(&paths)) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// Stat, language, line count and git status of a file in one call
+// Returns JSON {size, modified_ms, is_dir, language?, lines?, git_status?}, null if the path is missing
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `filepath` and `cwd` are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn file_info_ffi(filepath: *const c_char, cwd: *const c_char) -> *mut c_char {
+    let path_str = unsafe {
+        if filepath.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(filepath).to_str().unwrap_or("")
+    };
+
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
+    };
+
+    match stat::file_info(path_str, cwd_str) {
+        Ok(info) => match serde_json::to_string(&info) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// Lexically normalize a path (resolves `.` and `..` without touching the filesystem)
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `path` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn normalize_path_ffi(path: *const c_char) -> *mut c_char {
+    if path.is_null() {
+        return std::ptr::null_mut();
+    }
+    let path_str = CStr::from_ptr(path).to_str().unwrap_or("");
+
+    match CString::new(path::fs_normalize(path_str)) {
+        Ok(s) => s.into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// Path of `target` relative to `base`; returns null if the paths do not share a root
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `base` and `target` are valid, non-null,
+/// null-terminated C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn relative_path_ffi(
+    base: *const c_char,
+    target: *const c_char,
+) -> *mut c_char {
+    if base.is_null() || target.is_null() {
+        return std::ptr::null_mut();
+    }
+    let base_str = CStr::from_ptr(base).to_str().unwrap_or("");
+    let target_str = CStr::from_ptr(target).to_str().unwrap_or("");
+
+    match path::fs_relative(base_str, target_str).and_then(|s| CString::new(s).ok()) {
+        Some(s) => s.into_raw(),
+        None => std::ptr::null_mut(),
+    }
+}
+
+// Archive extraction
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `zip_path` and `dest_dir` are valid, non-null,
+/// null-terminated C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn extract_zip_ffi(zip_path: *const c_char, dest_dir: *const c_char) -> i32 {
+    let zip_path_str = unsafe {
+        if zip_path.is_null() {
+            return -1;
+        }
+        CStr::from_ptr(zip_path).to_str().unwrap_or("")
+    };
+
+    let dest_dir_str = unsafe {
+        if dest_dir.is_null() {
+            return -1;
+        }
+        CStr::from_ptr(dest_dir).to_str().unwrap_or("")
+    };
+
+    match archive::extract_zip(zip_path_str, dest_dir_str) {
+        Ok(_) => 0,   // Success
+        Err(_) => -1, // Error
+    }
+}
+
+/// Extract a single entry of a zip to `dest_dir/entry_name`
+/// Returns 0 on success, -1 if the entry is missing, unsafe or can't be written
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure that `zip_path`, `entry_name` and `dest_dir` are valid,
+/// non-null, null-terminated C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn extract_zip_entry_ffi(
+    zip_path: *const c_char,
+    entry_name: *const c_char,
+    dest_dir: *const c_char,
+) -> i32 {
+    let zip_path_str = unsafe {
+        if zip_path.is_null() {
+            return -1;
+        }
+        CStr::from_ptr(zip_path).to_str().unwrap_or("")
+    };
+
+    let entry_name_str = unsafe {
+        if entry_name.is_null() {
+            return -1;
+        }
+        CStr::from_ptr(entry_name).to_str().unwrap_or("")
+    };
+
+    let dest_dir_str = unsafe {
+        if dest_dir.is_null() {
+            return -1;
+        }
+        CStr::from_ptr(dest_dir).to_str().unwrap_or("")
+    };
+
+    match archive::extract_entry(zip_path_str, entry_name_str, dest_dir_str) {
+        Ok(_) => 0,
+        Err(_) => -1,
+    }
+}
+
+// Fuzzy search FFI
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure all string pointers are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn fuzzy_search_ffi(
+    query: *const c_char,
+    items_json: *const c_char,
+    limit: i32,
+) -> *mut c_char {
+    let query_str = unsafe {
+        if query.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(query).to_str().unwrap_or("")
+    };
+
+    let items_str = unsafe {
+        if items_json.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(items_json).to_str().unwrap_or("[]")
+    };
+
+    // Parse JSON array of strings
+    let items: Vec<String> = match serde_json::from_str(items_str) {
+        Ok(items) => items,
+        Err(_) => return std::ptr::null_mut(),
+    };
+
+    // Convert limit (-1 means no limit)
+    let limit_opt = if limit < 0 {
+        None
+    } else {
+        Some(limit as usize)
+    };
+
+    // Perform fuzzy search
+    let results = fuzzy::search(query_str, &items, limit_opt, fuzzy::CaseMode::default());
+
+    // Serialize results back to JSON
+    match serde_json::to_string(&results) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// Fuzzy search FFI with options
+// options_json: {"limit": 50, "case": "insensitive" | "sensitive" | "smart"} (both optional)
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `query` and `items_json` are valid, non-null, null-terminated
+/// C strings, and that `options_json` is either null or a valid C string.
+pub unsafe extern "C" fn fuzzy_search_with_options_ffi(
+    query: *const c_char,
+    items_json: *const c_char,
+    options_json: *const c_char,
+) -> *mut c_char {
+    let query_str = unsafe {
+        if query.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(query).to_str().unwrap_or("")
+    };
+
+    let items_str = unsafe {
+        if items_json.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(items_json).to_str().unwrap_or("[]")
+    };
+
+    let items: Vec<String> = match serde_json::from_str(items_str) {
+        Ok(items) => items,
+        Err(_) => return std::ptr::null_mut(),
+    };
+
+    let options: fuzzy::FuzzyOptions = unsafe {
+        if options_json.is_null() {
+            fuzzy::FuzzyOptions::default()
+        } else {
+            match serde_json::from_str(CStr::from_ptr(options_json).to_str().unwrap_or("{}")) {
+                Ok(options) => options,
+                Err(_) => return std::ptr::null_mut(),
+            }
+        }
+    };
+
+    let results = fuzzy::search(query_str, &items, options.limit, options.case);
+
+    match serde_json::to_string(&results) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// Fuzzy search over two weighted fields per item
+// items_json: [{"primary": "open file", "secondary": "Pick a file from disk"}, ...]
+// Returns JSON: [{"index": 2, "score": 140.0}, ...] ranked best first (limit -1 = no limit)
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure all string pointers are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn fuzzy_search_fields_ffi(
+    query: *const c_char,
+    items_json: *const c_char,
+    primary_weight: f64,
+    secondary_weight: f64,
+    limit: i32,
+) -> *mut c_char {
+    let query_str = unsafe {
+        if query.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(query).to_str().unwrap_or("")
+    };
+
+    let items_str = unsafe {
+        if items_json.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(items_json).to_str().unwrap_or("[]")
+    };
+
+    let items: Vec<fuzzy::FieldItem> = match serde_json::from_str(items_str) {
+        Ok(items) => items,
+        Err(_) => return std::ptr::null_mut(),
+    };
+
+    let limit_opt = if limit < 0 {
+        None
+    } else {
+        Some(limit as usize)
+    };
+
+    let results = fuzzy::search_fields(
+        query_str,
+        &items,
+        primary_weight,
+        secondary_weight,
+        limit_opt,
+    );
+
+    match serde_json::to_string(&results) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// Optimized fuzzy search FFI - uses newline-separated input/output to avoid JSON overhead
+// NOTE: Currently NOT used in production - fuzzysort (JavaScript) is faster
+// Kept for future optimization attempts. See RUST_MIGRATION_PLAN.md section 2.1
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure all string pointers are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn fuzzy_search_raw_ffi(
+    query: *const c_char,
+    items_newline_separated: *const c_char,
+    limit: i32,
+) -> *mut c_char {
+    let query_str = unsafe {
+        if query.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(query).to_str().unwrap_or("")
+    };
+
+    let items_str = unsafe {
+        if items_newline_separated.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(items_newline_separated)
+            .to_str()
+            .unwrap_or("")
+    };
+
+    // Parse newline-separated items (much faster than JSON)
+    let items: Vec<String> = items_str.lines().map(|s| s.to_string()).collect();
+
+    // Convert limit (-1 means no limit)
+    let limit_opt = if limit < 0 {
+        None
+    } else {
+        Some(limit as usize)
+    };
+
+    // Perform fuzzy search and return raw newline-separated string
+    let result = fuzzy::search_raw(query_str, &items, limit_opt, fuzzy::CaseMode::default());
+
+    match CString::new(result) {
+        Ok(cstring) => cstring.into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// Fuzzy search with nucleo algorithm (Helix editor - closest to fuzzysort performance)
+// NOTE: Currently NOT used in production - kept for future optimization
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure all string pointers are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn fuzzy_search_nucleo_ffi(
+    query: *const c_char,
+    items_newline_separated: *const c_char,
+    limit: i32,
+) -> *mut c_char {
+    let query_str = unsafe {
+        if query.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(query).to_str().unwrap_or("")
+    };
+
+    let items_str = unsafe {
+        if items_newline_separated.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(items_newline_separated)
+            .to_str()
+            .unwrap_or("")
+    };
+
+    let items: Vec<String> = items_str.lines().map(|s| s.to_string()).collect();
+    let limit_opt = if limit < 0 {
+        None
+    } else {
+        Some(limit as usize)
+    };
+
+    let results = fuzzy::search_nucleo(query_str, &items, limit_opt, fuzzy::CaseMode::default());
+    let result_str = results.join("\n");
+
+    match CString::new(result_str) {
+        Ok(cstring) => cstring.into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// Bash command parsing FFI
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `command` and `cwd` are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn parse_bash_command_ffi(
+    command: *const c_char,
+    cwd: *const c_char,
+) -> *mut c_char {
+    let command_str = unsafe {
+        if command.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(command).to_str().unwrap_or("")
+    };
+
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
+    };
+
+    match shell::parse_bash_command(command_str, cwd_str) {
+        Ok(result) => match serde_json::to_string(&result) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// Read an environment variable; returns null if unset
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `name` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn get_env_ffi(name: *const c_char) -> *mut c_char {
+    if name.is_null() {
+        return std::ptr::null_mut();
+    }
+    let name_str = CStr::from_ptr(name).to_str().unwrap_or("");
+
+    match shell::get_env(name_str).and_then(|value| CString::new(value).ok()) {
+        Some(value) => value.into_raw(),
+        None => std::ptr::null_mut(),
+    }
+}
+
+// Expand $VAR / ${VAR} in `template`; unknown variables are dropped unless `keep_unknown`
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `template` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn expand_env_ffi(
+    template: *const c_char,
+    keep_unknown: bool,
+) -> *mut c_char {
+    if template.is_null() {
+        return std::ptr::null_mut();
+    }
+    let template_str = CStr::from_ptr(template).to_str().unwrap_or("");
+
+    match CString::new(shell::expand_env(template_str, keep_unknown)) {
+        Ok(s) => s.into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// Split a command line into argv with shell quoting rules
+// Returns a JSON array of strings, or null on an unterminated quote
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `command` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn split_command_ffi(command: *const c_char) -> *mut c_char {
+    if command.is_null() {
+        return std::ptr::null_mut();
+    }
+    let command_str = CStr::from_ptr(command).to_str().unwrap_or("");
+
+    match shell::split(command_str) {
+        Ok(args) => match serde_json::to_string(&args) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// File listing FFI (replacement for ripgrep --files)
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure all string pointers are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn file_list_ffi(
+    cwd: *const c_char,
+    globs_json: *const c_char,
+    hidden: bool,
+    follow: bool,
+    max_depth: i32,
+) -> *mut c_char {
+    file_list_json(cwd, globs_json, hidden, follow, max_depth, false)
+}
+
+// Same as `file_list_ffi`, returning absolute paths under the canonicalized `cwd`
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure all string pointers are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn file_list_absolute_ffi(
+    cwd: *const c_char,
+    globs_json: *const c_char,
+    hidden: bool,
+    follow: bool,
+    max_depth: i32,
+) -> *mut c_char {
+    file_list_json(cwd, globs_json, hidden, follow, max_depth, true)
+}
+
+unsafe fn file_list_json(
+    cwd: *const c_char,
+    globs_json: *const c_char,
+    hidden: bool,
+    follow: bool,
+    max_depth: i32,
+    absolute: bool,
+) -> *mut c_char {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
+    };
+
+    let globs: Vec<String> = unsafe {
+        if globs_json.is_null() {
+            vec![]
+        } else {
+            let json_str = CStr::from_ptr(globs_json).to_str().unwrap_or("[]");
+            serde_json::from_str(json_str).unwrap_or_else(|_| vec![])
+        }
+    };
+
+    let max_depth_opt = if max_depth < 0 {
+        None
+    } else {
+        Some(max_depth as usize)
+    };
+
+    match file_list::list_files(cwd_str, globs, hidden, follow, max_depth_opt, absolute) {
+        Ok(files) => match serde_json::to_string(&files) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(err) => {
+            // Return error as JSON
+            let error_obj = serde_json::json!({
+                "code": error::ErrorCode::from(&err),
+                "error": err.message,
+            });
+            match serde_json::to_string(&error_obj) {
+                Ok(json) => CString::new(json).unwrap().into_raw(),
+                Err(_) => std::ptr::null_mut(),
+            }
+        }
+    }
+}
+
+// Bounded file listing: returns {"files": [...], "total": n, "truncated": bool}
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure all string pointers are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn file_list_limited_ffi(
+    cwd: *const c_char,
+    globs_json: *const c_char,
+    hidden: bool,
+    follow: bool,
+    max_depth: i32,
+    limit: u32,
+) -> *mut c_char {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
+    };
+
+    let globs: Vec<String> = unsafe {
+        if globs_json.is_null() {
+            vec![]
+        } else {
+            let json_str = CStr::from_ptr(globs_json).to_str().unwrap_or("[]");
+            serde_json::from_str(json_str).unwrap_or_else(|_| vec![])
+        }
+    };
+
+    let max_depth_opt = if max_depth < 0 {
+        None
+    } else {
+        Some(max_depth as usize)
+    };
+
+    match file_list::list_files_limited(
+        cwd_str,
+        globs,
+        hidden,
+        follow,
+        max_depth_opt,
+        limit as usize,
+    ) {
+        Ok(list) => match serde_json::to_string(&list) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(err) => {
+            let error_obj = serde_json::json!({
+                "code": error::ErrorCode::from(&err),
+                "error": err.message,
+            });
+            match serde_json::to_string(&error_obj) {
+                Ok(json) => CString::new(json).unwrap().into_raw(),
+                Err(_) => std::ptr::null_mut(),
+            }
+        }
+    }
+}
+
+// Web fetch (EXPERIMENTAL - NOT RECOMMENDED FOR PRODUCTION)
+// Benchmark results: TypeScript is better for this use case (0.71ms avg processing)
+// Network latency (500-2000ms) >> Processing time (1-60ms)
+// To enable: cargo build --release --features webfetch
+#[cfg(feature = "webfetch")]
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure all string pointers are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn webfetch_ffi(
+    url: *const c_char,
+    format: *const c_char,
+    timeout_secs: u64,
+) -> *mut c_char {
+    let url_str = unsafe {
+        if url.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(url).to_str().unwrap_or("")
+    };
+
+    let format_str = unsafe {
+        if format.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(format).to_str().unwrap_or("markdown")
+    };
+
+    let content_format = match format_str {
+        "text" => webfetch::ContentFormat::Text,
+        "html" => webfetch::ContentFormat::Html,
+        _ => webfetch::ContentFormat::Markdown,
+    };
+
+    match webfetch::fetch_url(url_str, content_format, timeout_secs) {
+        Ok(result) => {
+            #[derive(serde::Serialize)]
+            struct Response {
+                content: String,
+                content_type: String,
+                status: u16,
+                final_url: String,
+            }
+
+            let response = Response {
+                content: result.content,
+                content_type: result.content_type,
+                status: result.status,
+                final_url: result.final_url,
+            };
+
+            match serde_json::to_string(&response) {
+                Ok(json) => CString::new(json).unwrap().into_raw(),
+                Err(_) => std::ptr::null_mut(),
+            }
+        }
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// =====================
+// File Watcher FFI
+// =====================
+
+/// Create a file watcher with event queue
+/// Returns error string on failure, null on success
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure all string pointers are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn watcher_create_ffi(
+    id: *const c_char,
+    path: *const c_char,
+    ignore_patterns_json: *const c_char,
+    max_queue_size: u64,
+) -> *mut c_char {
+    unsafe {
+        watcher_create_with_snapshot_ffi(id, path, ignore_patterns_json, max_queue_size, false)
+    }
+}
+
+/// Same as `watcher_create_ffi`; with `emit_initial` set, existing files are
+/// queued as `add` events so the first poll returns the current tree.
+/// Returns error string on failure, null on success
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure all string pointers are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn watcher_create_with_snapshot_ffi(
+    id: *const c_char,
+    path: *const c_char,
+    ignore_patterns_json: *const c_char,
+    max_queue_size: u64,
+    emit_initial: bool,
+) -> *mut c_char {
+    let id_str = unsafe {
+        if id.is_null() {
+            return CString::new("id is null").unwrap().into_raw();
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    let path_str = unsafe {
+        if path.is_null() {
+            return CString::new("path is null").unwrap().into_raw();
+        }
+        CStr::from_ptr(path).to_str().unwrap_or("")
+    };
+
+    let ignore_patterns_str = unsafe {
+        if ignore_patterns_json.is_null() {
+            "[]"
+        } else {
+            CStr::from_ptr(ignore_patterns_json)
+                .to_str()
+                .unwrap_or("[]")
+        }
+    };
+
+    let ignore_patterns: Vec<String> = match serde_json::from_str(ignore_patterns_str) {
+        Ok(p) => p,
+        Err(e) => {
+            return CString::new(format!("Invalid JSON: {}", e))
+                .unwrap()
+                .into_raw()
+        }
+    };
+
+    match watcher::create(
+        id_str.to_string(),
+        path_str.to_string(),
+        ignore_patterns,
+        max_queue_size as usize,
+        emit_initial,
+    ) {
+        Ok(_) => std::ptr::null_mut(), // Success
+        Err(e) => CString::new(e).unwrap().into_raw(),
+    }
+}
+
+/// Poll events from watcher (non-blocking)
+/// Returns JSON array of events
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn watcher_poll_events_ffi(id: *const c_char) -> *mut c_char {
+    let id_str = unsafe {
+        if id.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    match watcher::poll_events(id_str) {
+        Ok(events) => match serde_json::to_string(&events) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(e) => {
+            let error_obj = serde_json::json!({
+                "code": error::ErrorCode::from(&e),
+                "error": e.message,
+            });
+            match serde_json::to_string(&error_obj) {
+                Ok(json) => CString::new(json).unwrap().into_raw(),
+                Err(_) => std::ptr::null_mut(),
+            }
+        }
+    }
+}
+
+/// Poll at most `max_batch` events (0 = no limit), leaving the rest queued
+/// Returns JSON: {"events": [...], "has_more": bool}
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn watcher_poll_events_batch_ffi(
+    id: *const c_char,
+    max_batch: u32,
+) -> *mut c_char {
+    let id_str = unsafe {
+        if id.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    let max_batch_opt = if max_batch > 0 {
+        Some(max_batch as usize)
+    } else {
+        None
+    };
+
+    match watcher::poll_events_batch(id_str, max_batch_opt) {
+        Ok(batch) => match serde_json::to_string(&batch) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(e) => {
+            let error_obj = serde_json::json!({
+                "code": error::ErrorCode::from(&e),
+                "error": e.message,
+            });
+            match serde_json::to_string(&error_obj) {
+                Ok(json) => CString::new(json).unwrap().into_raw(),
+                Err(_) => std::ptr::null_mut(),
+            }
+        }
+    }
+}
+
+/// Get pending event count
+/// Returns count as i32, or -1 on error
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn watcher_pending_count_ffi(id: *const c_char) -> i32 {
+    let id_str = unsafe {
+        if id.is_null() {
+            return -1;
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    match watcher::pending_count(id_str) {
+        Ok(count) => count as i32,
+        Err(_) => -1,
+    }
+}
+
+/// Remove a file watcher
+/// Returns error string on failure, null on success
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn watcher_remove_ffi(id: *const c_char) -> *mut c_char {
+    let id_str = unsafe {
+        if id.is_null() {
+            return CString::new("id is null").unwrap().into_raw();
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    match watcher::remove(id_str.to_string()) {
+        Ok(_) => std::ptr::null_mut(), // Success
+        Err(e) => CString::new(e).unwrap().into_raw(),
+    }
+}
+
+/// Pause a watcher: events are dropped (and counted) while the OS watch stays alive
+/// Returns null on success, error string on failure
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn watcher_pause_ffi(id: *const c_char) -> *mut c_char {
+    let id_str = unsafe {
+        if id.is_null() {
+            return CString::new("id is null").unwrap().into_raw();
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    match watcher::pause(id_str) {
+        Ok(_) => std::ptr::null_mut(), // Success
+        Err(e) => CString::new(e).unwrap().into_raw(),
+    }
+}
+
+/// Resume a paused watcher
+/// Returns null on success, error string on failure
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn watcher_resume_ffi(id: *const c_char) -> *mut c_char {
+    let id_str = unsafe {
+        if id.is_null() {
+            return CString::new("id is null").unwrap().into_raw();
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    match watcher::resume(id_str) {
+        Ok(_) => std::ptr::null_mut(), // Success
+        Err(e) => CString::new(e).unwrap().into_raw(),
+    }
+}
+
+/// List all active watchers
+/// Returns JSON array of watcher IDs
+#[no_mangle]
+/// # Safety
+/// This function is safe to call from C as it doesn't take any pointer arguments.
+pub unsafe extern "C" fn watcher_list_ffi() -> *mut c_char {
+    let ids = watcher::list();
+    match serde_json::to_string(&ids) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Get watcher info
+/// Returns JSON object with watcher details, or error string
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `id` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn watcher_get_info_ffi(id: *const c_char) -> *mut c_char {
+    let id_str = unsafe {
+        if id.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(id).to_str().unwrap_or("")
+    };
+
+    match watcher::get_info(id_str.to_string()) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(e) => CString::new(format!("{{\"error\":\"{}\"}}", e))
+            .unwrap()
+            .into_raw(),
+    }
+}
+
+// ============================================================================
+// Git/VCS FFI Functions
+// ============================================================================
+
+/// Get detailed Git status with file list
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `cwd` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn git_status_detailed_ffi(cwd: *const c_char) -> *mut c_char {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
+    };
+
+    match vcs::get_status_detailed(cwd_str) {
+        Ok(status) => match serde_json::to_string(&status) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Stage files (git add)
+/// paths_json: JSON array of file paths, empty array for "git add ."
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure all string pointers are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn git_stage_files_ffi(
+    cwd: *const c_char,
+    paths_json: *const c_char,
+) -> *mut c_char {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            return CString::new("cwd is null").unwrap().into_raw();
+        }
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
+    };
 
-    for chunk in data.chunks(3) {
-        let b1 = chunk[0];
-        let b2 = chunk.get(1).copied().unwrap_or(0);
-        let b3 = chunk.get(2).copied().unwrap_or(0);
+    let paths: Vec<String> = unsafe {
+        if paths_json.is_null() {
+            vec![]
+        } else {
+            let json_str = CStr::from_ptr(paths_json).to_str().unwrap_or("[]");
+            serde_json::from_str(json_str).unwrap_or_else(|_| vec![])
+        }
+    };
 
-        result.push(CHARS[((b1 >> 2) & 0x3F) as usize] as char);
-        result.push(CHARS[(((b1 << 4) | (b2 >> 4)) & 0x3F) as usize] as char);
+    match vcs::stage_files(cwd_str, paths) {
+        Ok(_) => std::ptr::null_mut(), // Success
+        Err(e) => CString::new(format!("{}", e)).unwrap().into_raw(),
+    }
+}
 
-        if chunk.len() > 1 {
-            result.push(CHARS[(((b2 << 2) | (b3 >> 6)) & 0x3F) as usize] as char);
-        } else {
-            result.push('=');
+/// Stage a single hunk of a file
+/// hunk_json: {"old_start": 1, "old_lines": 5, "new_start": 1, "new_lines": 6},
+/// the ranges from the hunk's `@@` header in `git diff`
+/// Returns null on success, error string on failure
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure all string pointers are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn git_stage_hunk_ffi(
+    cwd: *const c_char,
+    file_path: *const c_char,
+    hunk_json: *const c_char,
+) -> *mut c_char {
+    if cwd.is_null() || file_path.is_null() || hunk_json.is_null() {
+        return CString::new("argument is null").unwrap().into_raw();
+    }
+    let cwd_str = CStr::from_ptr(cwd).to_str().unwrap_or(".");
+    let file_str = CStr::from_ptr(file_path).to_str().unwrap_or("");
+    let hunk_str = CStr::from_ptr(hunk_json).to_str().unwrap_or("");
+    let hunk: vcs::HunkSelector = match serde_json::from_str(hunk_str) {
+        Ok(hunk) => hunk,
+        Err(e) => {
+            return CString::new(format!("Invalid hunk: {}", e))
+                .unwrap()
+                .into_raw()
         }
+    };
 
-        if chunk.len() > 2 {
-            result.push(CHARS[(b3 & 0x3F) as usize] as char);
+    match vcs::stage_hunk(cwd_str, file_str, &hunk) {
+        Ok(_) => std::ptr::null_mut(), // Success
+        Err(e) => CString::new(format!("{}", e)).unwrap().into_raw(),
+    }
+}
+
+/// Unstage a single hunk of a file
+/// hunk_json: {"old_start": 1, "old_lines": 5, "new_start": 1, "new_lines": 6},
+/// the ranges from the hunk's `@@` header in `git diff --cached`
+/// Returns null on success, error string on failure
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure all string pointers are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn git_unstage_hunk_ffi(
+    cwd: *const c_char,
+    file_path: *const c_char,
+    hunk_json: *const c_char,
+) -> *mut c_char {
+    if cwd.is_null() || file_path.is_null() || hunk_json.is_null() {
+        return CString::new("argument is null").unwrap().into_raw();
+    }
+    let cwd_str = CStr::from_ptr(cwd).to_str().unwrap_or(".");
+    let file_str = CStr::from_ptr(file_path).to_str().unwrap_or("");
+    let hunk_str = CStr::from_ptr(hunk_json).to_str().unwrap_or("");
+    let hunk: vcs::HunkSelector = match serde_json::from_str(hunk_str) {
+        Ok(hunk) => hunk,
+        Err(e) => {
+            return CString::new(format!("Invalid hunk: {}", e))
+                .unwrap()
+                .into_raw()
+        }
+    };
+
+    match vcs::unstage_hunk(cwd_str, file_str, &hunk) {
+        Ok(_) => std::ptr::null_mut(), // Success
+        Err(e) => CString::new(format!("{}", e)).unwrap().into_raw(),
+    }
+}
+
+/// Unstage files (git reset)
+/// paths_json: JSON array of file paths, empty array for reset all
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure all string pointers are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn git_unstage_files_ffi(
+    cwd: *const c_char,
+    paths_json: *const c_char,
+) -> *mut c_char {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            return CString::new("cwd is null").unwrap().into_raw();
+        }
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
+    };
+
+    let paths: Vec<String> = unsafe {
+        if paths_json.is_null() {
+            vec![]
         } else {
-            result.push('=');
+            let json_str = CStr::from_ptr(paths_json).to_str().unwrap_or("[]");
+            serde_json::from_str(json_str).unwrap_or_else(|_| vec![])
         }
+    };
+
+    match vcs::unstage_files(cwd_str, paths) {
+        Ok(_) => std::ptr::null_mut(), // Success
+        Err(e) => CString::new(format!("{}", e)).unwrap().into_raw(),
     }
+}
 
-    result
+/// Commit staged changes
+/// Returns commit SHA on success, error string on failure
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `cwd` and `message` are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn git_commit_ffi(cwd: *const c_char, message: *const c_char) -> *mut c_char {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            return CString::new("cwd is null").unwrap().into_raw();
+        }
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
+    };
+
+    let message_str = unsafe {
+        if message.is_null() {
+            return CString::new("message is null").unwrap().into_raw();
+        }
+        CStr::from_ptr(message).to_str().unwrap_or("")
+    };
+
+    match vcs::commit(cwd_str, message_str) {
+        Ok(commit_sha) => {
+            let result = serde_json::json!({ "success": true, "commit": commit_sha });
+            match serde_json::to_string(&result) {
+                Ok(json) => CString::new(json).unwrap().into_raw(),
+                Err(_) => std::ptr::null_mut(),
+            }
+        }
+        Err(e) => {
+            let result = serde_json::json!({
+                "success": false,
+                "code": error::ErrorCode::from(&e),
+                "error": format!("{}", e),
+            });
+            match serde_json::to_string(&result) {
+                Ok(json) => CString::new(json).unwrap().into_raw(),
+                Err(_) => std::ptr::null_mut(),
+            }
+        }
+    }
 }
 
-// VCS FFI function
+/// List all local branches
+#[no_mangle]
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure that `cwd` is a valid, non-null, null-terminated
+/// The caller must ensure `cwd` is a valid, non-null, null-terminated
 /// C string that remains valid for the duration of the call.
-#[no_mangle]
-pub unsafe extern "C" fn vcs_info_ffi(cwd: *const c_char) -> *mut c_char {
+pub unsafe extern "C" fn git_list_branches_ffi(cwd: *const c_char) -> *mut c_char {
     let cwd_str = unsafe {
         if cwd.is_null() {
             return std::ptr::null_mut();
@@ -618,8 +3447,8 @@ pub unsafe extern "C" fn vcs_info_ffi(cwd: *const c_char) -> *mut c_char {
         CStr::from_ptr(cwd).to_str().unwrap_or(".")
     };
 
-    match vcs::get_info(cwd_str) {
-        Ok(info) => match serde_json::to_string(&info) {
+    match vcs::list_branches(cwd_str) {
+        Ok(branches) => match serde_json::to_string(&branches) {
             Ok(json) => CString::new(json).unwrap().into_raw(),
             Err(_) => std::ptr::null_mut(),
         },
@@ -627,337 +3456,500 @@ pub unsafe extern "C" fn vcs_info_ffi(cwd: *const c_char) -> *mut c_char {
     }
 }
 
-// Edit FFI function
+/// Checkout branch
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure all string pointers are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn git_checkout_branch_ffi(
+    cwd: *const c_char,
+    branch_name: *const c_char,
+) -> *mut c_char {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            return CString::new("cwd is null").unwrap().into_raw();
+        }
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
+    };
+
+    let branch_str = unsafe {
+        if branch_name.is_null() {
+            return CString::new("branch_name is null").unwrap().into_raw();
+        }
+        CStr::from_ptr(branch_name).to_str().unwrap_or("")
+    };
+
+    match vcs::checkout_branch(cwd_str, branch_str) {
+        Ok(_) => std::ptr::null_mut(), // Success
+        Err(e) => CString::new(format!("{}", e)).unwrap().into_raw(),
+    }
+}
+
+/// Get file diff
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure all string pointers are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn git_file_diff_ffi(
+    cwd: *const c_char,
+    file_path: *const c_char,
+    staged: bool,
+) -> *mut c_char {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
+    };
+
+    let file_str = unsafe {
+        if file_path.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(file_path).to_str().unwrap_or("")
+    };
+
+    match vcs::get_file_diff(cwd_str, file_str, staged) {
+        Ok(diff) => match CString::new(diff) {
+            Ok(cstring) => cstring.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Get file diff as structured hunks.
+/// Returns JSON: [{"old_start", "old_lines", "new_start", "new_lines",
+/// "lines": [{"origin": "+", "content", "old_lineno"?, "new_lineno"?}]}], null on error.
+#[no_mangle]
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure that `content`, `old_string`, and `new_string` are valid,
-/// non-null, null-terminated C strings that remain valid for the duration of the call.
-#[no_mangle]
-pub unsafe extern "C" fn edit_replace_ffi(
-    content: *const c_char,
-    old_string: *const c_char,
-    new_string: *const c_char,
-    replace_all: bool,
+/// The caller must ensure all string pointers are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn git_file_diff_structured_ffi(
+    cwd: *const c_char,
+    file_path: *const c_char,
+    staged: bool,
 ) -> *mut c_char {
-    let content_str = unsafe {
-        if content.is_null() {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(content).to_str().unwrap_or("")
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
     };
 
-    let old_str = unsafe {
-        if old_string.is_null() {
+    let file_str = unsafe {
+        if file_path.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(old_string).to_str().unwrap_or("")
+        CStr::from_ptr(file_path).to_str().unwrap_or("")
     };
 
-    let new_str = unsafe {
-        if new_string.is_null() {
+    match vcs::file_diff_structured(cwd_str, file_str, staged) {
+        Ok(hunks) => match serde_json::to_string(&hunks) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Push to remote
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `cwd` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn git_push_ffi(cwd: *const c_char) -> *mut c_char {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(new_string).to_str().unwrap_or("")
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
     };
 
     #[derive(serde::Serialize)]
-    struct Response {
+    struct PushResult {
         success: bool,
-        content: Option<String>,
+        message: Option<String>,
         error: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        code: Option<error::ErrorCode>,
     }
 
-    let response = match edit::replace(content_str, old_str, new_str, replace_all) {
-        Ok(result) => Response {
+    let result = match vcs::push_to_remote(cwd_str) {
+        Ok(message) => PushResult {
             success: true,
-            content: Some(result),
+            message: Some(message),
             error: None,
+            code: None,
         },
-        Err(edit::ReplaceError::NotFound) => Response {
-            success: false,
-            content: None,
-            error: Some("oldString not found in content".to_string()),
-        },
-        Err(edit::ReplaceError::MultipleMatches) => Response {
-            success: false,
-            content: None,
-            error: Some(
-                "Found multiple matches for oldString. Provide more surrounding lines in oldString to identify the correct match.".to_string(),
-            ),
-        },
-        Err(edit::ReplaceError::SameStrings) => Response {
+        Err(e) => PushResult {
             success: false,
-            content: None,
-            error: Some("oldString and newString must be different".to_string()),
+            message: None,
+            error: Some(e.to_string()),
+            code: Some(error::ErrorCode::from(&e)),
         },
     };
 
-    match serde_json::to_string(&response) {
-        Ok(json) => CString::new(json).unwrap().into_raw(),
+    match serde_json::to_string(&result) {
+        Ok(json) => match CString::new(json) {
+            Ok(cstring) => cstring.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
         Err(_) => std::ptr::null_mut(),
     }
 }
 
-// File existence check
+/// Initialize a new repository
+/// Returns JSON: {"success": true, "path": "..."} or {"success": false, "code", "error": "..."}
 #[no_mangle]
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure that `filepath` is a valid, non-null, null-terminated
+/// The caller must ensure `cwd` is a valid, non-null, null-terminated
 /// C string that remains valid for the duration of the call.
-pub unsafe extern "C" fn file_exists_ffi(filepath: *const c_char) -> i32 {
-    let path_str = unsafe {
-        if filepath.is_null() {
-            return 0;
+pub unsafe extern "C" fn git_init_ffi(
+    cwd: *const c_char,
+    bare: bool,
+    initial_commit: bool,
+) -> *mut c_char {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            return std::ptr::null_mut();
         }
-        CStr::from_ptr(filepath).to_str().unwrap_or("")
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
     };
 
-    if std::path::Path::new(path_str).exists() {
-        1
-    } else {
-        0
+    let result = match vcs::init(cwd_str, bare, initial_commit) {
+        Ok(path) => serde_json::json!({ "success": true, "path": path }),
+        Err(e) => serde_json::json!({
+            "success": false,
+            "code": error::ErrorCode::from(&e),
+            "error": format!("{}", e),
+        }),
+    };
+
+    match serde_json::to_string(&result) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
     }
 }
 
-// Get file metadata (size, modified time, etc)
+/// Check whether git ignores a path
+/// Returns 1 if ignored, 0 if not, -1 on error (not a repo, path outside repo)
 #[no_mangle]
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure that `filepath` is a valid, non-null, null-terminated
-/// C string that remains valid for the duration of the call.
-pub unsafe extern "C" fn file_stat_ffi(filepath: *const c_char) -> *mut c_char {
-    let path_str = unsafe {
-        if filepath.is_null() {
-            return std::ptr::null_mut();
+/// The caller must ensure `cwd` and `path` are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn git_is_ignored_ffi(cwd: *const c_char, path: *const c_char) -> i32 {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            return -1;
         }
-        CStr::from_ptr(filepath).to_str().unwrap_or("")
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
     };
 
-    #[derive(serde::Serialize)]
-    struct FileStat {
-        exists: bool,
-        size: u64,
-        modified: u64,
-        is_file: bool,
-        is_dir: bool,
-    }
-
-    let stat = match std::fs::metadata(path_str) {
-        Ok(meta) => {
-            let modified = meta
-                .modified()
-                .ok()
-                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                .map(|d| d.as_secs())
-                .unwrap_or(0);
-
-            FileStat {
-                exists: true,
-                size: meta.len(),
-                modified,
-                is_file: meta.is_file(),
-                is_dir: meta.is_dir(),
-            }
+    let path_str = unsafe {
+        if path.is_null() {
+            return -1;
         }
-        Err(_) => FileStat {
-            exists: false,
-            size: 0,
-            modified: 0,
-            is_file: false,
-            is_dir: false,
-        },
+        CStr::from_ptr(path).to_str().unwrap_or("")
     };
 
-    match serde_json::to_string(&stat) {
-        Ok(json) => CString::new(json).unwrap().into_raw(),
-        Err(_) => std::ptr::null_mut(),
+    match vcs::is_ignored(cwd_str, path_str) {
+        Ok(true) => 1,
+        Ok(false) => 0,
+        Err(_) => -1,
     }
 }
 
-// Archive extraction
+/// Check whether the working tree and index are clean (untracked files count as changes)
+/// Returns 1 if clean, 0 if there are changes, -1 on error (not a repo)
 #[no_mangle]
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure that `zip_path` and `dest_dir` are valid, non-null,
-/// null-terminated C strings that remain valid for the duration of the call.
-pub unsafe extern "C" fn extract_zip_ffi(zip_path: *const c_char, dest_dir: *const c_char) -> i32 {
-    let zip_path_str = unsafe {
-        if zip_path.is_null() {
+/// The caller must ensure `cwd` is a valid, non-null, null-terminated C string.
+pub unsafe extern "C" fn git_is_clean_ffi(cwd: *const c_char) -> i32 {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
             return -1;
         }
-        CStr::from_ptr(zip_path).to_str().unwrap_or("")
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
     };
 
-    let dest_dir_str = unsafe {
-        if dest_dir.is_null() {
-            return -1;
+    match vcs::is_clean(cwd_str) {
+        Ok(true) => 1,
+        Ok(false) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Get the repository's default branch (origin/HEAD, then main/master/trunk/develop, then HEAD)
+/// Returns the branch name, or null on error
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `cwd` is a valid, non-null, null-terminated C string.
+pub unsafe extern "C" fn git_default_branch_ffi(cwd: *const c_char) -> *mut c_char {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            return std::ptr::null_mut();
         }
-        CStr::from_ptr(dest_dir).to_str().unwrap_or("")
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
     };
 
-    match archive::extract_zip(zip_path_str, dest_dir_str) {
-        Ok(_) => 0,   // Success
-        Err(_) => -1, // Error
+    match vcs::default_branch(cwd_str) {
+        Ok(branch) => CString::new(branch).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
     }
 }
 
-// Fuzzy search FFI
+/// Get the commit HEAD points at
+/// Returns JSON: {"success": true, "commit": {"sha", "short_sha", "summary", "author", "timestamp"}}
+/// or {"success": false, "code", "error": "..."} (including "No commits yet" in an empty repository)
 #[no_mangle]
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure all string pointers are valid, non-null, null-terminated
-/// C strings that remain valid for the duration of the call.
-pub unsafe extern "C" fn fuzzy_search_ffi(
-    query: *const c_char,
-    items_json: *const c_char,
-    limit: i32,
-) -> *mut c_char {
-    let query_str = unsafe {
-        if query.is_null() {
+/// The caller must ensure `cwd` is a valid, non-null, null-terminated C string.
+pub unsafe extern "C" fn git_current_commit_ffi(cwd: *const c_char) -> *mut c_char {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(query).to_str().unwrap_or("")
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
     };
 
-    let items_str = unsafe {
-        if items_json.is_null() {
+    let result = match vcs::current_commit(cwd_str) {
+        Ok(commit) => serde_json::json!({ "success": true, "commit": commit }),
+        Err(e) => serde_json::json!({
+            "success": false,
+            "code": error::ErrorCode::from(&e),
+            "error": format!("{}", e),
+        }),
+    };
+
+    match serde_json::to_string(&result) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Get the upstream (tracking) branch of the current branch
+/// Returns JSON: {"success": true, "upstream": {"remote", "branch", "full_ref"}}, with
+/// "upstream": null when none is set, or {"success": false, "code", "error": "..."}
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `cwd` is a valid, non-null, null-terminated C string.
+pub unsafe extern "C" fn git_upstream_ffi(cwd: *const c_char) -> *mut c_char {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(items_json).to_str().unwrap_or("[]")
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
     };
 
-    // Parse JSON array of strings
-    let items: Vec<String> = match serde_json::from_str(items_str) {
-        Ok(items) => items,
-        Err(_) => return std::ptr::null_mut(),
+    let result = match vcs::upstream(cwd_str) {
+        Ok(upstream) => serde_json::json!({ "success": true, "upstream": upstream }),
+        Err(e) => serde_json::json!({
+            "success": false,
+            "code": error::ErrorCode::from(&e),
+            "error": format!("{}", e),
+        }),
     };
 
-    // Convert limit (-1 means no limit)
-    let limit_opt = if limit < 0 {
-        None
-    } else {
-        Some(limit as usize)
+    match serde_json::to_string(&result) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Get the absolute working directory root of the repository containing `cwd`
+/// Returns JSON: {"success": true, "root": "/path/to/repo"} or {"success": false, "code", "error": "..."}
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `cwd` is a valid, non-null, null-terminated C string.
+pub unsafe extern "C" fn git_repo_root_ffi(cwd: *const c_char) -> *mut c_char {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
     };
 
-    // Perform fuzzy search
-    let results = fuzzy::search(query_str, &items, limit_opt);
+    let result = match vcs::repo_root(cwd_str) {
+        Ok(root) => serde_json::json!({ "success": true, "root": root }),
+        Err(e) => serde_json::json!({
+            "success": false,
+            "code": error::ErrorCode::from(&e),
+            "error": format!("{}", e),
+        }),
+    };
 
-    // Serialize results back to JSON
-    match serde_json::to_string(&results) {
+    match serde_json::to_string(&result) {
         Ok(json) => CString::new(json).unwrap().into_raw(),
         Err(_) => std::ptr::null_mut(),
     }
 }
 
-// Optimized fuzzy search FFI - uses newline-separated input/output to avoid JSON overhead
-// NOTE: Currently NOT used in production - fuzzysort (JavaScript) is faster
-// Kept for future optimization attempts. See RUST_MIGRATION_PLAN.md section 2.1
+/// Cherry-pick a commit onto the current branch
+/// Returns JSON: {"success": true, "commit": "abc1234" | null, "conflicts": [...]}
+/// or {"success": false, "code", "error": "..."}
 #[no_mangle]
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure all string pointers are valid, non-null, null-terminated
+/// The caller must ensure `cwd` and `sha` are valid, non-null, null-terminated
 /// C strings that remain valid for the duration of the call.
-pub unsafe extern "C" fn fuzzy_search_raw_ffi(
-    query: *const c_char,
-    items_newline_separated: *const c_char,
-    limit: i32,
+pub unsafe extern "C" fn git_cherry_pick_ffi(
+    cwd: *const c_char,
+    sha: *const c_char,
 ) -> *mut c_char {
-    let query_str = unsafe {
-        if query.is_null() {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
+    };
+
+    let sha_str = unsafe {
+        if sha.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(sha).to_str().unwrap_or("")
+    };
+
+    let result = match vcs::cherry_pick(cwd_str, sha_str) {
+        Ok(applied) => serde_json::json!({
+            "success": true,
+            "commit": applied.commit,
+            "conflicts": applied.conflicts,
+        }),
+        Err(e) => serde_json::json!({
+            "success": false,
+            "code": error::ErrorCode::from(&e),
+            "error": format!("{}", e),
+        }),
+    };
+
+    match serde_json::to_string(&result) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Revert a commit on the current branch
+/// Returns JSON: {"success": true, "commit": "abc1234" | null, "conflicts": [...]}
+/// or {"success": false, "code", "error": "..."}
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `cwd` and `sha` are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn git_revert_ffi(cwd: *const c_char, sha: *const c_char) -> *mut c_char {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(query).to_str().unwrap_or("")
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
     };
 
-    let items_str = unsafe {
-        if items_newline_separated.is_null() {
+    let sha_str = unsafe {
+        if sha.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(items_newline_separated)
-            .to_str()
-            .unwrap_or("")
+        CStr::from_ptr(sha).to_str().unwrap_or("")
     };
 
-    // Parse newline-separated items (much faster than JSON)
-    let items: Vec<String> = items_str.lines().map(|s| s.to_string()).collect();
-
-    // Convert limit (-1 means no limit)
-    let limit_opt = if limit < 0 {
-        None
-    } else {
-        Some(limit as usize)
+    let result = match vcs::revert(cwd_str, sha_str) {
+        Ok(applied) => serde_json::json!({
+            "success": true,
+            "commit": applied.commit,
+            "conflicts": applied.conflicts,
+        }),
+        Err(e) => serde_json::json!({
+            "success": false,
+            "code": error::ErrorCode::from(&e),
+            "error": format!("{}", e),
+        }),
     };
 
-    // Perform fuzzy search and return raw newline-separated string
-    let result = fuzzy::search_raw(query_str, &items, limit_opt);
-
-    match CString::new(result) {
-        Ok(cstring) => cstring.into_raw(),
+    match serde_json::to_string(&result) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
         Err(_) => std::ptr::null_mut(),
     }
 }
 
-// Fuzzy search with nucleo algorithm (Helix editor - closest to fuzzysort performance)
-// NOTE: Currently NOT used in production - kept for future optimization
+/// Merge a branch into the current branch
+/// `options_json` is optional: {"no_ff": false, "message": "..."}
+/// Returns JSON: {"success": true, "commit": "abc1234" | null, "fast_forward": bool,
+/// "up_to_date": bool, "conflicts": [...]} or {"success": false, "code", "error": "..."}
 #[no_mangle]
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure all string pointers are valid, non-null, null-terminated
-/// C strings that remain valid for the duration of the call.
-pub unsafe extern "C" fn fuzzy_search_nucleo_ffi(
-    query: *const c_char,
-    items_newline_separated: *const c_char,
-    limit: i32,
+/// The caller must ensure `cwd` and `branch` are valid, non-null, null-terminated
+/// C strings, and `options_json` is either null or such a string.
+pub unsafe extern "C" fn git_merge_ffi(
+    cwd: *const c_char,
+    branch: *const c_char,
+    options_json: *const c_char,
 ) -> *mut c_char {
-    let query_str = unsafe {
-        if query.is_null() {
+    let cwd_str = unsafe {
+        if cwd.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(query).to_str().unwrap_or("")
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
     };
 
-    let items_str = unsafe {
-        if items_newline_separated.is_null() {
+    let branch_str = unsafe {
+        if branch.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(items_newline_separated)
-            .to_str()
-            .unwrap_or("")
+        CStr::from_ptr(branch).to_str().unwrap_or("")
     };
 
-    let items: Vec<String> = items_str.lines().map(|s| s.to_string()).collect();
-    let limit_opt = if limit < 0 {
-        None
-    } else {
-        Some(limit as usize)
+    let options: vcs::MergeOptions = unsafe {
+        if options_json.is_null() {
+            vcs::MergeOptions::default()
+        } else {
+            match serde_json::from_str(CStr::from_ptr(options_json).to_str().unwrap_or("{}")) {
+                Ok(options) => options,
+                Err(_) => return std::ptr::null_mut(),
+            }
+        }
     };
 
-    let results = fuzzy::search_nucleo(query_str, &items, limit_opt);
-    let result_str = results.join("\n");
+    let result = match vcs::merge(cwd_str, branch_str, &options) {
+        Ok(merged) => serde_json::json!({
+            "success": true,
+            "commit": merged.commit,
+            "fast_forward": merged.fast_forward,
+            "up_to_date": merged.up_to_date,
+            "conflicts": merged.conflicts,
+        }),
+        Err(e) => serde_json::json!({
+            "success": false,
+            "code": error::ErrorCode::from(&e),
+            "error": format!("{}", e),
+        }),
+    };
 
-    match CString::new(result_str) {
-        Ok(cstring) => cstring.into_raw(),
+    match serde_json::to_string(&result) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
         Err(_) => std::ptr::null_mut(),
     }
 }
 
-// Bash command parsing FFI
+/// List tags
+/// Returns JSON: [{"name": "v1.0.0", "target_sha": "abc1234", "is_annotated": true, "message": "..."}]
 #[no_mangle]
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure `command` and `cwd` are valid, non-null, null-terminated
-/// C strings that remain valid for the duration of the call.
-pub unsafe extern "C" fn parse_bash_command_ffi(
-    command: *const c_char,
-    cwd: *const c_char,
-) -> *mut c_char {
-    let command_str = unsafe {
-        if command.is_null() {
-            return std::ptr::null_mut();
-        }
-        CStr::from_ptr(command).to_str().unwrap_or("")
-    };
-
+/// The caller must ensure `cwd` is a valid, non-null, null-terminated C string.
+pub unsafe extern "C" fn git_list_tags_ffi(cwd: *const c_char) -> *mut c_char {
     let cwd_str = unsafe {
         if cwd.is_null() {
             return std::ptr::null_mut();
@@ -965,8 +3957,8 @@ pub unsafe extern "C" fn parse_bash_command_ffi(
         CStr::from_ptr(cwd).to_str().unwrap_or(".")
     };
 
-    match shell::parse_bash_command(command_str, cwd_str) {
-        Ok(result) => match serde_json::to_string(&result) {
+    match vcs::list_tags(cwd_str) {
+        Ok(tags) => match serde_json::to_string(&tags) {
             Ok(json) => CString::new(json).unwrap().into_raw(),
             Err(_) => std::ptr::null_mut(),
         },
@@ -974,18 +3966,19 @@ pub unsafe extern "C" fn parse_bash_command_ffi(
     }
 }
 
-// File listing FFI (replacement for ripgrep --files)
+/// Create a tag. `target` and `message` may be null (HEAD / lightweight tag).
+/// Returns JSON: {"success": true, "target_sha": "abc1234"} or {"success": false, "code", "error": "..."}
 #[no_mangle]
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure all string pointers are valid, non-null, null-terminated
-/// C strings that remain valid for the duration of the call.
-pub unsafe extern "C" fn file_list_ffi(
+/// The caller must ensure `cwd` and `name` are valid, non-null, null-terminated
+/// C strings, and that `target` and `message` are either null or valid C strings.
+pub unsafe extern "C" fn git_create_tag_ffi(
     cwd: *const c_char,
-    globs_json: *const c_char,
-    hidden: bool,
-    follow: bool,
-    max_depth: i32,
+    name: *const c_char,
+    target: *const c_char,
+    message: *const c_char,
+    force: bool,
 ) -> *mut c_char {
     let cwd_str = unsafe {
         if cwd.is_null() {
@@ -994,177 +3987,238 @@ pub unsafe extern "C" fn file_list_ffi(
         CStr::from_ptr(cwd).to_str().unwrap_or(".")
     };
 
-    let globs: Vec<String> = unsafe {
-        if globs_json.is_null() {
-            vec![]
-        } else {
-            let json_str = CStr::from_ptr(globs_json).to_str().unwrap_or("[]");
-            serde_json::from_str(json_str).unwrap_or_else(|_| vec![])
+    let name_str = unsafe {
+        if name.is_null() {
+            return std::ptr::null_mut();
         }
+        CStr::from_ptr(name).to_str().unwrap_or("")
     };
 
-    let max_depth_opt = if max_depth < 0 {
-        None
-    } else {
-        Some(max_depth as usize)
+    let target_opt = unsafe {
+        if target.is_null() {
+            None
+        } else {
+            CStr::from_ptr(target).to_str().ok()
+        }
     };
 
-    match file_list::list_files(cwd_str, globs, hidden, follow, max_depth_opt) {
-        Ok(files) => match serde_json::to_string(&files) {
-            Ok(json) => CString::new(json).unwrap().into_raw(),
-            Err(_) => std::ptr::null_mut(),
-        },
-        Err(err) => {
-            // Return error as JSON
-            let error_obj = serde_json::json!({ "error": err });
-            match serde_json::to_string(&error_obj) {
-                Ok(json) => CString::new(json).unwrap().into_raw(),
-                Err(_) => std::ptr::null_mut(),
-            }
+    let message_opt = unsafe {
+        if message.is_null() {
+            None
+        } else {
+            CStr::from_ptr(message).to_str().ok()
         }
+    };
+
+    let result = match vcs::create_tag(cwd_str, name_str, target_opt, message_opt, force) {
+        Ok(sha) => serde_json::json!({ "success": true, "target_sha": sha }),
+        Err(e) => serde_json::json!({
+            "success": false,
+            "code": error::ErrorCode::from(&e),
+            "error": format!("{}", e),
+        }),
+    };
+
+    match serde_json::to_string(&result) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
     }
 }
 
-// Web fetch (EXPERIMENTAL - NOT RECOMMENDED FOR PRODUCTION)
-// Benchmark results: TypeScript is better for this use case (0.71ms avg processing)
-// Network latency (500-2000ms) >> Processing time (1-60ms)
-// To enable: cargo build --release --features webfetch
-#[cfg(feature = "webfetch")]
+/// Delete a tag
+/// Returns null on success, error message otherwise
 #[no_mangle]
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
 /// The caller must ensure all string pointers are valid, non-null, null-terminated
 /// C strings that remain valid for the duration of the call.
-pub unsafe extern "C" fn webfetch_ffi(
-    url: *const c_char,
-    format: *const c_char,
-    timeout_secs: u64,
+pub unsafe extern "C" fn git_delete_tag_ffi(
+    cwd: *const c_char,
+    name: *const c_char,
 ) -> *mut c_char {
-    let url_str = unsafe {
-        if url.is_null() {
-            return std::ptr::null_mut();
+    let cwd_str = unsafe {
+        if cwd.is_null() {
+            return CString::new("cwd is null").unwrap().into_raw();
         }
-        CStr::from_ptr(url).to_str().unwrap_or("")
+        CStr::from_ptr(cwd).to_str().unwrap_or(".")
     };
 
-    let format_str = unsafe {
-        if format.is_null() {
-            return std::ptr::null_mut();
+    let name_str = unsafe {
+        if name.is_null() {
+            return CString::new("name is null").unwrap().into_raw();
         }
-        CStr::from_ptr(format).to_str().unwrap_or("markdown")
+        CStr::from_ptr(name).to_str().unwrap_or("")
     };
 
-    let content_format = match format_str {
-        "text" => webfetch::ContentFormat::Text,
-        "html" => webfetch::ContentFormat::Html,
-        _ => webfetch::ContentFormat::Markdown,
+    match vcs::delete_tag(cwd_str, name_str) {
+        Ok(_) => std::ptr::null_mut(),
+        Err(e) => CString::new(format!("{}", e)).unwrap().into_raw(),
+    }
+}
+
+// ============================================================================
+// Key/Value Store FFI Functions
+// ============================================================================
+
+/// Set a value in the in-memory key/value store (not persisted to disk)
+/// Returns 0 on success, -1 on error
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `key` and `value` are valid, non-null, null-terminated
+/// C strings that remain valid for the duration of the call.
+pub unsafe extern "C" fn kv_set_ffi(key: *const c_char, value: *const c_char) -> i32 {
+    if key.is_null() || value.is_null() {
+        return -1;
+    }
+    let key_str = match CStr::from_ptr(key).to_str() {
+        Ok(k) => k,
+        Err(_) => return -1,
+    };
+    let value_str = match CStr::from_ptr(value).to_str() {
+        Ok(v) => v,
+        Err(_) => return -1,
     };
 
-    match webfetch::fetch_url(url_str, content_format, timeout_secs) {
-        Ok(result) => {
-            #[derive(serde::Serialize)]
-            struct Response {
-                content: String,
-                content_type: String,
-            }
+    kv::set(key_str, value_str);
+    0
+}
 
-            let response = Response {
-                content: result.content,
-                content_type: result.content_type,
-            };
+/// Get a value from the key/value store
+/// Returns the value, or null if the key is not set or is not valid UTF-8
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `key` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn kv_get_ffi(key: *const c_char) -> *mut c_char {
+    if key.is_null() {
+        return std::ptr::null_mut();
+    }
+    let key_str = match CStr::from_ptr(key).to_str() {
+        Ok(k) => k,
+        Err(_) => return std::ptr::null_mut(),
+    };
 
-            match serde_json::to_string(&response) {
-                Ok(json) => CString::new(json).unwrap().into_raw(),
-                Err(_) => std::ptr::null_mut(),
-            }
-        }
-        Err(_) => std::ptr::null_mut(),
+    match kv::get(key_str).and_then(|value| CString::new(value).ok()) {
+        Some(value) => value.into_raw(),
+        None => std::ptr::null_mut(),
     }
 }
 
-// =====================
-// File Watcher FFI
-// =====================
+/// Delete a key from the key/value store
+/// Returns 1 if the key was removed, 0 if it was not set, -1 on error
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `key` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn kv_delete_ffi(key: *const c_char) -> i32 {
+    if key.is_null() {
+        return -1;
+    }
+    match CStr::from_ptr(key).to_str() {
+        Ok(key_str) => kv::delete(key_str) as i32,
+        Err(_) => -1,
+    }
+}
 
-/// Create a file watcher with event queue
-/// Returns error string on failure, null on success
+/// List key/value store entries whose key starts with `prefix`
+/// Returns JSON: {"key": "value", ...}, or null if `prefix` is not valid UTF-8
 #[no_mangle]
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure all string pointers are valid, non-null, null-terminated
-/// C strings that remain valid for the duration of the call.
-pub unsafe extern "C" fn watcher_create_ffi(
-    id: *const c_char,
-    path: *const c_char,
-    ignore_patterns_json: *const c_char,
-    max_queue_size: u64,
-) -> *mut c_char {
-    let id_str = unsafe {
-        if id.is_null() {
-            return CString::new("id is null").unwrap().into_raw();
-        }
-        CStr::from_ptr(id).to_str().unwrap_or("")
+/// The caller must ensure `prefix` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn kv_list_prefix_ffi(prefix: *const c_char) -> *mut c_char {
+    if prefix.is_null() {
+        return std::ptr::null_mut();
+    }
+    let prefix_str = match CStr::from_ptr(prefix).to_str() {
+        Ok(p) => p,
+        Err(_) => return std::ptr::null_mut(),
     };
 
-    let path_str = unsafe {
-        if path.is_null() {
-            return CString::new("path is null").unwrap().into_raw();
+    match serde_json::to_string(&kv::list_prefix(prefix_str)) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// ============================================================================
+// Lock FFI Functions
+// ============================================================================
+
+/// Acquire a read lock for the given key
+/// Returns JSON: {"ticket": number, "acquired": boolean}
+#[no_mangle]
+/// # Safety
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `key` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn lock_acquire_read_ffi(key: *const c_char) -> *mut c_char {
+    let key_str = {
+        if key.is_null() {
+            return std::ptr::null_mut();
         }
-        CStr::from_ptr(path).to_str().unwrap_or("")
+        CStr::from_ptr(key).to_str().unwrap_or("")
     };
 
-    let ignore_patterns_str = unsafe {
-        if ignore_patterns_json.is_null() {
-            "[]"
-        } else {
-            CStr::from_ptr(ignore_patterns_json)
-                .to_str()
-                .unwrap_or("[]")
+    match lock::acquire_read_lock(key_str) {
+        Ok((ticket, acquired)) => {
+            let result = serde_json::json!({
+                "ticket": ticket,
+                "acquired": acquired
+            });
+            match serde_json::to_string(&result) {
+                Ok(json) => CString::new(json).unwrap().into_raw(),
+                Err(_) => std::ptr::null_mut(),
+            }
         }
-    };
-
-    let ignore_patterns: Vec<String> = match serde_json::from_str(ignore_patterns_str) {
-        Ok(p) => p,
         Err(e) => {
-            return CString::new(format!("Invalid JSON: {}", e))
-                .unwrap()
-                .into_raw()
+            let error_obj = serde_json::json!({
+                "code": error::ErrorCode::Other,
+                "error": e,
+            });
+            match serde_json::to_string(&error_obj) {
+                Ok(json) => CString::new(json).unwrap().into_raw(),
+                Err(_) => std::ptr::null_mut(),
+            }
         }
-    };
-
-    match watcher::create(
-        id_str.to_string(),
-        path_str.to_string(),
-        ignore_patterns,
-        max_queue_size as usize,
-    ) {
-        Ok(_) => std::ptr::null_mut(), // Success
-        Err(e) => CString::new(e).unwrap().into_raw(),
     }
 }
 
-/// Poll events from watcher (non-blocking)
-/// Returns JSON array of events
+/// Acquire a write lock for the given key
+/// Returns JSON: {"ticket": number, "acquired": boolean}
 #[no_mangle]
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure `id` is a valid, non-null, null-terminated
+/// The caller must ensure `key` is a valid, non-null, null-terminated
 /// C string that remains valid for the duration of the call.
-pub unsafe extern "C" fn watcher_poll_events_ffi(id: *const c_char) -> *mut c_char {
-    let id_str = unsafe {
-        if id.is_null() {
+pub unsafe extern "C" fn lock_acquire_write_ffi(key: *const c_char) -> *mut c_char {
+    let key_str = {
+        if key.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(id).to_str().unwrap_or("")
+        CStr::from_ptr(key).to_str().unwrap_or("")
     };
 
-    match watcher::poll_events(id_str) {
-        Ok(events) => match serde_json::to_string(&events) {
-            Ok(json) => CString::new(json).unwrap().into_raw(),
-            Err(_) => std::ptr::null_mut(),
-        },
+    match lock::acquire_write_lock(key_str) {
+        Ok((ticket, acquired)) => {
+            let result = serde_json::json!({
+                "ticket": ticket,
+                "acquired": acquired
+            });
+            match serde_json::to_string(&result) {
+                Ok(json) => CString::new(json).unwrap().into_raw(),
+                Err(_) => std::ptr::null_mut(),
+            }
+        }
         Err(e) => {
-            let error_obj = serde_json::json!({ "error": e });
+            let error_obj = serde_json::json!({
+                "code": error::ErrorCode::Other,
+                "error": e,
+            });
             match serde_json::to_string(&error_obj) {
                 Ok(json) => CString::new(json).unwrap().into_raw(),
                 Err(_) => std::ptr::null_mut(),
@@ -1173,233 +4227,221 @@ pub unsafe extern "C" fn watcher_poll_events_ffi(id: *const c_char) -> *mut c_ch
     }
 }
 
-/// Get pending event count
-/// Returns count as i32, or -1 on error
+/// Check if a read lock is ready
+/// Returns 1 if ready, 0 if not ready, -1 on error
 #[no_mangle]
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure `id` is a valid, non-null, null-terminated
+/// The caller must ensure `key` is a valid, non-null, null-terminated
 /// C string that remains valid for the duration of the call.
-pub unsafe extern "C" fn watcher_pending_count_ffi(id: *const c_char) -> i32 {
-    let id_str = unsafe {
-        if id.is_null() {
+pub unsafe extern "C" fn lock_check_read_ffi(key: *const c_char, ticket: u64) -> i32 {
+    let key_str = {
+        if key.is_null() {
             return -1;
         }
-        CStr::from_ptr(id).to_str().unwrap_or("")
+        CStr::from_ptr(key).to_str().unwrap_or("")
     };
 
-    match watcher::pending_count(id_str) {
-        Ok(count) => count as i32,
+    match lock::check_read_lock(key_str, ticket) {
+        Ok(true) => 1,
+        Ok(false) => 0,
         Err(_) => -1,
     }
 }
 
-/// Remove a file watcher
-/// Returns error string on failure, null on success
+/// Check if a write lock is ready
+/// Returns 1 if ready, 0 if not ready, -1 on error
 #[no_mangle]
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure `id` is a valid, non-null, null-terminated
+/// The caller must ensure `key` is a valid, non-null, null-terminated
 /// C string that remains valid for the duration of the call.
-pub unsafe extern "C" fn watcher_remove_ffi(id: *const c_char) -> *mut c_char {
-    let id_str = unsafe {
-        if id.is_null() {
-            return CString::new("id is null").unwrap().into_raw();
+pub unsafe extern "C" fn lock_check_write_ffi(key: *const c_char, ticket: u64) -> i32 {
+    let key_str = {
+        if key.is_null() {
+            return -1;
         }
-        CStr::from_ptr(id).to_str().unwrap_or("")
+        CStr::from_ptr(key).to_str().unwrap_or("")
     };
 
-    match watcher::remove(id_str.to_string()) {
-        Ok(_) => std::ptr::null_mut(), // Success
-        Err(e) => CString::new(e).unwrap().into_raw(),
+    match lock::check_write_lock(key_str, ticket) {
+        Ok(true) => 1,
+        Ok(false) => 0,
+        Err(_) => -1,
     }
 }
 
-/// List all active watchers
-/// Returns JSON array of watcher IDs
+/// Finalize acquiring a read lock
+/// Returns 0 on success, -1 on error
 #[no_mangle]
 /// # Safety
-/// This function is safe to call from C as it doesn't take any pointer arguments.
-pub unsafe extern "C" fn watcher_list_ffi() -> *mut c_char {
-    let ids = watcher::list();
-    match serde_json::to_string(&ids) {
-        Ok(json) => CString::new(json).unwrap().into_raw(),
-        Err(_) => std::ptr::null_mut(),
+/// This function is unsafe because it dereferences raw C string pointers.
+/// The caller must ensure `key` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn lock_finalize_read_ffi(key: *const c_char, ticket: u64) -> i32 {
+    let key_str = {
+        if key.is_null() {
+            return -1;
+        }
+        CStr::from_ptr(key).to_str().unwrap_or("")
+    };
+
+    match lock::finalize_read_lock(key_str, ticket) {
+        Ok(_) => 0,
+        Err(_) => -1,
     }
 }
 
-/// Get watcher info
-/// Returns JSON object with watcher details, or error string
+/// Finalize acquiring a write lock
+/// Returns 0 on success, -1 on error
 #[no_mangle]
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure `id` is a valid, non-null, null-terminated
+/// The caller must ensure `key` is a valid, non-null, null-terminated
 /// C string that remains valid for the duration of the call.
-pub unsafe extern "C" fn watcher_get_info_ffi(id: *const c_char) -> *mut c_char {
-    let id_str = unsafe {
-        if id.is_null() {
-            return std::ptr::null_mut();
+pub unsafe extern "C" fn lock_finalize_write_ffi(key: *const c_char, ticket: u64) -> i32 {
+    let key_str = {
+        if key.is_null() {
+            return -1;
         }
-        CStr::from_ptr(id).to_str().unwrap_or("")
+        CStr::from_ptr(key).to_str().unwrap_or("")
     };
 
-    match watcher::get_info(id_str.to_string()) {
-        Ok(json) => CString::new(json).unwrap().into_raw(),
-        Err(e) => CString::new(format!("{{\"error\":\"{}\"}}", e))
-            .unwrap()
-            .into_raw(),
+    match lock::finalize_write_lock(key_str, ticket) {
+        Ok(_) => 0,
+        Err(_) => -1,
     }
 }
 
-// ============================================================================
-// Git/VCS FFI Functions
-// ============================================================================
-
-/// Get detailed Git status with file list
+/// Release a read lock
+/// Returns 0 on success, -1 on error
 #[no_mangle]
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure `cwd` is a valid, non-null, null-terminated
+/// The caller must ensure `key` is a valid, non-null, null-terminated
 /// C string that remains valid for the duration of the call.
-pub unsafe extern "C" fn git_status_detailed_ffi(cwd: *const c_char) -> *mut c_char {
-    let cwd_str = unsafe {
-        if cwd.is_null() {
-            return std::ptr::null_mut();
+pub unsafe extern "C" fn lock_release_read_ffi(key: *const c_char) -> i32 {
+    let key_str = {
+        if key.is_null() {
+            return -1;
         }
-        CStr::from_ptr(cwd).to_str().unwrap_or(".")
+        CStr::from_ptr(key).to_str().unwrap_or("")
     };
 
-    match vcs::get_status_detailed(cwd_str) {
-        Ok(status) => match serde_json::to_string(&status) {
-            Ok(json) => CString::new(json).unwrap().into_raw(),
-            Err(_) => std::ptr::null_mut(),
-        },
-        Err(_) => std::ptr::null_mut(),
+    match lock::release_read_lock(key_str) {
+        Ok(_) => 0,
+        Err(_) => -1,
     }
 }
 
-/// Stage files (git add)
-/// paths_json: JSON array of file paths, empty array for "git add ."
+/// Release a write lock
+/// Returns 0 on success, -1 on error
 #[no_mangle]
 /// # Safety
 /// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure all string pointers are valid, non-null, null-terminated
-/// C strings that remain valid for the duration of the call.
-pub unsafe extern "C" fn git_stage_files_ffi(
-    cwd: *const c_char,
-    paths_json: *const c_char,
-) -> *mut c_char {
-    let cwd_str = unsafe {
-        if cwd.is_null() {
-            return CString::new("cwd is null").unwrap().into_raw();
+/// The caller must ensure `key` is a valid, non-null, null-terminated
+/// C string that remains valid for the duration of the call.
+pub unsafe extern "C" fn lock_release_write_ffi(key: *const c_char) -> i32 {
+    let key_str = {
+        if key.is_null() {
+            return -1;
         }
-        CStr::from_ptr(cwd).to_str().unwrap_or(".")
+        CStr::from_ptr(key).to_str().unwrap_or("")
     };
 
-    let paths: Vec<String> = unsafe {
-        if paths_json.is_null() {
-            vec![]
-        } else {
-            let json_str = CStr::from_ptr(paths_json).to_str().unwrap_or("[]");
-            serde_json::from_str(json_str).unwrap_or_else(|_| vec![])
-        }
-    };
+    match lock::release_write_lock(key_str) {
+        Ok(_) => 0,
+        Err(_) => -1,
+    }
+}
 
-    match vcs::stage_files(cwd_str, paths) {
-        Ok(_) => std::ptr::null_mut(), // Success
-        Err(e) => CString::new(format!("{}", e)).unwrap().into_raw(),
+/// Get lock statistics
+/// Returns JSON with stats
+#[no_mangle]
+/// # Safety
+/// This function is safe to call from C as it doesn't take any pointer arguments.
+pub unsafe extern "C" fn lock_get_stats_ffi() -> *mut c_char {
+    let stats = lock::get_lock_stats();
+    let result = serde_json::json!({
+        "total_locks": stats.total_locks,
+        "active_readers": stats.active_readers,
+        "active_writers": stats.active_writers,
+        "waiting_readers": stats.waiting_readers,
+        "waiting_writers": stats.waiting_writers,
+    });
+    match serde_json::to_string(&result) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
     }
 }
 
-/// Unstage files (git reset)
-/// paths_json: JSON array of file paths, empty array for reset all
+// ============================================================================
+// Code Search FFI (BM25 + tree-sitter)
+// ============================================================================
+
+/// Check that `content` parses as `language` (e.g. "rust", "typescript", "tsx").
+/// Returns JSON: {"ok": bool, "errors": [{"line", "column", "message"}]} or {"code", "error": "..."}.
 #[no_mangle]
 /// # Safety
-/// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure all string pointers are valid, non-null, null-terminated
-/// C strings that remain valid for the duration of the call.
-pub unsafe extern "C" fn git_unstage_files_ffi(
-    cwd: *const c_char,
-    paths_json: *const c_char,
+/// `content` and `language` must be valid, non-null, null-terminated C strings.
+pub unsafe extern "C" fn check_syntax_ffi(
+    content: *const c_char,
+    language: *const c_char,
 ) -> *mut c_char {
-    let cwd_str = unsafe {
-        if cwd.is_null() {
-            return CString::new("cwd is null").unwrap().into_raw();
+    let content_str = unsafe {
+        if content.is_null() {
+            return std::ptr::null_mut();
         }
-        CStr::from_ptr(cwd).to_str().unwrap_or(".")
-    };
-
-    let paths: Vec<String> = unsafe {
-        if paths_json.is_null() {
-            vec![]
-        } else {
-            let json_str = CStr::from_ptr(paths_json).to_str().unwrap_or("[]");
-            serde_json::from_str(json_str).unwrap_or_else(|_| vec![])
+        CStr::from_ptr(content).to_str().unwrap_or("")
+    };
+    let language_str = unsafe {
+        if language.is_null() {
+            return std::ptr::null_mut();
         }
+        CStr::from_ptr(language).to_str().unwrap_or("")
     };
 
-    match vcs::unstage_files(cwd_str, paths) {
-        Ok(_) => std::ptr::null_mut(), // Success
-        Err(e) => CString::new(format!("{}", e)).unwrap().into_raw(),
+    let json = match indexer::check_syntax(content_str, language_str) {
+        Ok(result) => serde_json::to_string(&result),
+        Err(e) => serde_json::to_string(&serde_json::json!({
+            "code": error::ErrorCode::from(&e),
+            "error": e.message,
+        })),
+    };
+
+    match json {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
     }
 }
 
-/// Commit staged changes
-/// Returns commit SHA on success, error string on failure
+/// Language version of each compiled-in tree-sitter grammar.
+/// Returns JSON: {"rust": 14, "tsx": 14, "bash": 14, ...}
 #[no_mangle]
 /// # Safety
-/// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure `cwd` and `message` are valid, non-null, null-terminated
-/// C strings that remain valid for the duration of the call.
-pub unsafe extern "C" fn git_commit_ffi(cwd: *const c_char, message: *const c_char) -> *mut c_char {
-    let cwd_str = unsafe {
-        if cwd.is_null() {
-            return CString::new("cwd is null").unwrap().into_raw();
-        }
-        CStr::from_ptr(cwd).to_str().unwrap_or(".")
-    };
-
-    let message_str = unsafe {
-        if message.is_null() {
-            return CString::new("message is null").unwrap().into_raw();
-        }
-        CStr::from_ptr(message).to_str().unwrap_or("")
-    };
-
-    match vcs::commit(cwd_str, message_str) {
-        Ok(commit_sha) => {
-            let result = serde_json::json!({ "success": true, "commit": commit_sha });
-            match serde_json::to_string(&result) {
-                Ok(json) => CString::new(json).unwrap().into_raw(),
-                Err(_) => std::ptr::null_mut(),
-            }
-        }
-        Err(e) => {
-            let result = serde_json::json!({ "success": false, "error": format!("{}", e) });
-            match serde_json::to_string(&result) {
-                Ok(json) => CString::new(json).unwrap().into_raw(),
-                Err(_) => std::ptr::null_mut(),
-            }
-        }
+/// This function is safe to call from C as it takes no pointer arguments.
+pub unsafe extern "C" fn grammar_info_ffi() -> *mut c_char {
+    match serde_json::to_string(&indexer::grammar_info()) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
     }
 }
 
-/// List all local branches
+/// Index a project directory for local code search.
+/// Returns JSON IndexStats on success, null on error.
 #[no_mangle]
 /// # Safety
-/// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure `cwd` is a valid, non-null, null-terminated
-/// C string that remains valid for the duration of the call.
-pub unsafe extern "C" fn git_list_branches_ffi(cwd: *const c_char) -> *mut c_char {
-    let cwd_str = unsafe {
-        if cwd.is_null() {
+/// `project_path` must be a valid, non-null, null-terminated C string.
+pub unsafe extern "C" fn codesearch_index_ffi(project_path: *const c_char) -> *mut c_char {
+    let path_str = unsafe {
+        if project_path.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(cwd).to_str().unwrap_or(".")
+        CStr::from_ptr(project_path).to_str().unwrap_or(".")
     };
 
-    match vcs::list_branches(cwd_str) {
-        Ok(branches) => match serde_json::to_string(&branches) {
+    match codesearch::index_project(path_str) {
+        Ok(stats) => match serde_json::to_string(&stats) {
             Ok(json) => CString::new(json).unwrap().into_raw(),
             Err(_) => std::ptr::null_mut(),
         },
@@ -1407,356 +4449,346 @@ pub unsafe extern "C" fn git_list_branches_ffi(cwd: *const c_char) -> *mut c_cha
     }
 }
 
-/// Checkout branch
+/// Same as `codesearch_index_ffi`, stoppable with `cancel_operation_ffi(op_id)`
+/// where `op_id` comes from `operation_begin_ffi`. A cancelled build returns
+/// JSON IndexStats with `"cancelled": true` for the files indexed so far.
 #[no_mangle]
 /// # Safety
-/// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure all string pointers are valid, non-null, null-terminated
-/// C strings that remain valid for the duration of the call.
-pub unsafe extern "C" fn git_checkout_branch_ffi(
-    cwd: *const c_char,
-    branch_name: *const c_char,
+/// `project_path` must be a valid, non-null, null-terminated C string.
+pub unsafe extern "C" fn codesearch_index_cancellable_ffi(
+    project_path: *const c_char,
+    op_id: u64,
 ) -> *mut c_char {
-    let cwd_str = unsafe {
-        if cwd.is_null() {
-            return CString::new("cwd is null").unwrap().into_raw();
-        }
-        CStr::from_ptr(cwd).to_str().unwrap_or(".")
-    };
-
-    let branch_str = unsafe {
-        if branch_name.is_null() {
-            return CString::new("branch_name is null").unwrap().into_raw();
+    let path_str = unsafe {
+        if project_path.is_null() {
+            return std::ptr::null_mut();
         }
-        CStr::from_ptr(branch_name).to_str().unwrap_or("")
+        CStr::from_ptr(project_path).to_str().unwrap_or(".")
     };
 
-    match vcs::checkout_branch(cwd_str, branch_str) {
-        Ok(_) => std::ptr::null_mut(), // Success
-        Err(e) => CString::new(format!("{}", e)).unwrap().into_raw(),
+    let token = cancel::CancelToken::for_op(op_id);
+    match codesearch::index_project_with_cancel(path_str, &token) {
+        Ok(stats) => match serde_json::to_string(&stats) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
     }
 }
 
-/// Get file diff
+/// Same as `codesearch_index_cancellable_ffi` with generated-file heuristics from
+/// `config_json`: {"generated_patterns": [globs], "max_avg_line_length", "name_boost",
+/// "ignore_globs": [globs]}. Missing
+/// fields (or a null `config_json`) use the defaults. `op_id` 0 means not cancellable.
+/// Returns JSON IndexStats, null on error or invalid config.
 #[no_mangle]
 /// # Safety
-/// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure all string pointers are valid, non-null, null-terminated
-/// C strings that remain valid for the duration of the call.
-pub unsafe extern "C" fn git_file_diff_ffi(
-    cwd: *const c_char,
-    file_path: *const c_char,
-    staged: bool,
+/// `project_path` must be a valid, non-null, null-terminated C string.
+/// `config_json` must be null or a valid, null-terminated C string.
+pub unsafe extern "C" fn codesearch_index_with_config_ffi(
+    project_path: *const c_char,
+    config_json: *const c_char,
+    op_id: u64,
 ) -> *mut c_char {
-    let cwd_str = unsafe {
-        if cwd.is_null() {
+    let path_str = unsafe {
+        if project_path.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(cwd).to_str().unwrap_or(".")
+        CStr::from_ptr(project_path).to_str().unwrap_or(".")
     };
 
-    let file_str = unsafe {
-        if file_path.is_null() {
-            return std::ptr::null_mut();
+    let config = if config_json.is_null() {
+        codesearch::IndexConfig::default()
+    } else {
+        let json = unsafe { CStr::from_ptr(config_json).to_str().unwrap_or("{}") };
+        match serde_json::from_str(json) {
+            Ok(config) => config,
+            Err(_) => return std::ptr::null_mut(),
         }
-        CStr::from_ptr(file_path).to_str().unwrap_or("")
     };
 
-    match vcs::get_file_diff(cwd_str, file_str, staged) {
-        Ok(diff) => match CString::new(diff) {
-            Ok(cstring) => cstring.into_raw(),
+    let token = cancel::CancelToken::for_op(op_id);
+    match codesearch::index_project_with_config(path_str, &config, &token) {
+        Ok(stats) => match serde_json::to_string(&stats) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
             Err(_) => std::ptr::null_mut(),
         },
         Err(_) => std::ptr::null_mut(),
     }
 }
 
-/// Push to remote
+/// Same as `codesearch_index_ffi`, excluding paths matched by `ignore_globs_json`:
+/// a JSON array of gitignore-style patterns applied on top of `.gitignore`, where
+/// `!pattern` re-includes. Returns JSON IndexStats, null on error or invalid globs.
 #[no_mangle]
 /// # Safety
-/// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure `cwd` is a valid, non-null, null-terminated
-/// C string that remains valid for the duration of the call.
-pub unsafe extern "C" fn git_push_ffi(cwd: *const c_char) -> *mut c_char {
-    let cwd_str = unsafe {
-        if cwd.is_null() {
+/// `project_path` and `ignore_globs_json` must be valid, non-null, null-terminated C strings.
+pub unsafe extern "C" fn codesearch_index_ignore_ffi(
+    project_path: *const c_char,
+    ignore_globs_json: *const c_char,
+) -> *mut c_char {
+    let path_str = unsafe {
+        if project_path.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(cwd).to_str().unwrap_or(".")
+        CStr::from_ptr(project_path).to_str().unwrap_or(".")
     };
-
-    #[derive(serde::Serialize)]
-    struct PushResult {
-        success: bool,
-        message: Option<String>,
-        error: Option<String>,
-    }
-
-    let result = match vcs::push_to_remote(cwd_str) {
-        Ok(message) => PushResult {
-            success: true,
-            message: Some(message),
-            error: None,
-        },
-        Err(e) => PushResult {
-            success: false,
-            message: None,
-            error: Some(e.to_string()),
-        },
+    let globs_str = unsafe {
+        if ignore_globs_json.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(ignore_globs_json).to_str().unwrap_or("[]")
     };
 
-    match serde_json::to_string(&result) {
-        Ok(json) => match CString::new(json) {
-            Ok(cstring) => cstring.into_raw(),
+    let ignore_globs: Vec<String> = match serde_json::from_str(globs_str) {
+        Ok(globs) => globs,
+        Err(_) => return std::ptr::null_mut(),
+    };
+    let config = codesearch::IndexConfig {
+        ignore_globs,
+        ..codesearch::IndexConfig::default()
+    };
+    match codesearch::index_project_with_config(path_str, &config, &cancel::CancelToken::none()) {
+        Ok(stats) => match serde_json::to_string(&stats) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
             Err(_) => std::ptr::null_mut(),
         },
         Err(_) => std::ptr::null_mut(),
     }
 }
 
-// ============================================================================
-// Lock FFI Functions
-// ============================================================================
-
-/// Acquire a read lock for the given key
-/// Returns JSON: {"ticket": number, "acquired": boolean}
+/// Read the full source of the symbols named `name` in `file_path`.
+/// `name` may be qualified (`Parser::parse`) or not (`parse`).
+/// Returns JSON array of {"name", "kind", "line_start", "line_end", "content"}, null on error.
 #[no_mangle]
 /// # Safety
-/// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure `key` is a valid, non-null, null-terminated
-/// C string that remains valid for the duration of the call.
-pub unsafe extern "C" fn lock_acquire_read_ffi(key: *const c_char) -> *mut c_char {
-    let key_str = {
-        if key.is_null() {
+/// `file_path` and `name` must be valid, non-null, null-terminated C strings.
+pub unsafe extern "C" fn read_symbol_ffi(
+    file_path: *const c_char,
+    name: *const c_char,
+) -> *mut c_char {
+    let path_str = unsafe {
+        if file_path.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(key).to_str().unwrap_or("")
+        CStr::from_ptr(file_path).to_str().unwrap_or("")
     };
-
-    match lock::acquire_read_lock(key_str) {
-        Ok((ticket, acquired)) => {
-            let result = serde_json::json!({
-                "ticket": ticket,
-                "acquired": acquired
-            });
-            match serde_json::to_string(&result) {
-                Ok(json) => CString::new(json).unwrap().into_raw(),
-                Err(_) => std::ptr::null_mut(),
-            }
-        }
-        Err(e) => {
-            let error_obj = serde_json::json!({ "error": e });
-            match serde_json::to_string(&error_obj) {
-                Ok(json) => CString::new(json).unwrap().into_raw(),
-                Err(_) => std::ptr::null_mut(),
-            }
-        }
-    }
-}
-
-/// Acquire a write lock for the given key
-/// Returns JSON: {"ticket": number, "acquired": boolean}
-#[no_mangle]
-/// # Safety
-/// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure `key` is a valid, non-null, null-terminated
-/// C string that remains valid for the duration of the call.
-pub unsafe extern "C" fn lock_acquire_write_ffi(key: *const c_char) -> *mut c_char {
-    let key_str = {
-        if key.is_null() {
+    let name_str = unsafe {
+        if name.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(key).to_str().unwrap_or("")
-    };
-
-    match lock::acquire_write_lock(key_str) {
-        Ok((ticket, acquired)) => {
-            let result = serde_json::json!({
-                "ticket": ticket,
-                "acquired": acquired
-            });
-            match serde_json::to_string(&result) {
-                Ok(json) => CString::new(json).unwrap().into_raw(),
-                Err(_) => std::ptr::null_mut(),
-            }
-        }
-        Err(e) => {
-            let error_obj = serde_json::json!({ "error": e });
-            match serde_json::to_string(&error_obj) {
-                Ok(json) => CString::new(json).unwrap().into_raw(),
-                Err(_) => std::ptr::null_mut(),
-            }
-        }
-    }
-}
-
-/// Check if a read lock is ready
-/// Returns 1 if ready, 0 if not ready, -1 on error
-#[no_mangle]
-/// # Safety
-/// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure `key` is a valid, non-null, null-terminated
-/// C string that remains valid for the duration of the call.
-pub unsafe extern "C" fn lock_check_read_ffi(key: *const c_char, ticket: u64) -> i32 {
-    let key_str = {
-        if key.is_null() {
-            return -1;
-        }
-        CStr::from_ptr(key).to_str().unwrap_or("")
+        CStr::from_ptr(name).to_str().unwrap_or("")
     };
 
-    match lock::check_read_lock(key_str, ticket) {
-        Ok(true) => 1,
-        Ok(false) => 0,
-        Err(_) => -1,
+    match codesearch::read_symbol(path_str, name_str) {
+        Ok(symbols) => match serde_json::to_string(&symbols) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
     }
 }
 
-/// Check if a write lock is ready
-/// Returns 1 if ready, 0 if not ready, -1 on error
+/// Start indexing a project on a background thread.
+/// Returns the job id to pass to `codesearch_job_status_ffi`, or -1 on null input.
 #[no_mangle]
 /// # Safety
-/// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure `key` is a valid, non-null, null-terminated
-/// C string that remains valid for the duration of the call.
-pub unsafe extern "C" fn lock_check_write_ffi(key: *const c_char, ticket: u64) -> i32 {
-    let key_str = {
-        if key.is_null() {
+/// `project_path` must be a valid, non-null, null-terminated C string.
+pub unsafe extern "C" fn codesearch_index_async_ffi(project_path: *const c_char) -> i64 {
+    let path_str = unsafe {
+        if project_path.is_null() {
             return -1;
         }
-        CStr::from_ptr(key).to_str().unwrap_or("")
+        CStr::from_ptr(project_path).to_str().unwrap_or(".")
     };
 
-    match lock::check_write_lock(key_str, ticket) {
-        Ok(true) => 1,
-        Ok(false) => 0,
-        Err(_) => -1,
+    codesearch::index_project_async(path_str) as i64
+}
+
+/// Poll a background index job.
+/// Returns JSON {"state": "queued"|"running"|"done"|"failed", "progress", "stats"?, "error"?},
+/// or null for an unknown job id. A finished job is returned once, then forgotten.
+#[no_mangle]
+/// # Safety
+/// This function is safe to call from C as it takes no pointer arguments.
+pub unsafe extern "C" fn codesearch_job_status_ffi(job_id: i64) -> *mut c_char {
+    let status = match codesearch::job_status(job_id as u64) {
+        Some(status) => status,
+        None => return std::ptr::null_mut(),
+    };
+    match serde_json::to_string(&status) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
     }
 }
 
-/// Finalize acquiring a read lock
-/// Returns 0 on success, -1 on error
+/// Search the local code index. `+word` requires a term, `-word` excludes it.
+/// Returns JSON array of SearchResult on success, null on error.
 #[no_mangle]
 /// # Safety
-/// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure `key` is a valid, non-null, null-terminated
-/// C string that remains valid for the duration of the call.
-pub unsafe extern "C" fn lock_finalize_read_ffi(key: *const c_char, ticket: u64) -> i32 {
-    let key_str = {
-        if key.is_null() {
-            return -1;
+/// `query` must be a valid, non-null, null-terminated C string.
+pub unsafe extern "C" fn codesearch_search_ffi(
+    query: *const c_char,
+    top_k: i32,
+) -> *mut c_char {
+    let query_str = unsafe {
+        if query.is_null() {
+            return std::ptr::null_mut();
         }
-        CStr::from_ptr(key).to_str().unwrap_or("")
+        CStr::from_ptr(query).to_str().unwrap_or("")
     };
+    let k = if top_k <= 0 { 10 } else { top_k as usize };
 
-    match lock::finalize_read_lock(key_str, ticket) {
-        Ok(_) => 0,
-        Err(_) => -1,
+    match codesearch::search(query_str, k) {
+        Ok(results) => match serde_json::to_string(&results) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
     }
 }
 
-/// Finalize acquiring a write lock
-/// Returns 0 on success, -1 on error
+/// Run a search and hold the ranked results for `codesearch_search_next_ffi`.
+/// Returns JSON {"cursor_id", "total"}, null on error.
 #[no_mangle]
 /// # Safety
-/// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure `key` is a valid, non-null, null-terminated
-/// C string that remains valid for the duration of the call.
-pub unsafe extern "C" fn lock_finalize_write_ffi(key: *const c_char, ticket: u64) -> i32 {
-    let key_str = {
-        if key.is_null() {
-            return -1;
+/// `query` must be a valid, non-null, null-terminated C string.
+pub unsafe extern "C" fn codesearch_search_cursor_ffi(
+    query: *const c_char,
+    top_k: i32,
+) -> *mut c_char {
+    let query_str = unsafe {
+        if query.is_null() {
+            return std::ptr::null_mut();
         }
-        CStr::from_ptr(key).to_str().unwrap_or("")
+        CStr::from_ptr(query).to_str().unwrap_or("")
     };
+    let k = if top_k <= 0 { 10 } else { top_k as usize };
 
-    match lock::finalize_write_lock(key_str, ticket) {
-        Ok(_) => 0,
-        Err(_) => -1,
+    match codesearch::search_cursor(query_str, k) {
+        Ok(cursor) => match serde_json::to_string(&cursor) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
     }
 }
 
-/// Release a read lock
-/// Returns 0 on success, -1 on error
+/// Take the next `batch` results from a search cursor; the cursor is freed
+/// once `done` is true. Close cursors that won't be read to the end with
+/// `codesearch_search_cursor_close_ffi`.
+/// Returns JSON {"results": [SearchResult], "done"}, null for an unknown cursor.
 #[no_mangle]
 /// # Safety
-/// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure `key` is a valid, non-null, null-terminated
-/// C string that remains valid for the duration of the call.
-pub unsafe extern "C" fn lock_release_read_ffi(key: *const c_char) -> i32 {
-    let key_str = {
-        if key.is_null() {
-            return -1;
-        }
-        CStr::from_ptr(key).to_str().unwrap_or("")
-    };
+/// This function is safe to call from C as it takes no pointer arguments.
+pub unsafe extern "C" fn codesearch_search_next_ffi(cursor_id: u64, batch: u32) -> *mut c_char {
+    match codesearch::search_next(cursor_id, batch as usize) {
+        Ok(batch) => match serde_json::to_string(&batch) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
 
-    match lock::release_read_lock(key_str) {
-        Ok(_) => 0,
-        Err(_) => -1,
+/// Free a search cursor before it is read to the end.
+/// Returns 0 on success, -1 for an unknown or already finished cursor.
+#[no_mangle]
+/// # Safety
+/// This function is safe to call from C as it takes no pointer arguments.
+pub unsafe extern "C" fn codesearch_search_cursor_close_ffi(cursor_id: u64) -> i32 {
+    if codesearch::search_cursor_close(cursor_id) {
+        0
+    } else {
+        -1
     }
 }
 
-/// Release a write lock
-/// Returns 0 on success, -1 on error
+/// Search the local code index with hits grouped by file, best file first.
+/// Returns JSON array of {"file_path", "file_score", "symbols": [SearchResult]},
+/// null on error.
 #[no_mangle]
 /// # Safety
-/// This function is unsafe because it dereferences raw C string pointers.
-/// The caller must ensure `key` is a valid, non-null, null-terminated
-/// C string that remains valid for the duration of the call.
-pub unsafe extern "C" fn lock_release_write_ffi(key: *const c_char) -> i32 {
-    let key_str = {
-        if key.is_null() {
-            return -1;
+/// `query` must be a valid, non-null, null-terminated C string.
+pub unsafe extern "C" fn codesearch_search_grouped_ffi(
+    query: *const c_char,
+    top_k: i32,
+) -> *mut c_char {
+    let query_str = unsafe {
+        if query.is_null() {
+            return std::ptr::null_mut();
         }
-        CStr::from_ptr(key).to_str().unwrap_or("")
+        CStr::from_ptr(query).to_str().unwrap_or("")
     };
+    let k = if top_k <= 0 { 10 } else { top_k as usize };
 
-    match lock::release_write_lock(key_str) {
-        Ok(_) => 0,
-        Err(_) => -1,
+    match codesearch::search_grouped(query_str, k) {
+        Ok(groups) => match serde_json::to_string(&groups) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
     }
 }
 
-/// Get lock statistics
-/// Returns JSON with stats
+/// Look up symbols by name ("go to symbol"); `exact` makes the match case-sensitive.
+/// Returns JSON array of CodeSymbol ordered by file and line, or null on error.
 #[no_mangle]
 /// # Safety
-/// This function is safe to call from C as it doesn't take any pointer arguments.
-pub unsafe extern "C" fn lock_get_stats_ffi() -> *mut c_char {
-    let stats = lock::get_lock_stats();
-    let result = serde_json::json!({
-        "total_locks": stats.total_locks,
-        "active_readers": stats.active_readers,
-        "active_writers": stats.active_writers,
-        "waiting_readers": stats.waiting_readers,
-        "waiting_writers": stats.waiting_writers,
-    });
-    match serde_json::to_string(&result) {
-        Ok(json) => CString::new(json).unwrap().into_raw(),
+/// `name` must be a valid, non-null, null-terminated C string.
+pub unsafe extern "C" fn codesearch_find_by_name_ffi(
+    name: *const c_char,
+    exact: bool,
+) -> *mut c_char {
+    let name_str = unsafe {
+        if name.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(name).to_str().unwrap_or("")
+    };
+
+    match codesearch::find_by_name(name_str, exact) {
+        Ok(symbols) => match serde_json::to_string(&symbols) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
         Err(_) => std::ptr::null_mut(),
     }
 }
 
-// ============================================================================
-// Code Search FFI (BM25 + tree-sitter)
-// ============================================================================
-
-/// Index a project directory for local code search.
-/// Returns JSON IndexStats on success, null on error.
+/// Search the code index with a ranking config.
+/// `config_json`: {"kind_weights": {"function": 1.0, "chunk": 0.5, ...}}; null uses defaults.
+/// Returns JSON array of SearchResult, or null on error.
 #[no_mangle]
 /// # Safety
-/// `project_path` must be a valid, non-null, null-terminated C string.
-pub unsafe extern "C" fn codesearch_index_ffi(project_path: *const c_char) -> *mut c_char {
-    let path_str = unsafe {
-        if project_path.is_null() {
+/// `query` must be a valid, non-null, null-terminated C string.
+/// `config_json` must be null or a valid, null-terminated C string.
+pub unsafe extern "C" fn codesearch_search_with_config_ffi(
+    query: *const c_char,
+    top_k: i32,
+    config_json: *const c_char,
+) -> *mut c_char {
+    let query_str = unsafe {
+        if query.is_null() {
             return std::ptr::null_mut();
         }
-        CStr::from_ptr(project_path).to_str().unwrap_or(".")
+        CStr::from_ptr(query).to_str().unwrap_or("")
+    };
+    let k = if top_k <= 0 { 10 } else { top_k as usize };
+
+    let config: codesearch::SearchConfig = unsafe {
+        if config_json.is_null() {
+            codesearch::SearchConfig::default()
+        } else {
+            match serde_json::from_str(CStr::from_ptr(config_json).to_str().unwrap_or("{}")) {
+                Ok(config) => config,
+                Err(_) => return std::ptr::null_mut(),
+            }
+        }
     };
 
-    match codesearch::index_project(path_str) {
-        Ok(stats) => match serde_json::to_string(&stats) {
+    match codesearch::search_with_config(query_str, k, &config) {
+        Ok(results) => match serde_json::to_string(&results) {
             Ok(json) => CString::new(json).unwrap().into_raw(),
             Err(_) => std::ptr::null_mut(),
         },
@@ -1764,12 +4796,14 @@ pub unsafe extern "C" fn codesearch_index_ffi(project_path: *const c_char) -> *m
     }
 }
 
-/// Search the local code index.
-/// Returns JSON array of SearchResult on success, null on error.
+/// Search with per-term score breakdowns, for tuning relevance.
+/// Diagnostics only: built with `--features diagnostics`.
+/// Returns JSON array of ExplainedResult, or null on error.
+#[cfg(feature = "diagnostics")]
 #[no_mangle]
 /// # Safety
 /// `query` must be a valid, non-null, null-terminated C string.
-pub unsafe extern "C" fn codesearch_search_ffi(
+pub unsafe extern "C" fn codesearch_search_explain_ffi(
     query: *const c_char,
     top_k: i32,
 ) -> *mut c_char {
@@ -1781,7 +4815,7 @@ pub unsafe extern "C" fn codesearch_search_ffi(
     };
     let k = if top_k <= 0 { 10 } else { top_k as usize };
 
-    match codesearch::search(query_str, k) {
+    match codesearch::search_explain(query_str, k) {
         Ok(results) => match serde_json::to_string(&results) {
             Ok(json) => CString::new(json).unwrap().into_raw(),
             Err(_) => std::ptr::null_mut(),
@@ -1808,6 +4842,32 @@ pub unsafe extern "C" fn codesearch_update_ffi(file_path: *const c_char) -> i32
     }
 }
 
+/// Re-index files under `project_path` modified after `since_ms` (Unix ms) and
+/// drop indexed files that no longer exist.
+/// Returns JSON {"files_updated", "files_removed", "index_time_ms"}, null on error.
+#[no_mangle]
+/// # Safety
+/// `project_path` must be a valid, non-null, null-terminated C string.
+pub unsafe extern "C" fn codesearch_reindex_changed_ffi(
+    project_path: *const c_char,
+    since_ms: u64,
+) -> *mut c_char {
+    let path_str = unsafe {
+        if project_path.is_null() {
+            return std::ptr::null_mut();
+        }
+        CStr::from_ptr(project_path).to_str().unwrap_or(".")
+    };
+
+    match codesearch::reindex_changed(path_str, since_ms) {
+        Ok(stats) => match serde_json::to_string(&stats) {
+            Ok(json) => CString::new(json).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
 /// Remove a file from the index.
 /// Returns 0 on success, -1 on error.
 #[no_mangle]
@@ -1826,7 +4886,8 @@ pub unsafe extern "C" fn codesearch_remove_ffi(file_path: *const c_char) -> i32
     }
 }
 
-/// Get current index statistics.
+/// Get current index statistics, including the approximate `bytes_used`
+/// and the `primary_language` (most symbols).
 /// Returns JSON IndexStats on success, null on error.
 #[no_mangle]
 /// # Safety
@@ -1841,6 +4902,18 @@ pub unsafe extern "C" fn codesearch_stats_ffi() -> *mut c_char {
     }
 }
 
+/// Poll progress of a running (or the last) index build.
+/// Returns JSON IndexProgress: {"files_indexed", "files_total", "running"}.
+#[no_mangle]
+/// # Safety
+/// This function is safe to call from C as it takes no pointer arguments.
+pub unsafe extern "C" fn codesearch_index_progress_ffi() -> *mut c_char {
+    match serde_json::to_string(&codesearch::index_progress()) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
 /// Match a string against a wildcard pattern.
 /// Returns 1 if matches, 0 if not, -1 on null input.
 ///
@@ -1902,7 +4975,8 @@ pub unsafe extern "C" fn extract_prefix_ffi(tokens_json: *const c_char) -> *mut
 /// - `pattern_str`: the pattern to match (e.g. "ls -la", "/home/user/*")
 /// - `rules_json`: JSON array of `{permission, pattern, action}` objects
 ///
-/// Returns: JSON of matched `{permission, pattern, action}`, or default `{action:"ask"}`.
+/// Returns: JSON of matched `{permission, pattern, action}`, or default `{action:"ask"}`;
+/// null if `rules_json` is not a valid ruleset.
 ///
 /// # Safety
 /// The caller must ensure all pointers are valid, non-null, null-terminated C strings.
@@ -1933,8 +5007,11 @@ pub unsafe extern "C" fn evaluate_permission_ffi(
             Err(_) => return std::ptr::null_mut(),
         }
     };
-    let rules: Vec<permission::PermissionRule> =
-        serde_json::from_str(rules_str).unwrap_or_default();
+    // Malformed rules must not read as "no rules", which would fail open
+    let rules: Vec<permission::PermissionRule> = match serde_json::from_str(rules_str) {
+        Ok(rules) => rules,
+        Err(_) => return std::ptr::null_mut(),
+    };
     let result = permission::evaluate_permission(permission, pattern, &rules);
     match serde_json::to_string(&result) {
         Ok(json) => match CString::new(json) {
@@ -1945,13 +5022,61 @@ pub unsafe extern "C" fn evaluate_permission_ffi(
     }
 }
 
+/// Evaluate every sub-command of a shell command line against a ruleset.
+///
+/// Arguments:
+/// - `command`: the shell command line (pipelines and `&&`/`||` chains are split)
+/// - `rules_json`: JSON array of `{permission, pattern, action}` objects
+///
+/// Returns: JSON `{action, subcommands: [{command, prefix, action, rule}]}` where
+/// `action` is the most restrictive sub-command action, or null on parse error
+/// (of the command or of `rules_json`).
+///
+/// # Safety
+/// The caller must ensure all pointers are valid, non-null, null-terminated C strings.
+#[no_mangle]
+pub unsafe extern "C" fn evaluate_command_ffi(
+    command: *const c_char,
+    rules_json: *const c_char,
+) -> *mut c_char {
+    let command_str = unsafe {
+        if command.is_null() { return std::ptr::null_mut(); }
+        match CStr::from_ptr(command).to_str() {
+            Ok(s) => s,
+            Err(_) => return std::ptr::null_mut(),
+        }
+    };
+    let rules_str = unsafe {
+        if rules_json.is_null() { return std::ptr::null_mut(); }
+        match CStr::from_ptr(rules_json).to_str() {
+            Ok(s) => s,
+            Err(_) => return std::ptr::null_mut(),
+        }
+    };
+    // Malformed rules must not read as "no rules", which would fail open
+    let rules: Vec<permission::PermissionRule> = match serde_json::from_str(rules_str) {
+        Ok(rules) => rules,
+        Err(_) => return std::ptr::null_mut(),
+    };
+    match permission::evaluate(command_str, &rules) {
+        Ok(result) => match serde_json::to_string(&result) {
+            Ok(json) => match CString::new(json) {
+                Ok(c) => c.into_raw(),
+                Err(_) => std::ptr::null_mut(),
+            },
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
 /// Return the subset of tools denied by a ruleset.
 ///
 /// Arguments:
 /// - `tools_json`: JSON array of tool name strings
 /// - `ruleset_json`: JSON array of `{permission, pattern, action}` objects
 ///
-/// Returns: JSON array of denied tool names.
+/// Returns: JSON array of denied tool names, or null if either argument is not valid JSON.
 ///
 /// # Safety
 /// The caller must ensure all pointers are valid, non-null, null-terminated C strings.
@@ -1974,9 +5099,14 @@ pub unsafe extern "C" fn disabled_tools_ffi(
             Err(_) => return std::ptr::null_mut(),
         }
     };
-    let tools: Vec<String> = serde_json::from_str(tools_str).unwrap_or_default();
-    let ruleset: Vec<permission::PermissionRule> =
-        serde_json::from_str(ruleset_str).unwrap_or_default();
+    let tools: Vec<String> = match serde_json::from_str(tools_str) {
+        Ok(tools) => tools,
+        Err(_) => return std::ptr::null_mut(),
+    };
+    let ruleset: Vec<permission::PermissionRule> = match serde_json::from_str(ruleset_str) {
+        Ok(ruleset) => ruleset,
+        Err(_) => return std::ptr::null_mut(),
+    };
     let result = permission::disabled_tools(&tools, &ruleset);
     match serde_json::to_string(&result) {
         Ok(json) => match CString::new(json) {
@@ -2033,3 +5163,12 @@ pub unsafe extern "C" fn file_ignore_match_ffi(
     };
     if file_ignore::file_ignore_match(filepath, &whitelist, &extra) { 1 } else { 0 }
 }
+
+/// Install the JSON call logger configured by `IRONCODE_LOG` (`stderr` or a file path).
+/// Only built with `--features logging`; call once after loading the library.
+/// Returns 1 if logging was enabled, 0 if it is off or was already initialized.
+#[cfg(feature = "logging")]
+#[no_mangle]
+pub extern "C" fn logging_init_ffi() -> i32 {
+    logging::init() as i32
+}
@@ -0,0 +1,83 @@
+//! Structured JSON logging for debugging FFI calls (`logging` feature).
+//!
+//! Instrumented module functions (codesearch indexing, edit and grep replace,
+//! bash parsing, git operations) emit one JSON line per call when the span closes,
+//! including its arguments summary and timing, plus an error line if the call
+//! failed. Output goes to the destination named by `IRONCODE_LOG`:
+//! `stderr` (or `1`) for stderr, anything else is treated as a file path and
+//! appended to. Nothing is recorded until `init` is called.
+
+use std::fs::OpenOptions;
+use std::sync::{Mutex, Once};
+use tracing_subscriber::fmt::format::FmtSpan;
+
+pub const LOG_ENV: &str = "IRONCODE_LOG";
+
+static INIT: Once = Once::new();
+
+/// Install the global JSON subscriber according to `IRONCODE_LOG`.
+/// Safe to call more than once; only the first call has any effect.
+/// Returns false if `IRONCODE_LOG` is unset, the log file cannot be opened,
+/// or another subscriber was already installed.
+pub fn init() -> bool {
+    let mut installed = false;
+    INIT.call_once(|| {
+        let target = match std::env::var(LOG_ENV) {
+            Ok(t) if !t.is_empty() => t,
+            _ => return,
+        };
+        let builder = tracing_subscriber::fmt()
+            .json()
+            .with_span_events(FmtSpan::CLOSE)
+            .with_max_level(tracing::Level::DEBUG);
+
+        let result = if target == "stderr" || target == "1" {
+            builder.with_writer(std::io::stderr).try_init()
+        } else {
+            match OpenOptions::new().create(true).append(true).open(&target) {
+                Ok(file) => builder.with_writer(Mutex::new(file)).try_init(),
+                Err(_) => return,
+            }
+        };
+        installed = result.is_ok();
+    });
+    installed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_path;
+
+    #[test]
+    fn test_init_writes_json_lines() {
+        let log_path = temp_path("logging");
+        std::env::set_var(LOG_ENV, &log_path);
+
+        assert!(init());
+        assert!(!init());
+
+        crate::shell::parse_bash_command("API_TOKEN=hunter2 curl -u admin:s3cret x", "/tmp")
+            .unwrap();
+        assert!(crate::edit::replace("one two", "three", "four", false).is_err());
+
+        let output = std::fs::read_to_string(&log_path).unwrap();
+        let line = output
+            .lines()
+            .find(|l| l.contains("parse_bash_command"))
+            .expect("log line for parse_bash_command");
+        let json: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(json["span"]["name"], "parse_bash_command");
+        assert_eq!(json["span"]["program"], "curl");
+        // Arguments and assignments may hold secrets and are never logged
+        assert!(!output.contains("hunter2") && !output.contains("s3cret"));
+
+        let error = output
+            .lines()
+            .find(|l| l.contains("\"replace\"") && l.contains("ERROR"))
+            .expect("error event for replace");
+        assert!(error.contains("NotFound"));
+
+        std::fs::remove_file(&log_path).ok();
+    }
+}
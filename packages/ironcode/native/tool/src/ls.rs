@@ -1,11 +1,97 @@
 use crate::types::{Metadata, Output};
+use crate::vcs;
 use ignore::WalkBuilder;
+use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
 const LIMIT: usize = 100;
 
+/// A listed file and its git status
+#[derive(Serialize)]
+pub struct StatusEntry {
+    /// Relative to the listed directory, `/`-separated
+    pub path: String,
+    /// "modified", "untracked", "staged" or "clean"; omitted outside a repository
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<&'static str>,
+}
+
+/// `ls` output plus per-file git status, for trees that show change badges
+#[derive(Serialize)]
+pub struct StatusOutput {
+    #[serde(flatten)]
+    pub listing: Output,
+    pub entries: Vec<StatusEntry>,
+}
+
 pub fn execute(search_path: &str, ignore_patterns: Vec<String>) -> Result<Output, String> {
+    let files = list_files(search_path, &ignore_patterns);
+    Ok(render(search_path, &files))
+}
+
+/// List `search_path` like `execute` and annotate each file with its status
+/// in the repository containing `cwd`. Status is computed once for the whole
+/// repository; outside a repository every entry's status is omitted.
+pub fn execute_with_status(
+    search_path: &str,
+    cwd: &str,
+    ignore_patterns: Vec<String>,
+) -> Result<StatusOutput, String> {
+    let files = list_files(search_path, &ignore_patterns);
+
+    let repo_status = match (vcs::repo_root(cwd), vcs::get_status_detailed(cwd)) {
+        (Ok(root), Ok(status)) => {
+            let by_path: HashMap<String, &'static str> = status
+                .files
+                .into_iter()
+                .map(|file| {
+                    let badge = file.badge();
+                    (file.path, badge)
+                })
+                .collect();
+            // Untracked directories are reported once, as "dir/"
+            let by_dir: HashMap<String, &'static str> = by_path
+                .iter()
+                .filter(|(path, _)| path.ends_with('/'))
+                .map(|(path, status)| (path.clone(), *status))
+                .collect();
+            let root = Path::new(&root).canonicalize().map_err(|e| e.to_string())?;
+            Some((root, by_path, by_dir))
+        }
+        _ => None,
+    };
+    let listed = Path::new(search_path)
+        .canonicalize()
+        .map_err(|e| e.to_string())?;
+
+    let entries = files
+        .iter()
+        .map(|file| {
+            let status = repo_status.as_ref().and_then(|(root, by_path, by_dir)| {
+                let abs = listed.join(file);
+                let repo_rel = abs.strip_prefix(root).ok()?;
+                let key = repo_rel.to_string_lossy().replace('\\', "/");
+                let status = by_path.get(&key).copied().or_else(|| {
+                    key.match_indices('/')
+                        .find_map(|(i, _)| by_dir.get(&key[..=i]).copied())
+                });
+                Some(status.unwrap_or("clean"))
+            });
+            StatusEntry {
+                path: file.replace('\\', "/"),
+                status,
+            }
+        })
+        .collect();
+
+    Ok(StatusOutput {
+        listing: render(search_path, &files),
+        entries,
+    })
+}
+
+fn list_files(search_path: &str, ignore_patterns: &[String]) -> Vec<String> {
     let mut builder = WalkBuilder::new(search_path);
     builder
         .git_ignore(false)
@@ -53,16 +139,22 @@ pub fn execute(search_path: &str, ignore_patterns: Vec<String>) -> Result<Output
             break;
         }
     }
+    files
+}
 
+fn render(search_path: &str, files: &[String]) -> Output {
     // Build directory structure
     let mut dirs = HashSet::new();
     let mut files_by_dir: HashMap<String, Vec<String>> = HashMap::new();
 
-    for file in &files {
+    for file in files {
         let path = Path::new(file);
         let dir = path
             .parent()
             .and_then(|p| p.to_str())
+            // Top-level files have an empty parent; left as is it would be
+            // rendered as a nameless subdirectory and panic in `render_dir`
+            .filter(|p| !p.is_empty())
             .unwrap_or(".")
             .to_string();
 
@@ -143,12 +235,72 @@ pub fn execute(search_path: &str, ignore_patterns: Vec<String>) -> Result<Output
     let tree_output = render_dir(".", 0, &dirs, &files_by_dir);
     let output = format!("{}/\n{}", search_path, tree_output);
 
-    Ok(Output {
+    Output {
         title: search_path.to_string(),
         metadata: Metadata {
             count: files.len(),
             truncated: files.len() >= LIMIT,
+            cancelled: false,
+            scanned: None,
         },
         output,
-    })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::fs;
+
+    #[test]
+    fn test_ls_renders_top_level_files() {
+        let dir = temp_dir("ls_top");
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("README.md"), "readme\n").unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+
+        let search = dir.to_str().unwrap();
+        let result = execute(search, vec![]).unwrap();
+        assert_eq!(result.metadata.count, 2);
+        assert_eq!(
+            result.output,
+            format!("{}/\n  src/\n    main.rs\n  README.md\n", search)
+        );
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_ls_with_status_marks_modified_file() {
+        let dir = temp_dir("ls_status");
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.join("src/lib.rs"), "pub fn lib() {}\n").unwrap();
+        let cwd = dir.to_str().unwrap();
+        vcs::init(cwd, false, true).unwrap();
+        vcs::stage_files(cwd, vec![]).unwrap();
+        vcs::commit(cwd, "init").unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() { run(); }\n").unwrap();
+        fs::write(dir.join("src/new.rs"), "fn new() {}\n").unwrap();
+        fs::create_dir_all(dir.join("src/gen")).unwrap();
+        fs::write(dir.join("src/gen/out.rs"), "fn out() {}\n").unwrap();
+
+        let src = dir.join("src");
+        let result = execute_with_status(src.to_str().unwrap(), cwd, vec![]).unwrap();
+        let status_of = |name: &str| {
+            result
+                .entries
+                .iter()
+                .find(|e| e.path == name)
+                .and_then(|e| e.status)
+        };
+        assert_eq!(status_of("main.rs"), Some("modified"));
+        assert_eq!(status_of("new.rs"), Some("untracked"));
+        assert_eq!(status_of("gen/out.rs"), Some("untracked"));
+        assert_eq!(status_of("lib.rs"), Some("clean"));
+        assert_eq!(result.listing.metadata.count, 4);
+
+        fs::remove_dir_all(&dir).ok();
+    }
 }
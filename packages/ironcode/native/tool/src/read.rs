@@ -1,12 +1,79 @@
 use crate::types::Output;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use serde::Serialize;
 use std::fs;
-use std::io::{BufRead, BufReader, Read};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 use std::path::Path;
+use unicode_width::UnicodeWidthStr;
 
 const DEFAULT_READ_LIMIT: usize = 2000;
 const MAX_LINE_LENGTH: usize = 2000;
 const MAX_BYTES: usize = 50 * 1024;
 
+/// Files at least this large are memory-mapped instead of read through a buffer
+pub const MMAP_THRESHOLD: u64 = 4 * 1024 * 1024;
+
+/// Block size used when scanning backwards from the end of a file
+const TAIL_CHUNK: u64 = 64 * 1024;
+
+/// Block size used when reading forwards from the start of a file
+const HEAD_CHUNK: usize = 64 * 1024;
+
+/// Bytes sniffed from the start of a file by `detect_encoding`
+const ENCODING_SAMPLE: usize = 8 * 1024;
+
+#[derive(Debug, Serialize)]
+pub struct TailResult {
+    pub lines: Vec<String>,
+    /// File size when read; the offset to resume from when following the file
+    pub size: u64,
+    /// Bytes read from the end of the file to find the lines
+    pub bytes_read: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HeadResult {
+    pub lines: Vec<String>,
+    /// True if the file continues past the returned lines
+    pub has_more: bool,
+    /// Bytes read from the start of the file to find the lines
+    pub bytes_read: u64,
+}
+
+/// Result of `execute_guarded`
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum GuardedRead {
+    Content(Output),
+    /// The file exceeded `max_bytes` and was not read
+    TooLarge {
+        too_large: bool,
+        size: u64,
+    },
+}
+
+/// `execute`, but files larger than `max_bytes` are reported as too large
+/// instead of being read. `None` leaves the size unbounded, like `execute`.
+pub fn execute_guarded(
+    filepath: &str,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    max_bytes: Option<usize>,
+) -> Result<GuardedRead, String> {
+    if let Some(max_bytes) = max_bytes {
+        let size = fs::metadata(filepath)
+            .map_err(|_| format!("File not found: {}", filepath))?
+            .len();
+        if size > max_bytes as u64 {
+            return Ok(GuardedRead::TooLarge {
+                too_large: true,
+                size,
+            });
+        }
+    }
+    execute(filepath, offset, limit).map(GuardedRead::Content)
+}
+
 pub fn execute(
     filepath: &str,
     offset: Option<usize>,
@@ -105,11 +172,353 @@ pub fn execute(
         metadata: crate::types::Metadata {
             count: raw.len(),
             truncated,
+            cancelled: false,
+            scanned: None,
         },
         output,
     })
 }
 
+/// Run `f` over the raw bytes of a file. Files of at least `MMAP_THRESHOLD` bytes
+/// are memory-mapped so their contents are not copied onto the heap; smaller files
+/// are read into a buffer.
+///
+/// Mapping a file that changes under us is inherently racy: concurrent writes can
+/// show up as torn content, and if another process truncates the file while it is
+/// mapped, touching the missing pages raises SIGBUS. The map only lives for the
+/// duration of `f`, so keep the closure short and copy out what you need.
+pub fn with_file_bytes<T>(filepath: &str, f: impl FnOnce(&[u8]) -> T) -> Result<T, String> {
+    let mut file = fs::File::open(filepath).map_err(|e| format!("Failed to open file: {}", e))?;
+    let len = file
+        .metadata()
+        .map_err(|e| format!("Failed to read file metadata: {}", e))?
+        .len();
+
+    if len >= MMAP_THRESHOLD {
+        // SAFETY: the mapping is read-only and dropped before returning; see the
+        // caveats above about files modified while mapped.
+        let map = unsafe { memmap2::Mmap::map(&file) }
+            .map_err(|e| format!("Failed to map file: {}", e))?;
+        Ok(f(&map))
+    } else {
+        let mut buffer = Vec::with_capacity(len as usize);
+        file.read_to_end(&mut buffer)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        Ok(f(&buffer))
+    }
+}
+
+/// Return the last `n_lines` lines of a file, like `tail -n`.
+///
+/// The file is scanned backwards from the end in `TAIL_CHUNK` blocks, so only
+/// the bytes covering those lines are read. To follow a file like `tail -f`,
+/// pair this with the watcher and read from `size` onwards on change events.
+pub fn tail(filepath: &str, n_lines: usize) -> Result<TailResult, String> {
+    let mut file = fs::File::open(filepath).map_err(|e| format!("Failed to open file: {}", e))?;
+    let size = file
+        .metadata()
+        .map_err(|e| format!("Failed to read file metadata: {}", e))?
+        .len();
+
+    let mut pos = size;
+    let mut tail_bytes: Vec<u8> = Vec::new();
+    let mut newlines = 0;
+    let mut start = None;
+
+    while n_lines > 0 && pos > 0 && start.is_none() {
+        let read_len = TAIL_CHUNK.min(pos);
+        pos -= read_len;
+        let mut chunk = vec![0u8; read_len as usize];
+        file.seek(SeekFrom::Start(pos))
+            .and_then(|_| file.read_exact(&mut chunk))
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+
+        for (i, &byte) in chunk.iter().enumerate().rev() {
+            // The newline terminating the last line does not start a new one
+            if byte != b'\n' || pos + i as u64 == size - 1 {
+                continue;
+            }
+            newlines += 1;
+            if newlines == n_lines {
+                start = Some(i + 1);
+                break;
+            }
+        }
+
+        chunk.extend_from_slice(&tail_bytes);
+        tail_bytes = chunk;
+    }
+
+    let bytes_read = tail_bytes.len() as u64;
+    let text = String::from_utf8_lossy(&tail_bytes[start.unwrap_or(0)..]);
+    Ok(TailResult {
+        lines: text.lines().map(|line| line.to_string()).collect(),
+        size,
+        bytes_read,
+    })
+}
+
+/// Return the first `n_lines` lines of a file, like `head -n`.
+///
+/// The file is read in `HEAD_CHUNK` blocks and reading stops as soon as the
+/// lines are complete, so the rest of a large file is never touched.
+pub fn head(filepath: &str, n_lines: usize) -> Result<HeadResult, String> {
+    let mut file = fs::File::open(filepath).map_err(|e| format!("Failed to open file: {}", e))?;
+    let size = file
+        .metadata()
+        .map_err(|e| format!("Failed to read file metadata: {}", e))?
+        .len();
+
+    let mut head_bytes: Vec<u8> = Vec::new();
+    let mut chunk = vec![0u8; HEAD_CHUNK];
+    let mut newlines = 0;
+    let mut end = None;
+
+    while n_lines > 0 && end.is_none() {
+        let n = file
+            .read(&mut chunk)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        if n == 0 {
+            break;
+        }
+        for (i, &byte) in chunk[..n].iter().enumerate() {
+            if byte != b'\n' {
+                continue;
+            }
+            newlines += 1;
+            if newlines == n_lines {
+                end = Some(head_bytes.len() + i + 1);
+                break;
+            }
+        }
+        head_bytes.extend_from_slice(&chunk[..n]);
+    }
+
+    let bytes_read = head_bytes.len() as u64;
+    let end = end.unwrap_or(head_bytes.len());
+    let text = String::from_utf8_lossy(&head_bytes[..end]);
+    Ok(HeadResult {
+        lines: text.lines().map(|line| line.to_string()).collect(),
+        has_more: (end as u64) < size,
+        bytes_read,
+    })
+}
+
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct Counts {
+    /// Newline count, plus one for a final line without a trailing newline
+    pub lines: u64,
+    pub words: u64,
+    pub bytes: u64,
+    /// Chars of the content decoded as UTF-8, lossily: each malformed
+    /// sequence counts as the one U+FFFD it would be replaced with
+    pub chars: u64,
+}
+
+/// Streaming `wc`: feeds arbitrary chunks, so words and chars split across
+/// chunk boundaries are counted once.
+#[derive(Default)]
+struct Counter {
+    counts: Counts,
+    in_word: bool,
+    last_byte: Option<u8>,
+    /// Start of a UTF-8 sequence cut off by the end of the previous chunk
+    pending: Vec<u8>,
+}
+
+impl Counter {
+    fn feed(&mut self, chunk: &[u8]) {
+        self.count_chars(chunk);
+        for &byte in chunk {
+            if byte == b'\n' {
+                self.counts.lines += 1;
+            }
+            if byte.is_ascii_whitespace() {
+                self.in_word = false;
+            } else if !self.in_word {
+                self.in_word = true;
+                self.counts.words += 1;
+            }
+        }
+        self.counts.bytes += chunk.len() as u64;
+        if let Some(&last) = chunk.last() {
+            self.last_byte = Some(last);
+        }
+    }
+
+    /// Count chars the way `String::from_utf8_lossy` would decode them
+    fn count_chars(&mut self, chunk: &[u8]) {
+        let joined;
+        let mut rest = chunk;
+        if !self.pending.is_empty() {
+            joined = [std::mem::take(&mut self.pending).as_slice(), chunk].concat();
+            rest = &joined;
+        }
+        loop {
+            let (valid, error) = match std::str::from_utf8(rest) {
+                Ok(_) => (rest, None),
+                Err(e) => (&rest[..e.valid_up_to()], Some(e)),
+            };
+            // In valid UTF-8 every byte but a continuation (0b10xxxxxx) starts a char
+            self.counts.chars += valid.iter().filter(|&&b| b & 0xC0 != 0x80).count() as u64;
+            let Some(error) = error else {
+                return;
+            };
+            let after = &rest[valid.len()..];
+            match error.error_len() {
+                Some(len) => {
+                    self.counts.chars += 1;
+                    rest = &after[len..];
+                }
+                None => {
+                    self.pending = after.to_vec();
+                    return;
+                }
+            }
+        }
+    }
+
+    fn finish(mut self) -> Counts {
+        if matches!(self.last_byte, Some(b) if b != b'\n') {
+            self.counts.lines += 1;
+        }
+        // A sequence still incomplete at the end decodes to one U+FFFD
+        if !self.pending.is_empty() {
+            self.counts.chars += 1;
+        }
+        self.counts
+    }
+}
+
+/// Count lines, words, bytes and chars of a file in a single streaming pass
+pub fn counts(filepath: &str) -> Result<Counts, String> {
+    let mut file = fs::File::open(filepath).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut counter = Counter::default();
+    let mut buffer = vec![0u8; 65536];
+    loop {
+        let n = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        if n == 0 {
+            break;
+        }
+        counter.feed(&buffer[..n]);
+    }
+    Ok(counter.finish())
+}
+
+/// Same as `counts`, for an in-memory buffer
+pub fn counts_bytes(data: &[u8]) -> Counts {
+    let mut counter = Counter::default();
+    counter.feed(data);
+    counter.finish()
+}
+
+/// Terminal display width of each line, for wrapping: CJK and other wide
+/// chars count 2 columns, combining marks and other zero-width chars 0.
+/// The file is decoded like `read_transcoded`; a trailing `\r` is not counted.
+pub fn line_widths(filepath: &str) -> Result<Vec<usize>, String> {
+    let file = read_transcoded(filepath)?;
+    Ok(file
+        .content
+        .lines()
+        .map(|line| line.strip_suffix('\r').unwrap_or(line).width())
+        .collect())
+}
+
+#[derive(Debug, Serialize)]
+pub struct TranscodedFile {
+    /// WHATWG name of the detected encoding, e.g. "UTF-8", "UTF-16LE", "windows-1252"
+    pub encoding: String,
+    /// Decoded content, without any BOM
+    pub content: String,
+    /// True if some bytes were malformed and replaced with U+FFFD
+    pub had_errors: bool,
+}
+
+/// Guess a file's text encoding from its first `ENCODING_SAMPLE` bytes.
+///
+/// A UTF-8/UTF-16 BOM wins. Otherwise text with NUL bytes mostly in odd
+/// (even) positions is taken as BOM-less UTF-16LE (BE), checked first since
+/// NULs are valid UTF-8; then valid UTF-8 is UTF-8, and anything else falls
+/// back to Windows-1252, the superset of Latin-1.
+pub fn detect_encoding(filepath: &str) -> Result<&'static Encoding, String> {
+    let file = fs::File::open(filepath).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut sample = Vec::with_capacity(ENCODING_SAMPLE);
+    file.take(ENCODING_SAMPLE as u64)
+        .read_to_end(&mut sample)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    if let Some((encoding, _)) = Encoding::for_bom(&sample) {
+        return Ok(encoding);
+    }
+    if let Some(encoding) = guess_utf16(&sample) {
+        return Ok(encoding);
+    }
+    match std::str::from_utf8(&sample) {
+        Ok(_) => Ok(UTF_8),
+        // The sample may end in the middle of a multi-byte char
+        Err(e) if e.error_len().is_none() => Ok(UTF_8),
+        Err(_) => Ok(WINDOWS_1252),
+    }
+}
+
+/// Read a file and decode it to UTF-8, using the same detection as
+/// `detect_encoding`. Valid UTF-8 without a BOM is returned as-is without
+/// transcoding.
+pub fn read_transcoded(filepath: &str) -> Result<TranscodedFile, String> {
+    let mut bytes = fs::read(filepath).map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let utf16 = guess_utf16(&bytes);
+    if Encoding::for_bom(&bytes).is_none() && utf16.is_none() {
+        match String::from_utf8(bytes) {
+            Ok(content) => {
+                return Ok(TranscodedFile {
+                    encoding: UTF_8.name().to_string(),
+                    content,
+                    had_errors: false,
+                })
+            }
+            Err(e) => bytes = e.into_bytes(),
+        }
+    }
+
+    let (encoding, body) = match Encoding::for_bom(&bytes) {
+        Some((encoding, bom_len)) => (encoding, &bytes[bom_len..]),
+        None => (utf16.unwrap_or(WINDOWS_1252), &bytes[..]),
+    };
+
+    let (content, had_errors) = encoding.decode_without_bom_handling(body);
+    Ok(TranscodedFile {
+        encoding: encoding.name().to_string(),
+        content: content.into_owned(),
+        had_errors,
+    })
+}
+
+/// BOM-less UTF-16LE or UTF-16BE, judged by where NUL bytes fall in the sample
+fn guess_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(ENCODING_SAMPLE)];
+    let pairs = sample.len() / 2;
+    if pairs > 0 {
+        let even_nuls = sample.iter().step_by(2).filter(|&&b| b == 0).count();
+        let odd_nuls = sample
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .filter(|&&b| b == 0)
+            .count();
+        // ASCII-range UTF-16 has a NUL in every other byte
+        if odd_nuls * 10 >= pairs * 3 && even_nuls * 10 < pairs {
+            return Some(UTF_16LE);
+        }
+        if even_nuls * 10 >= pairs * 3 && odd_nuls * 10 < pairs {
+            return Some(UTF_16BE);
+        }
+    }
+    None
+}
+
 fn is_binary_file(path: &Path) -> Result<bool, String> {
     let ext = path
         .extension()
@@ -159,3 +568,228 @@ fn is_binary_file(path: &Path) -> Result<bool, String> {
 
     Ok((non_printable_count as f64 / bytes_read as f64) > 0.3)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_path;
+
+    #[test]
+    fn test_tail_big_file_reads_only_the_end() {
+        let path = temp_path("tail_big");
+        let content: String = (1..=200_000).map(|i| format!("line {}\n", i)).collect();
+        fs::write(&path, &content).unwrap();
+
+        let result = tail(path.to_str().unwrap(), 3).unwrap();
+        assert_eq!(
+            result.lines,
+            vec!["line 199998", "line 199999", "line 200000"]
+        );
+        assert_eq!(result.size, content.len() as u64);
+        assert!(result.bytes_read <= TAIL_CHUNK);
+        assert!(result.bytes_read < result.size);
+
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_head_big_file_reads_only_the_start() {
+        let path = temp_path("head_big");
+        let content: String = (1..=200_000).map(|i| format!("line {}\n", i)).collect();
+        fs::write(&path, &content).unwrap();
+        let filepath = path.to_str().unwrap();
+
+        let result = head(filepath, 3).unwrap();
+        assert_eq!(result.lines, vec!["line 1", "line 2", "line 3"]);
+        assert!(result.has_more);
+        assert!(result.bytes_read <= HEAD_CHUNK as u64);
+        assert!(result.bytes_read < content.len() as u64);
+
+        fs::write(&path, "one\ntwo").unwrap();
+        let result = head(filepath, 2).unwrap();
+        assert_eq!(result.lines, vec!["one", "two"]);
+        assert!(!result.has_more);
+        assert!(head(filepath, 1).unwrap().has_more);
+        assert!(head(filepath, 0).unwrap().lines.is_empty());
+
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_execute_guarded_rejects_file_over_cap() {
+        let path = temp_path("guarded");
+        fs::write(&path, "0123456789\n".repeat(10)).unwrap();
+        let filepath = path.to_str().unwrap();
+
+        match execute_guarded(filepath, None, None, Some(64)).unwrap() {
+            GuardedRead::TooLarge { too_large, size } => {
+                assert!(too_large);
+                assert_eq!(size, 110);
+            }
+            GuardedRead::Content(_) => panic!("expected the guard result"),
+        }
+        let json =
+            serde_json::to_value(execute_guarded(filepath, None, None, Some(64)).unwrap()).unwrap();
+        assert_eq!(json, serde_json::json!({ "too_large": true, "size": 110 }));
+
+        match execute_guarded(filepath, None, None, Some(110)).unwrap() {
+            GuardedRead::Content(output) => assert_eq!(output.metadata.count, 10),
+            GuardedRead::TooLarge { .. } => panic!("file at the cap should be read"),
+        }
+
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_tail_short_file_without_trailing_newline() {
+        let path = temp_path("tail_short");
+        fs::write(&path, "a\nb\nc").unwrap();
+
+        let result = tail(path.to_str().unwrap(), 2).unwrap();
+        assert_eq!(result.lines, vec!["b", "c"]);
+        let result = tail(path.to_str().unwrap(), 10).unwrap();
+        assert_eq!(result.lines, vec!["a", "b", "c"]);
+        assert!(tail(path.to_str().unwrap(), 0).unwrap().lines.is_empty());
+
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_counts_without_trailing_newline() {
+        let path = temp_path("counts");
+        fs::write(&path, "one two\nthree\n  four  five").unwrap();
+
+        let result = counts(path.to_str().unwrap()).unwrap();
+        assert_eq!(
+            result,
+            Counts {
+                lines: 3,
+                words: 5,
+                bytes: 26,
+                chars: 26,
+            }
+        );
+        assert_eq!(counts_bytes(b"a\nb\n").lines, 2);
+        assert_eq!(counts_bytes(b""), Counts::default());
+
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_read_transcoded_utf16le_with_bom() {
+        let path = temp_path("utf16le");
+        let mut bytes = vec![0xFF, 0xFE];
+        for unit in "héllo\nwörld".encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+        fs::write(&path, &bytes).unwrap();
+
+        assert_eq!(detect_encoding(path.to_str().unwrap()).unwrap(), UTF_16LE);
+        let result = read_transcoded(path.to_str().unwrap()).unwrap();
+        assert_eq!(result.encoding, "UTF-16LE");
+        assert_eq!(result.content, "héllo\nwörld");
+        assert!(!result.had_errors);
+
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_read_transcoded_utf16_without_bom() {
+        let text = "fn main() {\n    println!(\"hi\");\n}\n";
+        let le: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let be: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+
+        for (tag, bytes, encoding) in [
+            ("utf16le_nobom", le, UTF_16LE),
+            ("utf16be_nobom", be, UTF_16BE),
+        ] {
+            let path = temp_path(tag);
+            fs::write(&path, &bytes).unwrap();
+
+            assert_eq!(detect_encoding(path.to_str().unwrap()).unwrap(), encoding);
+            let result = read_transcoded(path.to_str().unwrap()).unwrap();
+            assert_eq!(result.encoding, encoding.name());
+            assert_eq!(result.content, text);
+            assert!(!result.had_errors);
+
+            fs::remove_file(&path).ok();
+        }
+    }
+
+    #[test]
+    fn test_read_transcoded_windows_1252() {
+        let path = temp_path("cp1252");
+        // "café – €5" in Windows-1252: é = E9, en dash = 96, euro = 80
+        fs::write(&path, b"caf\xE9 \x96 \x805").unwrap();
+
+        assert_eq!(
+            detect_encoding(path.to_str().unwrap()).unwrap(),
+            WINDOWS_1252
+        );
+        let result = read_transcoded(path.to_str().unwrap()).unwrap();
+        assert_eq!(result.encoding, "windows-1252");
+        assert_eq!(result.content, "café – €5");
+        assert!(!result.had_errors);
+
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_read_transcoded_utf8_passthrough() {
+        let path = temp_path("utf8");
+        fs::write(&path, "plain ✓ text\n").unwrap();
+
+        assert_eq!(detect_encoding(path.to_str().unwrap()).unwrap(), UTF_8);
+        let result = read_transcoded(path.to_str().unwrap()).unwrap();
+        assert_eq!(result.encoding, "UTF-8");
+        assert_eq!(result.content, "plain ✓ text\n");
+
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_counts_multibyte() {
+        let result = counts_bytes("héllo wörld ✓\n".as_bytes());
+        assert_eq!(result.lines, 1);
+        assert_eq!(result.words, 3);
+        assert_eq!(result.chars, 14);
+        assert_eq!(result.bytes, 18);
+    }
+
+    #[test]
+    fn test_counts_chars_match_lossy_decoding() {
+        let inputs: [&[u8]; 5] = [
+            b"caf\xE9 ok",
+            b"\x80\x80abc",
+            b"\xE2\x9C",
+            b"a\xF0\x9F\x98b\xFF",
+            "héllo ✓".as_bytes(),
+        ];
+        for input in inputs {
+            let expected = String::from_utf8_lossy(input).chars().count() as u64;
+            assert_eq!(counts_bytes(input).chars, expected, "{:?}", input);
+
+            // Splitting anywhere, even inside a sequence, gives the same count
+            for split in 0..=input.len() {
+                let mut counter = Counter::default();
+                counter.feed(&input[..split]);
+                counter.feed(&input[split..]);
+                assert_eq!(counter.finish().chars, expected, "{:?} at {}", input, split);
+            }
+        }
+    }
+
+    #[test]
+    fn test_line_widths_wide_and_combining() {
+        let path = temp_path("widths");
+        // Two wide CJK chars, a space, then "e" + U+0301 COMBINING ACUTE ACCENT
+        let wide = "漢字 e\u{301}";
+        fs::write(&path, format!("plain\r\n{}\n\n", wide)).unwrap();
+
+        let widths = line_widths(path.to_str().unwrap()).unwrap();
+        assert_eq!(widths, vec![5, 6, 0]);
+        assert_eq!(wide.chars().count(), 5);
+
+        fs::remove_file(&path).ok();
+    }
+}
@@ -7,6 +7,23 @@ pub struct BashParseResult {
     pub directories: Vec<String>,
     pub patterns: Vec<String>,
     pub always: Vec<String>,
+    pub redirects: Vec<Redirect>,
+    /// Wrapper commands (`sudo`, `env`, `nice`, `time`, `xargs`, `nohup`)
+    /// stripped before computing patterns, in order of first appearance
+    #[serde(default)]
+    pub wrappers: Vec<String>,
+}
+
+/// A redirection such as `> file`, `2>&1` or `<<EOF`
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Redirect {
+    /// File descriptor being redirected, explicit (`2>`) or implied (`>` is 1,
+    /// `<` is 0). None for `&>`/`&>>`, which redirect both stdout and stderr.
+    pub fd: Option<u32>,
+    /// Operator as written: `>`, `>>`, `>|`, `<`, `>&`, `<&`, `&>`, `<<`, `<<<`, ...
+    pub op: String,
+    /// File, descriptor number, heredoc delimiter or herestring word
+    pub target: String,
 }
 
 // ---------------------------------------------------------------------------
@@ -226,12 +243,215 @@ pub fn extract_command_prefix(parts: &[String]) -> String {
     parts[..arity.min(parts.len())].join(" ")
 }
 
+// ---------------------------------------------------------------------------
+// Wrapper commands
+// ---------------------------------------------------------------------------
+
+/// Commands that run another command, with the flags of each that take a
+/// separate value argument
+const WRAPPERS: &[(&str, &[&str])] = &[
+    ("sudo", &["-u", "-g", "-p", "-C", "-D", "-r", "-t", "-U"]),
+    ("env", &["-u", "-C", "-S"]),
+    ("nice", &["-n"]),
+    ("time", &["-f", "-o"]),
+    ("xargs", &["-I", "-n", "-P", "-L", "-d", "-E", "-s", "-a"]),
+    ("nohup", &[]),
+];
+
+/// Split leading wrapper commands (and their flags, and `env` assignments)
+/// off `parts`. Returns the index of the wrapped command and the wrapper
+/// names. If nothing follows the wrappers, nothing is stripped.
+fn strip_wrappers(parts: &[String]) -> (usize, Vec<String>) {
+    let mut wrappers = Vec::new();
+    let mut i = 0;
+    while let Some((name, value_flags)) = parts
+        .get(i)
+        .and_then(|word| WRAPPERS.iter().find(|(name, _)| name == word))
+    {
+        wrappers.push(name.to_string());
+        i += 1;
+        while let Some(arg) = parts.get(i) {
+            if arg == "--" {
+                i += 1;
+                break;
+            }
+            let is_assignment = *name == "env" && !arg.starts_with('=') && arg.contains('=');
+            if !arg.starts_with('-') && !is_assignment {
+                break;
+            }
+            i += if value_flags.contains(&arg.as_str()) {
+                2
+            } else {
+                1
+            };
+        }
+    }
+
+    if i >= parts.len() {
+        return (0, Vec::new());
+    }
+    (i, wrappers)
+}
+
+// ---------------------------------------------------------------------------
+// Environment variables
+// ---------------------------------------------------------------------------
+
+/// Read an environment variable. Returns None if it is unset or not valid UTF-8.
+pub fn get_env(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+/// Expand `$VAR` and `${VAR}` references against the process environment.
+/// Unknown variables expand to an empty string, or are left as written when
+/// `keep_unknown` is true. A `$` not followed by a valid name is kept as-is.
+/// No other shell syntax (defaults, escapes, command substitution) is handled.
+pub fn expand_env(template: &str, keep_unknown: bool) -> String {
+    let is_name_start = |c: char| c.is_ascii_alphabetic() || c == '_';
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+
+        let (name, consumed) = if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end)
+                    if braced[..end].starts_with(is_name_start)
+                        && braced[..end].chars().all(is_name_char) =>
+                {
+                    (&braced[..end], end + 2)
+                }
+                _ => ("", 0),
+            }
+        } else if after.starts_with(is_name_start) {
+            let end = after.find(|c| !is_name_char(c)).unwrap_or(after.len());
+            (&after[..end], end)
+        } else {
+            ("", 0)
+        };
+
+        if name.is_empty() {
+            out.push('$');
+            rest = after;
+            continue;
+        }
+
+        match get_env(name) {
+            Some(value) => out.push_str(&value),
+            None if keep_unknown => out.push_str(&rest[pos..pos + 1 + consumed]),
+            None => {}
+        }
+        rest = &after[consumed..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Word splitting
+// ---------------------------------------------------------------------------
+
+/// Split a command line into argv following POSIX shell quoting: single
+/// quotes are literal, double quotes allow `\` to escape `$`, `` ` ``, `"`,
+/// `\` and newline, and an unquoted `\` escapes the next character. A `#`
+/// at the start of a word begins a comment. Nothing is expanded, so `$HOME`
+/// stays literal. Fails on an unterminated quote or a trailing backslash.
+pub fn split(command: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    // Distinguishes an empty quoted word (`''`) from no word at all
+    let mut in_word = false;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            '#' if !in_word => break,
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some(next) => {
+                    word.push(next);
+                    in_word = true;
+                }
+                None => return Err("Trailing backslash".to_string()),
+            },
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(ch) => word.push(ch),
+                        None => return Err("Unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('\n') => {}
+                            Some(ch @ ('$' | '`' | '"' | '\\')) => word.push(ch),
+                            Some(ch) => {
+                                word.push('\\');
+                                word.push(ch);
+                            }
+                            None => return Err("Unterminated double quote".to_string()),
+                        },
+                        Some(ch) => word.push(ch),
+                        None => return Err("Unterminated double quote".to_string()),
+                    }
+                }
+            }
+            c => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
 // ---------------------------------------------------------------------------
 // Bash command parser (tree-sitter)
 // ---------------------------------------------------------------------------
 
+/// Program a command runs, for logs: the first word that isn't a `NAME=value`
+/// assignment. Arguments and assignments can hold secrets, so they are left out.
+#[cfg(feature = "logging")]
+fn log_program(command: &str) -> &str {
+    let is_assignment = |word: &str| {
+        word.split_once('=').is_some_and(|(name, _)| {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+    };
+    let program = command
+        .split_whitespace()
+        .find(|word| !is_assignment(word))
+        .unwrap_or("");
+    match program.char_indices().nth(64) {
+        Some((end, _)) => &program[..end],
+        None => program,
+    }
+}
+
 /// Parse a bash command and extract directories, command patterns, and
 /// always-allow patterns.  Replaces the WASM tree-sitter parsing in `bash.ts`.
+#[cfg_attr(feature = "logging", tracing::instrument(skip_all, fields(program = %log_program(command), command_len = command.len()), err))]
 pub fn parse_bash_command(command: &str, _cwd: &str) -> Result<BashParseResult, String> {
     let mut parser = Parser::new();
     let language = tree_sitter_bash::LANGUAGE;
@@ -247,21 +467,23 @@ pub fn parse_bash_command(command: &str, _cwd: &str) -> Result<BashParseResult,
     let mut directories = Vec::new();
     let mut patterns = Vec::new();
     let mut always = Vec::new();
+    let mut redirects = Vec::new();
+    let mut wrappers: Vec<String> = Vec::new();
 
     let mut cursor = root_node.walk();
     walk_tree(&mut cursor, command.as_bytes(), &mut |node| {
+        if let Some(redirect) = parse_redirect(node, command.as_bytes()) {
+            redirects.push(redirect);
+            return;
+        }
         if node.kind() == "command" {
-            let command_text = if let Some(parent) = node.parent() {
-                if parent.kind() == "redirected_statement" {
-                    parent.utf8_text(command.as_bytes()).unwrap_or("")
-                } else {
-                    node.utf8_text(command.as_bytes()).unwrap_or("")
-                }
-            } else {
-                node.utf8_text(command.as_bytes()).unwrap_or("")
+            let text_node = match node.parent() {
+                Some(parent) if parent.kind() == "redirected_statement" => parent,
+                _ => node,
             };
 
             let mut command_parts = Vec::new();
+            let mut part_starts = Vec::new();
             for i in 0..node.child_count() {
                 if let Some(child) = node.child(i) {
                     let kind = child.kind();
@@ -273,6 +495,7 @@ pub fn parse_bash_command(command: &str, _cwd: &str) -> Result<BashParseResult,
                     {
                         if let Ok(text) = child.utf8_text(command.as_bytes()) {
                             command_parts.push(text.to_string());
+                            part_starts.push(child.start_byte());
                         }
                     }
                 }
@@ -282,6 +505,20 @@ pub fn parse_bash_command(command: &str, _cwd: &str) -> Result<BashParseResult,
                 return;
             }
 
+            // Judge `sudo rm -rf x` by `rm -rf x`, remembering the wrapper
+            let (start, found) = strip_wrappers(&command_parts);
+            for wrapper in found {
+                if !wrappers.contains(&wrapper) {
+                    wrappers.push(wrapper);
+                }
+            }
+            let text_start = if start == 0 {
+                text_node.start_byte()
+            } else {
+                part_starts[start]
+            };
+            let command_text = &command[text_start..text_node.end_byte()];
+            let command_parts = &command_parts[start..];
             let command_name = &command_parts[0];
 
             let fs_commands = [
@@ -292,13 +529,18 @@ pub fn parse_bash_command(command: &str, _cwd: &str) -> Result<BashParseResult,
                     if arg.starts_with('-') || (command_name == "chmod" && arg.starts_with('+')) {
                         continue;
                     }
-                    directories.push(arg.clone());
+                    // Single-quoted words are literal in bash
+                    if arg.starts_with('\'') {
+                        directories.push(arg.clone());
+                    } else {
+                        directories.push(expand_env(arg, true));
+                    }
                 }
             }
 
             if command_name != "cd" {
                 patterns.push(command_text.to_string());
-                let prefix = extract_command_prefix(&command_parts);
+                let prefix = extract_command_prefix(command_parts);
                 always.push(format!("{} *", prefix));
             }
         }
@@ -308,9 +550,44 @@ pub fn parse_bash_command(command: &str, _cwd: &str) -> Result<BashParseResult,
         directories,
         patterns,
         always,
+        redirects,
+        wrappers,
     })
 }
 
+/// Extract a redirect from `file_redirect`, `heredoc_redirect` and
+/// `herestring_redirect` nodes.
+fn parse_redirect(node: tree_sitter::Node, source: &[u8]) -> Option<Redirect> {
+    let text = |n: tree_sitter::Node| n.utf8_text(source).unwrap_or("").to_string();
+    // The operator is the first anonymous child (`>`, `>>`, `<<`, ...)
+    let op = (0..node.child_count())
+        .filter_map(|i| node.child(i))
+        .find(|child| !child.is_named())
+        .map(|child| child.kind().to_string())?;
+
+    let target = match node.kind() {
+        "file_redirect" => node.child_by_field_name("destination").map(text),
+        "heredoc_redirect" => (0..node.child_count())
+            .filter_map(|i| node.child(i))
+            .find(|child| child.kind() == "heredoc_start")
+            .map(text),
+        "herestring_redirect" => (0..node.child_count())
+            .filter_map(|i| node.child(i))
+            .find(|child| child.is_named())
+            .map(text),
+        _ => None,
+    }?;
+
+    let fd = match node.child_by_field_name("descriptor") {
+        Some(descriptor) => text(descriptor).parse().ok(),
+        None if op.starts_with('&') => None,
+        None if op.starts_with('<') => Some(0),
+        None => Some(1),
+    };
+
+    Some(Redirect { fd, op, target })
+}
+
 fn walk_tree<F>(cursor: &mut tree_sitter::TreeCursor, _source: &[u8], callback: &mut F)
 where
     F: FnMut(tree_sitter::Node),
@@ -417,4 +694,171 @@ mod tests {
     fn test_empty_command() {
         assert!(parse_bash_command("", "/tmp").is_ok());
     }
+
+    #[test]
+    fn test_expand_env_defined_and_undefined() {
+        std::env::set_var("IRONCODE_SHELL_TEST_DIR", "/tmp/work");
+        std::env::remove_var("IRONCODE_SHELL_TEST_UNSET");
+
+        assert_eq!(
+            get_env("IRONCODE_SHELL_TEST_DIR").as_deref(),
+            Some("/tmp/work")
+        );
+        assert_eq!(get_env("IRONCODE_SHELL_TEST_UNSET"), None);
+
+        assert_eq!(
+            expand_env("$IRONCODE_SHELL_TEST_DIR/src", false),
+            "/tmp/work/src"
+        );
+        assert_eq!(expand_env("a$IRONCODE_SHELL_TEST_UNSET/b", false), "a/b");
+        assert_eq!(
+            expand_env("a$IRONCODE_SHELL_TEST_UNSET/b", true),
+            "a$IRONCODE_SHELL_TEST_UNSET/b"
+        );
+        assert_eq!(expand_env("cost: $5 and $", false), "cost: $5 and $");
+    }
+
+    #[test]
+    fn test_expand_env_braced() {
+        std::env::set_var("IRONCODE_SHELL_TEST_NAME", "app");
+
+        assert_eq!(
+            expand_env("${IRONCODE_SHELL_TEST_NAME}_v2", false),
+            "app_v2"
+        );
+        assert_eq!(expand_env("${IRONCODE_SHELL_TEST_MISSING}x", false), "x");
+        assert_eq!(
+            expand_env("${IRONCODE_SHELL_TEST_MISSING}x", true),
+            "${IRONCODE_SHELL_TEST_MISSING}x"
+        );
+        assert_eq!(expand_env("${unclosed", false), "${unclosed");
+        assert_eq!(expand_env("${bad-name}", false), "${bad-name}");
+    }
+
+    #[test]
+    fn test_split_quoted_args() {
+        assert_eq!(
+            split("git commit -m 'fix: handle \"quotes\"'").unwrap(),
+            vec!["git", "commit", "-m", "fix: handle \"quotes\""]
+        );
+        assert_eq!(
+            split(r#"echo "a \"b\" \$HOME \n" '' end"#).unwrap(),
+            vec!["echo", r#"a "b" $HOME \n"#, "", "end"]
+        );
+        assert_eq!(split("  ls   -la  # list").unwrap(), vec!["ls", "-la"]);
+        assert!(split("   ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_split_escapes_and_mixed_quoting() {
+        assert_eq!(
+            split(r"cat My\ Documents/notes\ \(1\).txt").unwrap(),
+            vec!["cat", "My Documents/notes (1).txt"]
+        );
+        assert_eq!(
+            split(r#"grep -e foo"bar baz"'qux' a#b"#).unwrap(),
+            vec!["grep", "-e", "foobar bazqux", "a#b"]
+        );
+        assert_eq!(
+            split("echo one \\\ntwo").unwrap(),
+            vec!["echo", "one", "two"]
+        );
+    }
+
+    #[test]
+    fn test_split_unterminated() {
+        assert!(split("echo 'oops").is_err());
+        assert!(split("echo \"oops").is_err());
+        assert!(split("echo oops\\").is_err());
+    }
+
+    #[test]
+    fn test_parse_expands_directory_args() {
+        std::env::set_var("IRONCODE_SHELL_TEST_TARGET", "/tmp/target");
+        let result =
+            parse_bash_command("mkdir $IRONCODE_SHELL_TEST_TARGET/out '$HOME'", "/tmp").unwrap();
+        assert_eq!(result.directories, vec!["/tmp/target/out", "'$HOME'"]);
+    }
+
+    #[test]
+    fn test_redirect_truncate_and_append() {
+        let r = parse_bash_command("echo hi > out.txt", "/tmp").unwrap();
+        assert_eq!(
+            r.redirects,
+            vec![Redirect {
+                fd: Some(1),
+                op: ">".to_string(),
+                target: "out.txt".to_string(),
+            }]
+        );
+        // The redirect target is not mistaken for a command argument
+        assert_eq!(r.patterns, vec!["echo hi > out.txt"]);
+
+        let r = parse_bash_command("make build >> build.log", "/tmp").unwrap();
+        assert_eq!(r.redirects.len(), 1);
+        assert_eq!(r.redirects[0].op, ">>");
+        assert_eq!(r.redirects[0].target, "build.log");
+    }
+
+    #[test]
+    fn test_redirect_fd_duplication() {
+        let r = parse_bash_command("cargo test > log.txt 2>&1", "/tmp").unwrap();
+        let dup = r.redirects.iter().find(|d| d.op == ">&").unwrap();
+        assert_eq!(dup.fd, Some(2));
+        assert_eq!(dup.target, "1");
+        assert!(r
+            .redirects
+            .iter()
+            .any(|d| d.op == ">" && d.target == "log.txt" && d.fd == Some(1)));
+    }
+
+    #[test]
+    fn test_redirect_input_and_heredoc() {
+        let r = parse_bash_command("sort < in.txt", "/tmp").unwrap();
+        assert_eq!(r.redirects[0].fd, Some(0));
+        assert_eq!(r.redirects[0].op, "<");
+
+        let r = parse_bash_command("cat <<EOF > f.txt\nbody\nEOF\n", "/tmp").unwrap();
+        let ops: Vec<(&str, &str)> = r
+            .redirects
+            .iter()
+            .map(|d| (d.op.as_str(), d.target.as_str()))
+            .collect();
+        assert!(ops.contains(&("<<", "EOF")));
+        assert!(ops.contains(&(">", "f.txt")));
+    }
+
+    #[test]
+    fn test_sudo_is_stripped() {
+        let r = parse_bash_command("sudo rm -rf x", "/tmp").unwrap();
+        assert_eq!(r.wrappers, vec!["sudo"]);
+        assert_eq!(r.patterns, vec!["rm -rf x"]);
+        assert_eq!(r.always, vec!["rm *"]);
+        assert_eq!(r.directories, vec!["x"]);
+
+        let r = parse_bash_command("sudo -u deploy nohup rm old.log", "/tmp").unwrap();
+        assert_eq!(r.wrappers, vec!["sudo", "nohup"]);
+        assert_eq!(r.patterns, vec!["rm old.log"]);
+    }
+
+    #[test]
+    fn test_env_assignments_are_stripped() {
+        let r = parse_bash_command("env FOO=1 git status", "/tmp").unwrap();
+        assert_eq!(r.wrappers, vec!["env"]);
+        assert_eq!(r.patterns, vec!["git status"]);
+        assert_eq!(r.always, vec!["git status *"]);
+    }
+
+    #[test]
+    fn test_time_is_stripped() {
+        let r = parse_bash_command("time npm run build", "/tmp").unwrap();
+        assert_eq!(r.wrappers, vec!["time"]);
+        assert_eq!(r.patterns, vec!["npm run build"]);
+        assert_eq!(r.always, vec!["npm run build *"]);
+
+        // A bare wrapper is left as the command itself
+        let r = parse_bash_command("sudo -l", "/tmp").unwrap();
+        assert!(r.wrappers.is_empty());
+        assert_eq!(r.patterns, vec!["sudo -l"]);
+    }
 }
@@ -0,0 +1,209 @@
+use crate::{indexer, read, vcs};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Serialize)]
+pub struct FileStat {
+    pub exists: bool,
+    pub size: u64,
+    /// Modification time in seconds since the Unix epoch
+    pub modified: u64,
+    pub is_file: bool,
+    pub is_dir: bool,
+}
+
+impl FileStat {
+    fn missing() -> Self {
+        Self {
+            exists: false,
+            size: 0,
+            modified: 0,
+            is_file: false,
+            is_dir: false,
+        }
+    }
+}
+
+/// Stat a single path. Missing or unreadable paths report `exists: false`.
+pub fn fs_stat(path: &str) -> FileStat {
+    match fs::metadata(path) {
+        Ok(meta) => {
+            let modified = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+
+            FileStat {
+                exists: true,
+                size: meta.len(),
+                modified,
+                is_file: meta.is_file(),
+                is_dir: meta.is_dir(),
+            }
+        }
+        Err(_) => FileStat::missing(),
+    }
+}
+
+/// Everything a file header shows, from one stat and one read of the file
+#[derive(Debug, Serialize)]
+pub struct FileInfo {
+    pub size: u64,
+    /// Modification time in milliseconds since the Unix epoch
+    pub modified_ms: u64,
+    pub is_dir: bool,
+    /// Language name as used by the indexer, e.g. "rust"; omitted if unknown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Line count as reported by `read::counts`; omitted for directories
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<u64>,
+    /// "staged", "untracked", "ignored", "modified" or "clean"; omitted outside
+    /// a repository
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_status: Option<&'static str>,
+}
+
+/// Stat, language, line count and git status of `path` in one call. A relative
+/// `path` is resolved against `cwd`. The file is read once; git status is
+/// looked up for this file only, in the repository containing `cwd`.
+pub fn file_info(path: &str, cwd: &str) -> Result<FileInfo, String> {
+    // Resolve once so the stat, the read and the git lookup see the same file
+    // whatever the process working directory is
+    let resolved = Path::new(cwd).join(path);
+    let resolved = fs::canonicalize(&resolved).unwrap_or(resolved);
+    let resolved_str = resolved.to_string_lossy();
+    let meta = fs::metadata(&resolved).map_err(|e| format!("Failed to stat {}: {}", path, e))?;
+    let modified_ms = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+
+    let (language, lines) = if meta.is_dir() {
+        (None, None)
+    } else {
+        read::with_file_bytes(&resolved_str, |bytes| {
+            let file = Path::new(path);
+            let language = indexer::detect_language(file)
+                .or_else(|| indexer::detect_language_from_content(file, bytes));
+            (language, read::counts_bytes(bytes).lines)
+        })
+        .map(|(language, lines)| (language.map(indexer::language_name), Some(lines)))?
+    };
+
+    let git_status = if meta.is_dir() {
+        None
+    } else {
+        match vcs::file_status(cwd, &resolved_str) {
+            Ok(Some(status)) => Some(status.badge()),
+            Ok(None) => Some("clean"),
+            Err(_) => None,
+        }
+    };
+
+    Ok(FileInfo {
+        size: meta.len(),
+        modified_ms,
+        is_dir: meta.is_dir(),
+        language: language.map(str::to_string),
+        lines,
+        git_status,
+    })
+}
+
+/// Stat many paths in parallel. The output is in the same order as `paths`.
+pub fn fs_stat_many(paths: &[String]) -> Vec<FileStat> {
+    paths.par_iter().map(|path| fs_stat(path)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{temp_dir, temp_path};
+
+    #[test]
+    fn test_stat_many_preserves_order() {
+        let dir = temp_dir("stat");
+        let file = dir.join("a.txt");
+        fs::write(&file, "hello").unwrap();
+
+        let paths = vec![
+            dir.join("missing.txt").to_string_lossy().to_string(),
+            file.to_string_lossy().to_string(),
+            dir.to_string_lossy().to_string(),
+        ];
+        let stats = fs_stat_many(&paths);
+
+        assert_eq!(stats.len(), 3);
+        assert!(!stats[0].exists);
+        assert_eq!(stats[0].size, 0);
+        assert!(stats[1].exists && stats[1].is_file);
+        assert_eq!(stats[1].size, 5);
+        assert!(stats[1].modified > 0);
+        assert!(stats[2].exists && stats[2].is_dir);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_file_info_matches_individual_calls() {
+        let dir = temp_dir("stat_info");
+        let cwd = dir.to_str().unwrap();
+        vcs::init(cwd, false, true).unwrap();
+        let file = dir.join("main.rs");
+        fs::write(&file, "fn main() {\n    run();\n}\n").unwrap();
+        let path = file.to_str().unwrap();
+
+        let info = file_info(path, cwd).unwrap();
+        let stat = fs_stat(path);
+        assert_eq!(info.size, stat.size);
+        assert_eq!(info.modified_ms / 1000, stat.modified);
+        assert!(!info.is_dir);
+        assert_eq!(info.language.as_deref(), Some("rust"));
+        assert_eq!(info.lines, Some(read::counts(path).unwrap().lines));
+        let status = vcs::get_status_detailed(cwd).unwrap();
+        let listed = status.files.iter().find(|f| f.path == "main.rs").unwrap();
+        assert_eq!(info.git_status, Some(listed.badge()));
+        assert_eq!(info.git_status, Some("untracked"));
+
+        vcs::stage_files(cwd, vec![]).unwrap();
+        vcs::commit(cwd, "add main").unwrap();
+        assert_eq!(file_info(path, cwd).unwrap().git_status, Some("clean"));
+
+        // Relative paths resolve against `cwd`, not the process directory
+        let relative = file_info("main.rs", cwd).unwrap();
+        assert_eq!(relative.size, info.size);
+        assert_eq!(relative.git_status, Some("clean"));
+
+        fs::write(dir.join(".gitignore"), "*.log\n").unwrap();
+        fs::write(dir.join("debug.log"), "trace\n").unwrap();
+        let ignored = file_info("debug.log", cwd).unwrap();
+        assert_eq!(ignored.git_status, Some("ignored"));
+
+        // A non-canonical absolute path still maps into the repository
+        #[cfg(unix)]
+        {
+            let link = temp_path("stat_link");
+            std::os::unix::fs::symlink(&dir, &link).unwrap();
+            let via_link = link.join("main.rs");
+            let linked = file_info(via_link.to_str().unwrap(), link.to_str().unwrap()).unwrap();
+            assert_eq!(linked.git_status, Some("clean"));
+            let linked = file_info(via_link.to_str().unwrap(), cwd).unwrap();
+            assert_eq!(linked.git_status, Some("clean"));
+            fs::remove_file(&link).ok();
+        }
+
+        let outside = file_info(cwd, &std::env::temp_dir().to_string_lossy()).unwrap();
+        assert!(outside.is_dir);
+        assert_eq!(outside.lines, None);
+        assert_eq!(outside.git_status, None);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
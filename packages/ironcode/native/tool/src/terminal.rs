@@ -1,6 +1,7 @@
 use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
 use std::io::{Read, Write};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
@@ -9,6 +10,14 @@ use std::time::{Duration, Instant};
 const BUFFER_LIMIT: usize = 1024 * 1024 * 2; // 2MB
 const BUFFER_CHUNK: usize = 64 * 1024; // 64KB
 const READ_CHUNK: usize = 4096; // 4KB read chunks
+const CLEAR_SEQUENCE: &[u8] = b"\x1b[3J\x1b[H\x1b[2J"; // scrollback, home, screen
+const MAX_SEARCH_MATCHES: usize = 1000;
+const OSC7_PREFIX: &[u8] = b"\x1b]7;";
+const MAX_PENDING_OSC: usize = 4096; // give up on an unterminated OSC 7 past this
+const RUN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+// Size used by `create` when the host passes 0 (PTYs misbehave at 0x0)
+const DEFAULT_ROWS: u16 = 24;
+const DEFAULT_COLS: u16 = 80;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -21,11 +30,15 @@ pub enum ProcessStatus {
 pub struct TerminalInfo {
     pub id: String,
     pub pid: u32,
+    /// Starting directory, then kept current from the shell's OSC 7 reports
     pub cwd: String,
     pub status: ProcessStatus,
     pub title: String,
     pub command: String,
     pub args: Vec<String>,
+    /// Set once the process has exited and been reaped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<u32>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -34,6 +47,14 @@ pub struct TerminalOutput {
     pub buffered_size: usize,
 }
 
+/// Output captured by `run`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RunOutput {
+    pub output: String,
+    /// Output was still arriving when `timeout_ms` elapsed
+    pub timed_out: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BufferInfo {
     pub size: usize,
@@ -41,6 +62,17 @@ pub struct BufferInfo {
     pub chunks: usize,
 }
 
+/// A scrollback search hit. `line`/`column` are 1-based positions in the
+/// ANSI-stripped text (column counts chars); `offset`/`length` locate the
+/// match in the raw buffer, including any escape sequences inside it.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct BufferMatch {
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+    pub length: usize,
+}
+
 // Ring buffer for efficient terminal output storage
 struct RingBuffer {
     data: VecDeque<u8>,
@@ -86,17 +118,142 @@ impl RingBuffer {
     }
 }
 
+/// Tracks OSC 7 (`ESC ] 7 ; file://host/path BEL`) cwd reports in PTY output.
+/// A sequence split across reads is held in `pending` until it completes.
+#[derive(Default)]
+struct Osc7Scanner {
+    pending: Vec<u8>,
+}
+
+impl Osc7Scanner {
+    /// Feed newly read bytes; returns the last complete cwd reported, if any.
+    fn feed(&mut self, bytes: &[u8]) -> Option<String> {
+        let mut data = std::mem::take(&mut self.pending);
+        data.extend_from_slice(bytes);
+
+        let mut cwd = None;
+        let mut pos = 0;
+        while let Some(found) = find_bytes(&data[pos..], OSC7_PREFIX) {
+            let start = pos + found;
+            let payload_start = start + OSC7_PREFIX.len();
+            let end = data[payload_start..]
+                .iter()
+                .position(|&b| b == 0x07 || b == 0x1b)
+                .map(|i| payload_start + i);
+            match end {
+                // ESC must be followed by `\` to terminate; wait for the next byte
+                Some(end) if data[end] == 0x1b && end + 1 >= data.len() => {
+                    pos = start;
+                    break;
+                }
+                Some(end) => {
+                    if let Some(path) = parse_file_uri(&data[payload_start..end]) {
+                        cwd = Some(path);
+                    }
+                    pos = if data[end] == 0x1b { end + 2 } else { end + 1 };
+                }
+                None => {
+                    pos = start;
+                    break;
+                }
+            }
+        }
+
+        // Keep an unterminated sequence, or a tail that could start one
+        let rest = &data[pos.min(data.len())..];
+        if rest.starts_with(OSC7_PREFIX) {
+            if rest.len() <= MAX_PENDING_OSC {
+                self.pending = rest.to_vec();
+            }
+        } else {
+            let keep = (1..OSC7_PREFIX.len())
+                .rev()
+                .find(|&n| rest.ends_with(&OSC7_PREFIX[..n]))
+                .unwrap_or(0);
+            self.pending = rest[rest.len() - keep..].to_vec();
+        }
+        cwd
+    }
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Extract the percent-decoded path from `file://host/path`.
+fn parse_file_uri(uri: &[u8]) -> Option<String> {
+    let rest = uri.strip_prefix(b"file://")?;
+    let path = &rest[rest.iter().position(|&b| b == b'/')?..];
+
+    let mut decoded = Vec::with_capacity(path.len());
+    let mut i = 0;
+    while i < path.len() {
+        let hex = path
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (path[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    Some(String::from_utf8_lossy(&decoded).into_owned())
+}
+
 pub struct TerminalSession {
     master: Box<dyn MasterPty + Send>,
-    #[allow(dead_code)]
     child: Box<dyn Child + Send + Sync>,
     reader: Arc<Mutex<Box<dyn Read + Send>>>,
     writer: Arc<Mutex<Box<dyn Write + Send>>>,
     buffer: Arc<Mutex<RingBuffer>>,
     info: Arc<Mutex<TerminalInfo>>,
     last_read: Arc<Mutex<Instant>>,
+    /// Transcript file that receives every byte read from the PTY
+    recording: Arc<Mutex<Option<File>>>,
+    /// Why the recording stopped on its own; returned by the next `stop_recording`
+    recording_error: Arc<Mutex<Option<String>>>,
+    /// Picks up shell-reported working directory changes (OSC 7)
+    osc7: Arc<Mutex<Osc7Scanner>>,
+    /// PTY master. Non-blocking for the session's whole life (see
+    /// `ensure_nonblocking`); the cloned reader shares its file status flags.
     #[cfg(unix)]
     reader_fd: std::os::unix::io::RawFd,
+    /// `F_GETFL` flags of the PTY master before the session changed them,
+    /// restored by `close`
+    #[cfg(unix)]
+    original_flags: i32,
+}
+
+/// Put `fd` in non-blocking mode unless it already is, returning the flags it
+/// had. Sessions are always non-blocking so `read` can drain whatever is
+/// available and return; the flag lives on the open file description, so it
+/// also covers the reader cloned from the master.
+#[cfg(unix)]
+fn ensure_nonblocking(fd: std::os::unix::io::RawFd) -> Result<i32, String> {
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL, 0) };
+    if flags < 0 {
+        return Err(format!(
+            "Failed to get PTY flags: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    if flags & libc::O_NONBLOCK == 0
+        && unsafe { libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) } < 0
+    {
+        return Err(format!(
+            "Failed to set PTY non-blocking: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(flags)
 }
 
 lazy_static::lazy_static! {
@@ -116,8 +273,8 @@ pub fn create(
 
     let pair = pty_system
         .openpty(PtySize {
-            rows,
-            cols,
+            rows: if rows == 0 { DEFAULT_ROWS } else { rows },
+            cols: if cols == 0 { DEFAULT_COLS } else { cols },
             pixel_width: 0,
             pixel_height: 0,
         })
@@ -133,14 +290,15 @@ pub fn create(
         cmd.arg(arg);
     }
 
-    let child = pair
-        .slave
-        .spawn_command(cmd)
-        .map_err(|e| format!("Failed to spawn shell: {}", e))?;
-
-    let pid = child
-        .process_id()
-        .ok_or_else(|| "Failed to get process ID".to_string())?;
+    // Everything that can fail on the master happens before the spawn, so an
+    // error never leaves a child running without a session
+    #[cfg(unix)]
+    let reader_fd = pair
+        .master
+        .as_raw_fd()
+        .ok_or_else(|| "Failed to get PTY file descriptor".to_string())?;
+    #[cfg(unix)]
+    let original_flags = ensure_nonblocking(reader_fd)?;
 
     let reader = pair
         .master
@@ -151,8 +309,19 @@ pub fn create(
         .take_writer()
         .map_err(|e| format!("Failed to take writer: {}", e))?;
 
-    #[cfg(unix)]
-    let reader_fd = { pair.master.as_raw_fd().expect("Failed to get raw FD") };
+    let mut child = pair
+        .slave
+        .spawn_command(cmd)
+        .map_err(|e| format!("Failed to spawn shell: {}", e))?;
+
+    let pid = match child.process_id() {
+        Some(pid) => pid,
+        None => {
+            child.kill().ok();
+            child.wait().ok();
+            return Err("Failed to get process ID".to_string());
+        }
+    };
 
     let info = TerminalInfo {
         id: id.to_string(),
@@ -164,6 +333,7 @@ pub fn create(
             .to_string(),
         command: shell.clone(),
         args: args.clone(),
+        exit_code: None,
     };
 
     let session = TerminalSession {
@@ -174,8 +344,13 @@ pub fn create(
         buffer: Arc::new(Mutex::new(RingBuffer::new(BUFFER_LIMIT))),
         info: Arc::new(Mutex::new(info.clone())),
         last_read: Arc::new(Mutex::new(Instant::now())),
+        recording: Arc::new(Mutex::new(None)),
+        recording_error: Arc::new(Mutex::new(None)),
+        osc7: Arc::new(Mutex::new(Osc7Scanner::default())),
         #[cfg(unix)]
         reader_fd,
+        #[cfg(unix)]
+        original_flags,
     };
 
     let mut sessions = SESSIONS.lock().unwrap();
@@ -202,24 +377,19 @@ pub fn write(id: &str, data: &str) -> Result<(), String> {
 }
 
 pub fn read(id: &str) -> Result<TerminalOutput, String> {
-    let sessions = SESSIONS.lock().unwrap();
+    let mut sessions = SESSIONS.lock().unwrap();
     let session = sessions
-        .get(id)
+        .get_mut(id)
         .ok_or_else(|| format!("Session {} not found", id))?;
 
-    // Set non-blocking mode on the file descriptor
+    // Set at creation; only re-applied if something cleared it since
     #[cfg(unix)]
-    {
-        let fd = session.reader_fd;
-        unsafe {
-            let flags = libc::fcntl(fd, libc::F_GETFL, 0);
-            libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK);
-        }
-    }
+    ensure_nonblocking(session.reader_fd)?;
 
     let mut reader = session.reader.lock().unwrap();
     let mut temp_buffer = [0u8; READ_CHUNK];
     let mut total_read = Vec::new();
+    let mut eof = false;
 
     // Read all available data in chunks
     loop {
@@ -229,11 +399,22 @@ pub fn read(id: &str) -> Result<TerminalOutput, String> {
                 // Update last read time
                 *session.last_read.lock().unwrap() = Instant::now();
             }
-            Ok(_) => break, // EOF or no more data
+            // Non-blocking reads report "no data" as WouldBlock, so 0 is a real EOF
+            Ok(_) => {
+                eof = true;
+                break;
+            }
             Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+            // Linux reports a closed slave side as EIO rather than EOF
+            #[cfg(unix)]
+            Err(e) if e.raw_os_error() == Some(libc::EIO) => {
+                eof = true;
+                break;
+            }
             Err(e) => return Err(format!("Failed to read from PTY: {}", e)),
         }
     }
+    drop(reader);
 
     // If we read new data, add it to buffer
     if !total_read.is_empty() {
@@ -241,8 +422,31 @@ pub fn read(id: &str) -> Result<TerminalOutput, String> {
         buffer.push(&total_read);
     }
 
-    let buffer = session.buffer.lock().unwrap();
-    let buffered_size = buffer.len();
+    // Follow `cd` in the shell via OSC 7 working-directory reports
+    if !total_read.is_empty() {
+        if let Some(cwd) = session.osc7.lock().unwrap().feed(&total_read) {
+            session.info.lock().unwrap().cwd = cwd;
+        }
+    }
+
+    // Tee raw bytes to the transcript. A failed write stops the recording; the
+    // output is still returned and the failure surfaces from `stop_recording`.
+    if !total_read.is_empty() {
+        let mut recording = session.recording.lock().unwrap();
+        if let Some(file) = recording.as_mut() {
+            if let Err(e) = file.write_all(&total_read) {
+                *recording = None;
+                *session.recording_error.lock().unwrap() =
+                    Some(format!("Failed to write recording: {}", e));
+            }
+        }
+    }
+
+    if eof {
+        record_exit(session);
+    }
+
+    let buffered_size = session.buffer.lock().unwrap().len();
 
     Ok(TerminalOutput {
         data: total_read,
@@ -250,12 +454,96 @@ pub fn read(id: &str) -> Result<TerminalOutput, String> {
     })
 }
 
+/// Mark the session exited, with its exit code once the child can be reaped
+fn record_exit(session: &mut TerminalSession) {
+    let exit_code = match session.child.try_wait() {
+        Ok(Some(status)) => Some(status.exit_code()),
+        _ => None,
+    };
+    let mut info = session.info.lock().unwrap();
+    info.status = ProcessStatus::Exited;
+    if exit_code.is_some() {
+        info.exit_code = exit_code;
+    }
+}
+
+/// Write `command` plus a newline, then read until the session has been
+/// quiet for `idle_ms`, `timeout_ms` has passed, or the shell exits. Returns
+/// everything read, including the echoed command line, with ANSI escapes
+/// removed when `strip` is set. Output still goes to the scrollback buffer.
+pub fn run(
+    id: &str,
+    command: &str,
+    idle_ms: u64,
+    timeout_ms: u64,
+    strip: bool,
+) -> Result<RunOutput, String> {
+    write(id, &format!("{}\n", command))?;
+
+    let start = Instant::now();
+    let idle = Duration::from_millis(idle_ms);
+    let timeout = Duration::from_millis(timeout_ms);
+    let mut last_output = start;
+    let mut captured = Vec::new();
+    let mut timed_out = false;
+    loop {
+        let chunk = read(id)?;
+        let now = Instant::now();
+        if !chunk.data.is_empty() {
+            captured.extend_from_slice(&chunk.data);
+            last_output = now;
+        } else if check_status(id)? == ProcessStatus::Exited || now - last_output >= idle {
+            break;
+        }
+        if now - start >= timeout {
+            timed_out = true;
+            break;
+        }
+        std::thread::sleep(RUN_POLL_INTERVAL);
+    }
+
+    if strip {
+        captured = strip_ansi(&captured).0;
+    }
+    Ok(RunOutput {
+        output: String::from_utf8_lossy(&captured).into_owned(),
+        timed_out,
+    })
+}
+
+/// Resize one session. A 0 dimension is rejected.
 pub fn resize(id: &str, rows: u16, cols: u16) -> Result<(), String> {
+    check_size(rows, cols)?;
     let sessions = SESSIONS.lock().unwrap();
     let session = sessions
         .get(id)
         .ok_or_else(|| format!("Session {} not found", id))?;
 
+    resize_session(session, rows, cols)
+}
+
+/// Resize every session, e.g. when the host window changes size. Returns the
+/// ids whose PTY couldn't be resized; a 0 dimension is rejected outright.
+pub fn resize_all(rows: u16, cols: u16) -> Result<Vec<String>, String> {
+    check_size(rows, cols)?;
+    let sessions = SESSIONS.lock().unwrap();
+    let mut failed: Vec<String> = sessions
+        .iter()
+        .filter(|(_, session)| resize_session(session, rows, cols).is_err())
+        .map(|(id, _)| id.clone())
+        .collect();
+    failed.sort();
+    Ok(failed)
+}
+
+fn check_size(rows: u16, cols: u16) -> Result<(), String> {
+    if rows == 0 || cols == 0 {
+        return Err(format!("Invalid terminal size {}x{}", cols, rows));
+    }
+    Ok(())
+}
+
+fn resize_session(session: &TerminalSession, rows: u16, cols: u16) -> Result<(), String> {
     session
         .master
         .resize(PtySize {
@@ -264,9 +552,7 @@ pub fn resize(id: &str, rows: u16, cols: u16) -> Result<(), String> {
             pixel_width: 0,
             pixel_height: 0,
         })
-        .map_err(|e| format!("Failed to resize PTY: {}", e))?;
-
-    Ok(())
+        .map_err(|e| format!("Failed to resize PTY: {}", e))
 }
 
 pub fn close(id: &str) -> Result<(), String> {
@@ -275,10 +561,54 @@ pub fn close(id: &str) -> Result<(), String> {
         .remove(id)
         .ok_or_else(|| format!("Session {} not found", id))?;
 
+    // The open file description can outlive the master (a descriptor inherited
+    // elsewhere), so hand it back with the flags it came with
+    #[cfg(unix)]
+    unsafe {
+        libc::fcntl(session.reader_fd, libc::F_SETFL, session.original_flags)
+    };
     drop(session);
     Ok(())
 }
 
+// Start recording raw PTY output to a file (appends if it already exists)
+pub fn start_recording(id: &str, file_path: &str) -> Result<(), String> {
+    let sessions = SESSIONS.lock().unwrap();
+    let session = sessions
+        .get(id)
+        .ok_or_else(|| format!("Session {} not found", id))?;
+
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file_path)
+        .map_err(|e| format!("Failed to open recording file {}: {}", file_path, e))?;
+
+    *session.recording.lock().unwrap() = Some(file);
+    *session.recording_error.lock().unwrap() = None;
+    Ok(())
+}
+
+// Stop recording and flush the transcript file. If a write failed while
+// recording, the recording already stopped and that failure is returned.
+pub fn stop_recording(id: &str) -> Result<(), String> {
+    let sessions = SESSIONS.lock().unwrap();
+    let session = sessions
+        .get(id)
+        .ok_or_else(|| format!("Session {} not found", id))?;
+
+    let mut recording = session.recording.lock().unwrap();
+    match recording.take() {
+        Some(mut file) => file
+            .flush()
+            .map_err(|e| format!("Failed to flush recording: {}", e)),
+        None => match session.recording_error.lock().unwrap().take() {
+            Some(error) => Err(error),
+            None => Err(format!("Session {} is not recording", id)),
+        },
+    }
+}
+
 // Get terminal info (including status)
 pub fn get_info(id: &str) -> Result<TerminalInfo, String> {
     let sessions = SESSIONS.lock().unwrap();
@@ -290,6 +620,81 @@ pub fn get_info(id: &str) -> Result<TerminalInfo, String> {
     Ok(info.clone())
 }
 
+// Pid of the foreground job (e.g. the running command rather than the shell).
+// On Unix this is the tty's foreground process group leader via tcgetpgrp.
+// Falls back to the shell pid when there is no distinct foreground job; on
+// Windows the child (shell) pid is always returned.
+pub fn foreground_pid(id: &str) -> Result<u32, String> {
+    let sessions = SESSIONS.lock().unwrap();
+    let session = sessions
+        .get(id)
+        .ok_or_else(|| format!("Session {} not found", id))?;
+
+    let shell_pid = session.info.lock().unwrap().pid;
+
+    #[cfg(unix)]
+    {
+        let pgrp = unsafe { libc::tcgetpgrp(session.reader_fd) };
+        if pgrp > 0 {
+            return Ok(pgrp as u32);
+        }
+    }
+
+    Ok(shell_pid)
+}
+
+// Switch the PTY line discipline between raw and cooked mode via tcsetattr.
+// Raw clears ICANON, ECHO, ISIG and IEXTEN (c_lflag), ICRNL and IXON
+// (c_iflag) and OPOST (c_oflag), and sets VMIN=1/VTIME=0 so reads return
+// per byte. Cooked sets those same flags back; other termios settings are
+// left alone. Not supported on Windows, where ConPTY owns the line discipline.
+pub fn set_mode(id: &str, raw: bool) -> Result<(), String> {
+    let sessions = SESSIONS.lock().unwrap();
+    let session = sessions
+        .get(id)
+        .ok_or_else(|| format!("Session {} not found", id))?;
+
+    #[cfg(unix)]
+    {
+        let fd = session.reader_fd;
+        let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(fd, &mut termios) } != 0 {
+            return Err(format!(
+                "Failed to get terminal mode: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+
+        let lflags = libc::ICANON | libc::ECHO | libc::ISIG | libc::IEXTEN;
+        let iflags = libc::ICRNL | libc::IXON;
+        if raw {
+            termios.c_lflag &= !lflags;
+            termios.c_iflag &= !iflags;
+            termios.c_oflag &= !libc::OPOST;
+            termios.c_cc[libc::VMIN] = 1;
+            termios.c_cc[libc::VTIME] = 0;
+        } else {
+            termios.c_lflag |= lflags;
+            termios.c_iflag |= iflags;
+            termios.c_oflag |= libc::OPOST;
+        }
+
+        if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &termios) } != 0 {
+            return Err(format!(
+                "Failed to set terminal mode: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = (session, raw);
+        Err("Setting the terminal mode is unsupported on this platform".to_string())
+    }
+}
+
 // Update terminal title
 pub fn update_title(id: &str, title: &str) -> Result<(), String> {
     let sessions = SESSIONS.lock().unwrap();
@@ -304,19 +709,26 @@ pub fn update_title(id: &str, title: &str) -> Result<(), String> {
 
 // Check if process has exited and update status
 pub fn check_status(id: &str) -> Result<ProcessStatus, String> {
-    let sessions = SESSIONS.lock().unwrap();
+    let mut sessions = SESSIONS.lock().unwrap();
     let session = sessions
-        .get(id)
+        .get_mut(id)
         .ok_or_else(|| format!("Session {} not found", id))?;
 
-    // Try to get exit status from child process
-    let info = session.info.lock().unwrap();
-
-    // Note: portable-pty doesn't provide direct exit status check
-    // We rely on read() returning EOF when process exits
-    // TypeScript layer should call this periodically or on read EOF
+    // read() marks the session exited on EOF; also catch a child that has
+    // exited but whose output has not been read yet, and pick up the exit
+    // code if it was not available at EOF
+    let needs_reap = {
+        let info = session.info.lock().unwrap();
+        info.status == ProcessStatus::Running || info.exit_code.is_none()
+    };
+    if needs_reap {
+        if let Ok(Some(_)) = session.child.try_wait() {
+            record_exit(session);
+        }
+    }
 
-    Ok(info.status.clone())
+    let status = session.info.lock().unwrap().status.clone();
+    Ok(status)
 }
 
 // Mark session as exited (called from TypeScript when detecting EOF)
@@ -383,6 +795,127 @@ pub fn clear_buffer(id: &str) -> Result<(), String> {
     Ok(())
 }
 
+// Reset the emulator (scrollback, cursor, screen) and drop buffered output so
+// reconnecting clients don't replay what was cleared
+pub fn clear_screen(id: &str) -> Result<(), String> {
+    let sessions = SESSIONS.lock().unwrap();
+    let session = sessions
+        .get(id)
+        .ok_or_else(|| format!("Session {} not found", id))?;
+
+    let mut writer = session.writer.lock().unwrap();
+    writer
+        .write_all(CLEAR_SEQUENCE)
+        .map_err(|e| format!("Failed to write to PTY: {}", e))?;
+    writer
+        .flush()
+        .map_err(|e| format!("Failed to flush PTY: {}", e))?;
+
+    session.buffer.lock().unwrap().clear();
+    Ok(())
+}
+
+// Search the scrollback with ANSI escapes and carriage returns stripped
+pub fn search_buffer(
+    id: &str,
+    query: &str,
+    case_insensitive: bool,
+) -> Result<Vec<BufferMatch>, String> {
+    let raw = get_buffer(id)?;
+    search_bytes(&raw, query, case_insensitive)
+}
+
+fn search_bytes(
+    raw: &[u8],
+    query: &str,
+    case_insensitive: bool,
+) -> Result<Vec<BufferMatch>, String> {
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    let regex = regex::bytes::RegexBuilder::new(&regex::escape(query))
+        .case_insensitive(case_insensitive)
+        .build()
+        .map_err(|e| format!("Invalid query: {}", e))?;
+
+    let (text, raw_offsets) = strip_ansi(raw);
+    let mut matches = Vec::new();
+    let mut line = 1;
+    let mut line_start = 0;
+    let mut scanned = 0;
+
+    for m in regex.find_iter(&text).take(MAX_SEARCH_MATCHES) {
+        for (i, &byte) in text[scanned..m.start()].iter().enumerate() {
+            if byte == b'\n' {
+                line += 1;
+                line_start = scanned + i + 1;
+            }
+        }
+        scanned = m.start();
+
+        let column = text[line_start..m.start()]
+            .iter()
+            .filter(|&&b| b & 0xC0 != 0x80)
+            .count()
+            + 1;
+        let offset = raw_offsets[m.start()];
+        let length = raw_offsets[m.end() - 1] + 1 - offset;
+        matches.push(BufferMatch {
+            line,
+            column,
+            offset,
+            length,
+        });
+    }
+    Ok(matches)
+}
+
+/// Strip ANSI escape sequences (CSI, OSC, two-byte escapes) and `\r`.
+/// Returns the text plus, for each of its bytes, the offset in `raw`.
+fn strip_ansi(raw: &[u8]) -> (Vec<u8>, Vec<usize>) {
+    let mut text = Vec::with_capacity(raw.len());
+    let mut offsets = Vec::with_capacity(raw.len());
+    let mut i = 0;
+
+    while i < raw.len() {
+        match raw[i] {
+            0x1b => {
+                i += 1;
+                match raw.get(i) {
+                    // CSI: parameters then a final byte in 0x40..=0x7e
+                    Some(b'[') => {
+                        i += 1;
+                        while i < raw.len() && !(0x40..=0x7e).contains(&raw[i]) {
+                            i += 1;
+                        }
+                        i += 1;
+                    }
+                    // OSC: terminated by BEL or ESC \
+                    Some(b']') => {
+                        i += 1;
+                        while i < raw.len() && raw[i] != 0x07 && raw[i] != 0x1b {
+                            i += 1;
+                        }
+                        if raw.get(i) == Some(&0x1b) {
+                            i += 1;
+                        }
+                        i += 1;
+                    }
+                    Some(_) => i += 1,
+                    None => {}
+                }
+            }
+            b'\r' => i += 1,
+            byte => {
+                text.push(byte);
+                offsets.push(i);
+                i += 1;
+            }
+        }
+    }
+    (text, offsets)
+}
+
 // Get buffer info (size, limit, etc.)
 pub fn get_buffer_info(id: &str) -> Result<BufferInfo, String> {
     let sessions = SESSIONS.lock().unwrap();
@@ -447,6 +980,7 @@ fn get_shell() -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::temp_path;
 
     #[test]
     fn test_ring_buffer_basic() {
@@ -550,4 +1084,378 @@ mod tests {
 
         close(id).unwrap();
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_terminal_foreground_pid() {
+        let id = "test-terminal-6";
+        let info = create(id, Some("/bin/sh"), vec![], Some("."), None, 24, 80).unwrap();
+
+        // An idle shell is its own foreground job
+        assert_eq!(foreground_pid(id).unwrap(), info.pid);
+        assert!(foreground_pid("missing-terminal").is_err());
+
+        close(id).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_terminal_eof_marks_exited() {
+        let id = "test-terminal-11";
+        create(id, Some("/bin/sh"), vec![], Some("."), None, 24, 80).unwrap();
+        write(id, "exit 3\n").unwrap();
+
+        let deadline = Instant::now() + std::time::Duration::from_secs(5);
+        while get_info(id).unwrap().status == ProcessStatus::Running {
+            assert!(Instant::now() < deadline, "session never reached EOF");
+            read(id).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+
+        // No mark_exited call: EOF alone updates the status
+        assert_eq!(check_status(id).unwrap(), ProcessStatus::Exited);
+        assert_eq!(get_info(id).unwrap().exit_code, Some(3));
+
+        close(id).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_terminal_run_captures_output() {
+        let id = "test-terminal-12";
+        create(id, Some("/bin/sh"), vec![], Some("."), None, 24, 80).unwrap();
+
+        // Split by quotes so only the command's output, not the echoed input, matches
+        let result = run(id, "echo hel''lo", 300, 5000, true).unwrap();
+        assert!(result.output.contains("hello"), "{:?}", result.output);
+        assert!(!result.output.contains('\x1b'));
+        assert!(!result.timed_out);
+        assert!(run("missing-terminal", "echo hi", 100, 1000, true).is_err());
+
+        close(id).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_terminal_read_stays_nonblocking() {
+        let id = "test-terminal-13";
+        create(
+            id,
+            Some("/bin/sh"),
+            vec!["-c".to_string(), "sleep 2".to_string()],
+            Some("."),
+            None,
+            24,
+            80,
+        )
+        .unwrap();
+        let fd = SESSIONS.lock().unwrap()[id].reader_fd;
+        let flags = || unsafe { libc::fcntl(fd, libc::F_GETFL, 0) };
+        let before = flags();
+        assert_ne!(before & libc::O_NONBLOCK, 0, "non-blocking from creation");
+
+        // Nothing to read: both calls return straight away with the flags untouched
+        for _ in 0..2 {
+            let start = Instant::now();
+            assert!(read(id).unwrap().data.is_empty());
+            assert!(start.elapsed() < Duration::from_secs(1));
+            assert_eq!(flags(), before);
+        }
+
+        // A cleared flag is restored by the next read instead of blocking it
+        unsafe { libc::fcntl(fd, libc::F_SETFL, before & !libc::O_NONBLOCK) };
+        read(id).unwrap();
+        assert_eq!(flags(), before);
+
+        // Closing restores the flags saved at creation on the shared description
+        let original = SESSIONS.lock().unwrap()[id].original_flags;
+        assert_eq!(original & libc::O_NONBLOCK, 0);
+        let dup = unsafe { libc::dup(fd) };
+        close(id).unwrap();
+        assert_eq!(unsafe { libc::fcntl(dup, libc::F_GETFL, 0) }, original);
+        unsafe { libc::close(dup) };
+    }
+
+    #[test]
+    fn test_terminal_resize_all() {
+        let ids = ["test-terminal-14", "test-terminal-15"];
+        for id in ids {
+            create(id, None, vec![], Some("."), None, 0, 0).unwrap();
+        }
+        let size = |id: &str| {
+            let size = SESSIONS.lock().unwrap()[id].master.get_size().unwrap();
+            (size.rows, size.cols)
+        };
+        assert_eq!(size(ids[0]), (DEFAULT_ROWS, DEFAULT_COLS));
+
+        // Other tests may have sessions open too; none of ours may fail
+        let failed = resize_all(40, 120).unwrap();
+        for id in ids {
+            assert!(!failed.iter().any(|f| f == id));
+            assert_eq!(size(id), (40, 120));
+        }
+
+        assert!(resize_all(0, 120).is_err());
+        assert!(resize(ids[0], 40, 0).is_err());
+        assert_eq!(size(ids[0]), (40, 120));
+
+        for id in ids {
+            close(id).unwrap();
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_terminal_set_mode() {
+        let id = "test-terminal-10";
+        create(
+            id,
+            Some("/bin/sh"),
+            vec![
+                "-c".to_string(),
+                "read line; echo \"got:$line\"; sleep 2".to_string(),
+            ],
+            Some("."),
+            None,
+            24,
+            80,
+        )
+        .unwrap();
+
+        let lflag = || {
+            let sessions = SESSIONS.lock().unwrap();
+            let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+            unsafe { libc::tcgetattr(sessions[id].reader_fd, &mut termios) };
+            termios.c_lflag
+        };
+
+        set_mode(id, true).unwrap();
+        assert_eq!(lflag() & libc::ICANON, 0);
+        set_mode(id, false).unwrap();
+        assert_ne!(lflag() & libc::ICANON, 0);
+        assert!(set_mode("missing-terminal", true).is_err());
+
+        write(id, "hello\n").unwrap();
+        let output = || String::from_utf8_lossy(&get_buffer(id).unwrap()).into_owned();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline && !output().contains("got:hello") {
+            let _ = read(id);
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert!(output().contains("got:hello"), "output: {:?}", output());
+
+        close(id).unwrap();
+    }
+
+    #[test]
+    fn test_terminal_clear_screen() {
+        let id = "test-terminal-7";
+        create(
+            id,
+            Some("/bin/sh"),
+            vec!["-c".to_string(), "echo before-clear; sleep 2".to_string()],
+            Some("."),
+            None,
+            24,
+            80,
+        )
+        .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline && get_buffer_info(id).unwrap().size == 0 {
+            let _ = read(id);
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert!(get_buffer_info(id).unwrap().size > 0);
+
+        clear_screen(id).unwrap();
+        assert_eq!(get_buffer_info(id).unwrap().size, 0);
+        assert!(get_buffer(id).unwrap().is_empty());
+        assert!(clear_screen("missing-terminal").is_err());
+
+        close(id).unwrap();
+    }
+
+    #[test]
+    fn test_search_bytes_maps_to_raw_offsets() {
+        let raw = b"build ok\r\n\x1b[31mERR\x1b[0mor: \x1b]0;title\x07bad thing\r\n";
+        let matches = search_bytes(raw, "error", true).unwrap();
+        assert_eq!(matches.len(), 1);
+        let m = &matches[0];
+        assert_eq!((m.line, m.column), (2, 1));
+        // The raw span starts at "ERR" and includes the reset sequence inside it
+        assert_eq!(&raw[m.offset..m.offset + m.length], b"ERR\x1b[0mor");
+
+        let m = &search_bytes(raw, "bad", false).unwrap()[0];
+        assert_eq!((m.line, m.column), (2, 8));
+        assert!(search_bytes(raw, "err", false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_osc7_scanner_split_sequences() {
+        let mut scanner = Osc7Scanner::default();
+        assert_eq!(scanner.feed(b"prompt$ \x1b]7;file://host/home/u"), None);
+        assert_eq!(
+            scanner.feed(b"ser/my%20dir\x07more output"),
+            Some("/home/user/my dir".to_string())
+        );
+
+        // Split inside the prefix and before the ST terminator's backslash
+        assert_eq!(scanner.feed(b"text\x1b]"), None);
+        assert_eq!(scanner.feed(b"7;file:///tmp/a\x1b"), None);
+        assert_eq!(scanner.feed(b"\\"), Some("/tmp/a".to_string()));
+
+        // The last report in a read wins; other OSC codes are ignored
+        assert_eq!(
+            scanner.feed(b"\x1b]0;title\x07\x1b]7;file://h/one\x07\x1b]7;file://h/two\x07"),
+            Some("/two".to_string())
+        );
+        assert!(scanner.pending.is_empty());
+    }
+
+    #[test]
+    fn test_terminal_osc7_updates_cwd() {
+        let id = "test-terminal-9";
+        create(
+            id,
+            Some("/bin/sh"),
+            vec![
+                "-c".to_string(),
+                "printf '\\033]7;file://localhost/tmp/osc%%20seven\\007'; sleep 2".to_string(),
+            ],
+            Some("."),
+            None,
+            24,
+            80,
+        )
+        .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline && get_info(id).unwrap().cwd == "." {
+            let _ = read(id);
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert_eq!(get_info(id).unwrap().cwd, "/tmp/osc seven");
+
+        close(id).unwrap();
+    }
+
+    #[test]
+    fn test_terminal_search_buffer() {
+        let id = "test-terminal-8";
+        create(
+            id,
+            Some("/bin/sh"),
+            vec![
+                "-c".to_string(),
+                "printf 'first\\n\\033[1mfind me\\033[0m\\n'; sleep 2".to_string(),
+            ],
+            Some("."),
+            None,
+            24,
+            80,
+        )
+        .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut matches = Vec::new();
+        while Instant::now() < deadline && matches.is_empty() {
+            let _ = read(id);
+            matches = search_buffer(id, "FIND ME", true).unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].column, 1);
+        let raw = get_buffer(id).unwrap();
+        assert!(raw[matches[0].offset..].starts_with(b"find me"));
+        assert!(search_buffer("missing-terminal", "x", false).is_err());
+
+        close(id).unwrap();
+    }
+
+    #[test]
+    fn test_terminal_recording() {
+        let id = "test-terminal-4";
+        let path = temp_path("terminal");
+        let path_str = path.to_str().unwrap();
+
+        create(
+            id,
+            Some("/bin/sh"),
+            vec![
+                "-c".to_string(),
+                "sleep 0.2; echo recorded-marker; sleep 2".to_string(),
+            ],
+            Some("."),
+            None,
+            24,
+            80,
+        )
+        .unwrap();
+        start_recording(id, path_str).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut seen = Vec::new();
+        while Instant::now() < deadline {
+            match read(id) {
+                Ok(output) => seen.extend_from_slice(&output.data),
+                Err(_) => break,
+            }
+            if String::from_utf8_lossy(&seen).contains("recorded-marker") {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        stop_recording(id).unwrap();
+        assert!(stop_recording(id).is_err());
+        close(id).unwrap();
+
+        let recorded = std::fs::read(&path).unwrap();
+        assert!(String::from_utf8_lossy(&recorded).contains("recorded-marker"));
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_terminal_recording_unwritable_path() {
+        let id = "test-terminal-5";
+        create(id, None, vec![], Some("."), None, 24, 80).unwrap();
+
+        let result = start_recording(id, "/nonexistent-dir/ironcode/transcript.log");
+        assert!(result.is_err());
+
+        close(id).unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_terminal_recording_write_failure_keeps_output() {
+        let id = "test-terminal-16";
+        create(
+            id,
+            Some("/bin/sh"),
+            vec!["-c".to_string(), "echo lost-nothing; sleep 2".to_string()],
+            Some("."),
+            None,
+            24,
+            80,
+        )
+        .unwrap();
+        // Every write to /dev/full fails with ENOSPC
+        start_recording(id, "/dev/full").unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut seen = Vec::new();
+        while Instant::now() < deadline && !String::from_utf8_lossy(&seen).contains("lost-nothing")
+        {
+            seen.extend_from_slice(&read(id).unwrap().data);
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(String::from_utf8_lossy(&seen).contains("lost-nothing"));
+
+        let error = stop_recording(id).unwrap_err();
+        assert!(error.contains("Failed to write recording"));
+        assert!(stop_recording(id).unwrap_err().contains("not recording"));
+        close(id).unwrap();
+    }
 }
@@ -0,0 +1,27 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+/// A path under the system temp dir that no other test uses. Nothing is
+/// created; the process id and a counter keep parallel tests and runs apart.
+pub(crate) fn temp_path(tag: &str) -> PathBuf {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    std::env::temp_dir().join(format!(
+        "ironcode_test_{}_{}_{}_{}",
+        tag,
+        std::process::id(),
+        nanos,
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Create an empty directory at a fresh [`temp_path`].
+pub(crate) fn temp_dir(tag: &str) -> PathBuf {
+    let dir = temp_path(tag);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
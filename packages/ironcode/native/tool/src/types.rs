@@ -4,6 +4,23 @@ use serde::Serialize;
 pub struct Metadata {
     pub count: usize,
     pub truncated: bool,
+    /// Set when the operation was cancelled and the output is partial
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub cancelled: bool,
+    /// How many files a search looked at, for tools that walk a tree
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scanned: Option<ScanSummary>,
+}
+
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct ScanSummary {
+    pub files_searched: usize,
+    pub files_skipped_binary: usize,
+    /// Ignored files the walk came across; files inside an ignored directory
+    /// are not visited and not counted
+    pub files_skipped_ignored: usize,
+    /// Ignored directories the walk did not descend into
+    pub dirs_skipped_ignored: usize,
 }
 
 #[derive(Serialize)]
@@ -1,6 +1,9 @@
-use git2::{BranchType, IndexAddOption, ObjectType, Repository, Signature, Status, StatusOptions};
-use serde::Serialize;
-use std::path::Path;
+use git2::{
+    ApplyLocation, ApplyOptions, Branch, BranchType, DiffHunk, DiffOptions, IndexAddOption,
+    ObjectType, Repository, Signature, Status, StatusOptions,
+};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
 #[derive(Serialize)]
 pub struct VcsInfo {
@@ -20,6 +23,22 @@ pub struct FileStatus {
     pub staged: bool,
 }
 
+impl FileStatus {
+    /// Coarse badge for file trees and headers: "staged", "untracked", "ignored"
+    /// or "modified"
+    pub fn badge(&self) -> &'static str {
+        if self.staged {
+            "staged"
+        } else if self.status == "untracked" {
+            "untracked"
+        } else if self.status == "ignored" {
+            "ignored"
+        } else {
+            "modified"
+        }
+    }
+}
+
 #[derive(Serialize)]
 pub struct GitStatus {
     pub branch: String,
@@ -32,17 +51,137 @@ pub struct BranchInfo {
     pub is_head: bool,
 }
 
+#[derive(Serialize)]
+pub struct TagInfo {
+    pub name: String,
+    pub target_sha: String,
+    pub is_annotated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// The commit HEAD points at
+#[derive(Debug, Serialize)]
+pub struct CommitInfo {
+    pub sha: String,
+    pub short_sha: String,
+    /// First line of the message
+    pub summary: String,
+    pub author: String,
+    /// Commit time in seconds since the Unix epoch
+    pub timestamp: i64,
+}
+
+/// The branch the current branch tracks
+#[derive(Debug, Serialize)]
+pub struct Upstream {
+    /// Remote name, or `.` when tracking a local branch
+    pub remote: String,
+    /// Branch name on the remote, e.g. `main`
+    pub branch: String,
+    /// e.g. `refs/remotes/origin/main`
+    pub full_ref: String,
+}
+
+/// Identifies one hunk of a file's diff by the ranges in its
+/// `@@ -old_start,old_lines +new_start,new_lines @@` header.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+pub struct HunkSelector {
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+}
+
+impl HunkSelector {
+    fn matches(&self, hunk: &DiffHunk) -> bool {
+        hunk.old_start() == self.old_start
+            && hunk.old_lines() == self.old_lines
+            && hunk.new_start() == self.new_start
+            && hunk.new_lines() == self.new_lines
+    }
+
+    /// The same hunk as seen from the other side of a reversed diff
+    fn reversed(&self) -> Self {
+        Self {
+            old_start: self.new_start,
+            old_lines: self.new_lines,
+            new_start: self.old_start,
+            new_lines: self.old_lines,
+        }
+    }
+}
+
+/// One hunk of a file diff, with its lines already split out so hosts do not
+/// have to re-parse patch text.
+#[derive(Debug, Serialize)]
+pub struct FileDiffHunk {
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+/// `origin` is ' ' (context), '+' or '-', or one of '=', '>', '<' for the
+/// "no newline at end of file" markers. `content` keeps its line ending.
+#[derive(Debug, Serialize)]
+pub struct DiffLine {
+    pub origin: char,
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_lineno: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_lineno: Option<u32>,
+}
+
+/// Outcome of applying a commit (cherry-pick, revert).
+/// `commit` is the new short SHA when clean; otherwise `conflicts` lists the
+/// conflicted paths and nothing is committed.
+#[derive(Serialize)]
+pub struct ApplyResult {
+    pub commit: Option<String>,
+    pub conflicts: Vec<String>,
+}
+
+/// Options for `merge`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MergeOptions {
+    /// Always create a merge commit, even when a fast-forward is possible
+    pub no_ff: bool,
+    /// Merge commit message; defaults to `Merge branch '<branch>'`
+    pub message: Option<String>,
+}
+
+/// `commit` is the new HEAD short SHA (or the current one when already up to
+/// date); on conflicts it is `None`, `conflicts` lists the paths and the merge
+/// is left in progress.
+#[derive(Debug, Serialize)]
+pub struct MergeResult {
+    pub commit: Option<String>,
+    pub fast_forward: bool,
+    pub up_to_date: bool,
+    pub conflicts: Vec<String>,
+}
+
 #[derive(Debug)]
 pub enum VcsError {
     NotGitRepo(String),
     GitError(String),
+    /// A branch, ref, commit, tag, remote or hunk that does not exist
+    NotFound(String),
+    /// A tag or repository that is already there
+    AlreadyExists(String),
 }
 
 impl std::fmt::Display for VcsError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             VcsError::NotGitRepo(msg) => write!(f, "Not a git repository: {}", msg),
-            VcsError::GitError(msg) => write!(f, "Git error: {}", msg),
+            VcsError::GitError(msg) | VcsError::NotFound(msg) | VcsError::AlreadyExists(msg) => {
+                write!(f, "Git error: {}", msg)
+            }
         }
     }
 }
@@ -51,7 +190,12 @@ impl std::error::Error for VcsError {}
 
 impl From<git2::Error> for VcsError {
     fn from(err: git2::Error) -> Self {
-        VcsError::GitError(err.message().to_string())
+        let message = err.message().to_string();
+        match err.code() {
+            git2::ErrorCode::NotFound => VcsError::NotFound(message),
+            git2::ErrorCode::Exists => VcsError::AlreadyExists(message),
+            _ => VcsError::GitError(message),
+        }
     }
 }
 
@@ -128,6 +272,7 @@ fn get_status(repo: &Repository) -> Result<(u32, u32, u32), VcsError> {
 }
 
 /// Get detailed Git status with individual file information
+#[cfg_attr(feature = "logging", tracing::instrument(skip_all, fields(cwd = %cwd), err))]
 pub fn get_status_detailed(cwd: &str) -> Result<GitStatus, VcsError> {
     let path = Path::new(cwd);
     let repo =
@@ -144,39 +289,119 @@ pub fn get_status_detailed(cwd: &str) -> Result<GitStatus, VcsError> {
     let statuses = repo.statuses(Some(&mut opts))?;
 
     for entry in statuses.iter() {
-        let status_flags = entry.status();
         let path_str = entry.path().unwrap_or("").to_string();
+        if let Some((status, staged)) = classify_status(entry.status()) {
+            files.push(FileStatus {
+                path: path_str,
+                status,
+                staged,
+            });
+        }
+    }
 
-        // Determine status and staged state
-        let (status, staged) = if status_flags.contains(Status::INDEX_NEW) {
-            ("added".to_string(), true)
-        } else if status_flags.contains(Status::INDEX_MODIFIED) {
-            ("modified".to_string(), true)
-        } else if status_flags.contains(Status::INDEX_DELETED) {
-            ("deleted".to_string(), true)
-        } else if status_flags.contains(Status::WT_NEW) {
-            ("untracked".to_string(), false)
-        } else if status_flags.contains(Status::WT_MODIFIED)
-            || status_flags.contains(Status::WT_RENAMED)
-        {
-            ("modified".to_string(), false)
-        } else if status_flags.contains(Status::WT_DELETED) {
-            ("deleted".to_string(), false)
-        } else {
-            continue;
-        };
+    Ok(GitStatus { branch, files })
+}
+
+/// Status name and staged state for a file's status flags; None when unchanged
+fn classify_status(status_flags: Status) -> Option<(String, bool)> {
+    let (status, staged) = if status_flags.contains(Status::INDEX_NEW) {
+        ("added", true)
+    } else if status_flags.contains(Status::INDEX_MODIFIED) {
+        ("modified", true)
+    } else if status_flags.contains(Status::INDEX_DELETED) {
+        ("deleted", true)
+    } else if status_flags.contains(Status::WT_NEW) {
+        ("untracked", false)
+    } else if status_flags.contains(Status::WT_MODIFIED)
+        || status_flags.contains(Status::WT_RENAMED)
+    {
+        ("modified", false)
+    } else if status_flags.contains(Status::WT_DELETED) {
+        ("deleted", false)
+    } else if status_flags.contains(Status::IGNORED) {
+        ("ignored", false)
+    } else {
+        return None;
+    };
+    Some((status.to_string(), staged))
+}
+
+/// Status of a single file (relative to `cwd` or absolute), without computing
+/// the status of the whole repository. Returns None for an unchanged file.
+pub fn file_status(cwd: &str, file_path: &str) -> Result<Option<FileStatus>, VcsError> {
+    let path = Path::new(cwd);
+    let repo =
+        Repository::discover(path).map_err(|e| VcsError::NotGitRepo(e.message().to_string()))?;
 
-        files.push(FileStatus {
-            path: path_str,
+    let relative = workdir_relative(&repo, path, file_path)?;
+    let status_flags = repo.status_file(&relative)?;
+    Ok(
+        classify_status(status_flags).map(|(status, staged)| FileStatus {
+            path: relative.to_string_lossy().replace('\\', "/"),
             status,
             staged,
-        });
-    }
+        }),
+    )
+}
 
-    Ok(GitStatus { branch, files })
+/// `file_path` (relative to `cwd` or absolute) relative to the repository's working directory
+fn workdir_relative(repo: &Repository, cwd: &Path, file_path: &str) -> Result<PathBuf, VcsError> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| VcsError::GitError("Repository has no working directory".to_string()))?;
+    let workdir = workdir
+        .canonicalize()
+        .unwrap_or_else(|_| workdir.to_path_buf());
+
+    // The target may not exist yet (and may be a symlink git tracks as such),
+    // so canonicalize its parent directory rather than the target itself
+    let base = cwd.canonicalize().unwrap_or_else(|_| cwd.to_path_buf());
+    let target = base.join(file_path);
+    let target = match (target.parent(), target.file_name()) {
+        (Some(parent), Some(name)) => parent
+            .canonicalize()
+            .map(|parent| parent.join(name))
+            .unwrap_or(target),
+        _ => target,
+    };
+    // `..` left in a path whose parent doesn't exist would otherwise slip past
+    // the prefix check below
+    let target = PathBuf::from(crate::path::fs_normalize(&target.to_string_lossy()));
+
+    target
+        .strip_prefix(&workdir)
+        .ok()
+        .filter(|relative| {
+            !relative
+                .components()
+                .any(|c| c == std::path::Component::ParentDir)
+        })
+        .map(Path::to_path_buf)
+        .ok_or_else(|| {
+            VcsError::GitError(format!("Path '{}' is outside the repository", file_path))
+        })
+}
+
+/// Whether the working tree and index have no changes, untracked files
+/// included. Cheaper than `get_status_detailed`: untracked directories are not
+/// descended into and no file list is built.
+pub fn is_clean(cwd: &str) -> Result<bool, VcsError> {
+    let path = Path::new(cwd);
+    let repo =
+        Repository::discover(path).map_err(|e| VcsError::NotGitRepo(e.message().to_string()))?;
+
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true);
+    opts.recurse_untracked_dirs(false);
+    opts.include_ignored(false);
+    opts.exclude_submodules(false);
+
+    let statuses = repo.statuses(Some(&mut opts))?;
+    Ok(statuses.iter().next().is_none())
 }
 
 /// Stage files (git add)
+#[cfg_attr(feature = "logging", tracing::instrument(skip_all, fields(cwd = %cwd, count = paths.len()), err))]
 pub fn stage_files(cwd: &str, paths: Vec<String>) -> Result<(), VcsError> {
     let path = Path::new(cwd);
     let repo =
@@ -199,6 +424,7 @@ pub fn stage_files(cwd: &str, paths: Vec<String>) -> Result<(), VcsError> {
 }
 
 /// Unstage files (git reset)
+#[cfg_attr(feature = "logging", tracing::instrument(skip_all, fields(cwd = %cwd, count = paths.len()), err))]
 pub fn unstage_files(cwd: &str, paths: Vec<String>) -> Result<(), VcsError> {
     let path = Path::new(cwd);
     let repo =
@@ -220,7 +446,75 @@ pub fn unstage_files(cwd: &str, paths: Vec<String>) -> Result<(), VcsError> {
     Ok(())
 }
 
+/// Stage a single hunk of `file_path` (git add -p)
+/// `hunk` uses the ranges shown by `git diff` (index → working tree).
+#[cfg_attr(feature = "logging", tracing::instrument(skip_all, fields(cwd = %cwd, file = %file_path), err))]
+pub fn stage_hunk(cwd: &str, file_path: &str, hunk: &HunkSelector) -> Result<(), VcsError> {
+    let path = Path::new(cwd);
+    let repo =
+        Repository::discover(path).map_err(|e| VcsError::NotGitRepo(e.message().to_string()))?;
+
+    let mut opts = DiffOptions::new();
+    opts.pathspec(file_path).disable_pathspec_match(true);
+    let diff = repo.diff_index_to_workdir(None, Some(&mut opts))?;
+
+    apply_hunk_to_index(&repo, &diff, hunk, file_path)
+}
+
+/// Unstage a single hunk of `file_path` (git reset -p)
+/// `hunk` uses the ranges shown by `git diff --cached` (HEAD → index).
+#[cfg_attr(feature = "logging", tracing::instrument(skip_all, fields(cwd = %cwd, file = %file_path), err))]
+pub fn unstage_hunk(cwd: &str, file_path: &str, hunk: &HunkSelector) -> Result<(), VcsError> {
+    let path = Path::new(cwd);
+    let repo =
+        Repository::discover(path).map_err(|e| VcsError::NotGitRepo(e.message().to_string()))?;
+
+    let head_tree = match repo.head() {
+        Ok(head) => Some(head.peel_to_tree()?),
+        Err(_) => None, // unborn branch: everything staged is new
+    };
+
+    // Applying the reversed staged diff to the index undoes the hunk
+    let mut opts = DiffOptions::new();
+    opts.pathspec(file_path)
+        .disable_pathspec_match(true)
+        .reverse(true);
+    let diff = repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut opts))?;
+
+    apply_hunk_to_index(&repo, &diff, &hunk.reversed(), file_path)
+}
+
+fn apply_hunk_to_index(
+    repo: &Repository,
+    diff: &git2::Diff,
+    hunk: &HunkSelector,
+    file_path: &str,
+) -> Result<(), VcsError> {
+    let mut found = false;
+    diff.foreach(
+        &mut |_, _| true,
+        None,
+        Some(&mut |_, h| {
+            found |= hunk.matches(&h);
+            true
+        }),
+        None,
+    )?;
+    if !found {
+        return Err(VcsError::NotFound(format!(
+            "No hunk @@ -{},{} +{},{} @@ in {}",
+            hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines, file_path
+        )));
+    }
+
+    let mut apply_opts = ApplyOptions::new();
+    apply_opts.hunk_callback(|h| h.map(|h| hunk.matches(&h)).unwrap_or(false));
+    repo.apply(diff, ApplyLocation::Index, Some(&mut apply_opts))?;
+    Ok(())
+}
+
 /// Commit staged changes
+#[cfg_attr(feature = "logging", tracing::instrument(skip_all, fields(cwd = %cwd), err))]
 pub fn commit(cwd: &str, message: &str) -> Result<String, VcsError> {
     let path = Path::new(cwd);
     let repo =
@@ -277,6 +571,7 @@ pub fn list_branches(cwd: &str) -> Result<Vec<BranchInfo>, VcsError> {
 }
 
 /// Checkout branch
+#[cfg_attr(feature = "logging", tracing::instrument(skip_all, fields(cwd = %cwd, branch = %branch_name), err))]
 pub fn checkout_branch(cwd: &str, branch_name: &str) -> Result<(), VcsError> {
     let path = Path::new(cwd);
     let repo =
@@ -286,7 +581,7 @@ pub fn checkout_branch(cwd: &str, branch_name: &str) -> Result<(), VcsError> {
     let branch_ref = format!("refs/heads/{}", branch_name);
     let reference = repo
         .find_reference(&branch_ref)
-        .map_err(|_| VcsError::GitError(format!("Branch '{}' not found", branch_name)))?;
+        .map_err(|_| VcsError::NotFound(format!("Branch '{}' not found", branch_name)))?;
 
     // Get the commit that the branch points to
     let commit = reference.peel_to_commit()?;
@@ -305,7 +600,99 @@ pub fn get_file_diff(cwd: &str, file_path: &str, staged: bool) -> Result<String,
     let path = Path::new(cwd);
     let repo =
         Repository::discover(path).map_err(|e| VcsError::NotGitRepo(e.message().to_string()))?;
+    let diff = file_diff(&repo, file_path, staged)?;
+
+    let mut diff_text = String::new();
+    diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
+        let origin = line.origin();
+        let content = std::str::from_utf8(line.content()).unwrap_or("");
 
+        match origin {
+            '+' => diff_text.push_str(&format!("+{}", content)),
+            '-' => diff_text.push_str(&format!("-{}", content)),
+            ' ' => diff_text.push_str(&format!(" {}", content)),
+            _ => diff_text.push_str(content),
+        }
+
+        true
+    })?;
+
+    Ok(diff_text)
+}
+
+/// Same diff as `get_file_diff`, as structured hunks
+pub fn file_diff_structured(
+    cwd: &str,
+    file_path: &str,
+    staged: bool,
+) -> Result<Vec<FileDiffHunk>, VcsError> {
+    let path = Path::new(cwd);
+    let repo =
+        Repository::discover(path).map_err(|e| VcsError::NotGitRepo(e.message().to_string()))?;
+    let diff = file_diff(&repo, file_path, staged)?;
+
+    let mut hunks: Vec<FileDiffHunk> = Vec::new();
+    diff.print(git2::DiffFormat::Patch, |_delta, hunk, line| {
+        match (line.origin(), hunk) {
+            // File headers carry no lines of their own
+            ('F', _) => {}
+            ('H', Some(hunk)) => hunks.push(FileDiffHunk {
+                old_start: hunk.old_start(),
+                old_lines: hunk.old_lines(),
+                new_start: hunk.new_start(),
+                new_lines: hunk.new_lines(),
+                lines: Vec::new(),
+            }),
+            (origin, _) => {
+                if let Some(current) = hunks.last_mut() {
+                    current.lines.push(DiffLine {
+                        origin,
+                        content: String::from_utf8_lossy(line.content()).to_string(),
+                        old_lineno: line.old_lineno(),
+                        new_lineno: line.new_lineno(),
+                    });
+                }
+            }
+        }
+        true
+    })?;
+
+    Ok(hunks)
+}
+
+/// Paths, relative to the repository root, that differ between `base_ref` and
+/// the working directory, staged or not. Untracked files are included and
+/// deleted files are left out, so every path exists on disk.
+pub fn changed_files(cwd: &str, base_ref: &str) -> Result<Vec<String>, VcsError> {
+    let path = Path::new(cwd);
+    let repo =
+        Repository::discover(path).map_err(|e| VcsError::NotGitRepo(e.message().to_string()))?;
+    let base = repo
+        .revparse_single(base_ref)
+        .map_err(|_| VcsError::NotFound(format!("Ref '{}' not found", base_ref)))?
+        .peel_to_tree()?;
+
+    let mut diff_opts = DiffOptions::new();
+    diff_opts
+        .include_untracked(true)
+        .recurse_untracked_dirs(true);
+    let diff = repo.diff_tree_to_workdir_with_index(Some(&base), Some(&mut diff_opts))?;
+
+    Ok(diff
+        .deltas()
+        .filter(|delta| delta.status() != git2::Delta::Deleted)
+        .filter_map(|delta| delta.new_file().path())
+        .map(|path| path.to_string_lossy().replace('\\', "/"))
+        .collect())
+}
+
+/// Diff of a single file: index against HEAD when `staged`, otherwise the
+/// working directory against the index
+fn file_diff<'r>(
+    repo: &'r Repository,
+    file_path: &str,
+    staged: bool,
+) -> Result<git2::Diff<'r>, VcsError> {
     let mut diff_opts = git2::DiffOptions::new();
     diff_opts.pathspec(file_path);
 
@@ -319,26 +706,445 @@ pub fn get_file_diff(cwd: &str, file_path: &str, staged: bool) -> Result<String,
         // Unstaged changes: compare working directory with index
         repo.diff_index_to_workdir(None, Some(&mut diff_opts))?
     };
+    Ok(diff)
+}
 
-    let mut diff_text = String::new();
-    diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
-        let origin = line.origin();
-        let content = std::str::from_utf8(line.content()).unwrap_or("");
+/// Initialize a new repository (git init)
+/// Optionally creates an empty initial commit so HEAD resolves right away.
+/// Returns the path of the repository's git directory.
+pub fn init(cwd: &str, bare: bool, initial_commit: bool) -> Result<String, VcsError> {
+    let path = Path::new(cwd);
 
-        match origin {
-            '+' => diff_text.push_str(&format!("+{}", content)),
-            '-' => diff_text.push_str(&format!("-{}", content)),
-            ' ' => diff_text.push_str(&format!(" {}", content)),
-            _ => diff_text.push_str(content),
+    if Repository::open(path).is_ok() {
+        return Err(VcsError::AlreadyExists(format!(
+            "'{}' is already a git repository",
+            cwd
+        )));
+    }
+
+    let repo = if bare {
+        Repository::init_bare(path)?
+    } else {
+        Repository::init(path)?
+    };
+
+    if initial_commit {
+        let signature = match repo.signature() {
+            Ok(sig) => sig,
+            Err(_) => Signature::now("IronCode", "ironcode@local")?,
+        };
+        let tree_id = repo.treebuilder(None)?.write()?;
+        let tree = repo.find_tree(tree_id)?;
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "Initial commit",
+            &tree,
+            &[],
+        )?;
+    }
+
+    Ok(repo.path().to_string_lossy().to_string())
+}
+
+/// Details of the commit HEAD points at. A repository without commits yet
+/// returns a "No commits yet" error rather than libgit2's unborn-branch error.
+pub fn current_commit(cwd: &str) -> Result<CommitInfo, VcsError> {
+    let path = Path::new(cwd);
+    let repo =
+        Repository::discover(path).map_err(|e| VcsError::NotGitRepo(e.message().to_string()))?;
+
+    let head = match repo.head() {
+        Ok(head) => head,
+        Err(e)
+            if matches!(
+                e.code(),
+                git2::ErrorCode::UnbornBranch | git2::ErrorCode::NotFound
+            ) =>
+        {
+            return Err(VcsError::GitError("No commits yet".to_string()));
         }
+        Err(e) => return Err(e.into()),
+    };
+    let commit = head.peel_to_commit()?;
+    let sha = commit.id().to_string();
+    let author = commit.author().name().unwrap_or("").to_string();
+
+    let info = CommitInfo {
+        short_sha: sha[..7].to_string(),
+        sha,
+        summary: commit.summary().unwrap_or("").to_string(),
+        author,
+        timestamp: commit.time().seconds(),
+    };
+    Ok(info)
+}
 
-        true
+/// The upstream configured for the current branch (`branch.<name>.merge`).
+/// None when no upstream is set, HEAD is detached, or there are no commits yet.
+pub fn upstream(cwd: &str) -> Result<Option<Upstream>, VcsError> {
+    let path = Path::new(cwd);
+    let repo =
+        Repository::discover(path).map_err(|e| VcsError::NotGitRepo(e.message().to_string()))?;
+
+    let head = match repo.head() {
+        Ok(head) if head.is_branch() => head,
+        Ok(_) => return Ok(None),
+        Err(e)
+            if matches!(
+                e.code(),
+                git2::ErrorCode::UnbornBranch | git2::ErrorCode::NotFound
+            ) =>
+        {
+            return Ok(None);
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let local_ref = head.name().unwrap_or("").to_string();
+    let tracked = match Branch::wrap(head).upstream() {
+        Ok(tracked) => tracked,
+        Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let full_ref = tracked.get().name().unwrap_or("").to_string();
+    let remote_buf = repo.branch_upstream_remote(&local_ref)?;
+    let remote = remote_buf.as_str().unwrap_or("").to_string();
+    let branch = full_ref
+        .strip_prefix(&format!("refs/remotes/{}/", remote))
+        .or_else(|| full_ref.strip_prefix("refs/heads/"))
+        .unwrap_or(&full_ref)
+        .to_string();
+    Ok(Some(Upstream {
+        remote,
+        branch,
+        full_ref,
+    }))
+}
+
+/// Check whether git would ignore a path (relative to `cwd` or absolute)
+pub fn is_ignored(cwd: &str, file_path: &str) -> Result<bool, VcsError> {
+    let path = Path::new(cwd);
+    let repo =
+        Repository::discover(path).map_err(|e| VcsError::NotGitRepo(e.message().to_string()))?;
+
+    let relative = workdir_relative(&repo, path, file_path)?;
+    Ok(repo.is_path_ignored(relative)?)
+}
+
+/// List paths with unresolved conflicts in the index
+pub fn list_conflicts(cwd: &str) -> Result<Vec<String>, VcsError> {
+    let path = Path::new(cwd);
+    let repo =
+        Repository::discover(path).map_err(|e| VcsError::NotGitRepo(e.message().to_string()))?;
+
+    let index = repo.index()?;
+    conflict_paths(&index)
+}
+
+fn conflict_paths(index: &git2::Index) -> Result<Vec<String>, VcsError> {
+    let mut paths = Vec::new();
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        // Prefer "ours", fall back to "theirs"/ancestor for add/delete conflicts
+        let entry = conflict.our.or(conflict.their).or(conflict.ancestor);
+        if let Some(entry) = entry {
+            paths.push(String::from_utf8_lossy(&entry.path).to_string());
+        }
+    }
+    Ok(paths)
+}
+
+/// Commit the index on top of HEAD after a cherry-pick/revert, or report conflicts
+fn commit_applied(
+    repo: &Repository,
+    author: &Signature,
+    message: &str,
+) -> Result<ApplyResult, VcsError> {
+    let mut index = repo.index()?;
+    if index.has_conflicts() {
+        return Ok(ApplyResult {
+            commit: None,
+            conflicts: conflict_paths(&index)?,
+        });
+    }
+
+    let committer = match repo.signature() {
+        Ok(sig) => sig,
+        Err(_) => Signature::now("IronCode", "ironcode@local")?,
+    };
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let parent = repo.head()?.peel_to_commit()?;
+    let commit_id = repo.commit(Some("HEAD"), author, &committer, message, &tree, &[&parent])?;
+    repo.cleanup_state()?;
+
+    Ok(ApplyResult {
+        commit: Some(format!("{:.7}", commit_id)),
+        conflicts: Vec::new(),
+    })
+}
+
+/// Cherry-pick a commit onto the current branch
+#[cfg_attr(feature = "logging", tracing::instrument(skip_all, fields(cwd = %cwd, commit = %commit_sha), err))]
+pub fn cherry_pick(cwd: &str, commit_sha: &str) -> Result<ApplyResult, VcsError> {
+    let path = Path::new(cwd);
+    let repo =
+        Repository::discover(path).map_err(|e| VcsError::NotGitRepo(e.message().to_string()))?;
+
+    let commit = repo
+        .revparse_single(commit_sha)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|_| VcsError::NotFound(format!("Commit '{}' not found", commit_sha)))?;
+
+    if commit.parent_count() > 1 {
+        return Err(VcsError::GitError(
+            "Cannot cherry-pick a merge commit without a mainline".to_string(),
+        ));
+    }
+
+    repo.cherrypick(&commit, None)?;
+    let author = commit.author();
+    commit_applied(&repo, &author, commit.message().unwrap_or(""))
+}
+
+/// Revert a commit on the current branch
+/// The revert commit message is `Revert "<original summary>"`.
+#[cfg_attr(feature = "logging", tracing::instrument(skip_all, fields(cwd = %cwd, commit = %commit_sha), err))]
+pub fn revert(cwd: &str, commit_sha: &str) -> Result<ApplyResult, VcsError> {
+    let path = Path::new(cwd);
+    let repo =
+        Repository::discover(path).map_err(|e| VcsError::NotGitRepo(e.message().to_string()))?;
+
+    let commit = repo
+        .revparse_single(commit_sha)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|_| VcsError::NotFound(format!("Commit '{}' not found", commit_sha)))?;
+
+    if commit.parent_count() > 1 {
+        return Err(VcsError::GitError(
+            "Cannot revert a merge commit without a mainline".to_string(),
+        ));
+    }
+
+    repo.revert(&commit, None)?;
+
+    let message = format!(
+        "Revert \"{}\"\n\nThis reverts commit {}.\n",
+        commit.summary().unwrap_or(""),
+        commit.id()
+    );
+    let author = match repo.signature() {
+        Ok(sig) => sig,
+        Err(_) => Signature::now("IronCode", "ironcode@local")?,
+    };
+    commit_applied(&repo, &author, &message)
+}
+
+/// Merge `branch` (or any revision) into the current branch. A fast-forward
+/// just moves HEAD unless `no_ff` is set; otherwise a merge commit is created
+/// when the merge is clean.
+#[cfg_attr(feature = "logging", tracing::instrument(skip_all, fields(cwd = %cwd, branch = %branch), err))]
+pub fn merge(cwd: &str, branch: &str, options: &MergeOptions) -> Result<MergeResult, VcsError> {
+    let path = Path::new(cwd);
+    let repo =
+        Repository::discover(path).map_err(|e| VcsError::NotGitRepo(e.message().to_string()))?;
+
+    let theirs = repo
+        .revparse_single(branch)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|_| VcsError::NotFound(format!("Branch '{}' not found", branch)))?;
+    let annotated = repo.find_annotated_commit(theirs.id())?;
+    let head = repo.head()?.peel_to_commit()?;
+
+    let (analysis, _) = repo.merge_analysis(&[&annotated])?;
+    if analysis.is_up_to_date() {
+        return Ok(MergeResult {
+            commit: Some(format!("{:.7}", head.id())),
+            fast_forward: false,
+            up_to_date: true,
+            conflicts: Vec::new(),
+        });
+    }
+
+    if analysis.is_fast_forward() && !options.no_ff {
+        repo.checkout_tree(theirs.as_object(), None)?;
+        repo.head()?
+            .set_target(theirs.id(), &format!("merge {}: Fast-forward", branch))?;
+        return Ok(MergeResult {
+            commit: Some(format!("{:.7}", theirs.id())),
+            fast_forward: true,
+            up_to_date: false,
+            conflicts: Vec::new(),
+        });
+    }
+
+    repo.merge(&[&annotated], None, None)?;
+    let mut index = repo.index()?;
+    if index.has_conflicts() {
+        return Ok(MergeResult {
+            commit: None,
+            fast_forward: false,
+            up_to_date: false,
+            conflicts: conflict_paths(&index)?,
+        });
+    }
+
+    let signature = match repo.signature() {
+        Ok(sig) => sig,
+        Err(_) => Signature::now("IronCode", "ironcode@local")?,
+    };
+    let message = options
+        .message
+        .clone()
+        .unwrap_or_else(|| format!("Merge branch '{}'", branch));
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let commit_id = repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        &message,
+        &tree,
+        &[&head, &theirs],
+    )?;
+    repo.cleanup_state()?;
+
+    Ok(MergeResult {
+        commit: Some(format!("{:.7}", commit_id)),
+        fast_forward: false,
+        up_to_date: false,
+        conflicts: Vec::new(),
+    })
+}
+
+/// List tags
+pub fn list_tags(cwd: &str) -> Result<Vec<TagInfo>, VcsError> {
+    let path = Path::new(cwd);
+    let repo =
+        Repository::discover(path).map_err(|e| VcsError::NotGitRepo(e.message().to_string()))?;
+
+    let mut tags = Vec::new();
+    for name in repo.tag_names(None)?.iter().flatten() {
+        let reference = repo.find_reference(&format!("refs/tags/{}", name))?;
+        let target = reference.peel(ObjectType::Any)?;
+        // Annotated tags point at a tag object; lightweight tags point straight at the target
+        let annotated = reference.target().and_then(|oid| repo.find_tag(oid).ok());
+        tags.push(TagInfo {
+            name: name.to_string(),
+            target_sha: format!("{:.7}", target.id()),
+            is_annotated: annotated.is_some(),
+            message: annotated
+                .as_ref()
+                .and_then(|tag| tag.message())
+                .map(|msg| msg.trim_end().to_string()),
+        });
+    }
+
+    Ok(tags)
+}
+
+/// Create a tag on `target` (defaults to HEAD)
+/// The tag is annotated when a message is given, lightweight otherwise.
+/// Returns the short SHA of the tagged object.
+#[cfg_attr(feature = "logging", tracing::instrument(skip_all, fields(cwd = %cwd, name = %name), err))]
+pub fn create_tag(
+    cwd: &str,
+    name: &str,
+    target: Option<&str>,
+    message: Option<&str>,
+    force: bool,
+) -> Result<String, VcsError> {
+    let path = Path::new(cwd);
+    let repo =
+        Repository::discover(path).map_err(|e| VcsError::NotGitRepo(e.message().to_string()))?;
+
+    if !force && repo.find_reference(&format!("refs/tags/{}", name)).is_ok() {
+        return Err(VcsError::AlreadyExists(format!(
+            "Tag '{}' already exists",
+            name
+        )));
+    }
+
+    let spec = target.unwrap_or("HEAD");
+    let object = repo
+        .revparse_single(spec)
+        .map_err(|_| VcsError::NotFound(format!("Target '{}' not found", spec)))?;
+
+    match message {
+        Some(msg) => {
+            let signature = match repo.signature() {
+                Ok(sig) => sig,
+                Err(_) => Signature::now("IronCode", "ironcode@local")?,
+            };
+            repo.tag(name, &object, &signature, msg, force)?;
+        }
+        None => {
+            repo.tag_lightweight(name, &object, force)?;
+        }
+    }
+
+    Ok(format!("{:.7}", object.id()))
+}
+
+/// Delete a tag
+#[cfg_attr(feature = "logging", tracing::instrument(skip_all, fields(cwd = %cwd, name = %name), err))]
+pub fn delete_tag(cwd: &str, name: &str) -> Result<(), VcsError> {
+    let path = Path::new(cwd);
+    let repo =
+        Repository::discover(path).map_err(|e| VcsError::NotGitRepo(e.message().to_string()))?;
+
+    repo.tag_delete(name).map_err(|e| match e.code() {
+        git2::ErrorCode::NotFound => VcsError::NotFound(format!("Tag '{}' not found", name)),
+        _ => e.into(),
     })?;
 
-    Ok(diff_text)
+    Ok(())
+}
+
+/// Branch names tried, in order, when origin/HEAD is not set
+const DEFAULT_BRANCH_CANDIDATES: &[&str] = &["main", "master", "trunk", "develop"];
+
+/// Get the repository's default branch
+/// Uses `refs/remotes/origin/HEAD`, then common names that exist locally,
+/// then the current HEAD.
+pub fn default_branch(cwd: &str) -> Result<String, VcsError> {
+    let path = Path::new(cwd);
+    let repo =
+        Repository::discover(path).map_err(|e| VcsError::NotGitRepo(e.message().to_string()))?;
+
+    if let Ok(origin_head) = repo.find_reference("refs/remotes/origin/HEAD") {
+        if let Some(name) = origin_head
+            .symbolic_target()
+            .and_then(|target| target.strip_prefix("refs/remotes/origin/"))
+        {
+            return Ok(name.to_string());
+        }
+    }
+
+    for name in DEFAULT_BRANCH_CANDIDATES {
+        if repo.find_branch(name, BranchType::Local).is_ok() {
+            return Ok(name.to_string());
+        }
+    }
+
+    get_branch(&repo)
+}
+
+/// Absolute path of the working directory root of the repository containing
+/// `cwd`, without a trailing separator. Bare repositories have no root.
+pub fn repo_root(cwd: &str) -> Result<String, VcsError> {
+    let path = Path::new(cwd);
+    let repo =
+        Repository::discover(path).map_err(|e| VcsError::NotGitRepo(e.message().to_string()))?;
+    let workdir = repo.workdir().ok_or_else(|| {
+        VcsError::GitError("Bare repository has no working directory".to_string())
+    })?;
+    let root: std::path::PathBuf = workdir.components().collect();
+    Ok(root.to_string_lossy().to_string())
 }
 
 /// Push commits to remote
+#[cfg_attr(feature = "logging", tracing::instrument(skip_all, fields(cwd = %cwd), err))]
 pub fn push_to_remote(cwd: &str) -> Result<String, VcsError> {
     let path = Path::new(cwd);
     let repo =
@@ -356,7 +1162,7 @@ pub fn push_to_remote(cwd: &str) -> Result<String, VcsError> {
     let remote_name = "origin"; // Default to origin
     let mut remote = repo
         .find_remote(remote_name)
-        .map_err(|e| VcsError::GitError(format!("Remote '{}' not found: {}", remote_name, e)))?;
+        .map_err(|e| VcsError::NotFound(format!("Remote '{}' not found: {}", remote_name, e)))?;
 
     // Push current branch to remote
     let refspec = format!("refs/heads/{}:refs/heads/{}", branch_name, branch_name);
@@ -457,6 +1263,8 @@ pub fn push_to_remote(cwd: &str) -> Result<String, VcsError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::temp_dir;
+    use std::fs;
 
     #[test]
     fn test_get_info_current_repo() {
@@ -486,4 +1294,463 @@ mod tests {
             Err(e) => panic!("Unexpected error: {}", e),
         }
     }
+
+    #[test]
+    fn test_init_creates_repo() {
+        let dir = temp_dir("init");
+        let cwd = dir.to_str().unwrap();
+
+        let git_dir = init(cwd, false, false).unwrap();
+        assert!(dir.join(".git").is_dir());
+        assert!(git_dir.contains(".git"));
+
+        // A second init on the same directory is rejected
+        assert!(init(cwd, false, false).is_err());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_init_with_initial_commit() {
+        let dir = temp_dir("init_commit");
+        let cwd = dir.to_str().unwrap();
+
+        init(cwd, false, true).unwrap();
+        let repo = Repository::open(&dir).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.parent_count(), 0);
+
+        // HEAD exists, so a regular commit works immediately
+        fs::write(dir.join("a.txt"), "a").unwrap();
+        stage_files(cwd, vec!["a.txt".to_string()]).unwrap();
+        assert!(commit(cwd, "add a").is_ok());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_is_ignored() {
+        let dir = temp_dir("ignored");
+        let cwd = dir.to_str().unwrap();
+        init(cwd, false, false).unwrap();
+        fs::write(dir.join(".gitignore"), "target/\n*.log\n").unwrap();
+
+        assert!(is_ignored(cwd, "debug.log").unwrap());
+        assert!(is_ignored(cwd, "target/out.bin").unwrap());
+        assert!(!is_ignored(cwd, "src/main.rs").unwrap());
+        assert!(is_ignored(cwd, "/definitely/outside/repo.txt").is_err());
+        // `..` through a directory that doesn't exist, or on its own
+        assert!(is_ignored(cwd, "../nope/x.txt").is_err());
+        assert!(is_ignored(cwd, "missing/../../x.txt").is_err());
+        assert!(is_ignored(cwd, "..").is_err());
+        assert!(is_ignored(cwd, "missing/../debug.log").unwrap());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    /// Write a file, stage it and commit; returns the short SHA
+    fn commit_file(dir: &Path, name: &str, content: &str, message: &str) -> String {
+        let cwd = dir.to_str().unwrap();
+        fs::write(dir.join(name), content).unwrap();
+        stage_files(cwd, vec![name.to_string()]).unwrap();
+        commit(cwd, message).unwrap()
+    }
+
+    #[test]
+    fn test_stage_and_unstage_single_hunk() {
+        let dir = temp_dir("stage_hunk");
+        let cwd = dir.to_str().unwrap();
+        init(cwd, false, true).unwrap();
+        let original: Vec<String> = (1..=20).map(|i| format!("line {}", i)).collect();
+        commit_file(&dir, "a.txt", &(original.join("\n") + "\n"), "base");
+
+        let mut edited = original.clone();
+        edited[1] = "line 2 changed".to_string();
+        edited[17] = "line 18 changed".to_string();
+        fs::write(dir.join("a.txt"), edited.join("\n") + "\n").unwrap();
+
+        // First hunk: line 2 plus one line of leading and three of trailing context
+        let first = HunkSelector {
+            old_start: 1,
+            old_lines: 5,
+            new_start: 1,
+            new_lines: 5,
+        };
+        stage_hunk(cwd, "a.txt", &first).unwrap();
+
+        let staged = get_file_diff(cwd, "a.txt", true).unwrap();
+        assert!(staged.contains("+line 2 changed"));
+        assert!(!staged.contains("line 18 changed"));
+        let unstaged = get_file_diff(cwd, "a.txt", false).unwrap();
+        assert!(unstaged.contains("+line 18 changed"));
+        assert!(!unstaged.contains("line 2 changed"));
+
+        let missing = HunkSelector {
+            old_start: 40,
+            ..first
+        };
+        assert!(stage_hunk(cwd, "a.txt", &missing).is_err());
+
+        unstage_hunk(cwd, "a.txt", &first).unwrap();
+        assert!(!get_file_diff(cwd, "a.txt", true)
+            .unwrap()
+            .contains("line 2 changed"));
+        assert_eq!(
+            fs::read_to_string(dir.join("a.txt")).unwrap(),
+            edited.join("\n") + "\n"
+        );
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_file_diff_structured() {
+        let dir = temp_dir("diff_structured");
+        let cwd = dir.to_str().unwrap();
+        init(cwd, false, true).unwrap();
+        commit_file(&dir, "a.txt", "one\ntwo\nthree\n", "base");
+        fs::write(dir.join("a.txt"), "one\nTWO\nthree\nfour\n").unwrap();
+
+        let hunks = file_diff_structured(cwd, "a.txt", false).unwrap();
+        assert_eq!(hunks.len(), 1);
+        let hunk = &hunks[0];
+        assert_eq!((hunk.old_start, hunk.old_lines), (1, 3));
+        assert_eq!((hunk.new_start, hunk.new_lines), (1, 4));
+        let lines: Vec<(char, &str)> = hunk
+            .lines
+            .iter()
+            .map(|l| (l.origin, l.content.as_str()))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                (' ', "one\n"),
+                ('-', "two\n"),
+                ('+', "TWO\n"),
+                (' ', "three\n"),
+                ('+', "four\n"),
+            ]
+        );
+        assert_eq!(hunk.lines[1].old_lineno, Some(2));
+        assert_eq!(hunk.lines[1].new_lineno, None);
+        assert_eq!(hunk.lines[4].new_lineno, Some(4));
+
+        assert!(file_diff_structured(cwd, "a.txt", true).unwrap().is_empty());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_cherry_pick_from_side_branch() {
+        let dir = temp_dir("cherry_pick");
+        let cwd = dir.to_str().unwrap();
+        init(cwd, false, true).unwrap();
+        commit_file(&dir, "a.txt", "base\n", "base");
+
+        let repo = Repository::open(&dir).unwrap();
+        let main_branch = get_branch(&repo).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("side", &head, false).unwrap();
+
+        checkout_branch(cwd, "side").unwrap();
+        let picked = commit_file(&dir, "b.txt", "hotfix\n", "hotfix");
+        checkout_branch(cwd, &main_branch).unwrap();
+        assert!(!dir.join("b.txt").exists());
+
+        let result = cherry_pick(cwd, &picked).unwrap();
+        assert!(result.commit.is_some());
+        assert!(result.conflicts.is_empty());
+        assert_eq!(fs::read_to_string(dir.join("b.txt")).unwrap(), "hotfix\n");
+
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.message(), Some("hotfix"));
+        assert!(list_conflicts(cwd).unwrap().is_empty());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_revert_restores_file() {
+        let dir = temp_dir("revert");
+        let cwd = dir.to_str().unwrap();
+        init(cwd, false, true).unwrap();
+        commit_file(&dir, "a.txt", "original\n", "add a");
+        let changed = commit_file(&dir, "a.txt", "changed\n", "change a");
+
+        let result = revert(cwd, &changed).unwrap();
+        assert!(result.commit.is_some());
+        assert!(result.conflicts.is_empty());
+        assert_eq!(fs::read_to_string(dir.join("a.txt")).unwrap(), "original\n");
+
+        let repo = Repository::open(&dir).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.summary(), Some("Revert \"change a\""));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    /// Repo with a base commit and a `feature` branch off it; returns the
+    /// repo and the name of the original branch, which is checked out
+    fn repo_with_feature_branch(dir: &Path) -> (Repository, String) {
+        let cwd = dir.to_str().unwrap();
+        init(cwd, false, true).unwrap();
+        commit_file(dir, "a.txt", "base\n", "base");
+        let repo = Repository::open(dir).unwrap();
+        let main_branch = get_branch(&repo).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("feature", &head, false).unwrap();
+        drop(head);
+        (repo, main_branch)
+    }
+
+    #[test]
+    fn test_merge_fast_forward() {
+        let dir = temp_dir("merge_ff");
+        let cwd = dir.to_str().unwrap();
+        let (repo, main_branch) = repo_with_feature_branch(&dir);
+        checkout_branch(cwd, "feature").unwrap();
+        let tip = commit_file(&dir, "b.txt", "feature\n", "add b");
+        checkout_branch(cwd, &main_branch).unwrap();
+
+        let result = merge(cwd, "feature", &MergeOptions::default()).unwrap();
+        assert!(result.fast_forward);
+        assert_eq!(result.commit.as_deref(), Some(tip.as_str()));
+        assert_eq!(get_branch(&repo).unwrap(), main_branch);
+        assert_eq!(fs::read_to_string(dir.join("b.txt")).unwrap(), "feature\n");
+
+        let again = merge(cwd, "feature", &MergeOptions::default()).unwrap();
+        assert!(again.up_to_date);
+        assert!(merge(cwd, "missing", &MergeOptions::default()).is_err());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_merge_three_way_creates_merge_commit() {
+        let dir = temp_dir("merge_3way");
+        let cwd = dir.to_str().unwrap();
+        let (repo, main_branch) = repo_with_feature_branch(&dir);
+        checkout_branch(cwd, "feature").unwrap();
+        commit_file(&dir, "b.txt", "feature\n", "add b");
+        checkout_branch(cwd, &main_branch).unwrap();
+        commit_file(&dir, "c.txt", "main\n", "add c");
+
+        let options = MergeOptions {
+            no_ff: false,
+            message: Some("Merge feature work".to_string()),
+        };
+        let result = merge(cwd, "feature", &options).unwrap();
+        assert!(!result.fast_forward);
+        assert!(result.conflicts.is_empty());
+        assert!(result.commit.is_some());
+
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.parent_count(), 2);
+        assert_eq!(head.message(), Some("Merge feature work"));
+        assert!(dir.join("b.txt").exists() && dir.join("c.txt").exists());
+        assert_eq!(repo.state(), git2::RepositoryState::Clean);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_merge_conflict_is_reported_and_left_in_progress() {
+        let dir = temp_dir("merge_conflict");
+        let cwd = dir.to_str().unwrap();
+        let (repo, main_branch) = repo_with_feature_branch(&dir);
+        checkout_branch(cwd, "feature").unwrap();
+        commit_file(&dir, "a.txt", "feature\n", "feature edit");
+        checkout_branch(cwd, &main_branch).unwrap();
+        let before = commit_file(&dir, "a.txt", "main\n", "main edit");
+
+        let result = merge(cwd, "feature", &MergeOptions::default()).unwrap();
+        assert!(result.commit.is_none());
+        assert_eq!(result.conflicts, vec!["a.txt".to_string()]);
+        assert_eq!(list_conflicts(cwd).unwrap(), result.conflicts);
+        assert_eq!(repo.state(), git2::RepositoryState::Merge);
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(format!("{:.7}", head.id()), before);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_create_and_list_tags() {
+        let dir = temp_dir("tags");
+        let cwd = dir.to_str().unwrap();
+        init(cwd, false, true).unwrap();
+        let sha = commit_file(&dir, "a.txt", "a\n", "add a");
+
+        assert_eq!(create_tag(cwd, "v0.1.0", None, None, false).unwrap(), sha);
+        create_tag(cwd, "v0.2.0", Some(&sha), Some("Release 0.2.0"), false).unwrap();
+        assert!(create_tag(cwd, "v0.1.0", None, None, false).is_err());
+        assert!(create_tag(cwd, "v0.1.0", None, None, true).is_ok());
+
+        let tags = list_tags(cwd).unwrap();
+        assert_eq!(tags.len(), 2);
+        let light = tags.iter().find(|t| t.name == "v0.1.0").unwrap();
+        assert!(!light.is_annotated);
+        assert_eq!(light.target_sha, sha);
+        assert!(light.message.is_none());
+        let annotated = tags.iter().find(|t| t.name == "v0.2.0").unwrap();
+        assert!(annotated.is_annotated);
+        assert_eq!(annotated.target_sha, sha);
+        assert_eq!(annotated.message.as_deref(), Some("Release 0.2.0"));
+
+        delete_tag(cwd, "v0.1.0").unwrap();
+        assert!(matches!(
+            delete_tag(cwd, "v0.1.0"),
+            Err(VcsError::NotFound(msg)) if msg == "Tag 'v0.1.0' not found"
+        ));
+        // An invalid name is reported as is, not as a missing tag
+        assert!(matches!(
+            delete_tag(cwd, "bad..name"),
+            Err(VcsError::GitError(_))
+        ));
+        assert_eq!(list_tags(cwd).unwrap().len(), 1);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_repo_root_from_nested_dir() {
+        let dir = temp_dir("repo_root");
+        let cwd = dir.to_str().unwrap();
+        init(cwd, false, true).unwrap();
+        let nested = dir.join("src").join("deep");
+        fs::create_dir_all(&nested).unwrap();
+
+        let root = repo_root(nested.to_str().unwrap()).unwrap();
+        assert_eq!(
+            fs::canonicalize(&root).unwrap(),
+            fs::canonicalize(&dir).unwrap()
+        );
+        assert!(!root.ends_with('/'));
+        assert_eq!(repo_root(cwd).unwrap(), root);
+
+        let outside = temp_dir("repo_root_outside");
+        assert!(matches!(
+            repo_root(outside.to_str().unwrap()),
+            Err(VcsError::NotGitRepo(_))
+        ));
+
+        fs::remove_dir_all(&dir).ok();
+        fs::remove_dir_all(&outside).ok();
+    }
+
+    #[test]
+    fn test_is_clean_after_commit_and_edit() {
+        let dir = temp_dir("is_clean");
+        let cwd = dir.to_str().unwrap();
+        init(cwd, false, true).unwrap();
+        commit_file(&dir, "a.txt", "a\n", "add a");
+        assert!(is_clean(cwd).unwrap());
+
+        fs::write(dir.join("a.txt"), "changed\n").unwrap();
+        assert!(!is_clean(cwd).unwrap());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_current_commit_matches_last_commit() {
+        let empty = temp_dir("current_commit_empty");
+        init(empty.to_str().unwrap(), false, false).unwrap();
+        match current_commit(empty.to_str().unwrap()) {
+            Err(VcsError::GitError(msg)) => assert_eq!(msg, "No commits yet"),
+            other => panic!("expected no commits error, got {:?}", other),
+        }
+
+        let dir = temp_dir("current_commit");
+        let cwd = dir.to_str().unwrap();
+        init(cwd, false, true).unwrap();
+        let short = commit_file(&dir, "a.txt", "a\n", "add a\n\nlonger body");
+        let info = current_commit(cwd).unwrap();
+        assert_eq!(info.short_sha, short);
+        assert!(info.sha.starts_with(&short));
+        assert_eq!(info.sha.len(), 40);
+        assert_eq!(info.summary, "add a");
+        assert!(info.timestamp > 0);
+
+        fs::remove_dir_all(&dir).ok();
+        fs::remove_dir_all(&empty).ok();
+    }
+
+    #[test]
+    fn test_upstream_reports_tracking_branch() {
+        let dir = temp_dir("upstream");
+        let cwd = dir.to_str().unwrap();
+        init(cwd, false, true).unwrap();
+        commit_file(&dir, "a.txt", "a\n", "add a");
+
+        let repo = Repository::open(&dir).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.remote("origin", "https://example.com/repo.git")
+            .unwrap();
+        repo.reference("refs/remotes/origin/trunk", head.id(), false, "test")
+            .unwrap();
+        let mut current = repo
+            .find_branch(&get_branch(&repo).unwrap(), BranchType::Local)
+            .unwrap();
+        current.set_upstream(Some("origin/trunk")).unwrap();
+
+        let tracked = upstream(cwd).unwrap().unwrap();
+        assert_eq!(tracked.remote, "origin");
+        assert_eq!(tracked.branch, "trunk");
+        assert_eq!(tracked.full_ref, "refs/remotes/origin/trunk");
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_upstream_none_without_tracking() {
+        let dir = temp_dir("upstream_none");
+        let cwd = dir.to_str().unwrap();
+        init(cwd, false, true).unwrap();
+        commit_file(&dir, "a.txt", "a\n", "add a");
+        assert!(upstream(cwd).unwrap().is_none());
+
+        let empty = temp_dir("upstream_empty");
+        init(empty.to_str().unwrap(), false, false).unwrap();
+        assert!(upstream(empty.to_str().unwrap()).unwrap().is_none());
+
+        fs::remove_dir_all(&dir).ok();
+        fs::remove_dir_all(&empty).ok();
+    }
+
+    #[test]
+    fn test_default_branch() {
+        let dir = temp_dir("default_branch");
+        let cwd = dir.to_str().unwrap();
+        init(cwd, false, true).unwrap();
+        commit_file(&dir, "a.txt", "a\n", "add a");
+
+        let repo = Repository::open(&dir).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let mut current = repo
+            .find_branch(&get_branch(&repo).unwrap(), BranchType::Local)
+            .unwrap();
+        current.rename("feature-x", false).unwrap();
+
+        // No well-known branch: fall back to HEAD
+        assert_eq!(default_branch(cwd).unwrap(), "feature-x");
+
+        // A local well-known name wins over HEAD
+        repo.branch("trunk", &head, false).unwrap();
+        assert_eq!(default_branch(cwd).unwrap(), "trunk");
+
+        // origin/HEAD wins over everything
+        repo.reference("refs/remotes/origin/release", head.id(), false, "test")
+            .unwrap();
+        repo.reference_symbolic(
+            "refs/remotes/origin/HEAD",
+            "refs/remotes/origin/release",
+            false,
+            "test",
+        )
+        .unwrap();
+        assert_eq!(default_branch(cwd).unwrap(), "release");
+
+        fs::remove_dir_all(&dir).ok();
+    }
 }
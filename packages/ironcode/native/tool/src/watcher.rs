@@ -1,9 +1,12 @@
-use globset::{Glob, GlobSetBuilder};
+use crate::error::{ErrorCode, ToolError};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
 use lazy_static::lazy_static;
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::SystemTime;
 
@@ -12,14 +15,30 @@ pub struct WatcherEvent {
     pub path: String,
     pub event_type: String, // "add", "change", "unlink"
     pub timestamp: u64,     // Unix timestamp in milliseconds
+    /// Path relative to the watched root (None if the event is outside it)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relative_path: Option<String>,
+}
+
+/// Result of `poll_events_batch`
+#[derive(Debug, Clone, Serialize)]
+pub struct EventBatch {
+    pub events: Vec<WatcherEvent>,
+    /// Events are still queued; poll again to get them
+    pub has_more: bool,
 }
 
 struct WatcherState {
     #[allow(dead_code)]
     watcher: RecommendedWatcher,
+    root: String,
     ignore_patterns: Vec<String>,
     event_queue: Arc<Mutex<VecDeque<WatcherEvent>>>,
     max_queue_size: usize,
+    /// When set, events are counted as dropped instead of queued
+    paused: Arc<AtomicBool>,
+    /// Events lost to queue overflow or pausing; non-zero means the consumer should resync
+    dropped: Arc<AtomicU64>,
 }
 
 lazy_static! {
@@ -33,6 +52,8 @@ lazy_static! {
 /// * `path` - Directory path to watch
 /// * `ignore_patterns` - List of glob patterns to ignore
 /// * `max_queue_size` - Maximum events to queue (older events dropped if exceeded)
+/// * `emit_initial` - Queue an `add` event for every existing file once the watch
+///   has started, so the consumer is seeded without a separate listing
 ///
 /// Returns: Result<(), String>
 pub fn create(
@@ -40,6 +61,7 @@ pub fn create(
     path: String,
     ignore_patterns: Vec<String>,
     max_queue_size: usize,
+    emit_initial: bool,
 ) -> Result<(), String> {
     let mut watchers = WATCHERS.lock().map_err(|e| format!("Lock error: {}", e))?;
 
@@ -58,9 +80,18 @@ pub fn create(
         .map_err(|e| format!("Failed to build glob set: {}", e))?;
 
     let path_buf = PathBuf::from(&path);
+    // Backends may report canonical paths (e.g. /private/var on macOS), so
+    // relativize against both forms of the root
+    let root_canonical = path_buf.canonicalize().unwrap_or_else(|_| path_buf.clone());
+    let root_given = path_buf.clone();
     let glob_set_arc = Arc::new(glob_set);
+    let snapshot_globs = glob_set_arc.clone();
     let event_queue = Arc::new(Mutex::new(VecDeque::with_capacity(max_queue_size)));
     let event_queue_clone = event_queue.clone();
+    let paused = Arc::new(AtomicBool::new(false));
+    let paused_clone = paused.clone();
+    let dropped = Arc::new(AtomicU64::new(0));
+    let dropped_clone = dropped.clone();
 
     // Create watcher with event handler that queues events
     let mut watcher = notify::recommended_watcher(move |res: Result<Event, notify::Error>| {
@@ -90,17 +121,24 @@ pub fn create(
                         continue;
                     }
 
+                    if paused_clone.load(Ordering::SeqCst) {
+                        dropped_clone.fetch_add(1, Ordering::SeqCst);
+                        continue;
+                    }
+
                     // Queue the event
                     let watcher_event = WatcherEvent {
                         path: path_str.to_string(),
                         event_type: event_type.to_string(),
                         timestamp,
+                        relative_path: relative_to_root(&path, &root_given, &root_canonical),
                     };
 
                     if let Ok(mut queue) = event_queue_clone.lock() {
                         // If queue is full, remove oldest event
                         if queue.len() >= max_queue_size {
                             queue.pop_front();
+                            dropped_clone.fetch_add(1, Ordering::SeqCst);
                         }
                         queue.push_back(watcher_event);
                     }
@@ -118,11 +156,26 @@ pub fn create(
         .watch(&path_buf, RecursiveMode::Recursive)
         .map_err(|e| format!("Failed to watch path: {}", e))?;
 
+    // Walk after the watch is live so files created meanwhile are not missed;
+    // they may show up twice instead
+    if emit_initial {
+        queue_initial_files(
+            &path_buf,
+            &snapshot_globs,
+            &event_queue,
+            max_queue_size,
+            &dropped,
+        );
+    }
+
     let state = WatcherState {
         watcher,
+        root: path,
         ignore_patterns,
         event_queue,
         max_queue_size,
+        paused,
+        dropped,
     };
 
     watchers.insert(id, state);
@@ -130,23 +183,112 @@ pub fn create(
     Ok(())
 }
 
+/// Queue an `add` event per existing file under `root`, honouring .gitignore and
+/// the watcher's ignore globs and skipping `.git`. Files that do not fit in the queue are counted as
+/// dropped rather than evicting each other.
+fn queue_initial_files(
+    root: &Path,
+    glob_set: &GlobSet,
+    event_queue: &Mutex<VecDeque<WatcherEvent>>,
+    max_queue_size: usize,
+    dropped: &AtomicU64,
+) {
+    let root_canonical = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+
+    let mut queue = match event_queue.lock() {
+        Ok(queue) => queue,
+        Err(_) => return,
+    };
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+    for entry in walker.flatten() {
+        if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+            continue;
+        }
+        let path = entry.path();
+        let path_str = match path.to_str() {
+            Some(s) => s,
+            None => continue,
+        };
+        if glob_set.is_match(path) {
+            continue;
+        }
+        if queue.len() >= max_queue_size {
+            dropped.fetch_add(1, Ordering::SeqCst);
+            continue;
+        }
+        queue.push_back(WatcherEvent {
+            path: path_str.to_string(),
+            event_type: "add".to_string(),
+            timestamp,
+            relative_path: relative_to_root(path, root, &root_canonical),
+        });
+    }
+}
+
+fn relative_to_root(path: &Path, root: &Path, root_canonical: &Path) -> Option<String> {
+    path.strip_prefix(root)
+        .or_else(|_| path.strip_prefix(root_canonical))
+        .ok()
+        .map(|rel| rel.to_string_lossy().to_string())
+}
+
 /// Poll events from the watcher queue (non-blocking)
 ///
 /// Returns: Vec of events (may be empty if no events)
-pub fn poll_events(id: &str) -> Result<Vec<WatcherEvent>, String> {
-    let watchers = WATCHERS.lock().map_err(|e| format!("Lock error: {}", e))?;
+pub fn poll_events(id: &str) -> Result<Vec<WatcherEvent>, ToolError> {
+    let watchers = WATCHERS
+        .lock()
+        .map_err(|e| ToolError::new(ErrorCode::Other, format!("Lock error: {}", e)))?;
 
     if let Some(state) = watchers.get(id) {
         let mut queue = state
             .event_queue
             .lock()
-            .map_err(|e| format!("Queue lock error: {}", e))?;
+            .map_err(|e| ToolError::new(ErrorCode::Other, format!("Queue lock error: {}", e)))?;
 
         // Drain all events from queue
         let events: Vec<WatcherEvent> = queue.drain(..).collect();
         Ok(events)
     } else {
-        Err(format!("Watcher {} not found", id))
+        Err(ToolError::new(
+            ErrorCode::NotFound,
+            format!("Watcher {} not found", id),
+        ))
+    }
+}
+
+/// Poll at most `max_batch` events, oldest first, leaving the rest queued so a
+/// burst of changes can be processed in chunks. `None` drains the whole queue
+/// like `poll_events`.
+pub fn poll_events_batch(id: &str, max_batch: Option<usize>) -> Result<EventBatch, ToolError> {
+    let watchers = WATCHERS
+        .lock()
+        .map_err(|e| ToolError::new(ErrorCode::Other, format!("Lock error: {}", e)))?;
+
+    if let Some(state) = watchers.get(id) {
+        let mut queue = state
+            .event_queue
+            .lock()
+            .map_err(|e| ToolError::new(ErrorCode::Other, format!("Queue lock error: {}", e)))?;
+
+        let take = max_batch.unwrap_or(queue.len()).min(queue.len());
+        let events: Vec<WatcherEvent> = queue.drain(..take).collect();
+        Ok(EventBatch {
+            events,
+            has_more: !queue.is_empty(),
+        })
+    } else {
+        Err(ToolError::new(
+            ErrorCode::NotFound,
+            format!("Watcher {} not found", id),
+        ))
     }
 }
 
@@ -165,6 +307,28 @@ pub fn pending_count(id: &str) -> Result<usize, String> {
     }
 }
 
+/// Stop enqueuing events while keeping the OS watch alive.
+/// Events that arrive while paused are counted as dropped.
+pub fn pause(id: &str) -> Result<(), String> {
+    set_paused(id, true)
+}
+
+/// Resume enqueuing events after `pause`
+pub fn resume(id: &str) -> Result<(), String> {
+    set_paused(id, false)
+}
+
+fn set_paused(id: &str, paused: bool) -> Result<(), String> {
+    let watchers = WATCHERS.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    if let Some(state) = watchers.get(id) {
+        state.paused.store(paused, Ordering::SeqCst);
+        Ok(())
+    } else {
+        Err(format!("Watcher {} not found", id))
+    }
+}
+
 /// Stop and remove a watcher
 pub fn remove(id: String) -> Result<(), String> {
     let mut watchers = WATCHERS.lock().map_err(|e| format!("Lock error: {}", e))?;
@@ -192,9 +356,12 @@ pub fn get_info(id: String) -> Result<String, String> {
 
         let info = serde_json::json!({
             "id": id,
+            "root": state.root,
             "ignore_patterns": state.ignore_patterns,
             "max_queue_size": state.max_queue_size,
             "pending_events": queue_len,
+            "paused": state.paused.load(Ordering::SeqCst),
+            "dropped_events": state.dropped.load(Ordering::SeqCst),
         });
         Ok(info.to_string())
     } else {
@@ -205,6 +372,7 @@ pub fn get_info(id: String) -> Result<String, String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::temp_dir;
     use std::fs;
     use std::thread;
     use std::time::Duration;
@@ -219,6 +387,7 @@ mod tests {
             temp_dir.to_str().unwrap().to_string(),
             vec![],
             100,
+            false,
         );
         assert!(result.is_ok());
 
@@ -244,6 +413,7 @@ mod tests {
             temp_dir.to_str().unwrap().to_string(),
             vec![],
             100,
+            false,
         )
         .unwrap();
 
@@ -278,6 +448,7 @@ mod tests {
             temp_dir.to_str().unwrap().to_string(),
             vec![],
             5,
+            false,
         )
         .unwrap();
 
@@ -296,4 +467,167 @@ mod tests {
         remove("test3".to_string()).ok();
         fs::remove_dir_all(&temp_dir).ok();
     }
+
+    #[test]
+    fn test_relative_path_nested() {
+        let temp_dir = std::env::temp_dir().join("ironcode_watcher_test_relative");
+        let nested = temp_dir.join("src").join("deep");
+        fs::create_dir_all(&nested).unwrap();
+        let root = temp_dir.to_str().unwrap().to_string();
+
+        create("test4".to_string(), root.clone(), vec![], 100, false).unwrap();
+
+        let info: serde_json::Value =
+            serde_json::from_str(&get_info("test4".to_string()).unwrap()).unwrap();
+        assert_eq!(info["root"], root);
+
+        fs::write(nested.join("file.rs"), "fn main() {}").unwrap();
+        thread::sleep(Duration::from_millis(200));
+
+        let events = poll_events("test4").unwrap();
+        let event = events
+            .iter()
+            .find(|e| e.path.ends_with("file.rs"))
+            .expect("event for nested file");
+        let expected = Path::new("src").join("deep").join("file.rs");
+        assert_eq!(
+            event.relative_path.as_deref(),
+            Some(expected.to_str().unwrap())
+        );
+
+        remove("test4".to_string()).ok();
+        fs::remove_dir_all(&temp_dir).ok();
+    }
+
+    #[test]
+    fn test_pause_resume() {
+        let temp_dir = temp_dir("pause");
+
+        create(
+            "test5".to_string(),
+            temp_dir.to_str().unwrap().to_string(),
+            vec![],
+            100,
+            false,
+        )
+        .unwrap();
+
+        pause("test5").unwrap();
+        fs::write(temp_dir.join("paused.txt"), "content").unwrap();
+        thread::sleep(Duration::from_millis(200));
+
+        let events = poll_events("test5").unwrap();
+        assert!(events.is_empty());
+
+        let info: serde_json::Value =
+            serde_json::from_str(&get_info("test5".to_string()).unwrap()).unwrap();
+        assert_eq!(info["paused"], true);
+        assert!(info["dropped_events"].as_u64().unwrap() > 0);
+
+        resume("test5").unwrap();
+        fs::write(temp_dir.join("resumed.txt"), "content").unwrap();
+        thread::sleep(Duration::from_millis(200));
+
+        let events = poll_events("test5").unwrap();
+        assert!(events.iter().any(|e| e.path.ends_with("resumed.txt")));
+        assert!(!events.iter().any(|e| e.path.ends_with("paused.txt")));
+
+        assert!(pause("missing").is_err());
+
+        remove("test5".to_string()).ok();
+        fs::remove_dir_all(&temp_dir).ok();
+    }
+
+    #[test]
+    fn test_emit_initial_snapshot() {
+        let temp_dir = temp_dir("initial");
+        fs::create_dir_all(temp_dir.join("src")).unwrap();
+        fs::create_dir_all(temp_dir.join(".git/objects")).unwrap();
+        fs::write(temp_dir.join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+        fs::write(temp_dir.join(".git/objects/ab"), "blob").unwrap();
+        fs::write(temp_dir.join("a.txt"), "a").unwrap();
+        fs::write(temp_dir.join("src").join("b.rs"), "b").unwrap();
+        fs::write(temp_dir.join("skip.log"), "log").unwrap();
+
+        create(
+            "test6".to_string(),
+            temp_dir.to_str().unwrap().to_string(),
+            vec!["**/*.log".to_string()],
+            100,
+            true,
+        )
+        .unwrap();
+
+        let events = poll_events("test6").unwrap();
+        let mut initial: Vec<String> = events
+            .iter()
+            .filter(|e| e.event_type == "add")
+            .filter_map(|e| e.relative_path.clone())
+            .collect();
+        initial.sort();
+        let nested = Path::new("src").join("b.rs");
+        assert_eq!(initial, vec!["a.txt", nested.to_str().unwrap()]);
+        remove("test6".to_string()).ok();
+
+        // Only one event fits; the other file is counted as dropped
+        create(
+            "test7".to_string(),
+            temp_dir.to_str().unwrap().to_string(),
+            vec!["**/*.log".to_string()],
+            1,
+            true,
+        )
+        .unwrap();
+        assert_eq!(poll_events("test7").unwrap().len(), 1);
+        let info: serde_json::Value =
+            serde_json::from_str(&get_info("test7".to_string()).unwrap()).unwrap();
+        assert_eq!(info["dropped_events"], 1);
+
+        remove("test7".to_string()).ok();
+        fs::remove_dir_all(&temp_dir).ok();
+    }
+
+    #[test]
+    fn test_poll_events_batch() {
+        let temp_dir = temp_dir("batch");
+        for i in 0..7 {
+            fs::write(temp_dir.join(format!("file{}.txt", i)), "content").unwrap();
+        }
+
+        // The initial snapshot queues one event per file
+        create(
+            "test8".to_string(),
+            temp_dir.to_str().unwrap().to_string(),
+            vec![],
+            100,
+            true,
+        )
+        .unwrap();
+
+        let first = poll_events_batch("test8", Some(3)).unwrap();
+        assert_eq!(first.events.len(), 3);
+        assert!(first.has_more);
+        let second = poll_events_batch("test8", Some(3)).unwrap();
+        assert_eq!(second.events.len(), 3);
+        assert!(second.has_more);
+        let last = poll_events_batch("test8", Some(3)).unwrap();
+        assert_eq!(last.events.len(), 1);
+        assert!(!last.has_more);
+
+        let mut seen: Vec<String> = [first, second, last]
+            .into_iter()
+            .flat_map(|batch| batch.events)
+            .filter_map(|e| e.relative_path)
+            .collect();
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 7);
+
+        let empty = poll_events_batch("test8", None).unwrap();
+        assert!(empty.events.is_empty());
+        assert!(!empty.has_more);
+
+        remove("test8".to_string()).ok();
+        fs::remove_dir_all(&temp_dir).ok();
+    }
 }
@@ -55,6 +55,10 @@ pub enum ContentFormat {
 pub struct WebFetchResult {
     pub content: String,
     pub content_type: String,
+    /// HTTP status of the final response; non-2xx bodies are still returned
+    pub status: u16,
+    /// URL the content came from after following redirects
+    pub final_url: String,
 }
 
 pub fn fetch_url(
@@ -71,12 +75,10 @@ pub fn fetch_url(
     // Fetch the content
     let response = client.get(url).send()?;
 
-    if !response.status().is_success() {
-        return Err(WebFetchError::HttpError(format!(
-            "Request failed with status code: {}",
-            response.status()
-        )));
-    }
+    // Non-2xx responses are returned with their status so the caller can decide
+    // (link checking, soft-404 detection)
+    let status = response.status().as_u16();
+    let final_url = response.url().to_string();
 
     let content_type = response
         .headers()
@@ -109,6 +111,8 @@ pub fn fetch_url(
     Ok(WebFetchResult {
         content,
         content_type,
+        status,
+        final_url,
     })
 }
 
@@ -125,3 +129,50 @@ fn extract_text_from_html(html: &str) -> String {
     }
     result.trim().to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Serve `/start` -> `/middle` -> `/end` redirects, with `/end` answering 404
+    fn spawn_redirect_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(3) {
+                let mut stream = stream.unwrap();
+                let mut buf = [0u8; 2048];
+                let n = stream.read(&mut buf).unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+                let response = match path.as_str() {
+                    "/start" => "HTTP/1.1 302 Found\r\nLocation: /middle\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+                    "/middle" => "HTTP/1.1 301 Moved Permanently\r\nLocation: /end\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+                    _ => {
+                        let body = "page not found";
+                        format!(
+                            "HTTP/1.1 404 Not Found\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            body.len(),
+                            body
+                        )
+                    }
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn test_fetch_follows_redirects_and_reports_status() {
+        let base = spawn_redirect_server();
+        let result = fetch_url(&format!("{}/start", base), ContentFormat::Text, 5).unwrap();
+
+        assert_eq!(result.status, 404);
+        assert_eq!(result.final_url, format!("{}/end", base));
+        assert_eq!(result.content, "page not found");
+        assert!(result.content_type.starts_with("text/plain"));
+    }
+}
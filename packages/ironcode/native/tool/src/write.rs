@@ -1,6 +1,10 @@
+use crate::edit;
 use crate::types::Output;
 use std::fs;
+use std::io::{self, Write};
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
 
 pub fn execute(filepath: &str, content: &str) -> Result<Output, String> {
     let path = Path::new(filepath);
@@ -21,7 +25,293 @@ pub fn execute(filepath: &str, content: &str) -> Result<Output, String> {
         metadata: crate::types::Metadata {
             count: content.lines().count(),
             truncated: false,
+            cancelled: false,
+            scanned: None,
         },
         output: format!("Successfully wrote {} bytes to file", content.len()),
     })
 }
+
+/// `execute` with line endings normalized first. `eol` is `lf`, `crlf`, or
+/// `keep`, which matches the ending already used by the file on disk (content
+/// for a new file is written as given).
+pub fn write_normalized(filepath: &str, content: &str, eol: &str) -> Result<Output, String> {
+    let ending = match eol {
+        "lf" => Some("\n"),
+        "crlf" => Some("\r\n"),
+        "keep" => match fs::read(filepath) {
+            Ok(existing) => Some(edit::line_ending(&String::from_utf8_lossy(&existing))),
+            Err(_) => None,
+        },
+        _ => return Err(format!("Unknown line ending: {}", eol)),
+    };
+    match ending {
+        Some(ending) => execute(filepath, &edit::normalize_line_endings(content, ending)),
+        None => execute(filepath, content),
+    }
+}
+
+/// Replace the file at `path` so it is never seen half-written: `content` goes
+/// to a temporary file in the same directory, which is then renamed over
+/// `path`. An existing file keeps its permissions.
+pub fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let tmp = path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        name.to_string_lossy(),
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let result = (|| {
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&tmp)?;
+        file.write_all(content)?;
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    })();
+    if result.is_err() {
+        fs::remove_file(&tmp).ok();
+    }
+    result
+}
+
+/// Create a directory. Without `recursive` this behaves like `mkdir`: it fails
+/// if the parent is missing or the path already exists. With `recursive` it
+/// behaves like `mkdir -p`.
+pub fn fs_mkdir(path: &str, recursive: bool) -> Result<(), String> {
+    let result = if recursive {
+        fs::create_dir_all(path)
+    } else {
+        fs::create_dir(path)
+    };
+    result.map_err(|e| format!("Failed to create directory: {}", e))
+}
+
+/// Create an empty file if it is missing, otherwise bump its mtime to now.
+/// Existing content is left untouched.
+pub fn fs_touch(path: &str) -> Result<(), String> {
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+    file.set_modified(SystemTime::now())
+        .map_err(|e| format!("Failed to update modification time: {}", e))
+}
+
+/// Set a file's length to `len` bytes in place: shorter cuts the tail off,
+/// longer zero-extends. A missing file is created only when `len` is 0, so a
+/// typo'd path can't silently become a file full of zeros.
+pub fn fs_truncate(path: &str, len: u64) -> Result<(), String> {
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .create(len == 0)
+        .truncate(false)
+        .open(path)
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+    file.set_len(len)
+        .map_err(|e| format!("Failed to truncate file: {}", e))
+}
+
+/// Error returned by `fs_trash` where the platform has no trash to move to
+#[cfg(feature = "trash")]
+pub const TRASH_UNSUPPORTED: &str = "Moving to trash is unsupported on this platform";
+
+/// Move a file or directory to the OS trash (recycle bin on Windows) so the
+/// user can restore it. Fails with `TRASH_UNSUPPORTED` on platforms without
+/// one, so callers can fall back to deleting.
+#[cfg(feature = "trash")]
+pub fn fs_trash(path: &str) -> Result<(), String> {
+    if fs::symlink_metadata(path).is_err() {
+        return Err(format!("Path not found: {}", path));
+    }
+    #[cfg(any(
+        windows,
+        target_os = "macos",
+        all(unix, not(any(target_os = "ios", target_os = "android")))
+    ))]
+    {
+        trash::delete(path).map_err(|e| format!("Failed to move to trash: {}", e))
+    }
+    #[cfg(not(any(
+        windows,
+        target_os = "macos",
+        all(unix, not(any(target_os = "ios", target_os = "android")))
+    )))]
+    {
+        Err(TRASH_UNSUPPORTED.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::time::Duration;
+
+    #[test]
+    fn test_mkdir_recursive_and_not() {
+        let dir = temp_dir("mkdir");
+        let nested = dir.join("a").join("b");
+        let nested_str = nested.to_str().unwrap();
+
+        assert!(fs_mkdir(nested_str, false).is_err());
+        assert!(!nested.exists());
+
+        fs_mkdir(nested_str, true).unwrap();
+        assert!(nested.is_dir());
+        // mkdir -p is fine with an existing directory, plain mkdir is not
+        assert!(fs_mkdir(nested_str, true).is_ok());
+        assert!(fs_mkdir(nested_str, false).is_err());
+
+        let sibling = dir.join("a").join("c");
+        fs_mkdir(sibling.to_str().unwrap(), false).unwrap();
+        assert!(sibling.is_dir());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_touch_new_and_existing() {
+        let dir = temp_dir("touch");
+        let new_file = dir.join("new.txt");
+        fs_touch(new_file.to_str().unwrap()).unwrap();
+        assert_eq!(fs::read_to_string(&new_file).unwrap(), "");
+
+        let existing = dir.join("existing.txt");
+        fs::write(&existing, "keep me").unwrap();
+        let old = SystemTime::now() - Duration::from_secs(3600);
+        fs::File::options()
+            .write(true)
+            .open(&existing)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        fs_touch(existing.to_str().unwrap()).unwrap();
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "keep me");
+        let modified = fs::metadata(&existing).unwrap().modified().unwrap();
+        assert!(modified > old + Duration::from_secs(60));
+
+        assert!(fs_touch(dir.join("missing").join("x.txt").to_str().unwrap()).is_err());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_write_normalized_line_endings() {
+        let dir = temp_dir("eol");
+        let file = dir.join("nested").join("mixed.txt");
+        let file_str = file.to_str().unwrap();
+        let mixed = "one\r\ntwo\nthree\n";
+
+        write_normalized(file_str, mixed, "lf").unwrap();
+        assert_eq!(fs::read(&file).unwrap(), b"one\ntwo\nthree\n");
+
+        write_normalized(file_str, mixed, "crlf").unwrap();
+        assert_eq!(fs::read(&file).unwrap(), b"one\r\ntwo\r\nthree\r\n");
+
+        // The file is CRLF now, so `keep` converts the LF content to CRLF
+        write_normalized(file_str, "a\nb\n", "keep").unwrap();
+        assert_eq!(fs::read(&file).unwrap(), b"a\r\nb\r\n");
+
+        let fresh = dir.join("fresh.txt");
+        write_normalized(fresh.to_str().unwrap(), mixed, "keep").unwrap();
+        assert_eq!(fs::read(&fresh).unwrap(), mixed.as_bytes());
+
+        assert!(write_normalized(file_str, mixed, "cr").is_err());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_truncate_shrink_extend_and_empty() {
+        let dir = temp_dir("truncate");
+        let file = dir.join("app.log");
+        let file_str = file.to_str().unwrap();
+        fs::write(&file, "hello world").unwrap();
+
+        fs_truncate(file_str, 5).unwrap();
+        assert_eq!(fs::read(&file).unwrap(), b"hello");
+
+        fs_truncate(file_str, 8).unwrap();
+        assert_eq!(fs::read(&file).unwrap(), b"hello\0\0\0");
+
+        fs_truncate(file_str, 0).unwrap();
+        assert_eq!(fs::metadata(&file).unwrap().len(), 0);
+
+        let created = dir.join("new.log");
+        fs_truncate(created.to_str().unwrap(), 0).unwrap();
+        assert_eq!(fs::metadata(&created).unwrap().len(), 0);
+
+        let missing = dir.join("missing.log");
+        assert!(fs_truncate(missing.to_str().unwrap(), 10).is_err());
+        assert!(!missing.exists());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_content_and_keeps_permissions() {
+        let dir = temp_dir("atomic");
+        let file = dir.join("config.sh");
+        fs::write(&file, "old content that is longer\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&file, fs::Permissions::from_mode(0o750)).unwrap();
+        }
+
+        write_atomic(&file, b"new\n").unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "new\n");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&file).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o750);
+        }
+
+        write_atomic(&dir.join("fresh.txt"), b"created").unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("fresh.txt")).unwrap(),
+            "created"
+        );
+
+        // A failed write leaves no temporary file behind
+        assert!(write_atomic(&dir.join("missing").join("x.txt"), b"x").is_err());
+        let mut names: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["config.sh", "fresh.txt"]);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[cfg(feature = "trash")]
+    #[test]
+    fn test_trash_moves_file_away() {
+        let dir = temp_dir("trash");
+        let file = dir.join("unwanted.txt");
+        fs::write(&file, "recoverable").unwrap();
+
+        match fs_trash(file.to_str().unwrap()) {
+            Ok(()) => assert!(!file.exists()),
+            Err(e) => assert_eq!(e, TRASH_UNSUPPORTED),
+        }
+        assert!(fs_trash(dir.join("missing.txt").to_str().unwrap()).is_err());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
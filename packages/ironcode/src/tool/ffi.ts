@@ -88,7 +88,7 @@ const lib = dlopen(libPath, {
     returns: FFIType.ptr,
   },
   edit_replace_ffi: {
-    args: [FFIType.cstring, FFIType.cstring, FFIType.cstring, FFIType.bool],
+    args: [FFIType.cstring, FFIType.cstring, FFIType.cstring, FFIType.bool, FFIType.bool],
     returns: FFIType.ptr,
   },
   file_exists_ffi: {
@@ -448,6 +448,7 @@ export function editReplaceFFI(content: string, oldString: string, newString: st
     Buffer.from(oldString + "\0"),
     Buffer.from(newString + "\0"),
     replaceAll,
+    false,
   )
   if (!ptr) throw new Error("edit_replace_ffi returned null")
 